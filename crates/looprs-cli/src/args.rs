@@ -1,5 +1,6 @@
 use anyhow::{Result, anyhow};
 use std::env;
+use std::io::{IsTerminal, Read};
 
 #[derive(Debug, Clone)]
 pub struct CliArgs {
@@ -8,15 +9,23 @@ pub struct CliArgs {
     pub model: Option<String>,  // -m/--model
     pub quiet: bool,            // -q/--quiet
     pub no_hooks: bool,         // --no-hooks
+    pub no_rules: bool,         // --no-rules
+    pub approve_all: bool,      // --approve-all
+    pub deny_all: bool,         // --deny-all
     pub json_output: bool,      // --json
     pub machine_log: bool,      // --machine-log
+    pub dry_run: bool,          // --dry-run
+    pub stdin: bool,            // --stdin
+    pub stdin_piped: bool,      // stdin is not a TTY
 }
 
 impl CliArgs {
     /// Parse command-line arguments
     pub fn parse() -> Result<Self> {
         let args: Vec<String> = env::args().collect();
-        Self::parse_from(&args[1..])
+        let mut parsed = Self::parse_from(&args[1..])?;
+        parsed.stdin_piped = !std::io::stdin().is_terminal();
+        Ok(parsed)
     }
 
     /// Parse from a slice of arguments (for testing)
@@ -28,8 +37,14 @@ impl CliArgs {
             model: None,
             quiet: false,
             no_hooks: false,
+            no_rules: false,
+            approve_all: false,
+            deny_all: false,
             json_output: false,
             machine_log: false,
+            dry_run: false,
+            stdin: false,
+            stdin_piped: false,
         };
 
         let mut i = 0;
@@ -64,12 +79,27 @@ impl CliArgs {
                 "--no-hooks" => {
                     result.no_hooks = true;
                 }
+                "--no-rules" => {
+                    result.no_rules = true;
+                }
+                "--approve-all" => {
+                    result.approve_all = true;
+                }
+                "--deny-all" => {
+                    result.deny_all = true;
+                }
                 "--json" => {
                     result.json_output = true;
                 }
                 "--machine-log" => {
                     result.machine_log = true;
                 }
+                "--dry-run" => {
+                    result.dry_run = true;
+                }
+                "--stdin" => {
+                    result.stdin = true;
+                }
                 unknown => {
                     return Err(anyhow!("Unknown argument: {unknown}"));
                 }
@@ -78,28 +108,72 @@ impl CliArgs {
             i += 1;
         }
 
+        if result.approve_all && result.deny_all {
+            return Err(anyhow!(
+                "--approve-all and --deny-all cannot be used together"
+            ));
+        }
+
         Ok(result)
     }
 
+    /// Fixed answer for hook approval prompts in scriptable mode, if any.
+    pub fn approval_policy(&self) -> Option<bool> {
+        match (self.approve_all, self.deny_all) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+
     /// Determine if running in scriptable (non-interactive) mode
     pub fn is_scriptable(&self) -> bool {
-        self.prompt.is_some() || self.file.is_some()
+        self.prompt.is_some() || self.file.is_some() || self.stdin_piped
     }
 
-    /// Read prompt from file if specified, or use inline prompt
+    /// Whether the prompt includes stdin: with `-p -` or `--stdin`, or when
+    /// stdin is piped and there is no `-p`. A non-TTY stdin alone is not
+    /// enough with `-p`, since CI runners often leave it open and empty.
+    pub fn reads_stdin(&self) -> bool {
+        match self.prompt.as_deref() {
+            Some("-") => true,
+            Some(_) => self.stdin,
+            None => self.stdin || self.stdin_piped,
+        }
+    }
+
+    /// Read prompt from file if specified, otherwise combine the inline
+    /// prompt with stdin when `reads_stdin` says to
     pub fn get_prompt(&self) -> Result<Option<String>> {
         if let Some(ref file_path) = self.file {
             let content = std::fs::read_to_string(file_path)
                 .map_err(|e| anyhow!("Failed to read file {file_path}: {e}"))?;
             Ok(Some(content.trim().to_string()))
-        } else if let Some(ref prompt) = self.prompt {
-            Ok(Some(prompt.clone()))
+        } else if self.reads_stdin() {
+            let mut piped = String::new();
+            std::io::stdin()
+                .read_to_string(&mut piped)
+                .map_err(|e| anyhow!("Failed to read stdin: {e}"))?;
+            let prompt = self.prompt.as_deref().filter(|p| *p != "-");
+            Ok(assemble_prompt(prompt, Some(&piped)))
         } else {
-            Ok(None)
+            Ok(self.prompt.clone())
         }
     }
 }
 
+/// Combine an inline prompt with piped input. Piped input alone becomes the
+/// prompt; with a prompt it is appended as a fenced block. Blank input is ignored.
+pub fn assemble_prompt(prompt: Option<&str>, piped: Option<&str>) -> Option<String> {
+    let piped = piped.map(str::trim_end).filter(|p| !p.trim().is_empty());
+    match (prompt, piped) {
+        (Some(prompt), Some(piped)) => Some(format!("{prompt}\n\n```\n{piped}\n```")),
+        (Some(prompt), None) => Some(prompt.to_string()),
+        (None, Some(piped)) => Some(piped.trim().to_string()),
+        (None, None) => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -116,6 +190,7 @@ mod tests {
         assert!(parsed.model.is_none());
         assert!(!parsed.quiet);
         assert!(!parsed.no_hooks);
+        assert!(!parsed.no_rules);
         assert!(!parsed.json_output);
         assert!(!parsed.machine_log);
     }
@@ -175,6 +250,26 @@ mod tests {
         assert!(parsed.no_hooks);
     }
 
+    #[test]
+    fn parse_no_rules() {
+        let parsed = CliArgs::parse_from(&args(&["--no-rules"])).unwrap();
+        assert!(parsed.no_rules);
+        assert!(!parsed.no_hooks);
+    }
+
+    #[test]
+    fn parse_approval_policy() {
+        let approve = CliArgs::parse_from(&args(&["--approve-all", "-p", "hi"])).unwrap();
+        assert_eq!(approve.approval_policy(), Some(true));
+        let deny = CliArgs::parse_from(&args(&["--deny-all", "-p", "hi"])).unwrap();
+        assert_eq!(deny.approval_policy(), Some(false));
+        assert_eq!(
+            CliArgs::parse_from(&args(&[])).unwrap().approval_policy(),
+            None
+        );
+        assert!(CliArgs::parse_from(&args(&["--approve-all", "--deny-all"])).is_err());
+    }
+
     #[test]
     fn parse_json() {
         let parsed = CliArgs::parse_from(&args(&["--json"])).unwrap();
@@ -189,6 +284,14 @@ mod tests {
         assert!(!parsed.json_output);
     }
 
+    #[test]
+    fn parse_dry_run() {
+        let parsed = CliArgs::parse_from(&args(&["--dry-run", "-p", "hi"])).unwrap();
+        assert!(parsed.dry_run);
+        assert!(parsed.is_scriptable());
+        assert!(!CliArgs::parse_from(&args(&[])).unwrap().dry_run);
+    }
+
     #[test]
     fn parse_combined_args() {
         let parsed = CliArgs::parse_from(&args(&[
@@ -296,6 +399,53 @@ mod tests {
         assert_eq!(prompt, None);
     }
 
+    #[test]
+    fn assemble_prompt_appends_piped_input_as_fence() {
+        assert_eq!(
+            assemble_prompt(Some("review this"), Some("+added line\n-removed line\n")),
+            Some("review this\n\n```\n+added line\n-removed line\n```".to_string())
+        );
+    }
+
+    #[test]
+    fn assemble_prompt_uses_piped_input_alone() {
+        assert_eq!(
+            assemble_prompt(None, Some("  explain this error\n")),
+            Some("explain this error".to_string())
+        );
+        assert_eq!(assemble_prompt(None, Some(" \n")), None);
+        assert_eq!(
+            assemble_prompt(Some("hi"), Some("")),
+            Some("hi".to_string())
+        );
+    }
+
+    #[test]
+    fn stdin_is_read_only_without_an_inline_prompt_or_when_asked() {
+        let mut parsed = CliArgs::parse_from(&args(&["-p", "hello"])).unwrap();
+        parsed.stdin_piped = true;
+        assert!(!parsed.reads_stdin());
+
+        let mut parsed = CliArgs::parse_from(&args(&["-q"])).unwrap();
+        assert!(!parsed.reads_stdin());
+        parsed.stdin_piped = true;
+        assert!(parsed.reads_stdin());
+
+        let parsed = CliArgs::parse_from(&args(&["-p", "-"])).unwrap();
+        assert!(parsed.reads_stdin());
+
+        let parsed = CliArgs::parse_from(&args(&["-p", "review", "--stdin"])).unwrap();
+        assert!(parsed.stdin);
+        assert!(parsed.reads_stdin());
+    }
+
+    #[test]
+    fn piped_stdin_is_scriptable() {
+        let mut parsed = CliArgs::parse_from(&args(&["-q"])).unwrap();
+        parsed.stdin_piped = true;
+        assert!(parsed.is_scriptable());
+    }
+
     #[test]
     fn file_arg_overrides_in_get_prompt() {
         // When both are provided, file takes precedence in get_prompt
@@ -1,13 +1,35 @@
 pub enum CliCommand {
     Quit,
     Clear,
-    CustomCommand(String), // Custom command from .looprs/commands/
+    Tokens,
+    Skills,
+    Retry,
+    Checkpoint,
+    Rewind(Option<String>),              // /rewind [id]
+    Trash(String),                       // /trash list|empty|restore <entry>
+    Save(Option<String>),                // /save [name]
+    Resume(Option<String>),              // /resume [name]
+    CustomCommand(String),               // Custom command from .looprs/commands/
     InvokeSkill(String, Option<String>), // Explicit skill invocation: $skill-name
-    ColonCommand(String),  // Command-line settings: :set/:get/:unset/:help
-    FileRef(String),       // @path or @uri
+    ColonCommand(String),                // Command-line settings: :set/:get/:unset/:help
+    FileRef(String),                     // @path or @uri
     Message(String),
 }
 
+/// Slash commands handled by the REPL itself; never routed to custom commands.
+const BUILTIN_SLASH_COMMANDS: &[&str] = &[
+    "q",
+    "c",
+    "tokens",
+    "skills",
+    "retry",
+    "checkpoint",
+    "rewind",
+    "trash",
+    "save",
+    "resume",
+];
+
 pub fn parse_input(line: &str) -> Option<CliCommand> {
     let trimmed = line.trim();
 
@@ -46,7 +68,16 @@ pub fn parse_input(line: &str) -> Option<CliCommand> {
     // Check for custom commands (/ prefix)
     if trimmed.starts_with('/') && trimmed.len() > 1 {
         let command_name = trimmed[1..].split_whitespace().next().unwrap_or("");
-        if !command_name.is_empty() && command_name != "q" && command_name != "c" {
+        let rest = trimmed[1 + command_name.len()..].trim();
+        let optional_arg = || (!rest.is_empty()).then(|| rest.to_string());
+        match command_name {
+            "trash" => return Some(CliCommand::Trash(rest.to_string())),
+            "save" => return Some(CliCommand::Save(optional_arg())),
+            "resume" => return Some(CliCommand::Resume(optional_arg())),
+            "rewind" => return Some(CliCommand::Rewind(optional_arg())),
+            _ => {}
+        }
+        if !command_name.is_empty() && !BUILTIN_SLASH_COMMANDS.contains(&command_name) {
             return Some(CliCommand::CustomCommand(trimmed[1..].to_string()));
         }
     }
@@ -54,6 +85,10 @@ pub fn parse_input(line: &str) -> Option<CliCommand> {
     match trimmed {
         "/q" | "exit" | "quit" => Some(CliCommand::Quit),
         "/c" | "clear" => Some(CliCommand::Clear),
+        "/tokens" => Some(CliCommand::Tokens),
+        "/skills" => Some(CliCommand::Skills),
+        "/retry" => Some(CliCommand::Retry),
+        "/checkpoint" => Some(CliCommand::Checkpoint),
         msg => Some(CliCommand::Message(msg.to_string())),
     }
 }
@@ -76,6 +111,63 @@ mod tests {
         assert!(matches!(parse_input("clear"), Some(CliCommand::Clear)));
     }
 
+    #[test]
+    fn parse_tokens_command() {
+        assert!(matches!(parse_input("/tokens"), Some(CliCommand::Tokens)));
+    }
+
+    #[test]
+    fn parse_skills_command() {
+        assert!(matches!(parse_input("/skills"), Some(CliCommand::Skills)));
+    }
+
+    #[test]
+    fn parse_retry_command() {
+        assert!(matches!(parse_input("/retry"), Some(CliCommand::Retry)));
+    }
+
+    #[test]
+    fn parse_checkpoint_and_rewind_commands() {
+        assert!(matches!(
+            parse_input("/checkpoint"),
+            Some(CliCommand::Checkpoint)
+        ));
+        assert!(matches!(
+            parse_input("/rewind"),
+            Some(CliCommand::Rewind(None))
+        ));
+        assert!(matches!(
+            parse_input("/rewind 3"),
+            Some(CliCommand::Rewind(Some(ref id))) if id == "3"
+        ));
+    }
+
+    #[test]
+    fn parse_trash_command_with_args() {
+        assert!(matches!(parse_input("/trash"), Some(CliCommand::Trash(ref a)) if a.is_empty()));
+        assert!(matches!(
+            parse_input("/trash restore 2026-abc"),
+            Some(CliCommand::Trash(ref a)) if a == "restore 2026-abc"
+        ));
+    }
+
+    #[test]
+    fn parse_save_and_resume_commands() {
+        assert!(matches!(parse_input("/save"), Some(CliCommand::Save(None))));
+        assert!(matches!(
+            parse_input("/save refactor"),
+            Some(CliCommand::Save(Some(ref n))) if n == "refactor"
+        ));
+        assert!(matches!(
+            parse_input("/resume refactor"),
+            Some(CliCommand::Resume(Some(ref n))) if n == "refactor"
+        ));
+        assert!(matches!(
+            parse_input("/resume"),
+            Some(CliCommand::Resume(None))
+        ));
+    }
+
     #[test]
     fn parse_message_commands() {
         assert!(matches!(parse_input("hello"), Some(CliCommand::Message(_))));
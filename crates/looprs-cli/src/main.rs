@@ -5,16 +5,21 @@ use rustyline::error::ReadlineError;
 use rustyline::history::DefaultHistory;
 use std::collections::HashMap;
 use std::env;
+use std::path::Path;
 
 use looprs::ModelId;
 use looprs::app_config::AppConfig;
+use looprs::config_watch::ConfigWatcher;
 use looprs::file_refs::{AtReference, resolve_at_reference};
-use looprs::providers::{ProviderOverrides, create_provider_with_overrides};
+use looprs::providers::{
+    HttpClient, LLMProvider, ProviderOverrides, SamplingParams, create_provider_from_config,
+    with_fallbacks,
+};
 use looprs::ui;
 use looprs::{
     Agent, AgentRegistry, ApprovalCallback, Command, CommandRegistry, Event, EventContext,
-    HookRegistry, PromptCallback, SessionContext, SkillRegistry, console_approval_prompt,
-    console_prompt, console_secret_prompt,
+    HookRegistry, PromptCallback, SessionContext, SessionUsage, SkillRegistry,
+    console_approval_prompt, console_prompt, console_secret_prompt,
 };
 use looprs::{ProviderConfig, ProviderSettings};
 
@@ -77,12 +82,26 @@ async fn main() -> Result<()> {
     load_nu_env();
     let args: Vec<String> = env::args().collect();
     if matches!(args.get(1).map(String::as_str), Some("seed")) {
-        let dir_str = args.get(2).map(String::as_str).unwrap_or(".looprs");
-        let dir = looprs::seed::expand_tilde(dir_str);
-        match looprs::seed::seed_into(&dir) {
-            Ok(files) => {
-                for f in &files {
-                    println!("{}", f.display());
+        let (options, dir_str) = match looprs::seed::SeedOptions::from_args(&args[2..]) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                ui::error(format!("seed: {e}"));
+                print_usage();
+                std::process::exit(1);
+            }
+        };
+        let dir = looprs::seed::expand_tilde(&dir_str);
+        match looprs::seed::seed_into(&dir, &options) {
+            Ok(report) => {
+                for f in &report.written {
+                    println!("wrote {}", f.display());
+                }
+                for f in &report.skipped {
+                    println!(
+                        "{} {} (exists; use --force to overwrite)",
+                        "skipped".dimmed(),
+                        f.display()
+                    );
                 }
                 std::process::exit(0);
             }
@@ -93,6 +112,46 @@ async fn main() -> Result<()> {
         }
     }
 
+    if matches!(args.get(1).map(String::as_str), Some("hooks")) {
+        if args.get(2).map(String::as_str) == Some("list") {
+            std::process::exit(list_hooks());
+        }
+        if args.get(2).map(String::as_str) != Some("validate") {
+            print_usage();
+            std::process::exit(1);
+        }
+        let dir_str = args.get(3).map(String::as_str).unwrap_or(".looprs/hooks");
+        let dir = looprs::seed::expand_tilde(dir_str);
+        std::process::exit(validate_hooks(&dir));
+    }
+
+    if matches!(args.get(1).map(String::as_str), Some("trace")) {
+        std::process::exit(print_traces(args.get(2).map(String::as_str)));
+    }
+
+    if matches!(args.get(1).map(String::as_str), Some("observations")) {
+        std::process::exit(list_observations(&args[2..]));
+    }
+
+    if matches!(args.get(1).map(String::as_str), Some("rules")) {
+        std::process::exit(list_rules());
+    }
+
+    if matches!(args.get(1).map(String::as_str), Some("config")) {
+        std::process::exit(print_effective_config());
+    }
+
+    if matches!(args.get(1).map(String::as_str), Some("models")) {
+        std::process::exit(print_local_models(&HttpClient::new()?).await);
+    }
+
+    if matches!(args.get(1).map(String::as_str), Some("providers")) {
+        for info in looprs::providers::list_providers(&HttpClient::new()?).await {
+            println!("{}", format_provider(&info));
+        }
+        std::process::exit(0);
+    }
+
     // Parse command-line arguments
     let cli_args = match CliArgs::parse() {
         Ok(args) => args,
@@ -128,28 +187,13 @@ async fn main() -> Result<()> {
     let provider_name = bootstrap.provider_name;
     let model = bootstrap.model;
     let provider_config = bootstrap.provider_config;
+    let http = bootstrap.http;
     let mut agent = bootstrap.agent;
 
     // Load hooks from both user (~/.looprs/hooks/) and repo (.looprs/hooks/) directories
     // Repo hooks override user hooks with same name (unless --no-hooks)
     if !cli_args.no_hooks {
-        let user_hooks_dir = dirs::home_dir()
-            .unwrap_or_default()
-            .join(".looprs")
-            .join("hooks");
-
-        let repo_hooks_dir = env::current_dir()
-            .ok()
-            .map(|d| d.join(".looprs").join("hooks"));
-
-        let user_dir = if user_hooks_dir.exists() {
-            Some(user_hooks_dir)
-        } else {
-            None
-        };
-
-        let repo_dir = repo_hooks_dir.filter(|d| d.exists());
-
+        let (user_dir, repo_dir) = hook_dirs();
         if let Ok(hooks) = HookRegistry::load_dual_source(user_dir.as_ref(), repo_dir.as_ref()) {
             agent = agent.with_hooks(hooks);
         }
@@ -208,11 +252,20 @@ async fn main() -> Result<()> {
     }
 
     // Load rules from both user and repo directories (repo overrides user)
-    let rules = looprs::RuleRegistry::load_all();
-    if rules.count() > 0 {
-        println!("📋 Loaded {} project rule(s)", rules.count());
+    if !cli_args.no_rules {
+        let rules = looprs::RuleRegistry::load_all();
+        if rules.count() > 0 {
+            println!("📋 Loaded {} project rule(s)", rules.count());
+        }
+        agent = agent.with_rules(rules);
+    }
+
+    // Free-form instructions: .looprs/instructions.md overrides ~/.looprs/instructions.md
+    let instructions = looprs::ProjectInstructions::load_default();
+    if let Some(ref loaded) = instructions {
+        println!("📝 Loaded instructions from {}", loaded.source.display());
     }
-    agent = agent.with_rules(rules);
+    agent = agent.with_instructions(instructions);
 
     let user_agents_dir = dirs::home_dir()
         .unwrap_or_default()
@@ -275,8 +328,8 @@ async fn run_scriptable(
         std::process::exit(1);
     };
 
-    // Display header unless quiet mode
-    if !cli_args.quiet {
+    // Display header unless quiet mode; dry runs print only the request
+    if !cli_args.quiet && !cli_args.dry_run {
         ui::header(
             provider_name,
             model,
@@ -284,6 +337,11 @@ async fn run_scriptable(
         );
     }
 
+    // No console to ask: hook approval prompts are skipped unless a policy is given
+    if let Some(answer) = cli_args.approval_policy() {
+        agent = agent.with_hook_approval(looprs::hooks::constant_approval(answer));
+    }
+
     let (prepared_prompt, metadata, selected_agent) =
         prepare_user_prompt(&prompt, &app_config, &agent_registry);
     if !metadata.is_empty() {
@@ -294,6 +352,18 @@ async fn run_scriptable(
     }
     agent.add_user_message(prepared_prompt);
 
+    if cli_args.dry_run {
+        let skipped = agent.hook_names_for_event(&Event::UserPromptSubmit);
+        if !skipped.is_empty() {
+            ui::warn(format!(
+                "Dry run: not running UserPromptSubmit hooks, so their context is missing: {}",
+                skipped.join(", ")
+            ));
+        }
+        ui::info_full(serde_json::to_string_pretty(&agent.build_request())?);
+        return Ok(());
+    }
+
     if let Err(e) = agent.run_turn().await {
         if cli_args.json_output {
             let error_json = serde_json::json!({
@@ -307,6 +377,12 @@ async fn run_scriptable(
         std::process::exit(1);
     }
 
+    if cli_args.json_output {
+        let mut result_json = serde_json::to_value(agent.last_turn_summary())?;
+        result_json["success"] = serde_json::Value::Bool(true);
+        ui::info_full(serde_json::to_string_pretty(&result_json)?);
+    }
+
     Ok(())
 }
 
@@ -323,6 +399,13 @@ async fn run_interactive(
     skill_registry: SkillRegistry,
     agent_registry: AgentRegistry,
 ) -> Result<()> {
+    // Interactive sessions confirm mutating tools per `tool_approval` in config.json
+    agent = agent.with_tool_approval(Box::new(console_approval_prompt));
+    // Stream bash output as it is printed rather than after the command exits
+    agent = agent.with_tool_output_sink(std::sync::Arc::new(ui::tool_output_line));
+    // Show each write/edit as a diff before it touches the file
+    agent = agent.with_change_preview();
+
     let command_items = build_command_items(&command_registry);
     let skill_items = build_skill_items(&skill_registry);
     let settings_items = setting_keys();
@@ -330,6 +413,7 @@ async fn run_interactive(
         commands: command_items,
         skills: skill_items,
         settings: settings_items,
+        models: std::sync::Mutex::new(looprs::providers::known_models(&http, &provider_name).await),
     });
 
     let mut rl = Editor::<ReplHelper, DefaultHistory>::new()?;
@@ -388,9 +472,27 @@ async fn run_interactive(
         }
     }
 
-    ui::info("Commands: /q (quit), /c (clear history), :set (settings)");
+    ui::info(
+        "Commands: /q (quit), /c (clear history), /tokens (usage), /skills, /retry, /checkpoint, /rewind, /save, /resume, :set (settings)",
+    );
 
     let mut turn_count: usize = 0;
+    // Provider whose model ids the `:set model` completion currently offers
+    let mut completion_provider = provider_name.clone();
+
+    let config_watcher = if app_config.watch_config {
+        match ConfigWatcher::watch(Path::new(".looprs")) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                ui::warn(format!(
+                    "Warning: not watching .looprs for config changes: {e}"
+                ));
+                None
+            }
+        }
+    } else {
+        None
+    };
 
     let claude_statusline = env::var("LOOPRS_STATUSLINE")
         .ok()
@@ -404,9 +506,11 @@ async fn run_interactive(
         let prompt = if claude_statusline {
             let git = looprs::git_info::collect();
             let ctx_tokens = agent.estimated_context_tokens();
-            let ctx_max = agent.provider_model_max_tokens();
-            let (in_tok, out_tok) = agent.session_tokens();
-            let cost = agent.provider_model_id().estimate_cost(in_tok, out_tok);
+            let ctx_max = agent.provider_model_id().context_window();
+            let cost = agent
+                .usage()
+                .estimated_cost(agent.provider_model_id())
+                .unwrap_or(0.0);
             ui::statusline_prompt_statusline(&cwd_basename, &git, &model, ctx_tokens, ctx_max, cost)
         } else {
             ui::statusline_prompt(
@@ -427,12 +531,78 @@ async fn run_interactive(
 
                 let _ = rl.add_history_entry(&line);
 
+                if config_watcher
+                    .as_ref()
+                    .is_some_and(ConfigWatcher::take_change)
+                {
+                    apply_config_reload(
+                        &http,
+                        &mut app_config,
+                        &mut provider_config,
+                        &mut provider_name,
+                        &mut model,
+                        &mut agent,
+                    )
+                    .await;
+                }
+
                 match command {
                     CliCommand::Quit => break,
                     CliCommand::Clear => {
                         agent.clear_history();
                         ui::info("● Conversation cleared");
                     }
+                    CliCommand::Tokens => {
+                        let usage = agent.usage();
+                        ui::info(format!(
+                            "Tokens: {} in / {} out ({} total, {} inferences) · {}",
+                            usage.input_tokens,
+                            usage.output_tokens,
+                            usage.total_tokens(),
+                            usage.inferences,
+                            format_cost(&usage, agent.provider_model_id())
+                        ));
+                    }
+                    CliCommand::Skills => print_skills(&skill_registry),
+                    CliCommand::Retry => {
+                        if !agent.rewind_to_last_user_message() {
+                            ui::warn("Nothing to retry");
+                            continue;
+                        }
+                        if let Err(e) = run_turn_interruptible(&mut agent).await {
+                            report_turn_error(&e);
+                        } else {
+                            turn_count += 1;
+                        }
+                    }
+                    CliCommand::Checkpoint => {
+                        let id = agent.checkpoint();
+                        let count = agent.checkpoints().last().map_or(0, |(_, n)| *n);
+                        ui::info(format!(
+                            "● Checkpoint {id} ({count} messages); /rewind {id} to return here"
+                        ));
+                    }
+                    CliCommand::Rewind(id) => rewind_to_checkpoint(&mut agent, id.as_deref()),
+                    CliCommand::Save(name) => match save_session(&agent, name.as_deref()) {
+                        Ok(path) => ui::info(format!("● Session saved to {}", path.display())),
+                        Err(e) => {
+                            ui::error(format!("{} {}", "✗".red().bold(), e.to_string().red()))
+                        }
+                    },
+                    CliCommand::Resume(name) => match resume_session(&mut agent, name.as_deref()) {
+                        Ok((path, count)) => ui::info(format!(
+                            "● Resumed {count} messages from {}",
+                            path.display()
+                        )),
+                        Err(e) => {
+                            ui::error(format!("{} {}", "✗".red().bold(), e.to_string().red()))
+                        }
+                    },
+                    CliCommand::Trash(trash_args) => {
+                        if let Err(e) = handle_trash_command(&trash_args, agent.working_dir()) {
+                            ui::error(format!("{} {}", "✗".red().bold(), e.to_string().red()));
+                        }
+                    }
                     CliCommand::InvokeSkill(skill_name, trailing) => {
                         if let Some(skill) = skill_registry.get(&skill_name) {
                             ui::info(format!("📚 Loading skill: {}", skill.name));
@@ -455,23 +625,27 @@ async fn run_interactive(
                                 ui::info(format!("Delegated prompt to agent role: {agent_name}"));
                             }
 
+                            if !skill.allowed_tools.is_empty() {
+                                ui::info(format!(
+                                    "Tools limited to: {}",
+                                    skill.allowed_tools.join(", ")
+                                ));
+                            }
+                            agent.restrict_tools_for_turn(skill.allowed_tools.clone());
                             agent.add_user_message(prepared_message);
 
-                            if let Err(e) = agent.run_turn().await {
-                                ui::error(format!(
-                                    "\n{} {}",
-                                    "✗".red().bold(),
-                                    e.to_string().red()
-                                ));
+                            if let Err(e) = run_turn_interruptible(&mut agent).await {
+                                report_turn_error(&e);
                             }
                         } else {
                             ui::warn(format!("Skill not found: {skill_name}"));
-                            ui::info("Available skills: /skills (not yet implemented)");
+                            ui::info("Run /skills to list available skills");
                         }
                     }
                     CliCommand::ColonCommand(cmd) => {
                         if let Err(e) = handle_colon_command(
                             &cmd,
+                            &http,
                             &mut app_config,
                             &mut provider_config,
                             &mut provider_name,
@@ -508,6 +682,7 @@ async fn run_interactive(
 
                         if let Some(cmd) = command_registry.get(cmd_name) {
                             let mut state = SessionState {
+                                http: http.clone(),
                                 provider_config: provider_config.clone(),
                                 provider_name: provider_name.clone(),
                                 model: model.clone(),
@@ -583,14 +758,21 @@ async fn run_interactive(
 
                         agent.add_user_message(prepared_message);
 
-                        if let Err(e) = agent.run_turn().await {
-                            ui::error(format!("\n{} {}", "✗".red().bold(), e.to_string().red()));
+                        if let Err(e) = run_turn_interruptible(&mut agent).await {
+                            report_turn_error(&e);
                         } else {
                             turn_count += 1;
                         }
                     }
                 }
 
+                if provider_name != completion_provider {
+                    completion_provider = provider_name.clone();
+                    let models = looprs::providers::known_models(&http, &provider_name).await;
+                    if let Some(helper) = rl.helper() {
+                        helper.set_models(models);
+                    }
+                }
                 if let Some(helper) = rl.helper_mut() {
                     helper.reset();
                 }
@@ -606,41 +788,626 @@ async fn run_interactive(
         }
     }
 
-    // Fire SessionEnd event and save observations
-    let event_ctx = EventContext::new();
+    // Fire SessionEnd event (with aggregate usage) and save observations
+    let usage = agent.usage();
+    if usage.inferences > 0 {
+        ui::info(format!(
+            "Session: {} tokens over {} inferences · {}",
+            usage.total_tokens(),
+            usage.inferences,
+            format_cost(&usage, agent.provider_model_id())
+        ));
+    }
+    let mut event_ctx = EventContext::new()
+        .with_metadata(
+            "usage.input_tokens".to_string(),
+            usage.input_tokens.to_string(),
+        )
+        .with_metadata(
+            "usage.output_tokens".to_string(),
+            usage.output_tokens.to_string(),
+        );
+    if let Some(cost) = usage.estimated_cost(agent.provider_model_id()) {
+        event_ctx =
+            event_ctx.with_metadata("usage.estimated_cost".to_string(), format!("{cost:.4}"));
+    }
     agent.fire_event(Event::SessionEnd, &event_ctx);
     let _ = agent.execute_hooks_for_event(&Event::SessionEnd, &event_ctx);
 
     Ok(())
 }
 
+/// `est. $0.0123`, or a note that the model has no pricing entry.
+fn format_cost(usage: &SessionUsage, model: &ModelId) -> String {
+    match usage.estimated_cost(model) {
+        Some(cost) => format!("est. ${cost:.4}"),
+        None => format!("cost unknown for {}", model.as_str()),
+    }
+}
+
+/// Run a REPL turn with Ctrl-C bound to cancelling it rather than quitting.
+async fn run_turn_interruptible(agent: &mut Agent) -> Result<(), looprs::AgentError> {
+    if let Some((estimate, budget)) = agent.context_warning() {
+        ui::warn(format!(
+            "Warning: context is ~{estimate} of {budget} tokens (defaults.max_context_tokens); older turns will be compacted, or /c to clear"
+        ));
+    }
+    let cancel = looprs::CancellationToken::new();
+    let watcher = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        })
+    };
+    let result = agent.run_turn_cancellable(&cancel).await;
+    watcher.abort();
+    result
+}
+
+/// Surface a failed turn. Hitting the tool-iteration cap or Ctrl-C is a soft stop, not a crash.
+fn report_turn_error(e: &looprs::AgentError) {
+    if matches!(
+        e,
+        looprs::AgentError::MaxIterationsExceeded(_) | looprs::AgentError::Cancelled
+    ) {
+        ui::warn(format!(
+            "\n{} {}",
+            "⚠".yellow().bold(),
+            e.to_string().yellow()
+        ));
+    } else {
+        ui::error(format!("\n{} {}", "✗".red().bold(), e.to_string().red()));
+    }
+}
+
+/// `looprs hooks validate [DIR]` — report each hook file; exit code 1 if any fail to parse.
+fn validate_hooks(dir: &std::path::Path) -> i32 {
+    let reports = match looprs::hooks::HookRegistry::validate_directory(dir) {
+        Ok(reports) => reports,
+        Err(e) => {
+            ui::error(format!("hooks validate: {}: {e}", dir.display()));
+            return 1;
+        }
+    };
+    if reports.is_empty() {
+        println!("No hook files in {}", dir.display());
+        return 0;
+    }
+
+    let mut failed = 0;
+    for report in &reports {
+        match &report.outcome {
+            Ok(hook) => println!(
+                "{} {}: {} -> {} ({} action{}){}",
+                "✓".green(),
+                report.path.display(),
+                hook.name,
+                hook.trigger,
+                hook.actions.len(),
+                if hook.actions.len() == 1 { "" } else { "s" },
+                if hook.disabled { " [disabled]" } else { "" }
+            ),
+            Err(e) => {
+                failed += 1;
+                println!("{} {}: {e}", "✗".red(), report.path.display());
+            }
+        }
+        for warning in &report.warnings {
+            println!("    {} {warning}", "warning:".yellow());
+        }
+    }
+
+    println!("\n{} file(s) checked, {failed} failed", reports.len());
+    i32::from(failed > 0)
+}
+
+/// Existing user (`~/.looprs/hooks`) and repo (`.looprs/hooks`) hook directories.
+fn hook_dirs() -> (Option<std::path::PathBuf>, Option<std::path::PathBuf>) {
+    let user_hooks_dir = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".looprs")
+        .join("hooks");
+
+    let repo_hooks_dir = env::current_dir()
+        .ok()
+        .map(|d| d.join(".looprs").join("hooks"));
+
+    let user_dir = if user_hooks_dir.exists() {
+        Some(user_hooks_dir)
+    } else {
+        None
+    };
+
+    let repo_dir = repo_hooks_dir.filter(|d| d.exists());
+    (user_dir, repo_dir)
+}
+
+/// Print the hooks a session would register, with their source and any user
+/// hooks a repo hook overrode.
+// qual:allow(iosp) reason: "I/O boundary — loads hook directories, prints to stdout"
+fn list_hooks() -> i32 {
+    let (user_dir, repo_dir) = hook_dirs();
+    let registry = match HookRegistry::load_dual_source(user_dir.as_ref(), repo_dir.as_ref()) {
+        Ok(registry) => registry,
+        Err(e) => {
+            ui::error(format!("hooks list: {e}"));
+            return 1;
+        }
+    };
+    let hooks = registry.all_hooks();
+    if hooks.is_empty() && registry.disabled().is_empty() {
+        println!("No hooks registered");
+        return 0;
+    }
+    for hook in hooks {
+        println!("{} {} ({})", hook.trigger, hook.name, hook.source.as_str());
+    }
+    for hook in registry.disabled() {
+        println!(
+            "{} {} ({}, {})",
+            hook.trigger,
+            hook.name,
+            hook.source.as_str(),
+            "disabled".yellow()
+        );
+    }
+    for hook in registry.overridden() {
+        println!(
+            "{} user hook {} {} is overridden by the repo hook of the same name",
+            "note:".dimmed(),
+            hook.trigger,
+            hook.name
+        );
+    }
+    0
+}
+
+/// Print the rules a session injects, in prompt order, with their source and
+/// any user rules a repo rule overrode.
+// qual:allow(iosp) reason: "I/O boundary — loads rule directories, prints to stdout"
+fn list_rules() -> i32 {
+    let registry = looprs::RuleRegistry::load_all();
+    let described = registry.describe();
+    if described.is_empty() {
+        println!("No rules loaded");
+        return 0;
+    }
+    for entry in described.iter().filter(|d| !d.overridden) {
+        let rule = entry.rule;
+        println!(
+            "{} ({}) {}",
+            rule.id,
+            rule.origin.as_str(),
+            rule.source.display().to_string().dimmed()
+        );
+    }
+    for entry in described.iter().filter(|d| d.overridden) {
+        println!(
+            "{} user rule {} is overridden by the repo rule of the same name",
+            "note:".dimmed(),
+            entry.rule.id
+        );
+    }
+    0
+}
+
+/// Print every effective setting with the env var or file it came from.
+// qual:allow(iosp) reason: "I/O boundary — loads config files, prints to stdout"
+fn print_effective_config() -> i32 {
+    let configs = AppConfig::load().and_then(|app| Ok((app, ProviderConfig::load()?)));
+    let (app_config, provider_config) = match configs {
+        Ok(configs) => configs,
+        Err(e) => {
+            ui::error(format!("config: {e:#}"));
+            return 1;
+        }
+    };
+    // Raw config.json tells values the user set apart from filled-in defaults.
+    let app_file = std::fs::read_to_string(APP_CONFIG_FILE)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or(serde_json::Value::Null);
+
+    let settings = looprs::effective_config::effective_settings(
+        &app_config,
+        &app_file,
+        &provider_config,
+        &|name| env::var(name).ok(),
+    );
+    let width = settings.iter().map(|s| s.key.len()).max().unwrap_or(0);
+    for setting in &settings {
+        println!(
+            "{:width$} = {}  {}",
+            setting.key,
+            setting.value,
+            format!("({})", setting.source).dimmed()
+        );
+    }
+    0
+}
+
+/// List recent observations, one rolled-up line each (`read src/x.rs (x3)`).
+/// `--tool NAME` keeps one tool; any other argument is a search query.
+// qual:allow(iosp) reason: "I/O boundary — reads the observations database, prints to stdout"
+fn list_observations(args: &[String]) -> i32 {
+    let mut filter = looprs::ObservationFilter::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tool" => match args.next() {
+                Some(tool) => filter.tool = Some(tool.clone()),
+                None => {
+                    ui::error("observations: --tool needs a tool name");
+                    return 1;
+                }
+            },
+            flag if flag.starts_with('-') => {
+                ui::error(format!("observations: unknown flag '{flag}'"));
+                return 1;
+            }
+            query => filter.query = Some(query.to_string()),
+        }
+    }
+    let path = looprs::ObservationManager::default_db_path();
+    if !path.exists() {
+        println!("No observations recorded");
+        return 0;
+    }
+    let entries = match looprs::ObservationManager::load_observations(&path, &filter) {
+        Ok(entries) => entries,
+        Err(e) => {
+            ui::error(format!("observations: {e:#}"));
+            return 1;
+        }
+    };
+    if entries.is_empty() {
+        println!("No matching observations");
+    }
+    for entry in &entries {
+        println!("{} {}", entry.session_id.dimmed(), entry.summary());
+    }
+    0
+}
+
+/// Pretty-print the turn traces of `session`, or list the sessions that have
+/// traces when none is given.
+// qual:allow(iosp) reason: "I/O boundary — reads trace files, prints to stdout"
+fn print_traces(session: Option<&str>) -> i32 {
+    let Some(session) = session else {
+        let mut sessions: Vec<String> = std::fs::read_dir(looprs::observability::trace_dir())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "jsonl" {
+                    return None;
+                }
+                path.file_stem()?.to_str().map(str::to_string)
+            })
+            .collect();
+        sessions.sort();
+        if sessions.is_empty() {
+            println!("No traces recorded");
+        }
+        for id in sessions {
+            println!("{id}");
+        }
+        return 0;
+    };
+    if session.contains(['/', '\\']) || session.starts_with('.') {
+        ui::error(format!("trace: invalid session id: {session}"));
+        return 1;
+    }
+    let traces = match looprs::trace::read_traces(session) {
+        Ok(traces) => traces,
+        Err(e) => {
+            ui::error(format!("trace: {e:#}"));
+            return 1;
+        }
+    };
+    if traces.is_empty() {
+        ui::error(format!("trace: no traces for session {session}"));
+        return 1;
+    }
+    for (idx, trace) in traces.iter().enumerate() {
+        let request = &trace.request;
+        let response = &trace.response;
+        println!(
+            "{} {}",
+            format!("#{}", idx + 1).bold(),
+            trace
+                .timestamp
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .dimmed()
+        );
+        println!(
+            "  request:  {} | {} messages, {} tools, system {} bytes, max_tokens {}",
+            request.model,
+            request.message_count,
+            request.tool_count,
+            request.system_len,
+            request.max_tokens
+        );
+        println!(
+            "  response: {} | {} in / {} out tokens | {}",
+            response.stop_reason,
+            response.usage.input_tokens,
+            response.usage.output_tokens,
+            response.block_kinds.join(", ")
+        );
+    }
+    0
+}
+
+/// List the models installed in the local Ollama instance, one per line.
+// qual:allow(iosp) reason: "I/O boundary — queries Ollama, prints to stdout"
+async fn print_local_models(http: &HttpClient) -> i32 {
+    match looprs::providers::local::LocalProvider::list_models(http).await {
+        Ok(models) if models.is_empty() => {
+            ui::warn("No models installed in Ollama (try `ollama pull <model>`)");
+            0
+        }
+        Ok(models) => {
+            for name in &models {
+                println!("{name}");
+            }
+            0
+        }
+        Err(e) => {
+            ui::error(format!("models: {e}"));
+            1
+        }
+    }
+}
+
+/// `✓ gemini (google)   gemini-2.0-flash`: whether the provider is configured,
+/// the names that select it, and its default model.
+fn format_provider(info: &looprs::providers::ProviderInfo) -> String {
+    let mark = if info.configured { "✓" } else { "-" };
+    let names = if info.aliases.is_empty() {
+        info.name.to_string()
+    } else {
+        format!("{} ({})", info.name, info.aliases.join(", "))
+    };
+    let model = info
+        .default_model
+        .as_ref()
+        .map_or_else(|| "(set MODEL)".to_string(), ToString::to_string);
+    format!("{mark} {names:<28} {model}")
+}
+
 fn print_usage() {
     ui::error_full(
-        r#"Usage: looprs [OPTIONS] | looprs seed [DIR]
+        r#"Usage: looprs [OPTIONS] | looprs seed [--force] [DIR] [NAME] | looprs hooks validate [DIR] | looprs hooks list | looprs rules | looprs observations [--tool NAME] [QUERY] | looprs trace [SESSION] | looprs models | looprs providers | looprs config
 
 COMMANDS:
-  seed [DIR]             Write example config files to DIR (default: .looprs).
-                         Use ~ for home (e.g. ~/.looprs). Does not overwrite.
+  seed [--force] [DIR] [NAME]
+                         Write example config files to DIR (default: .looprs).
+                         Use ~ for home (e.g. ~/.looprs). NAME (config or
+                         provider) seeds just that example; write ./config to
+                         seed into a directory with that name. Existing
+                         examples are skipped unless --force is given.
+  hooks validate [DIR]   Parse every hook in DIR (default: .looprs/hooks) and
+                         report problems. Exits non-zero if any fail to parse.
+  hooks list             Show the hooks a session registers, whether each came
+                         from ~/.looprs/hooks (user) or .looprs/hooks (repo),
+                         and which user hooks a repo hook overrides.
+  rules                  Show the rules injected into the system prompt, in
+                         order, whether each came from ~/.looprs/rules (user)
+                         or .looprs/rules (repo), and which user rules a repo
+                         rule overrides.
+  observations [--tool NAME] [QUERY]
+                         List the most recent tool observations from
+                         ~/.looprs/observations.db, repeated calls rolled up
+                         as e.g. "read src/x.rs (x3)".
+  trace [SESSION]        Print the turn traces recorded for SESSION (request
+                         size, stop reason, token usage, block kinds), or list
+                         sessions with traces when SESSION is omitted.
+  models                 List models installed in the local Ollama instance
+                         (OLLAMA_HOST, default http://localhost:11434).
+  providers              List provider names accepted by PROVIDER and
+                         provider.json, whether each is configured (API key
+                         set, or Ollama reachable), and its default model.
+  config                 Print the effective settings and where each comes
+                         from (env var, provider.json, config.json, default).
 
 OPTIONS:
-  -p, --prompt <TEXT>    Run with single prompt and exit (scriptable mode)
-  -f, --file <FILE>      Read prompt from file
+  -p, --prompt <TEXT>    Run with single prompt and exit (scriptable mode);
+                         `-p -` reads the prompt from stdin
+  -f, --file <FILE>      Read prompt from file (piped stdin is used when
+                         neither -f nor -p is given)
+  --stdin                Append stdin to the -p prompt as a fenced block
   -m, --model <MODEL>    Override default model
   -q, --quiet            Suppress context and observations display
   --no-hooks             Skip loading hooks from ~/.looprs/hooks/
-  --json                 Output response as structured JSON
+  --no-rules             Skip loading rules, so no project guidelines are
+                         added to the system prompt
+  --approve-all          In scriptable mode, approve every hook action marked
+                         requires_approval (default: skip them)
+  --deny-all             In scriptable mode, decline every such hook action
+  --json                 Output the turn as JSON: success, response,
+                         tool_calls (name, input, output, is_error), usage,
+                         and stop_reason. Errors print {success, error}.
+  --dry-run              Print the request that would be sent (system prompt,
+                         messages, tools, max_tokens) as JSON and exit.
+                         UserPromptSubmit hooks are listed, not run.
 
 EXAMPLES:
   looprs                           # Interactive mode
   looprs seed                      # Create .looprs/config.json.example, etc.
   looprs seed ~/.looprs            # Seed home config dir
+  looprs seed --force provider     # Refresh provider.json.example
+  looprs hooks validate            # Check .looprs/hooks in CI
+  looprs models                    # Pick a model for `:set model`
   looprs -p "explain closures"     # Run single prompt and exit
+  git diff | looprs -p "review" --stdin  # Append piped stdin to the prompt
 "#,
     );
 }
 
+fn sessions_dir(agent: &looprs::Agent) -> std::path::PathBuf {
+    agent.working_dir().join(".looprs").join("sessions")
+}
+
+fn session_file(agent: &looprs::Agent, name: &str) -> Result<std::path::PathBuf> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        anyhow::bail!("Invalid session name: {name}");
+    }
+    Ok(sessions_dir(agent).join(format!("{name}.json")))
+}
+
+/// `/save [name]` — defaults to `session-<unix secs>` under `.looprs/sessions/`.
+fn save_session(agent: &looprs::Agent, name: Option<&str>) -> Result<std::path::PathBuf> {
+    let name = name.map(str::to_string).unwrap_or_else(|| {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        format!("session-{secs}")
+    });
+    let path = session_file(agent, &name)?;
+    agent.save_session(&path)?;
+    Ok(path)
+}
+
+/// `/resume [name]` — without a name, picks the most recently saved session.
+fn resume_session(
+    agent: &mut looprs::Agent,
+    name: Option<&str>,
+) -> Result<(std::path::PathBuf, usize)> {
+    let dir = sessions_dir(agent);
+    let path = match name {
+        Some(name) => session_file(agent, name)?,
+        None => std::fs::read_dir(&dir)
+            .map_err(|_| anyhow::anyhow!("No saved sessions in {}", dir.display()))?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .max_by_key(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+            .ok_or_else(|| anyhow::anyhow!("No saved sessions in {}", dir.display()))?,
+    };
+    if !path.exists() {
+        anyhow::bail!("Session not found: {}", path.display());
+    }
+    let saved = agent.load_session(&path)?;
+    Ok((path, saved.messages.len()))
+}
+
+/// `/trash list | empty | restore <entry>` over the working-dir `.looprs/trash/`.
+fn handle_trash_command(args: &str, working_dir: &std::path::Path) -> Result<()> {
+    let trash = looprs::trash::Trash::new(working_dir);
+    let mut parts = args.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (None | Some("list"), _) => {
+            let entries = trash.list()?;
+            if entries.is_empty() {
+                ui::info("Trash is empty");
+                return Ok(());
+            }
+            ui::section_title(&format!("Trash ({} entries):", entries.len()));
+            for entry in entries {
+                ui::info(format!(
+                    "  {}  {}  {}  {}",
+                    entry.id.cyan(),
+                    entry.trashed_at.format("%Y-%m-%d %H:%M:%S"),
+                    format_size(entry.size_bytes),
+                    entry.original_path.display()
+                ));
+            }
+        }
+        (Some("empty"), _) => {
+            let removed = trash.empty()?;
+            ui::info(format!("● Permanently removed {removed} trash entries"));
+        }
+        (Some("restore"), Some(key)) => {
+            let restored = trash.restore(key)?;
+            ui::info(format!("● Restored {}", restored.display()));
+        }
+        (Some("restore"), None) => ui::warn("Usage: /trash restore <entry-id|path>"),
+        (Some(other), _) => {
+            ui::warn(format!("Unknown /trash subcommand: {other}"));
+            ui::info("Try: /trash list | /trash empty | /trash restore <entry>");
+        }
+    }
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    const KIB: u64 = 1024;
+    const MIB: u64 = KIB * 1024;
+    match bytes {
+        b if b >= MIB => format!("{:.1} MiB", b as f64 / MIB as f64),
+        b if b >= KIB => format!("{:.1} KiB", b as f64 / KIB as f64),
+        b => format!("{b} B"),
+    }
+}
+
+/// `/skills` — every loaded skill with its triggers and origin.
+fn print_skills(skill_registry: &SkillRegistry) {
+    let summaries = skill_registry.describe();
+    if summaries.is_empty() {
+        ui::info("No skills loaded (add SKILL.md files under .looprs/skills/)");
+        return;
+    }
+    for summary in summaries {
+        ui::info(format!(
+            "  {} [{}] — {}",
+            format!("${}", summary.name).cyan(),
+            summary.source,
+            summary.description.as_deref().unwrap_or("(no description)")
+        ));
+        ui::info(format!("      triggers: {}", summary.triggers.join(", ")));
+    }
+}
+
+/// `/rewind [id]` — without an id, returns to the most recent checkpoint.
+fn rewind_to_checkpoint(agent: &mut Agent, id: Option<&str>) {
+    let id = match id {
+        Some(raw) => match raw.parse::<looprs::CheckpointId>() {
+            Ok(id) => id,
+            Err(_) => {
+                ui::warn(format!("Invalid checkpoint id: {raw}"));
+                return;
+            }
+        },
+        None => match agent.checkpoints().last() {
+            Some((id, _)) => *id,
+            None => {
+                ui::warn("No checkpoints; use /checkpoint first");
+                return;
+            }
+        },
+    };
+    if agent.restore(id) {
+        let count = agent.checkpoints().last().map_or(0, |(_, n)| *n);
+        ui::info(format!("● Rewound to checkpoint {id} ({count} messages)"));
+    } else {
+        let available: Vec<String> = agent
+            .checkpoints()
+            .iter()
+            .map(|(id, _)| id.to_string())
+            .collect();
+        ui::warn(format!(
+            "Unknown checkpoint {id}; available: {}",
+            if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            }
+        ));
+    }
+}
+
 fn build_command_items(command_registry: &CommandRegistry) -> Vec<String> {
-    let mut items = Vec::new();
+    let mut items = vec![
+        "/skills".to_string(),
+        "/retry".to_string(),
+        "/checkpoint".to_string(),
+        "/rewind".to_string(),
+    ];
     for cmd in command_registry.list() {
         items.push(format!("/{}", cmd.name));
         for alias in &cmd.aliases {
@@ -668,9 +1435,11 @@ fn setting_keys() -> Vec<String> {
         "provider",
         "model",
         "max_tokens",
+        "temperature",
         "timeout_secs",
         "defaults.max_context_tokens",
         "defaults.temperature",
+        "defaults.top_p",
         "defaults.timeout_seconds",
         "fs_mode",
     ]
@@ -679,29 +1448,36 @@ fn setting_keys() -> Vec<String> {
     .collect()
 }
 
+/// The provider.json section `:set`/`:get`/`:unset` use for `provider`.
+/// Providers without a section of their own (e.g. gemini) share `openai`.
+fn provider_section(provider: &str) -> &'static str {
+    ProviderConfig::section_name(provider).unwrap_or("openai")
+}
+
 fn provider_settings_mut<'a>(
     config: &'a mut ProviderConfig,
     provider: &str,
 ) -> &'a mut ProviderSettings {
-    match provider {
-        "anthropic" => config
-            .anthropic
-            .get_or_insert_with(ProviderSettings::default),
-        "openai" => config.openai.get_or_insert_with(ProviderSettings::default),
-        "local" | "ollama" => config.local.get_or_insert_with(ProviderSettings::default),
-        _ => config.openai.get_or_insert_with(ProviderSettings::default),
-    }
+    let section = match provider_section(provider) {
+        "anthropic" => &mut config.anthropic,
+        "gemini" => &mut config.gemini,
+        "local" => &mut config.local,
+        "replay" => &mut config.replay,
+        _ => &mut config.openai,
+    };
+    section.get_or_insert_with(ProviderSettings::default)
 }
 
 fn provider_settings_ref<'a>(
     config: &'a ProviderConfig,
     provider: &str,
 ) -> Option<&'a ProviderSettings> {
-    match provider {
+    match provider_section(provider) {
         "anthropic" => config.anthropic.as_ref(),
-        "openai" => config.openai.as_ref(),
-        "local" | "ollama" => config.local.as_ref(),
-        _ => None,
+        "gemini" => config.gemini.as_ref(),
+        "local" => config.local.as_ref(),
+        "replay" => config.replay.as_ref(),
+        _ => config.openai.as_ref(),
     }
 }
 
@@ -710,16 +1486,26 @@ fn build_runtime_settings(
     provider_config: &ProviderConfig,
     provider_name: &str,
 ) -> looprs::RuntimeSettings {
-    let max_tokens_override = provider_config.merged_settings(provider_name).max_tokens;
+    let merged = provider_config.merged_settings(provider_name);
     looprs::RuntimeSettings {
-        defaults: app_config.defaults.clone(),
-        max_tokens_override,
+        defaults: looprs::app_config::DefaultsConfig {
+            temperature: merged.temperature.or(app_config.defaults.temperature),
+            ..app_config.defaults.clone()
+        },
+        max_tokens_override: merged.max_tokens,
         fs_mode: app_config.agents.fs_mode,
+        tool_approval: app_config.tool_approval.clone(),
+        observations: app_config.observations.clone(),
+        enabled_tools: app_config.enabled_tools.clone(),
+        system_prompt_max_chars: app_config.system_prompt_max_chars,
+        update_mode_commands: app_config.agents.update_mode_commands.clone(),
+        ..looprs::RuntimeSettings::default()
     }
 }
 
 async fn handle_colon_command(
     cmd: &str,
+    http: &HttpClient,
     app_config: &mut AppConfig,
     provider_config: &mut ProviderConfig,
     provider_name: &mut String,
@@ -734,8 +1520,27 @@ async fn handle_colon_command(
 
     match action {
         "help" => {
-            ui::info("Usage: :set <key> <value>, :get <key>, :unset <key>");
-            ui::info("Keys: provider, model, max_tokens, timeout_secs, fs_mode, defaults.*");
+            ui::info(
+                "Usage: :set [--save] <key> <value>, :get <key>, :unset <key>, :models, :providers",
+            );
+            ui::info(
+                "Keys: provider, model, max_tokens, temperature, timeout_secs, fs_mode, defaults.*, file_references.*",
+            );
+        }
+        "models" => match looprs::providers::local::LocalProvider::list_models(http).await {
+            Ok(models) if models.is_empty() => ui::info("No models installed in Ollama"),
+            Ok(models) => {
+                for name in &models {
+                    let marker = if name == model.as_str() { "*" } else { " " };
+                    ui::info(format!("{marker} {name}"));
+                }
+            }
+            Err(e) => ui::warn(format!("Could not list Ollama models: {e}")),
+        },
+        "providers" => {
+            for info in looprs::providers::list_providers(http).await {
+                ui::info(format_provider(&info));
+            }
         }
         "get" => {
             let key = parts.next();
@@ -755,6 +1560,9 @@ async fn handle_colon_command(
                         if let Some(max_tokens) = settings.max_tokens {
                             ui::info(format!("max_tokens = {max_tokens}"));
                         }
+                        if let Some(temperature) = settings.temperature {
+                            ui::info(format!("temperature = {temperature}"));
+                        }
                         if let Some(timeout) = settings.timeout_secs {
                             ui::info(format!("timeout_secs = {timeout}"));
                         }
@@ -765,6 +1573,9 @@ async fn handle_colon_command(
                     if let Some(v) = app_config.defaults.temperature {
                         ui::info(format!("defaults.temperature = {v}"));
                     }
+                    if let Some(v) = app_config.defaults.top_p {
+                        ui::info(format!("defaults.top_p = {v}"));
+                    }
                     if let Some(v) = app_config.defaults.timeout_seconds {
                         ui::info(format!("defaults.timeout_seconds = {v}"));
                     }
@@ -794,14 +1605,21 @@ async fn handle_colon_command(
             ui::info(format!("Unset {key}"));
         }
         "set" => {
-            let key = parts.next().unwrap_or("");
+            let mut key = parts.next().unwrap_or("");
+            let persist = key == "--save";
+            if persist {
+                key = parts.next().unwrap_or("");
+            }
             if key.is_empty() {
-                ui::warn("Usage: :set <key> <value>");
+                ui::warn("Usage: :set [--save] <key> <value>");
                 return Ok(());
             }
+            if persist && setting_locations(key, provider_name).is_empty() {
+                anyhow::bail!("{key} cannot be saved to a config file");
+            }
             let value = parts.collect::<Vec<_>>().join(" ");
             if value.is_empty() {
-                ui::warn("Usage: :set <key> <value>");
+                ui::warn("Usage: :set [--save] <key> <value>");
                 return Ok(());
             }
 
@@ -839,6 +1657,16 @@ async fn handle_colon_command(
                     let settings = provider_settings_mut(provider_config, &target_provider);
                     settings.max_tokens = Some(parsed);
                 }
+                "temperature" => {
+                    let parsed = value.parse::<f32>()?;
+                    SamplingParams {
+                        temperature: Some(parsed),
+                        top_p: None,
+                    }
+                    .check_for(&target_provider)?;
+                    let settings = provider_settings_mut(provider_config, &target_provider);
+                    settings.temperature = Some(parsed);
+                }
                 "timeout_secs" => {
                     let parsed = value.parse::<u64>()?;
                     let settings = provider_settings_mut(provider_config, &target_provider);
@@ -848,11 +1676,32 @@ async fn handle_colon_command(
                     app_config.defaults.max_context_tokens = Some(value.parse::<u32>()?);
                 }
                 "defaults.temperature" => {
-                    app_config.defaults.temperature = Some(value.parse::<f32>()?);
+                    let parsed = value.parse::<f32>()?;
+                    SamplingParams {
+                        temperature: Some(parsed),
+                        top_p: None,
+                    }
+                    .check_for(provider_name)?;
+                    app_config.defaults.temperature = Some(parsed);
+                }
+                "defaults.top_p" => {
+                    let parsed = value.parse::<f32>()?;
+                    SamplingParams {
+                        temperature: None,
+                        top_p: Some(parsed),
+                    }
+                    .check_for(provider_name)?;
+                    app_config.defaults.top_p = Some(parsed);
                 }
                 "defaults.timeout_seconds" => {
                     app_config.defaults.timeout_seconds = Some(value.parse::<u64>()?);
                 }
+                "file_references.max_file_bytes" => {
+                    app_config.file_references.max_file_bytes = value.parse::<u64>()?;
+                }
+                "file_references.max_total_bytes" => {
+                    app_config.file_references.max_total_bytes = value.parse::<u64>()?;
+                }
                 _ => {
                     ui::warn(format!("Unknown setting: {key}"));
                     return Ok(());
@@ -860,14 +1709,23 @@ async fn handle_colon_command(
             }
 
             save_configs(app_config, provider_config)?;
+            if persist {
+                let target_provider = provider_config
+                    .provider
+                    .clone()
+                    .unwrap_or_else(|| provider_name.clone());
+                for file in persist_setting(key, &target_provider, app_config, provider_config)? {
+                    ui::info(format!("Saved {key} to {file}"));
+                }
+            }
 
             if reload_provider {
-                let provider =
-                    create_provider_with_overrides(ProviderOverrides { model: None }).await?;
+                let provider = rebuild_provider(http, app_config, provider_config).await?;
                 *provider_name = provider.name().to_string();
                 *model = provider.model().as_str().to_string();
                 agent.set_provider(provider);
                 ui::info(format!("Switched to {provider_name}/{model}"));
+                runtime::warn_if_unknown_model(provider_name, &ModelId::new(model.as_str()));
             }
 
             let runtime = build_runtime_settings(app_config, provider_config, provider_name);
@@ -898,6 +1756,9 @@ fn get_setting_value(
         "max_tokens" => provider_settings_ref(provider_config, provider_name)
             .and_then(|s| s.max_tokens)
             .map(|v| v.to_string()),
+        "temperature" => provider_settings_ref(provider_config, provider_name)
+            .and_then(|s| s.temperature)
+            .map(|v| v.to_string()),
         "timeout_secs" => provider_settings_ref(provider_config, provider_name)
             .and_then(|s| s.timeout_secs)
             .map(|v| v.to_string()),
@@ -906,8 +1767,15 @@ fn get_setting_value(
             .max_context_tokens
             .map(|v| v.to_string()),
         "defaults.temperature" => app_config.defaults.temperature.map(|v| v.to_string()),
+        "defaults.top_p" => app_config.defaults.top_p.map(|v| v.to_string()),
         "defaults.timeout_seconds" => app_config.defaults.timeout_seconds.map(|v| v.to_string()),
         "fs_mode" => Some(app_config.agents.fs_mode.as_str().to_string()),
+        "file_references.max_file_bytes" => {
+            Some(app_config.file_references.max_file_bytes.to_string())
+        }
+        "file_references.max_total_bytes" => {
+            Some(app_config.file_references.max_total_bytes.to_string())
+        }
         _ => None,
     }
 }
@@ -928,24 +1796,182 @@ fn unset_setting(
             let settings = provider_settings_mut(provider_config, provider_name);
             settings.max_tokens = None;
         }
+        "temperature" => {
+            let settings = provider_settings_mut(provider_config, provider_name);
+            settings.temperature = None;
+        }
         "timeout_secs" => {
             let settings = provider_settings_mut(provider_config, provider_name);
             settings.timeout_secs = None;
         }
         "defaults.max_context_tokens" => app_config.defaults.max_context_tokens = None,
         "defaults.temperature" => app_config.defaults.temperature = None,
+        "defaults.top_p" => app_config.defaults.top_p = None,
         "defaults.timeout_seconds" => app_config.defaults.timeout_seconds = None,
         "fs_mode" => app_config.agents.fs_mode = looprs::FsMode::Write,
+        "file_references.max_file_bytes" => {
+            app_config.file_references.max_file_bytes =
+                looprs::app_config::FileReferencesConfig::default().max_file_bytes;
+        }
+        "file_references.max_total_bytes" => {
+            app_config.file_references.max_total_bytes =
+                looprs::app_config::FileReferencesConfig::default().max_total_bytes;
+        }
         _ => {}
     }
 }
 
+/// Build the session provider from `provider_config`, wrapped in the
+/// `fallback_models` chain as at startup.
+async fn rebuild_provider(
+    http: &HttpClient,
+    app_config: &AppConfig,
+    provider_config: &ProviderConfig,
+) -> Result<Box<dyn LLMProvider>, looprs::ProviderError> {
+    let provider = create_provider_from_config(
+        http,
+        provider_config,
+        ProviderOverrides {
+            model: None,
+            sampling: app_config.defaults.sampling(),
+        },
+    )
+    .await?;
+    Ok(with_fallbacks(
+        http,
+        provider,
+        &app_config.fallback_models,
+        app_config.defaults.sampling(),
+        provider_config,
+    )
+    .await)
+}
+
+/// Re-read config.json and provider.json after an external edit. Runtime
+/// settings always follow the files; the provider is rebuilt only when the
+/// provider, its model, or `fallback_models` changed. A bad edit keeps the
+/// current config.
+// qual:allow(iosp) reason: "I/O boundary — reloads config files, may rebuild the provider"
+async fn apply_config_reload(
+    http: &HttpClient,
+    app_config: &mut AppConfig,
+    provider_config: &mut ProviderConfig,
+    provider_name: &mut String,
+    model: &mut String,
+    agent: &mut Agent,
+) {
+    let (new_app_config, new_provider_config) =
+        match looprs::config_watch::reload_configs(Path::new(".looprs")) {
+            Ok(configs) => configs,
+            Err(e) => {
+                ui::warn(format!("Warning: ignoring config change: {e:#}"));
+                return;
+            }
+        };
+
+    let provider_changed = new_provider_config.provider != provider_config.provider
+        || new_provider_config.merged_settings(provider_name).model
+            != provider_config.merged_settings(provider_name).model
+        || new_app_config.fallback_models != app_config.fallback_models;
+    *app_config = new_app_config;
+    *provider_config = new_provider_config;
+    looprs::model_info::set_overrides(app_config.model_limits.clone());
+
+    if provider_changed {
+        match rebuild_provider(http, app_config, provider_config).await {
+            Ok(provider) => {
+                *provider_name = provider.name().to_string();
+                *model = provider.model().as_str().to_string();
+                agent.set_provider(provider);
+                ui::info(format!(
+                    "Config changed; switched to {provider_name}/{model}"
+                ));
+                runtime::warn_if_unknown_model(provider_name, &ModelId::new(model.as_str()));
+            }
+            Err(e) => ui::warn(format!(
+                "Warning: keeping {provider_name}/{model}; new provider config failed: {e}"
+            )),
+        }
+    }
+
+    let runtime = build_runtime_settings(app_config, provider_config, provider_name);
+    agent.set_runtime_settings(runtime);
+    agent.set_file_ref_policy(app_config.file_ref_policy());
+    agent.set_system_prompt(app_config.system_prompt_template.clone());
+    ui::info("Reloaded config from .looprs");
+}
+
 /// Config files are user-owned; we no longer write config.json or provider.json.
-/// Session changes from :set/:unset apply in-memory only.
+/// Session changes from :set/:unset apply in-memory only unless the user asks
+/// for `:set --save`, which goes through `persist_setting`.
 fn save_configs(_app_config: &AppConfig, _provider_config: &ProviderConfig) -> Result<()> {
     Ok(())
 }
 
+const PROVIDER_CONFIG_FILE: &str = ".looprs/provider.json";
+const APP_CONFIG_FILE: &str = ".looprs/config.json";
+
+/// Where `key` lives on disk: the config file and the JSON path within it.
+/// Provider-scoped keys go under `provider`'s section, the same one
+/// `provider_settings_mut` writes. Empty for keys that cannot be saved.
+fn setting_locations(key: &str, provider: &str) -> Vec<(&'static str, Vec<String>)> {
+    let section = provider_section(provider);
+    match key {
+        "provider" => vec![(PROVIDER_CONFIG_FILE, vec!["provider".to_string()])],
+        "llm" => vec![
+            (PROVIDER_CONFIG_FILE, vec!["provider".to_string()]),
+            (
+                PROVIDER_CONFIG_FILE,
+                vec![section.to_string(), "model".to_string()],
+            ),
+        ],
+        "model" | "max_tokens" | "temperature" | "timeout_secs" => vec![(
+            PROVIDER_CONFIG_FILE,
+            vec![section.to_string(), key.to_string()],
+        )],
+        _ if key.starts_with("defaults.") || key.starts_with("file_references.") => vec![(
+            APP_CONFIG_FILE,
+            key.split('.').map(str::to_string).collect(),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Write the in-memory value of `key` into its config file, leaving every
+/// other key in the file untouched. Returns the files written.
+// qual:allow(iosp) reason: "I/O boundary — patches user config files on request"
+fn persist_setting(
+    key: &str,
+    provider: &str,
+    app_config: &AppConfig,
+    provider_config: &ProviderConfig,
+) -> Result<Vec<&'static str>> {
+    let locations = setting_locations(key, provider);
+    if locations.is_empty() {
+        anyhow::bail!("{key} cannot be saved to a config file");
+    }
+    let provider_json = serde_json::to_value(provider_config)?;
+    let app_json = serde_json::to_value(app_config)?;
+    let mut written = Vec::new();
+    for (file, path) in locations {
+        let source = if file == PROVIDER_CONFIG_FILE {
+            &provider_json
+        } else {
+            &app_json
+        };
+        let pointer = format!("/{}", path.join("/"));
+        let Some(value) = source.pointer(&pointer) else {
+            continue;
+        };
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        looprs::write_json_setting(Path::new(file), &path, value.clone())?;
+        if !written.contains(&file) {
+            written.push(file);
+        }
+    }
+    Ok(written)
+}
+
 fn prepare_user_prompt(
     raw_prompt: &str,
     app_config: &AppConfig,
@@ -1000,6 +2026,7 @@ fn prepare_user_prompt(
 
 /// Execute a custom command
 struct SessionState {
+    http: HttpClient,
     provider_config: ProviderConfig,
     provider_name: String,
     model: String,
@@ -1013,15 +2040,24 @@ async fn execute_command(
     agent_registry: &AgentRegistry,
     state: &mut SessionState,
 ) -> Result<()> {
+    let http = &state.http;
     let provider_config = &mut state.provider_config;
     let provider_name = &mut state.provider_name;
     let model = &mut state.model;
     use looprs::CommandAction;
 
+    // Everything after the command name, for `$ARGS`/`$1`-style placeholders.
+    let args = input
+        .trim_start()
+        .split_once(char::is_whitespace)
+        .map(|(_, rest)| rest.trim())
+        .unwrap_or("");
+
     match &cmd.action {
         CommandAction::Prompt { template, .. } => {
+            let prompt = looprs::expand_placeholders(template, args);
             let (prepared_prompt, metadata, selected_agent) =
-                prepare_user_prompt(template, app_config, agent_registry);
+                prepare_user_prompt(&prompt, app_config, agent_registry);
             if !metadata.is_empty() {
                 agent.set_turn_metadata(metadata);
             }
@@ -1029,18 +2065,13 @@ async fn execute_command(
                 ui::info(format!("Delegated prompt to agent role: {agent_name}"));
             }
             agent.add_user_message(prepared_prompt);
-            agent.run_turn().await?;
+            run_turn_interruptible(agent).await?;
         }
         CommandAction::Shell {
             command,
             inject_output,
         } => {
-            let args = input
-                .split_whitespace()
-                .skip(1)
-                .collect::<Vec<_>>()
-                .join(" ");
-            let command = command.replace("{args}", &args);
+            let command = looprs::expand_placeholders(command, args).replace("{args}", args);
             ui::running_command(&command);
             let output = looprs::shell::run_nu_command(&command)?;
 
@@ -1122,17 +2153,13 @@ async fn execute_command(
                 settings.model = Some(m.clone());
             }
 
-            match looprs::providers::create_provider_from_config(
-                provider_config,
-                ProviderOverrides { model: None },
-            )
-            .await
-            {
+            match rebuild_provider(http, app_config, provider_config).await {
                 Ok(provider) => {
                     *provider_name = provider.name().to_string();
                     *model = provider.model().as_str().to_string();
                     agent.set_provider(provider);
                     ui::info(format!("Switched to {provider_name}/{model}"));
+                    runtime::warn_if_unknown_model(provider_name, &ModelId::new(model.as_str()));
                 }
                 Err(e) => {
                     // Roll back config change on failure
@@ -20,11 +20,13 @@ pub enum ReplMode {
     Colon,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct MatchSets {
     pub commands: Vec<String>,
     pub skills: Vec<String>,
     pub settings: Vec<String>,
+    /// Model ids for the active provider, completed after `:set model`.
+    pub models: Mutex<Vec<String>>,
 }
 
 #[derive(Debug)]
@@ -73,6 +75,13 @@ impl ReplHelper {
             state.reset();
         }
     }
+
+    /// Replace the `:set model` candidates, e.g. after the provider changes.
+    pub fn set_models(&self, models: Vec<String>) {
+        if let Ok(mut current) = self.sets.models.lock() {
+            *current = models;
+        }
+    }
 }
 
 impl Highlighter for ReplHelper {}
@@ -102,7 +111,11 @@ impl Hinter for ReplHelper {
         match state.mode {
             ReplMode::Slash => completion_hint(line, pos, '/', &self.sets.commands),
             ReplMode::Skill => completion_hint(line, pos, '$', &self.sets.skills),
-            ReplMode::Colon => settings_hint(line, pos, &self.sets.settings),
+            ReplMode::Colon => {
+                let models = self.sets.models.lock().ok()?;
+                model_hint(line, pos, &models)
+                    .or_else(|| settings_hint(line, pos, &self.sets.settings))
+            }
             ReplMode::Normal => None,
         }
     }
@@ -303,6 +316,35 @@ fn settings_hint(line: &str, pos: usize, items: &[String]) -> Option<String> {
     }
 }
 
+/// Hint a model id for the value of `:set model <partial>`.
+fn model_hint(line: &str, pos: usize, models: &[String]) -> Option<String> {
+    if pos != line.len() {
+        return None;
+    }
+    let mut parts = line
+        .trim_start_matches(':')
+        .split_whitespace()
+        .filter(|part| *part != "--save");
+    if parts.next() != Some("set") || parts.next() != Some("model") {
+        return None;
+    }
+    let query = match parts.next() {
+        Some(query) => query,
+        None if line.ends_with(char::is_whitespace) => "",
+        None => return None,
+    };
+    if parts.next().is_some() || (line.ends_with(char::is_whitespace) && !query.is_empty()) {
+        return None;
+    }
+    let best = best_match(query, '\0', models)?;
+    let completion = best.strip_prefix(query).unwrap_or(best);
+    if completion.is_empty() {
+        None
+    } else {
+        Some(completion.to_string())
+    }
+}
+
 fn handle_completion_enter(
     line: &str,
     prefix: char,
@@ -527,6 +569,27 @@ mod tests {
         assert!(completion_hint("/refactor", 9, '/', &items).is_none());
     }
 
+    // ── model_hint ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn model_hint_completes_set_model_value() {
+        let models = vec!["gpt-5".to_string(), "gpt-5-mini".to_string()];
+        assert_eq!(
+            model_hint(":set model gpt-5-m", 18, &models).as_deref(),
+            Some("ini")
+        );
+        assert_eq!(
+            model_hint(":set model ", 11, &models).as_deref(),
+            Some("gpt-5")
+        );
+        assert_eq!(
+            model_hint(":set --save model gpt-5-", 24, &models).as_deref(),
+            Some("mini")
+        );
+        assert!(model_hint(":set model", 10, &models).is_none());
+        assert!(model_hint(":set provider gpt", 17, &models).is_none());
+    }
+
     // ── ReplState ────────────────────────────────────────────────────────────
 
     #[test]
@@ -2,52 +2,108 @@ use looprs::Agent;
 use looprs::ModelId;
 use looprs::ProviderConfig;
 use looprs::RuntimeSettings;
-use looprs::app_config::AppConfig;
-use looprs::providers::{ProviderOverrides, create_provider_with_overrides};
+use looprs::app_config::{AppConfig, DefaultsConfig};
+use looprs::event_log::{EVENT_LOG_PATH, EventLog};
+use looprs::providers::{
+    HttpClient, ProviderOverrides, create_provider_with_overrides, with_fallbacks,
+};
 use miette::miette;
+use std::path::Path;
 
 const MISSING_LOCAL_MODEL: &str = "No local model configured";
 
 pub struct BootstrappedRuntime {
     pub app_config: AppConfig,
     pub provider_config: ProviderConfig,
+    /// Connection pool for this process; reuse it when rebuilding the provider.
+    pub http: HttpClient,
     pub provider_name: String,
     pub model: String,
     pub agent: Agent,
 }
 
+/// Warn when `model` has no entry in the model limits table, so the
+/// conservative default context window and output cap apply. Ollama models
+/// are skipped: local model names are open-ended, and the default suits them.
+pub fn warn_if_unknown_model(provider: &str, model: &ModelId) {
+    if provider != "ollama" && !model.is_known() {
+        let info = looprs::model_info::UNKNOWN_MODEL;
+        looprs::ui::warn(format!(
+            "Warning: Unknown model '{model}'; assuming a {}-token context window and {}-token output. Add it to model_limits in .looprs/config.json to raise these.",
+            info.context_window, info.max_output_tokens
+        ));
+    }
+}
+
 pub async fn bootstrap_runtime(
     model_override: Option<ModelId>,
 ) -> anyhow::Result<BootstrappedRuntime> {
-    let app_config = AppConfig::load().unwrap_or_default();
-
-    let provider = create_provider_with_overrides(ProviderOverrides {
-        model: model_override,
-    })
+    let app_config = AppConfig::load()?;
+    looprs::ui::set_render_markdown(app_config.ui.render_markdown);
+    looprs::model_info::set_overrides(app_config.model_limits.clone());
+
+    let http = HttpClient::new()?;
+    let provider = create_provider_with_overrides(
+        &http,
+        ProviderOverrides {
+            model: model_override,
+            sampling: app_config.defaults.sampling(),
+        },
+    )
     .await?;
+    let provider_config = ProviderConfig::load()?;
+    let provider = with_fallbacks(
+        &http,
+        provider,
+        &app_config.fallback_models,
+        app_config.defaults.sampling(),
+        &provider_config,
+    )
+    .await;
 
     let provider_name = provider.name().to_string();
     let model = provider.model().as_str().to_string();
 
-    let provider_config = ProviderConfig::load().unwrap_or_default();
-    let max_tokens_override = provider_config.merged_settings(&provider_name).max_tokens;
+    let merged = provider_config.merged_settings(&provider_name);
     let runtime = RuntimeSettings {
-        defaults: app_config.defaults.clone(),
-        max_tokens_override,
+        defaults: DefaultsConfig {
+            temperature: merged.temperature.or(app_config.defaults.temperature),
+            ..app_config.defaults.clone()
+        },
+        max_tokens_override: merged.max_tokens,
         fs_mode: app_config.agents.fs_mode,
+        tool_approval: app_config.tool_approval.clone(),
+        observations: app_config.observations.clone(),
+        enabled_tools: app_config.enabled_tools.clone(),
+        system_prompt_max_chars: app_config.system_prompt_max_chars,
+        update_mode_commands: app_config.agents.update_mode_commands.clone(),
+        ..RuntimeSettings::default()
     };
     let session_logger = looprs::adapters::default_session_store();
-    let agent = Agent::new_with_runtime(
+    let mut agent = Agent::new_with_runtime(
         provider,
         runtime,
         app_config.file_ref_policy(),
         session_logger,
         Box::new(looprs::adapters::UiOutput),
     )?;
+    agent.set_system_prompt(app_config.system_prompt_template.clone());
+    warn_if_unknown_model(&provider_name, &ModelId::new(&model));
+    if app_config.event_log {
+        let path = Path::new(EVENT_LOG_PATH);
+        match EventLog::open(path) {
+            Ok(log) => agent = agent.with_event_log(log),
+            Err(e) => looprs::ui::warn(format!(
+                "Warning: Event log disabled; could not open {}: {e}",
+                path.display()
+            )),
+        }
+    }
 
     Ok(BootstrappedRuntime {
         app_config,
         provider_config,
+        http,
         provider_name,
         model,
         agent,
@@ -2,4 +2,4 @@ pub mod events;
 pub mod facade;
 pub mod session;
 
-pub use facade::{bootstrap_runtime, provider_bootstrap_report};
+pub use facade::{bootstrap_runtime, provider_bootstrap_report, warn_if_unknown_model};
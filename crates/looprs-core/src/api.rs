@@ -3,7 +3,7 @@ use serde_json::Value;
 
 use crate::types::{ModelId, ToolId, ToolName};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Message {
     pub role: String,
     pub content: Vec<ContentBlock>,
@@ -17,6 +17,24 @@ impl Message {
         }
     }
 
+    /// A user message with `text` followed by a base64-encoded image.
+    pub fn user_with_image(
+        text: impl Into<String>,
+        media_type: impl Into<String>,
+        data: impl Into<String>,
+    ) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: vec![
+                ContentBlock::Text { text: text.into() },
+                ContentBlock::Image {
+                    media_type: media_type.into(),
+                    data: data.into(),
+                },
+            ],
+        }
+    }
+
     pub fn assistant(content: Vec<ContentBlock>) -> Self {
         Self {
             role: "assistant".to_string(),
@@ -30,9 +48,15 @@ impl Message {
             content: results,
         }
     }
+
+    pub fn has_image(&self) -> bool {
+        self.content
+            .iter()
+            .any(|b| matches!(b, ContentBlock::Image { .. }))
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ContentBlock {
     Text {
@@ -47,6 +71,11 @@ pub enum ContentBlock {
         tool_use_id: ToolId,
         content: String,
     },
+    /// An inline image: `media_type` such as `image/png`, `data` base64-encoded.
+    Image {
+        media_type: String,
+        data: String,
+    },
 }
 
 #[allow(dead_code)]
@@ -184,6 +213,19 @@ mod tests {
         assert_eq!(json["content"], "output");
     }
 
+    #[test]
+    fn message_user_with_image_roundtrips() {
+        let msg = Message::user_with_image("what is this?", "image/png", "iVBORw0KGgo=");
+        assert!(msg.has_image());
+        assert!(!Message::user("plain").has_image());
+
+        let json = serde_json::to_value(&msg).expect("serialize Message");
+        assert_eq!(json["content"][1]["type"], "image");
+        assert_eq!(json["content"][1]["media_type"], "image/png");
+        let back: Message = serde_json::from_value(json).expect("deserialize Message");
+        assert_eq!(back, msg);
+    }
+
     #[test]
     fn message_serialization_roundtrip() {
         let original = Message::user("Test message");
@@ -4,13 +4,17 @@ domain_event!(Event {
     SessionStart,
     SessionEnd,
     UserPromptSubmit,
+    InferenceStart,
     PreToolUse,
     PostToolUse,
     InferenceComplete,
     OnError,
+    TurnError,
     OnWarning,
     DelegationStart,
     DelegationComplete,
+    BeforeCompaction,
+    AfterCompaction,
 });
 
 /// Context data that flows through events
@@ -63,6 +67,11 @@ impl EventContext {
         self
     }
 
+    pub fn with_warning(mut self, warning: String) -> Self {
+        self.warning = Some(warning);
+        self
+    }
+
     pub fn with_metadata(mut self, key: String, value: String) -> Self {
         self.metadata.insert(key, value);
         self
@@ -81,15 +90,25 @@ pub type EventHandler = Box<dyn Fn(Event, &EventContext) + Send + Sync>;
 /// Manager for dispatching events
 pub struct EventManager {
     handlers: HashMap<Event, Vec<EventHandler>>,
+    sinks: Vec<EventHandler>,
 }
 
 impl EventManager {
     pub fn new() -> Self {
         EventManager {
             handlers: HashMap::new(),
+            sinks: Vec::new(),
         }
     }
 
+    /// Register a handler that sees every event, after its per-event handlers.
+    pub fn on_any<F>(&mut self, handler: F)
+    where
+        F: Fn(Event, &EventContext) + Send + Sync + 'static,
+    {
+        self.sinks.push(Box::new(handler));
+    }
+
     pub fn on<F>(&mut self, event: Event, handler: F)
     where
         F: Fn(Event, &EventContext) + Send + Sync + 'static,
@@ -106,6 +125,9 @@ impl EventManager {
                 handler(event, context);
             }
         }
+        for sink in &self.sinks {
+            sink(event, context);
+        }
     }
 
     pub fn clear(&mut self, event: Event) {
@@ -130,6 +152,7 @@ mod tests {
         assert_eq!(Event::SessionEnd.name(), "SessionEnd");
         assert_eq!(Event::PreToolUse.name(), "PreToolUse");
         assert_eq!(Event::OnError.name(), "OnError");
+        assert_eq!(Event::TurnError.name(), "TurnError");
         assert_eq!(Event::DelegationStart.name(), "DelegationStart");
     }
 
@@ -198,6 +221,23 @@ mod tests {
         assert_eq!(*counter2.lock().unwrap(), 1);
     }
 
+    #[test]
+    fn event_manager_on_any_sees_every_event() {
+        let mut manager = EventManager::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+
+        manager.on_any(move |event, _ctx| {
+            seen_clone.lock().unwrap().push(event.name());
+        });
+
+        let ctx = EventContext::new();
+        manager.fire(Event::SessionStart, &ctx);
+        manager.fire(Event::PostToolUse, &ctx);
+
+        assert_eq!(*seen.lock().unwrap(), vec!["SessionStart", "PostToolUse"]);
+    }
+
     #[test]
     fn event_manager_clear() {
         let mut manager = EventManager::new();
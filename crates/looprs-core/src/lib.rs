@@ -12,6 +12,7 @@ pub mod adapters;
 pub mod ai_types;
 pub mod api;
 pub mod events;
+pub mod model_info;
 pub mod observation;
 pub mod ports;
 pub mod types;
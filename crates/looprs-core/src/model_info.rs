@@ -0,0 +1,164 @@
+//! Context window and output token limits per model.
+//!
+//! Built-in limits are matched by prefix of the lowercased model id, after
+//! any `vendor/` routing prefix; the longest matching prefix wins. Overrides installed with [`set_overrides`] are keyed by exact
+//! model id and take precedence, so custom or newly released models can be
+//! described without a code change.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelInfo {
+    /// Tokens the model accepts as input, prompt and history included.
+    pub context_window: u32,
+    /// Largest `max_tokens` the model accepts for a response.
+    pub max_output_tokens: u32,
+}
+
+/// Assumed for ids missing from both the overrides and the built-in table.
+/// Small enough that local and unfamiliar models accept it.
+pub const UNKNOWN_MODEL: ModelInfo = ModelInfo {
+    context_window: 8_192,
+    max_output_tokens: 4_096,
+};
+
+const BUILTIN: &[(&str, ModelInfo)] = &[
+    ("claude-opus-4", info(200_000, 32_000)),
+    ("claude-sonnet-4", info(200_000, 64_000)),
+    ("claude-haiku-4", info(200_000, 64_000)),
+    ("claude-3-7-sonnet", info(200_000, 64_000)),
+    ("claude-3-5", info(200_000, 8_192)),
+    ("claude", info(200_000, 4_096)),
+    ("gpt-5", info(400_000, 128_000)),
+    ("gpt-4.1", info(1_047_576, 32_768)),
+    ("gpt-4o", info(128_000, 16_384)),
+    ("gpt-4-turbo", info(128_000, 4_096)),
+    ("gpt-4-1106", info(128_000, 4_096)),
+    ("gpt-4-32k", info(32_768, 4_096)),
+    ("gpt-4", info(8_192, 4_096)),
+    ("gemini-2.5", info(1_048_576, 65_536)),
+    ("gemini", info(1_048_576, 8_192)),
+];
+
+/// Concrete model ids per API family, offered when completing `:set model`.
+const KNOWN_IDS: &[(&str, &[&str])] = &[
+    (
+        "anthropic",
+        &[
+            "claude-sonnet-4-6",
+            "claude-opus-4-1",
+            "claude-sonnet-4-5",
+            "claude-haiku-4-5",
+        ],
+    ),
+    ("openai", &["gpt-5", "gpt-5-mini", "gpt-4o", "gpt-4-turbo"]),
+    (
+        "gemini",
+        &["gemini-2.0-flash", "gemini-2.5-flash", "gemini-2.5-pro"],
+    ),
+];
+
+const fn info(context_window: u32, max_output_tokens: u32) -> ModelInfo {
+    ModelInfo {
+        context_window,
+        max_output_tokens,
+    }
+}
+
+fn overrides() -> &'static RwLock<HashMap<String, ModelInfo>> {
+    static OVERRIDES: OnceLock<RwLock<HashMap<String, ModelInfo>>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Replace the process-wide override table (`model_limits` in config.json).
+pub fn set_overrides(table: HashMap<String, ModelInfo>) {
+    *overrides().write().unwrap_or_else(PoisonError::into_inner) = table;
+}
+
+/// Limits for `model` from the overrides or the built-in table, if listed.
+pub fn lookup(model: &str) -> Option<ModelInfo> {
+    if let Some(info) = overrides()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(model)
+    {
+        return Some(*info);
+    }
+    let lower = model.to_lowercase();
+    let id = lower.rsplit('/').next().unwrap_or(&lower);
+    BUILTIN
+        .iter()
+        .filter(|(prefix, _)| id.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, info)| *info)
+}
+
+/// Model ids known for an API family (`anthropic`, `openai`, `gemini`).
+pub fn known_ids(family: &str) -> &'static [&'static str] {
+    KNOWN_IDS
+        .iter()
+        .find(|(name, _)| *name == family)
+        .map_or(&[], |(_, ids)| ids)
+}
+
+/// Limits for `model`, falling back to [`UNKNOWN_MODEL`].
+pub fn resolve(model: &str) -> ModelInfo {
+    lookup(model).unwrap_or(UNKNOWN_MODEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ModelId;
+
+    #[test]
+    fn known_model_uses_builtin_limits() {
+        let model = ModelId::new("claude-sonnet-4-6");
+        assert!(model.is_known());
+        assert_eq!(model.context_window(), 200_000);
+        assert_eq!(model.max_tokens(), 64_000);
+        assert_eq!(ModelId::new("claude-opus-4-1").max_tokens(), 32_000);
+        assert_eq!(ModelId::new("gpt-4-1106-preview").max_tokens(), 4_096);
+        assert_eq!(ModelId::new("gpt-5-mini").max_tokens(), 128_000);
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        assert_eq!(resolve("gpt-4o-mini"), info(128_000, 16_384));
+        assert_eq!(resolve("gpt-4.1-nano").context_window, 1_047_576);
+        assert_eq!(resolve("gpt-4-0613"), info(8_192, 4_096));
+        assert_eq!(
+            resolve("openrouter/anthropic/claude-sonnet-4-5").max_output_tokens,
+            64_000
+        );
+        assert_eq!(lookup("my-gpt-4-finetune"), None);
+    }
+
+    #[test]
+    fn custom_model_falls_back_to_conservative_default() {
+        let model = ModelId::new("my-finetune-7b");
+        assert!(!model.is_known());
+        assert_eq!(model.context_window(), UNKNOWN_MODEL.context_window);
+        assert_eq!(model.max_tokens(), UNKNOWN_MODEL.max_output_tokens);
+    }
+
+    #[test]
+    fn override_table_takes_precedence() {
+        let limits = info(32_000, 8_000);
+        set_overrides(HashMap::from([
+            ("qwen-override-test".to_string(), limits),
+            ("claude-override-test".to_string(), limits),
+        ]));
+        assert_eq!(resolve("qwen-override-test"), limits);
+        assert_eq!(ModelId::new("claude-override-test").max_tokens(), 8_000);
+        assert_eq!(
+            ModelId::new("claude-override-test").context_window(),
+            32_000
+        );
+        set_overrides(HashMap::new());
+        assert_eq!(lookup("qwen-override-test"), None);
+    }
+}
@@ -16,6 +16,13 @@ pub struct Observation {
     pub timestamp: u64,
     pub session_id: String,
     pub context: Option<String>,
+    /// Consecutive identical calls rolled into this observation.
+    #[serde(default = "single_call")]
+    pub count: u32,
+}
+
+fn single_call() -> u32 {
+    1
 }
 
 impl Observation {
@@ -39,6 +46,7 @@ impl Observation {
             timestamp,
             session_id,
             context: None,
+            count: 1,
         }
     }
 
@@ -81,10 +89,15 @@ impl Observation {
     }
 
     pub fn to_title(&self) -> String {
-        if let Some(ctx) = &self.context {
+        let title = if let Some(ctx) = &self.context {
             format!("Observation: {}", ctx.chars().take(60).collect::<String>())
         } else {
             format!("Observation: {}", self.tool_name)
+        };
+        if self.count > 1 {
+            format!("{title} (x{})", self.count)
+        } else {
+            title
         }
     }
 }
@@ -13,13 +13,14 @@ pub type InferStream =
     Pin<Box<dyn Stream<Item = Result<String, Box<dyn std::error::Error + Send + Sync>>> + Send>>;
 
 /// Request structure for LLM inference.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct InferenceRequest {
     pub model: ModelId,
     pub messages: Vec<Message>,
     pub tools: Vec<ToolDefinition>,
     pub max_tokens: u32,
     pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
     pub system: String,
 }
 
@@ -38,6 +39,38 @@ pub struct Usage {
     pub output_tokens: u32,
 }
 
+/// Error returned by the default implementations of optional
+/// `InferenceProvider` methods when a backend does not implement them.
+///
+/// Callers can downcast the boxed error to detect the case and fall back
+/// (the `looprs` crate maps it onto `ProviderError::Unsupported`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedFeature {
+    pub feature: String,
+    pub provider: String,
+}
+
+impl UnsupportedFeature {
+    pub fn new(feature: impl Into<String>, provider: impl Into<String>) -> Self {
+        Self {
+            feature: feature.into(),
+            provider: provider.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for UnsupportedFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is not supported by provider {}",
+            self.feature, self.provider
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedFeature {}
+
 /// Port: perform LLM inference.
 ///
 /// Implementations decide the backend (Anthropic, OpenAI, local Ollama, etc.).
@@ -68,6 +101,11 @@ pub trait InferenceProvider: Send + Sync {
         true
     }
 
+    /// Whether this provider accepts image content blocks.
+    fn supports_images(&self) -> bool {
+        true
+    }
+
     /// Whether this provider supports token-by-token streaming.
     fn supports_streaming(&self) -> bool {
         false
@@ -100,4 +138,14 @@ pub trait InferenceProvider: Send + Sync {
             Err(e) => Box::pin(stream::once(async move { Err(e) })),
         }
     }
+
+    /// Compute embedding vectors for the given inputs.
+    ///
+    /// Optional: the default returns [`UnsupportedFeature`].
+    async fn embed(
+        &self,
+        _inputs: &[String],
+    ) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+        Err(Box::new(UnsupportedFeature::new("embeddings", self.name())))
+    }
 }
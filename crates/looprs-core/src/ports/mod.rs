@@ -15,7 +15,7 @@ pub mod test_contracts;
 
 // Re-export all port traits and the Message domain type.
 pub use inference_provider::{
-    InferStream, InferenceProvider, InferenceRequest, InferenceResponse, Usage,
+    InferStream, InferenceProvider, InferenceRequest, InferenceResponse, UnsupportedFeature, Usage,
 };
 pub use message_broker::{Message, MessageBroker};
 pub use observation_store::ObservationStore;
@@ -191,6 +191,7 @@ pub async fn assert_inference_provider_live_contract(
         tools: vec![],
         max_tokens: 64,
         temperature: Some(0.0),
+        top_p: None,
         system: String::new(),
     };
 
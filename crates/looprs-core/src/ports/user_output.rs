@@ -23,4 +23,12 @@ pub trait UserOutput: Send + Sync {
     fn write_chunk(&self, chunk: &str) {
         self.assistant_text(chunk);
     }
+
+    /// Show a unified diff of a pending file change.
+    ///
+    /// The default delegates to `info`; terminal adapters may colour the
+    /// added and removed lines.
+    fn diff(&self, diff: &str) {
+        self.info(diff);
+    }
 }
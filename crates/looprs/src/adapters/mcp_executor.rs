@@ -1,6 +1,6 @@
 use serde_json::Value;
 
-use crate::tools::{ToolContext, ToolError, ToolExecutor};
+use crate::tools::{ToolContext, ToolError, ToolExecutor, ToolOutput};
 
 /// Adapter: routes tool calls to a remote MCP server via HTTP/JSON-RPC.
 ///
@@ -57,9 +57,14 @@ enum Either {
 }
 
 impl ToolExecutor for McpToolExecutor {
-    fn execute(&self, name: &str, args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+    fn execute(
+        &self,
+        name: &str,
+        args: &Value,
+        ctx: &ToolContext,
+    ) -> Result<ToolOutput, ToolError> {
         match self.try_mcp(name, args) {
-            Ok(output) => Ok(output),
+            Ok(output) => Ok(ToolOutput::Text(output)),
             Err(e) => {
                 if let Some(ref fb) = self.fallback {
                     fb.execute(name, args, ctx)
@@ -92,7 +97,7 @@ mod tests {
             crate::fs_mode::FsMode::Write,
         );
         let result = executor.execute("echo", &serde_json::json!({"text": "hi"}), &ctx);
-        assert_eq!(result.unwrap(), "fallback-result");
+        assert_eq!(result.unwrap().to_display_string(), "fallback-result");
     }
 
     #[test]
@@ -8,17 +8,22 @@
 //! | 1 (initial) | — |
 //! | 2 | 500 ms |
 //! | 3 | 1 000 ms |
+//!
+//! A `ProviderError::RateLimited` carrying a `Retry-After` delay waits that
+//! long instead. No wait exceeds `MAX_DELAY`, whatever the server asks for.
 
 use std::time::Duration;
 
 use async_trait::async_trait;
 use tokio::time::sleep;
 
+use crate::errors::ProviderError;
 use crate::providers::{InferenceRequest, InferenceResponse, LLMProvider};
 use crate::types::ModelId;
 
 const MAX_ATTEMPTS: u32 = 3;
 const BASE_DELAY_MS: u64 = 500;
+const MAX_DELAY: Duration = Duration::from_secs(30);
 
 /// Wraps an inner `LLMProvider` and retries failed `infer` calls with
 /// exponential backoff.
@@ -29,6 +34,7 @@ pub struct RetryProvider<P: LLMProvider> {
     inner: P,
     max_attempts: u32,
     base_delay_ms: u64,
+    max_delay: Duration,
 }
 
 impl<P: LLMProvider> RetryProvider<P> {
@@ -38,6 +44,7 @@ impl<P: LLMProvider> RetryProvider<P> {
             inner,
             max_attempts: MAX_ATTEMPTS,
             base_delay_ms: BASE_DELAY_MS,
+            max_delay: MAX_DELAY,
         }
     }
 
@@ -46,6 +53,12 @@ impl<P: LLMProvider> RetryProvider<P> {
         self.base_delay_ms = ms;
         self
     }
+
+    /// Override the longest wait between attempts.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
 }
 
 #[async_trait]
@@ -58,8 +71,22 @@ impl<P: LLMProvider> LLMProvider for RetryProvider<P> {
 
         for attempt in 0..self.max_attempts {
             if attempt > 0 {
-                let delay_ms = self.base_delay_ms * (1u64 << (attempt - 1));
-                sleep(Duration::from_millis(delay_ms)).await;
+                let backoff = Duration::from_millis(self.base_delay_ms * (1u64 << (attempt - 1)));
+                let delay = match last_err
+                    .as_deref()
+                    .and_then(|e| e.downcast_ref::<ProviderError>())
+                {
+                    Some(ProviderError::RateLimited {
+                        retry_after: Some(retry_after),
+                        ..
+                    }) => {
+                        let wait = (*retry_after).min(self.max_delay);
+                        crate::ui::warn(format!("Rate limited, retrying in {}s", wait.as_secs()));
+                        wait
+                    }
+                    _ => backoff,
+                };
+                sleep(delay.min(self.max_delay)).await;
             }
 
             match self.inner.infer(req).await {
@@ -88,6 +115,10 @@ impl<P: LLMProvider> LLMProvider for RetryProvider<P> {
     fn supports_tool_use(&self) -> bool {
         self.inner.supports_tool_use()
     }
+
+    fn supports_images(&self) -> bool {
+        self.inner.supports_images()
+    }
 }
 
 #[cfg(test)]
@@ -97,12 +128,12 @@ mod tests {
     use std::sync::atomic::{AtomicU32, Ordering};
 
     use crate::api::{ContentBlock, Message as ApiMessage};
-    use crate::errors::ProviderError;
     use crate::providers::Usage;
 
     struct CountingProvider {
         calls: Arc<AtomicU32>,
         fail_times: u32,
+        rate_limited: Option<Duration>,
         model: ModelId,
     }
 
@@ -111,9 +142,14 @@ mod tests {
             Self {
                 calls: Arc::new(AtomicU32::new(0)),
                 fail_times,
+                rate_limited: None,
                 model: ModelId::new("test-model"),
             }
         }
+        fn rate_limited(mut self, retry_after: Duration) -> Self {
+            self.rate_limited = Some(retry_after);
+            self
+        }
         fn call_count(&self) -> u32 {
             self.calls.load(Ordering::SeqCst)
         }
@@ -126,7 +162,13 @@ mod tests {
             _req: &InferenceRequest,
         ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
             let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
-            if n <= self.fail_times {
+            if let Some(retry_after) = self.rate_limited.filter(|_| n <= self.fail_times) {
+                Err(ProviderError::RateLimited {
+                    retry_after: Some(retry_after),
+                    message: "API Error 429: slow down".to_string(),
+                }
+                .into())
+            } else if n <= self.fail_times {
                 Err(ProviderError::ApiError(format!("simulated failure #{n}")).into())
             } else {
                 Ok(InferenceResponse {
@@ -160,6 +202,7 @@ mod tests {
             tools: vec![],
             max_tokens: 16,
             temperature: None,
+            top_p: None,
             system: String::new(),
         }
     }
@@ -193,6 +236,29 @@ mod tests {
         assert!(err.contains("simulated failure #3"), "got: {err}");
     }
 
+    #[tokio::test]
+    async fn rate_limited_waits_retry_after_instead_of_backoff() {
+        let inner = CountingProvider::new(1).rate_limited(Duration::ZERO);
+        let provider = RetryProvider::new(inner).with_base_delay_ms(60_000);
+        let result = tokio::time::timeout(Duration::from_secs(5), provider.infer(&dummy_req()))
+            .await
+            .expect("Retry-After of 0s should override the 60s backoff");
+        assert!(result.is_ok());
+        assert_eq!(provider.inner.call_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn retry_after_is_capped_and_body_kept() {
+        let inner = CountingProvider::new(5).rate_limited(Duration::from_secs(3600));
+        let provider = RetryProvider::new(inner).with_max_delay(Duration::ZERO);
+        let result = tokio::time::timeout(Duration::from_secs(5), provider.infer(&dummy_req()))
+            .await
+            .expect("an hour-long Retry-After should be capped at max_delay");
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("slow down"), "{err}");
+        assert_eq!(provider.inner.call_count(), 3);
+    }
+
     #[tokio::test]
     async fn delegates_metadata() {
         let inner = CountingProvider::new(0);
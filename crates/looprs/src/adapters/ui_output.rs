@@ -39,6 +39,10 @@ impl UserOutput for UiOutput {
     fn tool_err(&self, err_msg: &str) {
         ui::tool_err(err_msg);
     }
+
+    fn diff(&self, diff: &str) {
+        ui::diff_preview(diff);
+    }
 }
 
 #[cfg(test)]
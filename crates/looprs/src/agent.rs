@@ -1,11 +1,14 @@
 use crate::api::ContentBlock;
 use crate::api::Message;
-use crate::app_config::DefaultsConfig;
+use crate::app_config::{DefaultsConfig, ObservationsConfig, ToolApproval, ToolApprovalConfig};
+use crate::cancel::CancellationToken;
 use crate::errors::AgentError;
+use crate::event_log::EventLog;
 use crate::events::{Event, EventContext, EventManager};
 use crate::file_refs::FileRefPolicy;
 use crate::fs_mode::FsMode;
-use crate::hooks::{ApprovalCallback, HookExecutor, HookRegistry, PromptCallback};
+use crate::hooks::{ApprovalCallback, Hook, HookExecutor, HookRegistry, PromptCallback};
+use crate::instructions::ProjectInstructions;
 use crate::models_config::ModelsConfig;
 use crate::observation_manager::ObservationManager;
 use crate::ports::{SessionStore, UserOutput};
@@ -14,14 +17,32 @@ use crate::providers::{InferenceRequest, InferenceResponse};
 use crate::rules::RuleRegistry;
 use crate::session_log::SessionEvent;
 use crate::system_monitor::SystemMonitor;
-use crate::tools::{DefaultToolExecutor, ToolContext, ToolExecutor, get_tool_definitions};
+use crate::tools::{
+    DefaultToolExecutor, OutputSink, ToolContext, ToolExecutor, get_tool_definitions,
+};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tokio::time::{Duration, timeout};
 
 const TOOL_PREVIEW_LEN: usize = 60;
+const TOOL_APPROVAL_PREVIEW_LEN: usize = 200;
 const ON_REPEAT_THRESHOLD: usize = 3;
 
 const MAX_TOOL_RESULT_CHARS_IN_CONTEXT: usize = 16_000;
+/// Messages kept verbatim when older history is summarized by `compact_history`.
+const COMPACTION_KEEP_RECENT: usize = 6;
+const COMPACTION_PREVIEW_CHARS: usize = 160;
+/// How far back tool inputs count toward the paths that scope `applies_to` rules.
+const RULE_SCOPE_RECENT_MESSAGES: usize = 10;
+/// Flat per-image token estimate; providers bill images by size, not by the
+/// length of their base64 payload.
+const IMAGE_TOKEN_ESTIMATE: usize = 1_600;
+/// Fraction of `defaults.max_context_tokens` at which `context_warning` fires.
+const CONTEXT_WARNING_RATIO: f64 = 0.8;
+/// In-memory checkpoints kept by `Agent::checkpoint`; the oldest is dropped first.
+const MAX_CHECKPOINTS: usize = 20;
+/// Default cap on inference→tool round-trips within a single `run_turn`.
+pub const DEFAULT_MAX_TOOL_ITERATIONS: usize = 25;
 
 fn truncate_tool_result_for_context(content: &str) -> String {
     if content.chars().count() <= MAX_TOOL_RESULT_CHARS_IN_CONTEXT {
@@ -40,11 +61,158 @@ fn truncate_tool_result_for_context(content: &str) -> String {
     )
 }
 
-#[derive(Debug, Clone, Default)]
+/// Appended to a system prompt section cut short by `system_prompt_max_chars`.
+const TRIMMED_MARKER: &str = "\n[trimmed]";
+
+/// Shorten `sections`, visiting them in `order`, until their combined length
+/// fits in `budget` characters. A section too short to keep any of its text
+/// alongside the marker is dropped.
+fn trim_to_budget(sections: &mut [String], order: &[usize], budget: usize) {
+    let total: usize = sections.iter().map(|s| s.chars().count()).sum();
+    let mut excess = total.saturating_sub(budget);
+    let marker_len = TRIMMED_MARKER.chars().count();
+    for &idx in order {
+        if excess == 0 {
+            break;
+        }
+        let section = &mut sections[idx];
+        let len = section.chars().count();
+        if len <= excess + marker_len {
+            section.clear();
+            excess = excess.saturating_sub(len);
+            continue;
+        }
+        let mut trimmed: String = section.chars().take(len - excess - marker_len).collect();
+        trimmed.push_str(TRIMMED_MARKER);
+        *section = trimmed;
+        excess = 0;
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct RuntimeSettings {
     pub defaults: DefaultsConfig,
     pub max_tokens_override: Option<u32>,
     pub fs_mode: FsMode,
+    /// Stop a turn once the model has requested this many tool rounds.
+    pub max_tool_iterations: usize,
+    pub tool_approval: ToolApprovalConfig,
+    pub observations: ObservationsConfig,
+    /// Tools advertised to the model and allowed to run; `None` enables all.
+    pub enabled_tools: Option<Vec<String>>,
+    /// Trim lower-priority system prompt sections past this many characters.
+    pub system_prompt_max_chars: Option<usize>,
+    /// Bash commands allowed in `FsMode::Update` (`agents.update_mode_commands`).
+    pub update_mode_commands: Vec<String>,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            defaults: DefaultsConfig::default(),
+            max_tokens_override: None,
+            fs_mode: FsMode::default(),
+            max_tool_iterations: DEFAULT_MAX_TOOL_ITERATIONS,
+            tool_approval: ToolApprovalConfig::default(),
+            observations: ObservationsConfig::default(),
+            enabled_tools: None,
+            system_prompt_max_chars: None,
+            update_mode_commands: Vec::new(),
+        }
+    }
+}
+
+/// Running token totals accumulated across every inference in a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SessionUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// Number of inference calls folded into the totals.
+    pub inferences: u32,
+}
+
+impl SessionUsage {
+    pub fn record(&mut self, usage: &crate::providers::Usage) {
+        self.input_tokens = self.input_tokens.saturating_add(usage.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(usage.output_tokens);
+        self.inferences = self.inferences.saturating_add(1);
+    }
+
+    pub fn total_tokens(&self) -> u32 {
+        self.input_tokens.saturating_add(self.output_tokens)
+    }
+
+    /// Estimated USD cost of the totals at `model`'s pricing, if it is known.
+    pub fn estimated_cost(&self, model: &crate::types::ModelId) -> Option<f64> {
+        let usage = crate::providers::Usage {
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+        };
+        crate::providers::pricing::estimate_cost(model, &usage)
+    }
+}
+
+const SAVED_SESSION_VERSION: u32 = 1;
+
+/// On-disk form of a conversation written by `Agent::save_session`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SavedSession {
+    pub version: u32,
+    pub provider: String,
+    pub model: crate::types::ModelId,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub usage: SessionUsage,
+    pub messages: Vec<Message>,
+}
+
+/// One tool call made during the most recent turn.
+///
+/// `output` is the tool's structured result when it has one (see
+/// `ToolOutput`), otherwise its text; for failures it is the error message.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ToolCallRecord {
+    pub name: String,
+    pub input: serde_json::Value,
+    pub output: serde_json::Value,
+    pub is_error: bool,
+}
+
+/// What the most recent turn did: the final assistant text, each tool call,
+/// the tokens it used, and why the model stopped. Emitted by `--json`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct TurnSummary {
+    /// Serialized as `response` to keep the `--json` shape stable.
+    #[serde(rename = "response")]
+    pub assistant_text: Option<String>,
+    pub tool_calls: Vec<ToolCallRecord>,
+    pub usage: SessionUsage,
+    pub stop_reason: Option<String>,
+}
+
+/// Handle to an in-memory snapshot taken by `Agent::checkpoint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CheckpointId(u32);
+
+impl std::str::FromStr for CheckpointId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl std::fmt::Display for CheckpointId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+struct Checkpoint {
+    id: CheckpointId,
+    messages: Vec<Message>,
+    usage: SessionUsage,
 }
 
 pub struct Agent {
@@ -53,18 +221,36 @@ pub struct Agent {
     tool_ctx: ToolContext,
     pub(crate) events: EventManager,
     pub(crate) observations: ObservationManager,
+    observations_db: PathBuf,
     pub(crate) hooks: HookRegistry,
     pub(crate) rules: RuleRegistry,
+    instructions: Option<ProjectInstructions>,
     runtime: RuntimeSettings,
     file_ref_policy: FileRefPolicy,
     pending_metadata: HashMap<String, String>,
+    /// Tool allowlist for the next turn only (set by skills with `allowed_tools`).
+    turn_allowed_tools: Option<Vec<String>>,
     session_logger: Option<Box<dyn SessionStore>>,
     output: Box<dyn UserOutput>,
+    tool_approval_fn: Option<ApprovalCallback>,
+    /// Answers `requires_approval` hook actions fired during a turn.
+    hook_approval_fn: Option<ApprovalCallback>,
+    /// Show a diff of each `write`/`edit` before it runs.
+    preview_changes: bool,
     tool_executor: Box<dyn ToolExecutor>,
     models_config: Option<ModelsConfig>,
     system_monitor: SystemMonitor,
-    session_input_tokens: u32,
-    session_output_tokens: u32,
+    usage: SessionUsage,
+    turn_tool_calls: Vec<ToolCallRecord>,
+    turn_usage: SessionUsage,
+    turn_stop_reason: Option<String>,
+    /// `@` references in the latest user prompt, for scoping rules.
+    referenced_paths: Vec<String>,
+    /// Replaces the default base instruction; `{working_dir}` is substituted.
+    system_prompt_template: Option<String>,
+    /// Oldest first, at most `MAX_CHECKPOINTS`.
+    checkpoints: std::collections::VecDeque<Checkpoint>,
+    next_checkpoint: u32,
 }
 
 impl Agent {
@@ -89,21 +275,34 @@ impl Agent {
         Ok(Self {
             provider,
             messages: Vec::new(),
-            tool_ctx: ToolContext::new_with_mode(runtime.fs_mode)?,
+            tool_ctx: ToolContext::new_with_mode(runtime.fs_mode)?
+                .with_update_mode_commands(runtime.update_mode_commands.clone()),
             events: EventManager::new(),
             observations: ObservationManager::new(),
+            observations_db: ObservationManager::default_db_path(),
             hooks: HookRegistry::new(),
             rules: RuleRegistry::new(),
+            instructions: None,
             runtime,
             file_ref_policy,
             pending_metadata: HashMap::new(),
+            turn_allowed_tools: None,
             session_logger,
             output,
+            tool_approval_fn: None,
+            hook_approval_fn: None,
+            preview_changes: false,
             tool_executor: Box::new(DefaultToolExecutor),
             models_config: ModelsConfig::load().ok(),
             system_monitor: SystemMonitor::new(),
-            session_input_tokens: 0,
-            session_output_tokens: 0,
+            usage: SessionUsage::default(),
+            turn_tool_calls: Vec::new(),
+            turn_usage: SessionUsage::default(),
+            turn_stop_reason: None,
+            referenced_paths: Vec::new(),
+            system_prompt_template: None,
+            checkpoints: std::collections::VecDeque::new(),
+            next_checkpoint: 1,
         })
     }
 
@@ -121,6 +320,33 @@ impl Agent {
         self
     }
 
+    /// Ask `approve` before running tools whose policy is `Prompt`.
+    pub fn with_tool_approval(mut self, approve: ApprovalCallback) -> Self {
+        self.tool_approval_fn = Some(approve);
+        self
+    }
+
+    /// Pass each line a `bash` command prints to `sink` while it runs, so long
+    /// commands show progress. The tool result still holds the full output.
+    pub fn with_tool_output_sink(mut self, sink: OutputSink) -> Self {
+        self.tool_ctx.set_output_sink(Some(sink));
+        self
+    }
+
+    /// Print a diff against the current file before each `write`/`edit`. With
+    /// a `Prompt` policy the approval question then refers to that diff.
+    pub fn with_change_preview(mut self) -> Self {
+        self.preview_changes = true;
+        self
+    }
+
+    /// Ask `approve` before hook actions marked `requires_approval`. Without
+    /// one, those actions are skipped.
+    pub fn with_hook_approval(mut self, approve: ApprovalCallback) -> Self {
+        self.hook_approval_fn = Some(approve);
+        self
+    }
+
     pub fn with_hooks(mut self, hooks: HookRegistry) -> Self {
         self.hooks = hooks;
         self
@@ -131,6 +357,17 @@ impl Agent {
         self
     }
 
+    pub fn with_instructions(mut self, instructions: Option<ProjectInstructions>) -> Self {
+        self.instructions = instructions;
+        self
+    }
+
+    /// Append every event this agent fires to `log`.
+    pub fn with_event_log(mut self, log: EventLog) -> Self {
+        self.events.on_any(move |event, ctx| log.record(event, ctx));
+        self
+    }
+
     pub fn fire_event(&self, event: Event, context: &EventContext) {
         self.events.fire(event, context);
     }
@@ -141,9 +378,19 @@ impl Agent {
 
     pub fn set_runtime_settings(&mut self, runtime: RuntimeSettings) {
         self.tool_ctx.set_fs_mode(runtime.fs_mode);
+        self.tool_ctx
+            .set_update_mode_commands(runtime.update_mode_commands.clone());
         self.runtime = runtime;
     }
 
+    /// Replace the base instruction at the top of the system prompt. Rules,
+    /// instructions, and hook context are still appended after it. A
+    /// `{working_dir}` placeholder expands to the tool working directory;
+    /// `None` restores the default.
+    pub fn set_system_prompt(&mut self, template: Option<String>) {
+        self.system_prompt_template = template;
+    }
+
     pub fn set_file_ref_policy(&mut self, policy: FileRefPolicy) {
         self.file_ref_policy = policy;
     }
@@ -164,8 +411,16 @@ impl Agent {
         self.pending_metadata.extend(metadata);
     }
 
+    /// Limit the next turn to `tools` (by name). Offered definitions are
+    /// filtered and calls to anything else are refused. An empty list clears
+    /// the restriction.
+    pub fn restrict_tools_for_turn(&mut self, tools: Vec<String>) {
+        self.turn_allowed_tools = (!tools.is_empty()).then_some(tools);
+    }
+
     pub fn add_user_message(&mut self, text: impl Into<String>) {
         let text_str = text.into();
+        self.referenced_paths = crate::file_refs::referenced_paths(&text_str);
 
         // Resolve file references (@filename) if present
         let resolved = if crate::file_refs::has_file_references(&text_str) {
@@ -188,8 +443,190 @@ impl Agent {
         self.messages.push(Message::user(resolved));
     }
 
+    /// Like `add_user_message`, with a base64-encoded image attached after the
+    /// text. Providers without image support fail the turn with
+    /// `ProviderError::Unsupported`.
+    pub fn add_user_message_with_image(
+        &mut self,
+        text: impl Into<String>,
+        media_type: impl Into<String>,
+        data: impl Into<String>,
+    ) {
+        self.add_user_message(text);
+        if let Some(message) = self.messages.last_mut() {
+            message.content.push(ContentBlock::Image {
+                media_type: media_type.into(),
+                data: data.into(),
+            });
+        }
+    }
+
     pub fn clear_history(&mut self) {
         self.messages.clear();
+        self.referenced_paths.clear();
+    }
+
+    /// Index of the most recent user prompt, skipping user-role messages that
+    /// only carry tool results.
+    fn last_user_prompt_index(&self) -> Option<usize> {
+        self.messages.iter().rposition(|m| {
+            m.role == "user"
+                && m.content
+                    .iter()
+                    .any(|b| matches!(b, ContentBlock::Text { .. }))
+        })
+    }
+
+    /// Text of the most recent user prompt, as it was sent (references resolved).
+    pub fn last_user_message(&self) -> Option<String> {
+        let idx = self.last_user_prompt_index()?;
+        let text = self.messages[idx]
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        Some(text)
+    }
+
+    /// Drop assistant and tool messages after the most recent user prompt so
+    /// the turn can be re-run without repeating the prompt. Returns `false`
+    /// when there is no user prompt to retry.
+    pub fn rewind_to_last_user_message(&mut self) -> bool {
+        let Some(idx) = self.last_user_prompt_index() else {
+            return false;
+        };
+        self.messages.truncate(idx + 1);
+        true
+    }
+
+    /// Snapshot the history and usage totals so a later `restore` can undo
+    /// whatever happens next. Only the newest `MAX_CHECKPOINTS` are kept.
+    pub fn checkpoint(&mut self) -> CheckpointId {
+        let id = CheckpointId(self.next_checkpoint);
+        self.next_checkpoint += 1;
+        if self.checkpoints.len() == MAX_CHECKPOINTS {
+            self.checkpoints.pop_front();
+        }
+        self.checkpoints.push_back(Checkpoint {
+            id,
+            messages: self.messages.clone(),
+            usage: self.usage,
+        });
+        id
+    }
+
+    /// Roll history and usage back to checkpoint `id`. Checkpoints taken
+    /// after it are discarded; `id` itself stays so it can be restored again.
+    /// Returns `false` if `id` is unknown or has been evicted.
+    pub fn restore(&mut self, id: CheckpointId) -> bool {
+        let Some(pos) = self.checkpoints.iter().position(|c| c.id == id) else {
+            return false;
+        };
+        self.checkpoints.truncate(pos + 1);
+        let checkpoint = &self.checkpoints[pos];
+        self.messages = checkpoint.messages.clone();
+        self.usage = checkpoint.usage;
+        self.referenced_paths.clear();
+        true
+    }
+
+    /// Available checkpoints, oldest first, with their message counts.
+    pub fn checkpoints(&self) -> Vec<(CheckpointId, usize)> {
+        self.checkpoints
+            .iter()
+            .map(|c| (c.id, c.messages.len()))
+            .collect()
+    }
+
+    /// Summarize older history once the estimated size exceeds
+    /// `defaults.max_context_tokens`, keeping recent turns verbatim.
+    /// Returns whether anything was compacted.
+    ///
+    /// Fires `BeforeCompaction` while the history is still intact and
+    /// `AfterCompaction` once it has been replaced; both carry message and
+    /// estimated token counts in their metadata.
+    pub fn compact_history(&mut self) -> bool {
+        let Some(budget) = self.runtime.defaults.max_context_tokens else {
+            return false;
+        };
+        let tokens_before = estimate_tokens(&self.messages);
+        if tokens_before <= budget as usize {
+            return false;
+        }
+        let Some(compacted) = summarize_history(&self.messages, COMPACTION_KEEP_RECENT) else {
+            return false;
+        };
+        let before = self.messages.len();
+        let event_ctx = EventContext::new()
+            .with_metadata("messages_before".to_string(), before.to_string())
+            .with_metadata("tokens_before".to_string(), tokens_before.to_string());
+        self.events.fire(Event::BeforeCompaction, &event_ctx);
+        self.execute_hooks_for_event(&Event::BeforeCompaction, &event_ctx);
+
+        self.messages = compacted;
+        let after = self.messages.len();
+        let tokens_after = estimate_tokens(&self.messages);
+        log::info!("compacted history: {before} -> {after} messages");
+
+        let event_ctx = event_ctx
+            .with_metadata("messages_after".to_string(), after.to_string())
+            .with_metadata("tokens_after".to_string(), tokens_after.to_string())
+            .with_metadata(
+                "tokens_saved".to_string(),
+                tokens_before.saturating_sub(tokens_after).to_string(),
+            );
+        self.events.fire(Event::AfterCompaction, &event_ctx);
+        self.execute_hooks_for_event(&Event::AfterCompaction, &event_ctx);
+        true
+    }
+
+    /// Write the conversation, usage totals, and provider/model to `path` as JSON.
+    // qual:allow(iosp) reason: "I/O boundary — serializes session to disk"
+    pub fn save_session(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let saved = SavedSession {
+            version: SAVED_SESSION_VERSION,
+            provider: self.provider.name().to_string(),
+            model: self.provider.model().clone(),
+            saved_at: chrono::Utc::now(),
+            usage: self.usage,
+            messages: self.messages.clone(),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+        Ok(())
+    }
+
+    /// Replace the conversation and usage totals with a session saved by
+    /// `save_session`. Warns when it was recorded with a different provider/model.
+    // qual:allow(iosp) reason: "I/O boundary — reads session from disk"
+    pub fn load_session(&mut self, path: &std::path::Path) -> anyhow::Result<SavedSession> {
+        let content = std::fs::read_to_string(path)?;
+        let saved: SavedSession = serde_json::from_str(&content)?;
+        if saved.version > SAVED_SESSION_VERSION {
+            anyhow::bail!(
+                "Session {} uses format v{}, newer than supported v{SAVED_SESSION_VERSION}",
+                path.display(),
+                saved.version
+            );
+        }
+        if saved.provider != self.provider.name() || &saved.model != self.provider.model() {
+            self.output.warn(&format!(
+                "Warning: session was saved with {}/{}, resuming with {}/{}",
+                saved.provider,
+                saved.model,
+                self.provider.name(),
+                self.provider.model()
+            ));
+        }
+        self.messages = saved.messages.clone();
+        self.usage = saved.usage;
+        Ok(saved)
     }
 
     pub fn provider_model_max_tokens(&self) -> u32 {
@@ -202,22 +639,53 @@ impl Agent {
 
     /// Cumulative token usage for this session (input, output).
     pub fn session_tokens(&self) -> (u32, u32) {
-        (self.session_input_tokens, self.session_output_tokens)
+        (self.usage.input_tokens, self.usage.output_tokens)
+    }
+
+    /// Aggregate usage for this session. Survives `clear_history`.
+    pub fn usage(&self) -> SessionUsage {
+        self.usage
     }
 
-    /// Estimated context size in tokens (1 token ≈ 4 chars).
+    pub fn reset_usage(&mut self) {
+        self.usage = SessionUsage::default();
+    }
+
+    /// Estimated context size in tokens (1 token ≈ 4 chars), covering the
+    /// message history plus the system prompt the next turn would send.
+    /// Hook-injected context is not included since hooks have not run yet.
     pub fn estimated_context_tokens(&self) -> u32 {
-        let chars: usize = self
-            .messages
-            .iter()
-            .flat_map(|m| m.content.iter())
-            .map(|b| match b {
-                ContentBlock::Text { text } => text.len(),
-                ContentBlock::ToolUse { input, .. } => input.to_string().len(),
-                ContentBlock::ToolResult { content, .. } => content.len(),
-            })
-            .sum();
-        (chars / 4) as u32
+        let mut system_chars = self.base_system_prompt().len()
+            + self.rules.format_for_paths(&self.rule_scope_paths()).len();
+        if let Some(instructions) = &self.instructions {
+            system_chars += instructions.format_for_prompt().len();
+        }
+        (estimate_tokens(&self.messages) + system_chars / 4) as u32
+    }
+
+    /// `(estimate, budget)` when the estimated context has reached
+    /// `CONTEXT_WARNING_RATIO` of `defaults.max_context_tokens`, so the caller
+    /// can warn before the next send. `None` when under budget or unbounded.
+    pub fn context_warning(&self) -> Option<(u32, u32)> {
+        let budget = self.runtime.defaults.max_context_tokens?;
+        let estimate = self.estimated_context_tokens();
+        (f64::from(estimate) >= f64::from(budget) * CONTEXT_WARNING_RATIO)
+            .then_some((estimate, budget))
+    }
+
+    /// Tool calls made during the most recent turn, in order.
+    pub fn last_turn_tool_calls(&self) -> &[ToolCallRecord] {
+        &self.turn_tool_calls
+    }
+
+    /// Summary of the most recent turn.
+    pub fn last_turn_summary(&self) -> TurnSummary {
+        TurnSummary {
+            assistant_text: self.latest_assistant_text(),
+            tool_calls: self.turn_tool_calls.clone(),
+            usage: self.turn_usage,
+            stop_reason: self.turn_stop_reason.clone(),
+        }
     }
 
     pub fn latest_assistant_text(&self) -> Option<String> {
@@ -243,7 +711,21 @@ impl Agent {
     }
 
     pub fn execute_hooks_for_event(&self, event: &Event, context: &EventContext) -> EventContext {
-        self.execute_hooks_for_event_with_callbacks(event, context, None, None, None)
+        self.execute_hooks_for_event_with_callbacks(
+            event,
+            context,
+            self.hook_approval_fn.as_ref(),
+            None,
+            None,
+        )
+    }
+
+    /// Names of the hooks registered for `event`, without running them.
+    pub fn hook_names_for_event(&self, event: &Event) -> Vec<String> {
+        self.hooks
+            .hooks_for_event(event)
+            .map(|hooks| hooks.iter().map(|hook| hook.name.clone()).collect())
+            .unwrap_or_default()
     }
 
     // qual:allow(iosp) reason: "I/O boundary — orchestrates hook execution with callbacks"
@@ -256,22 +738,55 @@ impl Agent {
         secret_prompt_fn: Option<&PromptCallback>,
     ) -> EventContext {
         let mut enriched_context = context.clone();
+        let Some(hooks) = self.hooks.hooks_for_event(event) else {
+            return enriched_context;
+        };
 
-        if let Some(hooks) = self.hooks.hooks_for_event(event) {
-            for hook in hooks {
-                if let Ok(results) = HookExecutor::execute_hook_with_callbacks(
-                    hook,
-                    context,
-                    approval_fn,
-                    prompt_fn,
-                    secret_prompt_fn,
-                ) {
-                    // Inject hook outputs into context metadata
-                    for result in results {
-                        if let Some(key) = result.inject_key {
-                            enriched_context.metadata.insert(key, result.output);
-                        }
-                    }
+        let (parallel, serial): (Vec<&Hook>, Vec<&Hook>) =
+            hooks.iter().partition(|hook| hook.runs_in_parallel());
+
+        let (serial_results, mut parallel_results) = std::thread::scope(|scope| {
+            let handles: Vec<_> = parallel
+                .iter()
+                .map(|hook| {
+                    scope.spawn(move || {
+                        let results = HookExecutor::execute_hook(hook, context);
+                        (hook.name.clone(), results)
+                    })
+                })
+                .collect();
+
+            let serial_results: Vec<_> = serial
+                .iter()
+                .map(|hook| {
+                    HookExecutor::execute_hook_with_callbacks(
+                        hook,
+                        context,
+                        approval_fn,
+                        prompt_fn,
+                        secret_prompt_fn,
+                    )
+                })
+                .collect();
+            let parallel_results: Vec<_> = handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .collect();
+            (serial_results, parallel_results)
+        });
+
+        // Serial hooks merge in file order, then parallel hooks by name so
+        // collisions resolve the same way regardless of completion order.
+        parallel_results.sort_by(|a, b| a.0.cmp(&b.0));
+        let all_results = serial_results
+            .into_iter()
+            .chain(parallel_results.into_iter().map(|(_, results)| results));
+
+        // Inject hook outputs into context metadata
+        for results in all_results.flatten() {
+            for result in results {
+                if let Some(key) = result.inject_key {
+                    enriched_context.metadata.insert(key, result.output);
                 }
             }
         }
@@ -279,21 +794,69 @@ impl Agent {
         enriched_context
     }
 
-    fn build_system_prompt(&self, enriched_ctx: &EventContext) -> String {
-        let mut system_prompt = format!(
-            "You are a concise coding assistant. Current working directory: {}",
-            self.tool_ctx.working_dir.display()
+    /// Paths the current turn is about: the latest prompt's `@` references and
+    /// the `path`/`from`/`to` inputs of recent tool calls, relative to the
+    /// working directory where possible.
+    fn rule_scope_paths(&self) -> Vec<String> {
+        let recent = &self.messages[self
+            .messages
+            .len()
+            .saturating_sub(RULE_SCOPE_RECENT_MESSAGES)..];
+        let tool_paths = recent
+            .iter()
+            .flat_map(|m| m.content.iter())
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { input, .. } => Some(input),
+                _ => None,
+            })
+            .flat_map(|input| {
+                ["path", "from", "to"]
+                    .into_iter()
+                    .filter_map(|key| input.get(key)?.as_str())
+            })
+            .map(|path| {
+                Path::new(path)
+                    .strip_prefix(&self.tool_ctx.working_dir)
+                    .map_or_else(|_| path.to_string(), |rel| rel.display().to_string())
+            });
+        self.referenced_paths
+            .iter()
+            .cloned()
+            .chain(tool_paths)
+            .collect()
+    }
+
+    fn base_system_prompt(&self) -> String {
+        let template = self.system_prompt_template.as_deref().unwrap_or(
+            "You are a concise coding assistant. Current working directory: {working_dir}",
         );
+        template.replace(
+            "{working_dir}",
+            &self.tool_ctx.working_dir.display().to_string(),
+        )
+    }
 
-        let rules_section = self.rules.format_for_prompt();
-        if !rules_section.is_empty() {
-            system_prompt.push_str(&rules_section);
-        }
+    fn build_system_prompt(&self, enriched_ctx: &EventContext) -> String {
+        let base = self.base_system_prompt();
+
+        // Sections in prompt order; `trim_order` below lists which go first
+        // when the prompt is over `system_prompt_max_chars`.
+        let mut sections = vec![
+            self.rules.format_for_paths(&self.rule_scope_paths()),
+            self.instructions
+                .as_ref()
+                .map(|instructions| instructions.format_for_prompt())
+                .unwrap_or_default(),
+        ];
 
+        let hook_header = sections.len();
+        let mut hook_sections = Vec::new();
         if !enriched_ctx.metadata.is_empty() {
             const MAX_INJECTION_SIZE: usize = 2000;
-            system_prompt.push_str("\n\n## Additional Context from Hooks:");
-            for (key, value) in &enriched_ctx.metadata {
+            sections.push("\n\n## Additional Context from Hooks:".to_string());
+            let mut injections: Vec<_> = enriched_ctx.metadata.iter().collect();
+            injections.sort();
+            for (key, value) in injections {
                 let truncated_value = if value.len() > MAX_INJECTION_SIZE {
                     format!(
                         "{}... [truncated {} bytes]",
@@ -303,11 +866,41 @@ impl Agent {
                 } else {
                     value.clone()
                 };
-                system_prompt.push_str(&format!("\n### {key}\n{truncated_value}"));
+                hook_sections.push(sections.len());
+                sections.push(format!("\n### {key}\n{truncated_value}"));
+            }
+        }
+
+        let repo_context = sections.len();
+        sections.push(self.repo_context_section());
+
+        if let Some(budget) = self.runtime.system_prompt_max_chars {
+            // Largest hook injection first, then repo context, rules, and
+            // project instructions. The base instruction is never trimmed.
+            hook_sections.sort_by_key(|&idx| std::cmp::Reverse(sections[idx].len()));
+            let mut trim_order = hook_sections;
+            if trim_order.is_empty() {
+                trim_order.push(repo_context);
+            } else {
+                trim_order.extend([hook_header, repo_context]);
             }
+            trim_order.extend([0, 1]);
+            trim_to_budget(
+                &mut sections,
+                &trim_order,
+                budget.saturating_sub(base.chars().count()),
+            );
+        }
+
+        let mut system_prompt = base;
+        for section in sections {
+            system_prompt.push_str(&section);
         }
+        system_prompt
+    }
 
-        // M3: inject pipeline compaction context (diff, recent files, globs)
+    /// M3: pipeline compaction context (diff, recent files, globs), or empty.
+    fn repo_context_section(&self) -> String {
         #[cfg(not(test))]
         if let Ok(app_cfg) = crate::app_config::AppConfig::load()
             && let Ok(compacted) = crate::pipeline::context_compact::compact_context(
@@ -316,11 +909,74 @@ impl Agent {
             )
             && !compacted.text.is_empty()
         {
-            system_prompt.push_str("\n\n## Repo Context\n");
-            system_prompt.push_str(&compacted.text);
+            return format!("\n\n## Repo Context\n{}", compacted.text);
         }
+        String::new()
+    }
 
-        system_prompt
+    /// The request the next turn would send, without calling the provider.
+    ///
+    /// Runs no hooks and fires no events, so context that `UserPromptSubmit`
+    /// hooks would inject is missing; `hook_names_for_event` lists them.
+    /// History is left untouched.
+    pub fn build_request(&self) -> InferenceRequest {
+        let user_msg = self
+            .messages
+            .last()
+            .filter(|m| m.role == "user")
+            .and_then(|m| m.content.first())
+            .and_then(|block| match block {
+                ContentBlock::Text { text } => Some(text.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let mut event_ctx = EventContext::new().with_user_message(user_msg);
+        for (key, value) in &self.pending_metadata {
+            event_ctx.metadata.insert(key.clone(), value.clone());
+        }
+
+        let system_prompt = self.build_system_prompt(&event_ctx);
+        self.request_with(
+            system_prompt,
+            self.turn_tools(self.turn_allowed_tools.as_deref()),
+        )
+    }
+
+    /// Tool definitions for a turn: the configured `enabled_tools`, narrowed
+    /// further by a skill's per-turn allowlist.
+    fn turn_tools(&self, allowed: Option<&[String]>) -> Vec<crate::api::ToolDefinition> {
+        let mut tools = tool_definitions(self.runtime.enabled_tools.as_deref());
+        if let Some(allowed) = allowed {
+            tools.retain(|t| allowed.iter().any(|name| *name == t.name));
+        }
+        tools
+    }
+
+    /// Assemble a request from the current history with the resolved token limit.
+    fn request_with(
+        &self,
+        system: String,
+        tools: Vec<crate::api::ToolDefinition>,
+    ) -> InferenceRequest {
+        // Output cap only; `max_context_tokens` budgets the input below.
+        let mut max_tokens = self.provider.model().max_tokens();
+        if let Some(override_tokens) = self.runtime.max_tokens_override {
+            max_tokens = max_tokens.min(override_tokens);
+        }
+        let messages = if let Some(max_context) = self.runtime.defaults.max_context_tokens {
+            compact_messages(&self.messages, max_context as usize)
+        } else {
+            self.messages.clone()
+        };
+        InferenceRequest {
+            model: self.provider.model().clone(),
+            messages,
+            tools,
+            max_tokens,
+            temperature: self.runtime.defaults.temperature,
+            top_p: self.runtime.defaults.top_p,
+            system,
+        }
     }
 
     fn log_inference(&mut self, response: &InferenceResponse) {
@@ -356,27 +1012,10 @@ impl Agent {
 
         let enriched_ctx = EventContext::new();
         let system_prompt = self.build_system_prompt(&enriched_ctx);
-
-        let mut max_tokens = self.provider.model().max_tokens();
-        if let Some(ov) = self.runtime.max_tokens_override {
-            max_tokens = max_tokens.min(ov);
-        }
-        if let Some(max_ctx) = self.runtime.defaults.max_context_tokens {
-            max_tokens = max_tokens.min(max_ctx);
-        }
-        let messages = if let Some(max_ctx) = self.runtime.defaults.max_context_tokens {
-            compact_messages(&self.messages, max_ctx as usize)
-        } else {
-            self.messages.clone()
-        };
-        let req = InferenceRequest {
-            model: self.provider.model().clone(),
-            messages,
-            tools: get_tool_definitions(),
-            max_tokens,
-            temperature: self.runtime.defaults.temperature,
-            system: system_prompt,
-        };
+        let req = self.request_with(
+            system_prompt,
+            self.turn_tools(self.turn_allowed_tools.as_deref()),
+        );
 
         // Stream text chunks to the output port, accumulate full text.
         let mut stream = self.provider.infer_stream(&req).await;
@@ -387,7 +1026,11 @@ impl Agent {
                     self.output.write_chunk(&text);
                     accumulated.push_str(&text);
                 }
-                Err(e) => return Err(AgentError::Inference(e.to_string())),
+                Err(e) => {
+                    let error = AgentError::Inference(e.to_string());
+                    self.fire_turn_error(&error);
+                    return Err(error);
+                }
             }
         }
 
@@ -416,6 +1059,42 @@ impl Agent {
     //
     // Blocked by: stable AgentBuilder and AgentRuntime Clone impls.
     pub async fn run_turn(&mut self) -> Result<(), AgentError> {
+        self.run_turn_cancellable(&CancellationToken::new()).await
+    }
+
+    /// Like `run_turn`, but stops with `AgentError::Cancelled` once `cancel`
+    /// fires. An in-flight inference is aborted and no partial assistant
+    /// message is recorded for it. A running `bash` command is killed, and
+    /// tool calls not yet started get a "cancelled by user" result so the
+    /// history stays valid for the next turn.
+    pub async fn run_turn_cancellable(
+        &mut self,
+        cancel: &CancellationToken,
+    ) -> Result<(), AgentError> {
+        self.tool_ctx.set_cancel(Some(cancel.clone()));
+        let result = self.run_turn_inner(cancel).await;
+        self.tool_ctx.set_cancel(None);
+        if let Err(e) = &result {
+            self.fire_turn_error(e);
+        }
+        result
+    }
+
+    /// Fire `TurnError` for a turn that failed as a whole (provider error,
+    /// timeout, iteration cap, pipeline failure). A user cancel is not an error.
+    fn fire_turn_error(&self, error: &AgentError) {
+        if matches!(error, AgentError::Cancelled) {
+            return;
+        }
+        let event_ctx = EventContext::new().with_error(error.to_string());
+        self.events.fire(Event::TurnError, &event_ctx);
+        self.execute_hooks_for_event(&Event::TurnError, &event_ctx);
+    }
+
+    async fn run_turn_inner(&mut self, cancel: &CancellationToken) -> Result<(), AgentError> {
+        self.turn_tool_calls.clear();
+        self.turn_usage = SessionUsage::default();
+        self.turn_stop_reason = None;
         let delegated_agent = self.pending_metadata.get("orchestration.agent").cloned();
         if let Some(agent_name) = delegated_agent.clone() {
             let strategy = self
@@ -468,46 +1147,47 @@ impl Agent {
         }
 
         let system_prompt = self.build_system_prompt(&enriched_ctx);
+        let allowed_tools = self.turn_allowed_tools.take();
+        let tools = self.turn_tools(allowed_tools.as_deref());
 
         let mut tool_call_counts: std::collections::HashMap<String, usize> =
             std::collections::HashMap::new();
+        let mut tool_iterations = 0usize;
 
         loop {
-            let mut max_tokens = self.provider.model().max_tokens();
-            if let Some(override_tokens) = self.runtime.max_tokens_override {
-                max_tokens = max_tokens.min(override_tokens);
-            }
-            if let Some(max_context) = self.runtime.defaults.max_context_tokens {
-                max_tokens = max_tokens.min(max_context);
+            self.compact_history();
+            let mut req = self.request_with(system_prompt.clone(), tools.clone());
+            if let Some(system) = self.fire_inference_start(&req, &enriched_ctx) {
+                req.system = system;
             }
-            let messages = if let Some(max_context) = self.runtime.defaults.max_context_tokens {
-                compact_messages(&self.messages, max_context as usize)
-            } else {
-                self.messages.clone()
-            };
-            let req = InferenceRequest {
-                model: self.provider.model().clone(),
-                messages,
-                tools: get_tool_definitions(),
-                max_tokens,
-                temperature: self.runtime.defaults.temperature,
-                system: system_prompt.clone(),
-            };
 
-            let response = if let Some(timeout_secs) = self.runtime.defaults.timeout_seconds {
-                match timeout(Duration::from_secs(timeout_secs), self.provider.infer(&req)).await {
-                    Ok(res) => res.map_err(|e| AgentError::Inference(e.to_string()))?,
-                    Err(_) => return Err(AgentError::Timeout),
+            if cancel.is_cancelled() {
+                return Err(AgentError::Cancelled);
+            }
+            let inference = async {
+                if let Some(timeout_secs) = self.runtime.defaults.timeout_seconds {
+                    match timeout(Duration::from_secs(timeout_secs), self.provider.infer(&req))
+                        .await
+                    {
+                        Ok(res) => res.map_err(|e| AgentError::Inference(e.to_string())),
+                        Err(_) => Err(AgentError::Timeout),
+                    }
+                } else {
+                    self.provider
+                        .infer(&req)
+                        .await
+                        .map_err(|e| AgentError::Inference(e.to_string()))
                 }
-            } else {
-                self.provider
-                    .infer(&req)
-                    .await
-                    .map_err(|e| AgentError::Inference(e.to_string()))?
+            };
+            let response = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => return Err(AgentError::Cancelled),
+                res = inference => res?,
             };
 
-            self.session_input_tokens += response.usage.input_tokens;
-            self.session_output_tokens += response.usage.output_tokens;
+            self.usage.record(&response.usage);
+            self.turn_usage.record(&response.usage);
+            self.turn_stop_reason = Some(response.stop_reason.clone());
 
             self.log_inference(&response);
 
@@ -567,7 +1247,13 @@ impl Agent {
                 break;
             }
 
+            tool_iterations += 1;
+            if tool_iterations > self.runtime.max_tool_iterations {
+                return Err(self.stop_at_iteration_limit(&tool_indices));
+            }
+
             let mut tool_results = Vec::new();
+            let mut images = Vec::new();
             let assistant_message = self.messages.last().expect("assistant message just pushed");
 
             for idx in tool_indices {
@@ -575,6 +1261,13 @@ impl Agent {
                 else {
                     continue;
                 };
+                if cancel.is_cancelled() {
+                    tool_results.push(ContentBlock::ToolResult {
+                        tool_use_id: id.clone(),
+                        content: "cancelled by user".to_string(),
+                    });
+                    continue;
+                }
                 // Fire PreToolUse event
                 let event_ctx = EventContext::new().with_tool_name(name.as_str().to_string());
                 self.events.fire(Event::PreToolUse, &event_ctx);
@@ -605,13 +1298,58 @@ impl Agent {
                     .await;
                 }
 
+                let change_preview = if self.preview_changes {
+                    crate::tools::preview_change(name.as_str(), input, &self.tool_ctx)
+                } else {
+                    None
+                };
+                if let Some(reason) = self.tool_denial(
+                    name.as_str(),
+                    input,
+                    allowed_tools.as_deref(),
+                    change_preview.as_deref(),
+                ) {
+                    self.output.tool_err(&reason);
+                    self.turn_tool_calls.push(ToolCallRecord {
+                        name: name.as_str().to_string(),
+                        input: input.clone(),
+                        output: serde_json::Value::String(reason.clone()),
+                        is_error: true,
+                    });
+                    if let Some(ref mut logger) = self.session_logger {
+                        let _ = logger.log(SessionEvent::ToolResult {
+                            tool_use_id: id.to_string(),
+                            output: reason.clone(),
+                            is_error: true,
+                            provider: self.provider.name().to_string(),
+                        });
+                    }
+                    tool_results.push(ContentBlock::ToolResult {
+                        tool_use_id: id.clone(),
+                        content: reason,
+                    });
+                    continue;
+                }
+
                 let result = self
                     .tool_executor
                     .execute(name.as_str(), input, &self.tool_ctx);
                 let tool_is_error = result.is_err();
 
                 let raw_content = match result {
-                    Ok(ref output) => {
+                    Ok(tool_output) => {
+                        if let Some(image) = tool_output.image()
+                            && self.provider.supports_images()
+                        {
+                            images.push(image);
+                        }
+                        let output = &tool_output.to_display_string();
+                        self.turn_tool_calls.push(ToolCallRecord {
+                            name: name.as_str().to_string(),
+                            input: input.clone(),
+                            output: tool_output.to_json(),
+                            is_error: false,
+                        });
                         self.output.tool_ok();
                         // Capture observation
                         self.observations.capture(
@@ -620,6 +1358,9 @@ impl Agent {
                             output.clone(),
                             Some(id.clone()),
                         );
+                        if self.runtime.observations.autosave {
+                            self.persist_observations();
+                        }
                         // Fire PostToolUse event on success
                         let event_ctx = EventContext::new()
                             .with_tool_name(name.as_str().to_string())
@@ -630,6 +1371,12 @@ impl Agent {
                     }
                     Err(e) => {
                         let err_msg = format!("error: {e}");
+                        self.turn_tool_calls.push(ToolCallRecord {
+                            name: name.as_str().to_string(),
+                            input: input.clone(),
+                            output: serde_json::Value::String(err_msg.clone()),
+                            is_error: true,
+                        });
                         self.output.tool_err(&err_msg);
                         self.system_monitor.record_error();
                         // Fire OnError event
@@ -661,7 +1408,12 @@ impl Agent {
                 });
             }
 
+            // Images follow the tool results they belong to, in call order.
+            tool_results.extend(images);
             self.messages.push(Message::tool_results(tool_results));
+            if cancel.is_cancelled() {
+                return Err(AgentError::Cancelled);
+            }
 
             // M1: pipeline self-check after successful tool-use round-trip
             if let Ok(app_cfg) = crate::app_config::AppConfig::load()
@@ -699,35 +1451,150 @@ impl Agent {
         }
 
         // L3: auto-persist observations to SQLite at session end
-        if self.observations.count() > 0 {
-            let obs_db = dirs::home_dir()
-                .unwrap_or_else(|| std::path::PathBuf::from("."))
-                .join(".looprs")
-                .join("observations.db");
-            if let Err(e) = self.observations.persist(&obs_db) {
-                self.output
-                    .warn(&format!("Warning: failed to persist observations: {e}"));
-            }
-        }
+        self.persist_observations();
 
         Ok(())
     }
 
-    async fn maybe_score(&self, trigger: crate::scorer::ScoreTrigger) {
-        let Some(ref logger) = self.session_logger else {
-            return;
-        };
-        let Some(ref config) = self.models_config else {
+    /// Write not-yet-persisted observations to the observations database.
+    fn persist_observations(&mut self) {
+        if self.observations.pending() == 0 {
             return;
-        };
+        }
+        if let Err(e) = self.observations.persist(&self.observations_db) {
+            self.output
+                .warn(&format!("Warning: failed to persist observations: {e}"));
+        }
+    }
 
-        let scorer_model = config
-            .tier("judge")
-            .map(|t| t.model.as_str())
-            .unwrap_or("gpt-4o");
-        let db_path = config.magi_db();
-        let db_opt = if db_path.is_empty() {
-            None
+    /// Fire `InferenceStart` for `req`. Hooks see the model and message count;
+    /// anything they inject is merged into the turn's hook context and the
+    /// rebuilt system prompt is returned for this request only.
+    fn fire_inference_start(
+        &self,
+        req: &InferenceRequest,
+        turn_ctx: &EventContext,
+    ) -> Option<String> {
+        let event_ctx = EventContext::new()
+            .with_metadata("model".to_string(), req.model.as_str().to_string())
+            .with_metadata("message_count".to_string(), req.messages.len().to_string());
+        self.events.fire(Event::InferenceStart, &event_ctx);
+        let enriched = self.execute_hooks_for_event(&Event::InferenceStart, &event_ctx);
+
+        let mut request_ctx = turn_ctx.clone();
+        let mut injected = false;
+        for (key, value) in enriched.metadata {
+            if !event_ctx.metadata.contains_key(&key) {
+                request_ctx.metadata.insert(key, value);
+                injected = true;
+            }
+        }
+        injected.then(|| self.build_system_prompt(&request_ctx))
+    }
+
+    /// Apply `RuntimeSettings::tool_approval` to a pending tool call. Returns
+    /// the tool result to record instead of running it, or `None` to proceed.
+    /// `Prompt` tools run unprompted when no approval callback is installed.
+    /// A `change_preview` diff is shown unless the call is denied outright.
+    fn tool_denial(
+        &self,
+        tool_name: &str,
+        input: &serde_json::Value,
+        allowed_tools: Option<&[String]>,
+        change_preview: Option<&str>,
+    ) -> Option<String> {
+        if let Some(enabled) = &self.runtime.enabled_tools
+            && !enabled.iter().any(|t| t == tool_name)
+        {
+            return Some(format!("denied: {tool_name} is disabled by enabled_tools"));
+        }
+        if let Some(allowed) = allowed_tools
+            && !allowed.iter().any(|t| t == tool_name)
+        {
+            return Some(format!(
+                "denied: {tool_name} is not allowed by the active skill"
+            ));
+        }
+        let policy = self.runtime.tool_approval.policy_for(tool_name);
+        if policy != ToolApproval::Deny
+            && let Some(diff) = change_preview
+        {
+            self.output.diff(diff);
+        }
+        match policy {
+            ToolApproval::Allow => None,
+            ToolApproval::Deny => Some(format!("denied by config: {tool_name} is not allowed")),
+            ToolApproval::Prompt => {
+                let approve = self.tool_approval_fn.as_ref()?;
+                let question = if change_preview.is_some() {
+                    format!("apply the {tool_name} change above")
+                } else {
+                    let preview = serde_json::to_string(input)
+                        .unwrap_or_default()
+                        .chars()
+                        .take(TOOL_APPROVAL_PREVIEW_LEN)
+                        .collect::<String>();
+                    format!("run {tool_name} {preview}")
+                };
+                if approve(&question) {
+                    None
+                } else {
+                    Some("denied by user".to_string())
+                }
+            }
+        }
+    }
+
+    /// Close out a turn that hit `max_tool_iterations`: answer the pending
+    /// tool calls with an error result and append a synthetic assistant note
+    /// so the history stays well-formed for the next turn.
+    fn stop_at_iteration_limit(&mut self, tool_indices: &[usize]) -> AgentError {
+        let limit = self.runtime.max_tool_iterations;
+        let notice = format!("Stopped after reaching the limit of {limit} tool iterations");
+        let tool_results = self
+            .messages
+            .last()
+            .map(|m| {
+                tool_indices
+                    .iter()
+                    .filter_map(|&idx| match m.content.get(idx) {
+                        Some(ContentBlock::ToolUse { id, .. }) => Some(ContentBlock::ToolResult {
+                            tool_use_id: id.clone(),
+                            content: format!("error: not executed. {notice}."),
+                        }),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.messages.push(Message::tool_results(tool_results));
+        self.messages
+            .push(Message::assistant(vec![ContentBlock::Text {
+                text: format!("[{notice}.]"),
+            }]));
+
+        let event_ctx = EventContext::new().with_warning(notice);
+        self.events.fire(Event::OnWarning, &event_ctx);
+        self.execute_hooks_for_event(&Event::OnWarning, &event_ctx);
+
+        AgentError::MaxIterationsExceeded(limit)
+    }
+
+    async fn maybe_score(&self, trigger: crate::scorer::ScoreTrigger) {
+        let Some(ref logger) = self.session_logger else {
+            return;
+        };
+        let Some(ref config) = self.models_config else {
+            return;
+        };
+
+        let scorer_model = config
+            .tier("judge")
+            .map(|t| t.model.as_str())
+            .unwrap_or("gpt-4o");
+        let db_path = config.magi_db();
+        let db_opt = if db_path.is_empty() {
+            None
         } else {
             Some(db_path)
         };
@@ -752,23 +1619,34 @@ impl Agent {
     }
 }
 
+/// Tool definitions offered to the model, limited to `allowed` when set.
+fn tool_definitions(allowed: Option<&[String]>) -> Vec<crate::api::ToolDefinition> {
+    let mut tools = get_tool_definitions();
+    if let Some(allowed) = allowed {
+        tools.retain(|t| allowed.iter().any(|name| *name == t.name));
+    }
+    tools
+}
+
+/// Estimate tokens for `msgs` at 1 token ≈ 4 characters.
+fn estimate_tokens(msgs: &[Message]) -> usize {
+    msgs.iter()
+        .flat_map(|m| m.content.iter())
+        .map(|block| match block {
+            ContentBlock::Text { text } => text.len().div_ceil(4),
+            ContentBlock::ToolUse { input, .. } => input.to_string().len().div_ceil(4),
+            ContentBlock::ToolResult { content, .. } => content.len().div_ceil(4),
+            ContentBlock::Image { .. } => IMAGE_TOKEN_ESTIMATE,
+        })
+        .sum()
+}
+
 /// Trim `messages` to fit within an estimated `max_tokens` budget.
 ///
 /// Estimates 1 token ≈ 4 characters. Drops the oldest user+assistant pairs
 /// from the front until the total fits. Always starts the result on a user
 /// message and preserves at least one message.
 fn compact_messages(messages: &[Message], max_tokens: usize) -> Vec<Message> {
-    fn estimate_tokens(msgs: &[Message]) -> usize {
-        msgs.iter()
-            .flat_map(|m| m.content.iter())
-            .map(|block| match block {
-                ContentBlock::Text { text } => text.len().div_ceil(4),
-                ContentBlock::ToolUse { input, .. } => input.to_string().len().div_ceil(4),
-                ContentBlock::ToolResult { content, .. } => content.len().div_ceil(4),
-            })
-            .sum()
-    }
-
     let mut start = 0;
     while start < messages.len().saturating_sub(1)
         && estimate_tokens(&messages[start..]) > max_tokens
@@ -782,6 +1660,65 @@ fn compact_messages(messages: &[Message], max_tokens: usize) -> Vec<Message> {
     messages[start..].to_vec()
 }
 
+/// Replace everything before the last `keep_recent` messages with a short
+/// textual summary, prepended to the first kept user message.
+///
+/// The cut is moved back to a plain user message so tool_use/tool_result
+/// pairs are never split. Tool-result bodies in the summarized span are
+/// dropped entirely. Returns `None` when there is nothing to summarize.
+fn summarize_history(messages: &[Message], keep_recent: usize) -> Option<Vec<Message>> {
+    let is_plain_user = |m: &Message| {
+        m.role == "user"
+            && !m
+                .content
+                .iter()
+                .any(|b| matches!(b, ContentBlock::ToolResult { .. }))
+    };
+    let mut cut = messages.len().saturating_sub(keep_recent);
+    while cut > 0 && !is_plain_user(&messages[cut]) {
+        cut -= 1;
+    }
+    if cut == 0 {
+        return None;
+    }
+
+    let mut summary = format!(
+        "[Earlier conversation compacted: {cut} messages summarized, tool output omitted]\n"
+    );
+    for message in &messages[..cut] {
+        for block in &message.content {
+            let line = match block {
+                ContentBlock::Text { text } => {
+                    let preview: String = text.chars().take(COMPACTION_PREVIEW_CHARS).collect();
+                    let ellipsis = if text.chars().count() > COMPACTION_PREVIEW_CHARS {
+                        "…"
+                    } else {
+                        ""
+                    };
+                    format!(
+                        "- {}: {}{ellipsis}",
+                        message.role,
+                        preview.replace('\n', " ")
+                    )
+                }
+                ContentBlock::ToolUse { name, .. } => format!("- assistant called `{name}`"),
+                ContentBlock::ToolResult { .. } => continue,
+                ContentBlock::Image { media_type, .. } => {
+                    format!("- {} attached an image ({media_type})", message.role)
+                }
+            };
+            summary.push_str(&line);
+            summary.push('\n');
+        }
+    }
+
+    let mut kept = messages[cut..].to_vec();
+    kept[0]
+        .content
+        .insert(0, ContentBlock::Text { text: summary });
+    Some(kept)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -794,6 +1731,8 @@ mod tests {
         model: crate::types::ModelId,
         responses: Vec<InferenceResponse>,
         call_count: std::sync::Arc<std::sync::Mutex<usize>>,
+        /// Every request received, in order.
+        requests: std::sync::Arc<std::sync::Mutex<Vec<InferenceRequest>>>,
     }
 
     /// Convenience wrapper: creates an Agent with NullOutput so tests don't
@@ -810,6 +1749,7 @@ mod tests {
                 model: crate::types::ModelId::new("mock-model"),
                 responses,
                 call_count: std::sync::Arc::new(std::sync::Mutex::new(0)),
+                requests: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
             }
         }
 
@@ -831,8 +1771,9 @@ mod tests {
     impl LLMProvider for MockProvider {
         async fn infer(
             &self,
-            _req: &InferenceRequest,
+            req: &InferenceRequest,
         ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
+            self.requests.lock().unwrap().push(req.clone());
             let mut count = self.call_count.lock().unwrap();
             let idx = *count;
             *count += 1;
@@ -1044,6 +1985,122 @@ actions:
         );
     }
 
+    #[test]
+    fn build_request_does_not_run_user_prompt_submit_hooks() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("ran");
+        std::fs::write(
+            temp_dir.path().join("submit.yaml"),
+            format!(
+                r#"name: on_submit
+trigger: UserPromptSubmit
+actions:
+  - type: command
+    command: "touch '{}'; echo injected"
+    inject_as: "submit_context""#,
+                marker.display()
+            ),
+        )
+        .unwrap();
+        let hooks = HookRegistry::load_from_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let mut agent = agent_for_test(MockProvider::simple_text("test")).with_hooks(hooks);
+        agent.add_user_message("hello");
+
+        let req = agent.build_request();
+
+        assert!(!marker.exists());
+        assert!(!req.system.contains("injected"));
+        assert_eq!(
+            agent.hook_names_for_event(&Event::UserPromptSubmit),
+            ["on_submit"]
+        );
+    }
+
+    #[tokio::test]
+    async fn inference_start_fires_once_per_loop_iteration() {
+        let mut agent = agent_for_test(single_tool_call("read"))
+            .with_tool_executor(Box::new(crate::tools::executor::StubToolExecutor::default()));
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        agent.events.on(Event::InferenceStart, move |_, ctx| {
+            sink.lock().unwrap().push(ctx.metadata.clone());
+        });
+
+        agent.add_user_message("read it");
+        agent.run_turn().await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert!(seen.iter().all(|meta| meta["model"] == "mock-model"));
+        assert_eq!(seen[0]["message_count"], "1");
+        assert_eq!(seen[1]["message_count"], "3");
+    }
+
+    #[tokio::test]
+    async fn inference_start_hooks_inject_into_the_request_system_prompt() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("late_context.yaml"),
+            r#"name: late_context
+trigger: InferenceStart
+actions:
+  - type: command
+    command: "echo 'fresh build status'"
+    inject_as: "build_status""#,
+        )
+        .unwrap();
+        let hooks = HookRegistry::load_from_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let provider = MockProvider::simple_text("done");
+        let requests = provider.requests.clone();
+        let mut agent = agent_for_test(provider).with_hooks(hooks);
+
+        agent.add_user_message("status?");
+        agent.run_turn().await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].system.contains("### build_status"));
+        assert!(requests[0].system.contains("fresh build status"));
+        assert!(!requests[0].system.contains("### message_count"));
+    }
+
+    #[test]
+    fn parallel_hooks_run_concurrently() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["slow_a", "slow_b"] {
+            std::fs::write(
+                temp_dir.path().join(format!("{name}.yaml")),
+                format!(
+                    r#"name: {name}
+trigger: SessionStart
+parallel: true
+actions:
+  - type: command
+    command: "sleep 1sec; echo {name}"
+    inject_as: "{name}""#
+                ),
+            )
+            .unwrap();
+        }
+        let hooks = HookRegistry::load_from_directory(&temp_dir.path().to_path_buf()).unwrap();
+        let agent = agent_for_test(MockProvider::simple_text("test")).with_hooks(hooks);
+
+        let start = std::time::Instant::now();
+        let enriched = agent.execute_hooks_for_event(&Event::SessionStart, &EventContext::new());
+        let elapsed = start.elapsed();
+
+        assert_eq!(enriched.metadata.get("slow_a").unwrap(), "slow_a");
+        assert_eq!(enriched.metadata.get("slow_b").unwrap(), "slow_b");
+        assert!(
+            elapsed < std::time::Duration::from_millis(1800),
+            "parallel hooks took {elapsed:?}"
+        );
+    }
+
     #[test]
     fn test_context_injection_without_inject_as() {
         use std::io::Write;
@@ -1113,108 +2170,1008 @@ actions:
     }
 
     #[tokio::test]
-    async fn test_run_turn_streaming_accumulates_chunks() {
-        // MockProvider uses the default infer_stream (wraps infer → single chunk).
-        let provider = MockProvider::simple_text("streamed response");
+    async fn usage_accumulates_across_turns_and_survives_clear() {
+        let text_response = |text: &str, input_tokens, output_tokens| InferenceResponse {
+            content: vec![ContentBlock::Text {
+                text: text.to_string(),
+            }],
+            stop_reason: "end_turn".to_string(),
+            usage: Usage {
+                input_tokens,
+                output_tokens,
+            },
+        };
+        let provider = MockProvider::new(vec![
+            text_response("first", 10, 20),
+            text_response("second", 15, 5),
+        ]);
         let mut agent = agent_for_test(provider);
 
-        agent.add_user_message("Hello");
-        let result = agent.run_turn_streaming().await;
+        agent.add_user_message("one");
+        agent.run_turn().await.unwrap();
+        agent.add_user_message("two");
+        agent.run_turn().await.unwrap();
 
-        assert!(
-            result.is_ok(),
-            "run_turn_streaming should succeed: {result:?}"
-        );
-        assert_eq!(agent.messages.len(), 2, "user + assistant messages");
-        assert_eq!(agent.messages[1].role, "assistant");
-        let text = match &agent.messages[1].content[0] {
-            ContentBlock::Text { text } => text.clone(),
-            other => panic!("expected Text block, got {other:?}"),
-        };
-        assert_eq!(text, "streamed response");
+        let usage = agent.usage();
+        assert_eq!(usage.input_tokens, 25);
+        assert_eq!(usage.output_tokens, 25);
+        assert_eq!(usage.inferences, 2);
+        assert_eq!(usage.total_tokens(), 50);
+
+        agent.clear_history();
+        assert_eq!(agent.usage(), usage);
+
+        agent.reset_usage();
+        assert_eq!(agent.usage(), SessionUsage::default());
     }
 
-    #[test]
-    fn compact_messages_drops_oldest_pairs_to_fit_window() {
-        // 10 alternating user+assistant messages — each "word" ≈ 4 chars = 1 token
-        // With a tight window, oldest pairs should be dropped
-        let messages: Vec<Message> = (0..10)
-            .flat_map(|i| {
-                vec![
-                    Message::user(format!("user message number {i}")),
-                    Message::assistant(vec![ContentBlock::Text {
-                        text: format!("assistant reply number {i}"),
-                    }]),
-                ]
-            })
-            .collect();
-        // Allow ~60 tokens → should keep only the most recent few pairs
-        let compacted = compact_messages(&messages, 60);
+    #[tokio::test]
+    async fn run_turn_stops_at_max_tool_iterations() {
+        let tool_use = InferenceResponse {
+            content: vec![ContentBlock::ToolUse {
+                id: crate::types::ToolId::new("call_1"),
+                name: crate::types::ToolName::new("read"),
+                input: serde_json::json!({"path": "foo.rs"}),
+            }],
+            stop_reason: "tool_use".to_string(),
+            usage: Usage {
+                input_tokens: 1,
+                output_tokens: 1,
+            },
+        };
+        let provider = MockProvider::new(vec![tool_use; 10]);
+        let calls = provider.call_count.clone();
+        let mut agent = agent_for_test(provider)
+            .with_tool_executor(Box::new(crate::tools::executor::StubToolExecutor::default()));
+        agent.set_runtime_settings(RuntimeSettings {
+            max_tool_iterations: 3,
+            ..RuntimeSettings::default()
+        });
+
+        agent.add_user_message("loop forever");
+        let err = agent.run_turn().await.unwrap_err();
+
+        assert!(matches!(err, AgentError::MaxIterationsExceeded(3)));
+        assert_eq!(*calls.lock().unwrap(), 4);
+        let last = agent.messages.last().unwrap();
+        assert_eq!(last.role, "assistant");
         assert!(
-            compacted.len() < messages.len(),
-            "expected compaction but got {} messages (same as input {})",
-            compacted.len(),
-            messages.len()
+            agent
+                .latest_assistant_text()
+                .unwrap()
+                .contains("limit of 3 tool iterations")
         );
-        // First message of the result must be a user message (never start mid-pair)
-        assert_eq!(compacted[0].role, "user");
+        // The refused tool call still gets a result so history stays valid.
+        let pending = &agent.messages[agent.messages.len() - 2];
+        assert!(matches!(
+            pending.content.first(),
+            Some(ContentBlock::ToolResult { content, .. }) if content.starts_with("error:")
+        ));
     }
 
-    #[test]
-    fn compact_messages_preserves_all_when_under_limit() {
-        let messages = vec![
-            Message::user("hi".to_string()),
-            Message::assistant(vec![ContentBlock::Text {
-                text: "hello".to_string(),
-            }]),
-        ];
-        let compacted = compact_messages(&messages, 100_000);
-        assert_eq!(compacted.len(), messages.len());
+    /// Records the names of tools it was asked to run.
+    struct RecordingExecutor {
+        calls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
     }
 
-    #[test]
-    fn observation_manager_initialized() {
-        let provider = MockProvider::simple_text("test");
-        let agent = agent_for_test(provider);
+    impl ToolExecutor for RecordingExecutor {
+        fn execute(
+            &self,
+            name: &str,
+            _args: &serde_json::Value,
+            _ctx: &ToolContext,
+        ) -> Result<crate::tools::ToolOutput, crate::tools::ToolError> {
+            self.calls.lock().unwrap().push(name.to_string());
+            Ok("ran".to_string().into())
+        }
+    }
 
-        assert_eq!(agent.observations.count(), 0);
+    /// A plausible input for `tool`: a command for `bash`, a path otherwise.
+    fn fixture_input(tool: &str) -> serde_json::Value {
+        match tool {
+            "bash" => serde_json::json!({"cmd": "rm -rf target"}),
+            _ => serde_json::json!({"path": "src/main.rs"}),
+        }
     }
 
-    #[test]
-    fn test_event_manager_initialized() {
-        let provider = MockProvider::simple_text("test");
-        let agent = agent_for_test(provider);
+    fn single_tool_call(tool: &str) -> MockProvider {
+        MockProvider::new(vec![InferenceResponse {
+            content: vec![ContentBlock::ToolUse {
+                id: crate::types::ToolId::new("call_1"),
+                name: crate::types::ToolName::new(tool),
+                input: fixture_input(tool),
+            }],
+            stop_reason: "tool_use".to_string(),
+            usage: Usage {
+                input_tokens: 1,
+                output_tokens: 1,
+            },
+        }])
+    }
 
-        // EventManager should be initialized and ready to use
-        let ctx = EventContext::new();
-        agent.events.fire(Event::SessionStart, &ctx);
-        // Should not panic
+    fn tool_result_contents(agent: &Agent) -> Vec<String> {
+        agent
+            .messages
+            .iter()
+            .flat_map(|m| m.content.iter())
+            .filter_map(|b| match b {
+                ContentBlock::ToolResult { content, .. } => Some(content.clone()),
+                _ => None,
+            })
+            .collect()
     }
 
-    #[test]
-    fn test_file_reference_resolution() {
-        use std::io::Write;
-        use tempfile::TempDir;
+    /// Records how many observations are already in the database each time
+    /// a tool runs.
+    struct DbCountingExecutor {
+        db: PathBuf,
+        counts: std::sync::Arc<std::sync::Mutex<Vec<i64>>>,
+    }
 
-        let temp_dir = TempDir::new().unwrap();
-        let test_file = temp_dir.path().join("test.txt");
-        let mut file = std::fs::File::create(&test_file).unwrap();
-        writeln!(file, "Hello from file!").unwrap();
+    impl ToolExecutor for DbCountingExecutor {
+        fn execute(
+            &self,
+            _name: &str,
+            _args: &serde_json::Value,
+            _ctx: &ToolContext,
+        ) -> Result<crate::tools::ToolOutput, crate::tools::ToolError> {
+            let count = rusqlite::Connection::open(&self.db)
+                .and_then(|conn| {
+                    conn.query_row("SELECT COUNT(*) FROM observations", [], |row| row.get(0))
+                })
+                .unwrap_or(0);
+            self.counts.lock().unwrap().push(count);
+            Ok("ran".to_string().into())
+        }
+    }
 
-        let provider = MockProvider::simple_text("test");
-        let mut agent = agent_for_test(provider);
+    #[tokio::test]
+    async fn autosave_persists_observations_as_they_are_captured() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("observations.db");
+        let counts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let tool_use = |id: &str| ContentBlock::ToolUse {
+            id: crate::types::ToolId::new(id),
+            name: crate::types::ToolName::new("read"),
+            // Distinct inputs, so the calls aren't rolled into one observation.
+            input: serde_json::json!({"path": format!("{id}.rs")}),
+        };
+        let provider = MockProvider::new(vec![InferenceResponse {
+            content: vec![tool_use("call_1"), tool_use("call_2"), tool_use("call_3")],
+            stop_reason: "tool_use".to_string(),
+            usage: Usage {
+                input_tokens: 1,
+                output_tokens: 1,
+            },
+        }]);
+        let mut agent = agent_for_test(provider).with_tool_executor(Box::new(DbCountingExecutor {
+            db: db.clone(),
+            counts: counts.clone(),
+        }));
+        agent.observations_db = db.clone();
+        agent.set_runtime_settings(RuntimeSettings {
+            observations: ObservationsConfig { autosave: true },
+            ..RuntimeSettings::default()
+        });
 
-        // Override working directory to temp dir for this test
-        agent.tool_ctx.working_dir = temp_dir.path().to_path_buf();
+        agent.add_user_message("read it three times");
+        agent.run_turn().await.unwrap();
 
-        // Add message with file reference
-        agent.add_user_message("Check @test.txt please");
+        // Each call sees every earlier observation already on disk.
+        assert_eq!(*counts.lock().unwrap(), vec![0, 1, 2]);
+        // The end-of-turn flush doesn't write them a second time.
+        let mgr = ObservationManager::load_from(agent.observations.session_id(), &db).unwrap();
+        assert_eq!(mgr.count(), 3);
+    }
 
-        // Should have resolved the file reference
-        assert_eq!(agent.messages.len(), 1);
-        let msg_content = agent.messages[0].content.first().unwrap();
-        if let crate::api::ContentBlock::Text { text } = msg_content {
-            assert!(text.contains("Hello from file!"));
+    /// Fails its first inference, then answers.
+    struct FlakyProvider {
+        model: crate::types::ModelId,
+        calls: std::sync::Mutex<usize>,
+    }
+
+    #[async_trait::async_trait]
+    impl LLMProvider for FlakyProvider {
+        async fn infer(
+            &self,
+            _req: &InferenceRequest,
+        ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
+            let mut calls = self.calls.lock().unwrap();
+            *calls += 1;
+            if *calls == 1 {
+                return Err(
+                    crate::errors::ProviderError::ApiError("overloaded".to_string()).into(),
+                );
+            }
+            Ok(InferenceResponse {
+                content: vec![ContentBlock::Text {
+                    text: "recovered".to_string(),
+                }],
+                stop_reason: "end_turn".to_string(),
+                usage: Usage {
+                    input_tokens: 1,
+                    output_tokens: 1,
+                },
+            })
+        }
+
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn model(&self) -> &crate::types::ModelId {
+            &self.model
+        }
+
+        fn validate_config(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_turn_fires_turn_error_with_message() {
+        let provider = FlakyProvider {
+            model: crate::types::ModelId::new("mock-model"),
+            calls: std::sync::Mutex::new(0),
+        };
+        let mut agent = Agent::new(Box::new(provider))
+            .unwrap()
+            .with_output(Box::new(NullOutput));
+        let errors = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = errors.clone();
+        agent.events.on(Event::TurnError, move |_, ctx| {
+            seen.lock()
+                .unwrap()
+                .push(ctx.error.clone().unwrap_or_default());
+        });
+
+        agent.add_user_message("summarize the repo");
+        assert!(agent.run_turn().await.is_err());
+        agent.run_turn().await.unwrap();
+
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("overloaded"), "{}", errors[0]);
+    }
+
+    #[tokio::test]
+    async fn retry_reruns_last_user_message_without_duplicating_it() {
+        let provider = FlakyProvider {
+            model: crate::types::ModelId::new("mock-model"),
+            calls: std::sync::Mutex::new(0),
+        };
+        let mut agent = Agent::new(Box::new(provider))
+            .unwrap()
+            .with_output(Box::new(NullOutput));
+        agent.add_user_message("summarize the repo");
+
+        assert!(agent.run_turn().await.is_err());
+        assert_eq!(
+            agent.last_user_message().as_deref(),
+            Some("summarize the repo")
+        );
+
+        assert!(agent.rewind_to_last_user_message());
+        agent.run_turn().await.unwrap();
+
+        let roles: Vec<_> = agent.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["user", "assistant"]);
+        assert_eq!(agent.latest_assistant_text().as_deref(), Some("recovered"));
+    }
+
+    #[test]
+    fn rewind_drops_partial_tool_round() {
+        let mut agent = agent_for_test(MockProvider::simple_text("unused"));
+        agent.add_user_message("first");
+        agent
+            .messages
+            .push(Message::assistant(vec![ContentBlock::ToolUse {
+                id: crate::types::ToolId::new("call_1"),
+                name: crate::types::ToolName::new("read"),
+                input: serde_json::json!({}),
+            }]));
+        agent.messages.push(Message {
+            role: "user".to_string(),
+            content: vec![ContentBlock::ToolResult {
+                tool_use_id: crate::types::ToolId::new("call_1"),
+                content: "ok".to_string(),
+            }],
+        });
+
+        assert!(agent.rewind_to_last_user_message());
+        assert_eq!(agent.messages.len(), 1);
+        assert!(!agent_for_test(MockProvider::simple_text("x")).rewind_to_last_user_message());
+    }
+
+    #[tokio::test]
+    async fn denied_bash_is_not_executed() {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let prompts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = prompts.clone();
+        let mut agent = agent_for_test(single_tool_call("bash"))
+            .with_tool_executor(Box::new(RecordingExecutor {
+                calls: calls.clone(),
+            }))
+            .with_tool_approval(Box::new(move |msg: &str| {
+                seen.lock().unwrap().push(msg.to_string());
+                false
+            }));
+
+        agent.add_user_message("clean up");
+        agent.run_turn().await.unwrap();
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(prompts.lock().unwrap().len(), 1);
+        assert!(prompts.lock().unwrap()[0].contains("rm -rf target"));
+        assert_eq!(tool_result_contents(&agent), vec!["denied by user"]);
+    }
+
+    #[tokio::test]
+    async fn skill_tool_restriction_refuses_other_tools_for_one_turn() {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut agent = agent_for_test(single_tool_call("bash")).with_tool_executor(Box::new(
+            RecordingExecutor {
+                calls: calls.clone(),
+            },
+        ));
+
+        agent.restrict_tools_for_turn(vec!["read".to_string(), "grep".to_string()]);
+        agent.add_user_message("review this");
+        agent.run_turn().await.unwrap();
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(
+            tool_result_contents(&agent),
+            vec!["denied: bash is not allowed by the active skill"]
+        );
+        assert!(agent.turn_allowed_tools.is_none());
+    }
+
+    /// Returns a fixed image for every call.
+    struct ImageExecutor;
+
+    impl ToolExecutor for ImageExecutor {
+        fn execute(
+            &self,
+            _name: &str,
+            _args: &serde_json::Value,
+            _ctx: &ToolContext,
+        ) -> Result<crate::tools::ToolOutput, crate::tools::ToolError> {
+            Ok(crate::tools::ToolOutput::Image {
+                text: "(image logo.png: image/png, 8 bytes)".to_string(),
+                media_type: "image/png".to_string(),
+                data: "iVBORw0KGgo=".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn image_tool_output_is_attached_as_image_block() {
+        let mut agent =
+            agent_for_test(single_tool_call("read")).with_tool_executor(Box::new(ImageExecutor));
+
+        agent.add_user_message("look at the logo");
+        agent.run_turn().await.unwrap();
+
+        let results = agent
+            .messages
+            .iter()
+            .find(|m| {
+                m.content
+                    .iter()
+                    .any(|b| matches!(b, ContentBlock::ToolResult { .. }))
+            })
+            .unwrap();
+        assert_eq!(
+            results.content,
+            [
+                ContentBlock::ToolResult {
+                    tool_use_id: crate::types::ToolId::new("call_1"),
+                    content: "(image logo.png: image/png, 8 bytes)".to_string(),
+                },
+                ContentBlock::Image {
+                    media_type: "image/png".to_string(),
+                    data: "iVBORw0KGgo=".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn tool_definitions_filters_to_allowlist() {
+        let allowed = vec!["read".to_string(), "grep".to_string()];
+        let names: Vec<String> = tool_definitions(Some(&allowed))
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.iter().all(|n| allowed.contains(n)));
+        assert_eq!(tool_definitions(None).len(), get_tool_definitions().len());
+    }
+
+    #[tokio::test]
+    async fn enabled_tools_limits_advertised_tools_and_refuses_others() {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut agent = agent_for_test(single_tool_call("bash")).with_tool_executor(Box::new(
+            RecordingExecutor {
+                calls: calls.clone(),
+            },
+        ));
+        agent.set_runtime_settings(RuntimeSettings {
+            enabled_tools: Some(vec!["read".to_string(), "grep".to_string()]),
+            ..RuntimeSettings::default()
+        });
+        agent.add_user_message("clean up");
+
+        let names: Vec<String> = agent
+            .build_request()
+            .tools
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(
+            names.iter().all(|n| n == "read" || n == "grep"),
+            "{names:?}"
+        );
+
+        agent.run_turn().await.unwrap();
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(
+            tool_result_contents(&agent),
+            vec!["denied: bash is disabled by enabled_tools"]
+        );
+        assert!(agent.last_turn_tool_calls()[0].is_error);
+    }
+
+    #[tokio::test]
+    async fn read_tools_run_without_prompting() {
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let prompted = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = prompted.clone();
+        let mut agent = agent_for_test(single_tool_call("read"))
+            .with_tool_executor(Box::new(RecordingExecutor {
+                calls: calls.clone(),
+            }))
+            .with_tool_approval(Box::new(move |_: &str| {
+                flag.store(true, std::sync::atomic::Ordering::SeqCst);
+                false
+            }));
+
+        agent.add_user_message("read it");
+        agent.run_turn().await.unwrap();
+
+        assert!(!prompted.load(std::sync::atomic::Ordering::SeqCst));
+        assert_eq!(*calls.lock().unwrap(), vec!["read".to_string()]);
+        assert_eq!(tool_result_contents(&agent), vec!["ran"]);
+    }
+
+    #[tokio::test]
+    async fn turn_records_tool_calls_with_their_output() {
+        let mut agent = agent_for_test(single_tool_call("read"))
+            .with_tool_executor(Box::new(crate::tools::executor::StubToolExecutor::default()));
+
+        agent.add_user_message("read it");
+        agent.run_turn().await.unwrap();
+
+        assert_eq!(
+            agent.last_turn_tool_calls(),
+            [ToolCallRecord {
+                name: "read".to_string(),
+                input: serde_json::json!({"path": "src/main.rs"}),
+                output: serde_json::json!("ok"),
+                is_error: false,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn last_turn_summary_captures_tool_call_and_final_text() {
+        let mut agent = agent_for_test(single_tool_call("read"))
+            .with_tool_executor(Box::new(crate::tools::executor::StubToolExecutor::default()));
+
+        agent.add_user_message("read it");
+        agent.run_turn().await.unwrap();
+
+        let summary = agent.last_turn_summary();
+        assert_eq!(summary.tool_calls.len(), 1);
+        let call = &summary.tool_calls[0];
+        assert_eq!(call.name, "read");
+        assert_eq!(call.input, serde_json::json!({"path": "src/main.rs"}));
+        assert_eq!(call.output, serde_json::json!("ok"));
+        assert!(!call.is_error);
+        assert_eq!(summary.assistant_text.as_deref(), Some("default response"));
+        assert_eq!(summary.usage.inferences, 2);
+        assert_eq!(summary.stop_reason.as_deref(), Some("end_turn"));
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["tool_calls"][0]["name"], "read");
+        assert_eq!(json["usage"]["inferences"], 2);
+        assert_eq!(json["stop_reason"], "end_turn");
+        assert_eq!(json["response"], "default response");
+    }
+
+    struct SlowProvider {
+        model: crate::types::ModelId,
+    }
+
+    #[async_trait::async_trait]
+    impl LLMProvider for SlowProvider {
+        async fn infer(
+            &self,
+            _req: &InferenceRequest,
+        ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(InferenceResponse {
+                content: vec![ContentBlock::Text {
+                    text: "too late".to_string(),
+                }],
+                stop_reason: "end_turn".to_string(),
+                usage: Usage {
+                    input_tokens: 1,
+                    output_tokens: 1,
+                },
+            })
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn model(&self) -> &crate::types::ModelId {
+            &self.model
+        }
+
+        fn validate_config(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_turn_leaves_no_partial_assistant_message() {
+        let provider = SlowProvider {
+            model: crate::types::ModelId::new("slow-model"),
+        };
+        let mut agent = Agent::new(Box::new(provider))
+            .unwrap()
+            .with_output(Box::new(NullOutput));
+        agent.add_user_message("take your time");
+
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+
+        let err = agent.run_turn_cancellable(&cancel).await.unwrap_err();
+        assert!(matches!(err, AgentError::Cancelled));
+        assert_eq!(agent.messages.len(), 1);
+        assert_eq!(agent.messages[0].role, "user");
+        assert_eq!(agent.usage(), SessionUsage::default());
+    }
+
+    /// Cancels `cancel` from inside the first tool call, like Ctrl-C during a
+    /// long-running command.
+    struct CancellingExecutor {
+        cancel: CancellationToken,
+        calls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl crate::tools::ToolExecutor for CancellingExecutor {
+        fn execute(
+            &self,
+            name: &str,
+            _args: &serde_json::Value,
+            _ctx: &ToolContext,
+        ) -> Result<crate::tools::ToolOutput, crate::tools::ToolError> {
+            self.calls.lock().unwrap().push(name.to_string());
+            self.cancel.cancel();
+            Ok("ran".to_string().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn cancel_during_tools_skips_the_rest_and_keeps_results_paired() {
+        let tool_use = |id: &str| ContentBlock::ToolUse {
+            id: crate::types::ToolId::new(id),
+            name: crate::types::ToolName::new("read"),
+            input: fixture_input("read"),
+        };
+        let provider = MockProvider::new(vec![InferenceResponse {
+            content: vec![tool_use("call_1"), tool_use("call_2")],
+            stop_reason: "tool_use".to_string(),
+            usage: Usage {
+                input_tokens: 1,
+                output_tokens: 1,
+            },
+        }]);
+        let cancel = CancellationToken::new();
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut agent = agent_for_test(provider).with_tool_executor(Box::new(CancellingExecutor {
+            cancel: cancel.clone(),
+            calls: calls.clone(),
+        }));
+        agent.add_user_message("read twice");
+
+        let err = agent.run_turn_cancellable(&cancel).await.unwrap_err();
+
+        assert!(matches!(err, AgentError::Cancelled));
+        assert_eq!(calls.lock().unwrap().len(), 1);
+        assert_eq!(
+            tool_result_contents(&agent),
+            vec!["ran", "cancelled by user"]
+        );
+        assert!(agent.tool_ctx.cancel().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_turn_streaming_accumulates_chunks() {
+        // MockProvider uses the default infer_stream (wraps infer → single chunk).
+        let provider = MockProvider::simple_text("streamed response");
+        let mut agent = agent_for_test(provider);
+
+        agent.add_user_message("Hello");
+        let result = agent.run_turn_streaming().await;
+
+        assert!(
+            result.is_ok(),
+            "run_turn_streaming should succeed: {result:?}"
+        );
+        assert_eq!(agent.messages.len(), 2, "user + assistant messages");
+        assert_eq!(agent.messages[1].role, "assistant");
+        let text = match &agent.messages[1].content[0] {
+            ContentBlock::Text { text } => text.clone(),
+            other => panic!("expected Text block, got {other:?}"),
+        };
+        assert_eq!(text, "streamed response");
+    }
+
+    #[test]
+    fn compact_messages_drops_oldest_pairs_to_fit_window() {
+        // 10 alternating user+assistant messages — each "word" ≈ 4 chars = 1 token
+        // With a tight window, oldest pairs should be dropped
+        let messages: Vec<Message> = (0..10)
+            .flat_map(|i| {
+                vec![
+                    Message::user(format!("user message number {i}")),
+                    Message::assistant(vec![ContentBlock::Text {
+                        text: format!("assistant reply number {i}"),
+                    }]),
+                ]
+            })
+            .collect();
+        // Allow ~60 tokens → should keep only the most recent few pairs
+        let compacted = compact_messages(&messages, 60);
+        assert!(
+            compacted.len() < messages.len(),
+            "expected compaction but got {} messages (same as input {})",
+            compacted.len(),
+            messages.len()
+        );
+        // First message of the result must be a user message (never start mid-pair)
+        assert_eq!(compacted[0].role, "user");
+    }
+
+    #[test]
+    fn estimated_context_tokens_grows_with_history_and_counts_system_prompt() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        let system_only = agent.estimated_context_tokens();
+        assert_eq!(system_only as usize, agent.base_system_prompt().len() / 4);
+
+        let mut previous = system_only;
+        for i in 0..5 {
+            agent.add_user_message(format!("question {i}: {}", "x".repeat(40)));
+            agent
+                .messages
+                .push(Message::assistant(vec![ContentBlock::Text {
+                    text: format!("answer {i}: {}", "y".repeat(40)),
+                }]));
+            let current = agent.estimated_context_tokens();
+            assert!(current > previous, "{current} should exceed {previous}");
+            previous = current;
+        }
+    }
+
+    #[test]
+    fn add_user_message_with_image_appends_image_block() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        let before = agent.estimated_context_tokens();
+
+        agent.add_user_message_with_image("what is this?", "image/png", "iVBORw0KGgo=");
+
+        let last = agent.messages.last().unwrap();
+        assert_eq!(last.role, "user");
+        assert!(matches!(
+            last.content.as_slice(),
+            [ContentBlock::Text { text }, ContentBlock::Image { media_type, .. }]
+                if text == "what is this?" && media_type == "image/png"
+        ));
+        assert!(
+            agent.estimated_context_tokens() as usize >= before as usize + IMAGE_TOKEN_ESTIMATE
+        );
+    }
+
+    #[test]
+    fn context_warning_fires_at_eighty_percent_of_budget() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        assert_eq!(agent.context_warning(), None);
+
+        let budget = agent.estimated_context_tokens() + 100;
+        agent.set_runtime_settings(RuntimeSettings {
+            defaults: DefaultsConfig {
+                max_context_tokens: Some(budget),
+                ..DefaultsConfig::default()
+            },
+            ..RuntimeSettings::default()
+        });
+        assert_eq!(agent.context_warning(), None);
+
+        agent.add_user_message("x".repeat(400));
+        let estimate = agent.estimated_context_tokens();
+        assert!(f64::from(estimate) >= f64::from(budget) * CONTEXT_WARNING_RATIO);
+        assert_eq!(agent.context_warning(), Some((estimate, budget)));
+    }
+
+    #[tokio::test]
+    async fn restore_rolls_history_and_usage_back_to_checkpoint() {
+        let mut agent = agent_for_test(MockProvider::simple_text("first"));
+        agent.add_user_message("one");
+        agent.run_turn().await.unwrap();
+        let history = agent.messages.clone();
+        let usage = agent.usage();
+
+        let id = agent.checkpoint();
+        agent.add_user_message("two");
+        agent.run_turn().await.unwrap();
+        let later = agent.checkpoint();
+        agent.add_user_message("three");
+        assert_eq!(agent.messages.len(), history.len() + 3);
+
+        assert!(agent.restore(id));
+        assert_eq!(agent.messages, history);
+        assert_eq!(agent.usage(), usage);
+        assert_eq!(agent.checkpoints(), [(id, history.len())]);
+        assert!(!agent.restore(later));
+
+        agent.add_user_message("four");
+        assert!(agent.restore(id));
+        assert_eq!(agent.messages, history);
+    }
+
+    #[test]
+    fn checkpoints_are_bounded() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        let first = agent.checkpoint();
+        for i in 0..MAX_CHECKPOINTS {
+            agent.add_user_message(format!("message {i}"));
+            agent.checkpoint();
+        }
+
+        assert_eq!(agent.checkpoints().len(), MAX_CHECKPOINTS);
+        assert!(!agent.restore(first));
+    }
+
+    #[test]
+    fn compact_history_summarizes_old_messages_and_keeps_latest_user() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        agent.set_runtime_settings(RuntimeSettings {
+            defaults: DefaultsConfig {
+                max_context_tokens: Some(500),
+                ..DefaultsConfig::default()
+            },
+            ..RuntimeSettings::default()
+        });
+        for i in 0..20 {
+            agent.add_user_message(format!("question {i}: {}", "x".repeat(400)));
+            agent
+                .messages
+                .push(Message::assistant(vec![ContentBlock::Text {
+                    text: format!("answer {i}: {}", "y".repeat(400)),
+                }]));
+        }
+        agent.add_user_message("latest question");
+        let before = agent.messages.len();
+
+        assert!(agent.compact_history());
+
+        assert!(agent.messages.len() < before);
+        assert_eq!(agent.messages[0].role, "user");
+        assert!(matches!(
+            agent.messages[0].content.first(),
+            Some(ContentBlock::Text { text }) if text.starts_with("[Earlier conversation compacted")
+        ));
+        let last = agent.messages.last().unwrap();
+        assert!(matches!(
+            last.content.as_slice(),
+            [ContentBlock::Text { text }] if text == "latest question"
+        ));
+    }
+
+    #[test]
+    fn compact_history_fires_before_and_after_events() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        agent.set_runtime_settings(RuntimeSettings {
+            defaults: DefaultsConfig {
+                max_context_tokens: Some(500),
+                ..DefaultsConfig::default()
+            },
+            ..RuntimeSettings::default()
+        });
+        for i in 0..20 {
+            agent.add_user_message(format!("question {i}: {}", "x".repeat(400)));
+            agent
+                .messages
+                .push(Message::assistant(vec![ContentBlock::Text {
+                    text: format!("answer {i}: {}", "y".repeat(400)),
+                }]));
+        }
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        for event in [Event::BeforeCompaction, Event::AfterCompaction] {
+            let seen = seen.clone();
+            agent.events.on(event, move |event, ctx| {
+                seen.lock().unwrap().push((event, ctx.metadata.clone()));
+            });
+        }
+
+        assert!(agent.compact_history());
+
+        let seen = seen.lock().unwrap();
+        let [
+            (Event::BeforeCompaction, before),
+            (Event::AfterCompaction, after),
+        ] = seen.as_slice()
+        else {
+            panic!("unexpected events: {seen:?}");
+        };
+        let count = |meta: &std::collections::HashMap<String, String>, key: &str| -> usize {
+            meta[key].parse().unwrap()
+        };
+        assert_eq!(count(before, "messages_before"), 40);
+        assert!(count(before, "tokens_before") > 500);
+        assert_eq!(count(after, "messages_after"), agent.messages.len());
+        assert!(count(after, "messages_after") < count(after, "messages_before"));
+        assert_eq!(
+            count(after, "tokens_saved"),
+            count(after, "tokens_before") - count(after, "tokens_after")
+        );
+        assert!(count(after, "tokens_saved") > 0);
+    }
+
+    #[test]
+    fn save_and_load_session_round_trips() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("sessions").join("demo.json");
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        agent.add_user_message("read foo");
+        agent.messages.push(Message::assistant(vec![
+            ContentBlock::Text {
+                text: "Reading it".to_string(),
+            },
+            ContentBlock::ToolUse {
+                id: crate::types::ToolId::new("t1"),
+                name: crate::types::ToolName::new("read"),
+                input: serde_json::json!({"path": "foo.rs", "limit": 10}),
+            },
+        ]));
+        agent
+            .messages
+            .push(Message::tool_results(vec![ContentBlock::ToolResult {
+                tool_use_id: crate::types::ToolId::new("t1"),
+                content: "   1| fn main() {}".to_string(),
+            }]));
+        agent.usage.record(&Usage {
+            input_tokens: 12,
+            output_tokens: 34,
+        });
+        agent.save_session(&path).unwrap();
+
+        let mut restored = agent_for_test(MockProvider::simple_text("other"));
+        let saved = restored.load_session(&path).unwrap();
+
+        assert_eq!(restored.messages, agent.messages);
+        assert_eq!(restored.usage(), agent.usage());
+        assert_eq!(saved.provider, "mock");
+        assert_eq!(saved.model, crate::types::ModelId::new("mock-model"));
+    }
+
+    #[test]
+    fn compact_history_noop_under_budget() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        agent.add_user_message("hi");
+        assert!(!agent.compact_history());
+        assert_eq!(agent.messages.len(), 1);
+    }
+
+    #[test]
+    fn summarize_history_never_splits_tool_pairs() {
+        let tool_use = Message::assistant(vec![ContentBlock::ToolUse {
+            id: crate::types::ToolId::new("t1"),
+            name: crate::types::ToolName::new("read"),
+            input: serde_json::json!({"path": "a"}),
+        }]);
+        let tool_result = Message::tool_results(vec![ContentBlock::ToolResult {
+            tool_use_id: crate::types::ToolId::new("t1"),
+            content: "secret body".to_string(),
+        }]);
+        let messages = vec![
+            Message::user("first".to_string()),
+            tool_use,
+            tool_result,
+            Message::assistant(vec![ContentBlock::Text {
+                text: "done".to_string(),
+            }]),
+            Message::user("second".to_string()),
+        ];
+
+        // keep_recent = 3 lands on the tool_result; walking back reaches index 0
+        assert!(summarize_history(&messages, 3).is_none());
+
+        let compacted = summarize_history(&messages, 1).unwrap();
+        assert_eq!(compacted.len(), 1);
+        let ContentBlock::Text { text } = &compacted[0].content[0] else {
+            panic!("expected summary text block");
+        };
+        assert!(text.contains("assistant called `read`"));
+        assert!(!text.contains("secret body"));
+    }
+
+    #[test]
+    fn compact_messages_preserves_all_when_under_limit() {
+        let messages = vec![
+            Message::user("hi".to_string()),
+            Message::assistant(vec![ContentBlock::Text {
+                text: "hello".to_string(),
+            }]),
+        ];
+        let compacted = compact_messages(&messages, 100_000);
+        assert_eq!(compacted.len(), messages.len());
+    }
+
+    #[test]
+    fn observation_manager_initialized() {
+        let provider = MockProvider::simple_text("test");
+        let agent = agent_for_test(provider);
+
+        assert_eq!(agent.observations.count(), 0);
+    }
+
+    #[test]
+    fn test_event_manager_initialized() {
+        let provider = MockProvider::simple_text("test");
+        let agent = agent_for_test(provider);
+
+        // EventManager should be initialized and ready to use
+        let ctx = EventContext::new();
+        agent.events.fire(Event::SessionStart, &ctx);
+        // Should not panic
+    }
+
+    #[test]
+    fn test_file_reference_resolution() {
+        use std::io::Write;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("test.txt");
+        let mut file = std::fs::File::create(&test_file).unwrap();
+        writeln!(file, "Hello from file!").unwrap();
+
+        let provider = MockProvider::simple_text("test");
+        let mut agent = agent_for_test(provider);
+
+        // Override working directory to temp dir for this test
+        agent.tool_ctx.working_dir = temp_dir.path().to_path_buf();
+
+        // Add message with file reference
+        agent.add_user_message("Check @test.txt please");
+
+        // Should have resolved the file reference
+        assert_eq!(agent.messages.len(), 1);
+        let msg_content = agent.messages[0].content.first().unwrap();
+        if let crate::api::ContentBlock::Text { text } = msg_content {
+            assert!(text.contains("Hello from file!"));
             assert!(text.contains("// File: test.txt"));
         } else {
             panic!("Expected text content block");
@@ -1247,6 +3204,8 @@ actions:
             content: "Always use snake_case".to_string(),
             categories: vec![],
             source: std::path::PathBuf::from("test"),
+            origin: crate::rules::RuleOrigin::Repo,
+            applies_to: vec![],
         });
         agent.rules = rules;
 
@@ -1255,6 +3214,141 @@ actions:
         assert!(prompt.contains("Always use snake_case"));
     }
 
+    #[test]
+    fn build_system_prompt_scopes_rules_to_turn_paths() {
+        let scoped = |id: &str, content: &str, pattern: &str| crate::rules::Rule {
+            id: id.to_string(),
+            title: id.to_string(),
+            content: content.to_string(),
+            categories: vec![],
+            source: std::path::PathBuf::from(id),
+            origin: crate::rules::RuleOrigin::Repo,
+            applies_to: vec![pattern.to_string()],
+        };
+        let mut rules = RuleRegistry::new();
+        rules.register(scoped("rust", "No unwrap in library code", "src/**/*.rs"));
+        rules.register(scoped("docs", "Wrap prose at 80 columns", "docs/**"));
+        let mut agent = agent_for_test(MockProvider::simple_text("test")).with_rules(rules);
+
+        agent.add_user_message("explain @src/widgets/missing_button.rs:1-5");
+        let prompt = agent.build_system_prompt(&EventContext::new());
+        assert!(prompt.contains("No unwrap in library code"));
+        assert!(!prompt.contains("Wrap prose at 80 columns"));
+
+        agent
+            .messages
+            .push(Message::assistant(vec![ContentBlock::ToolUse {
+                id: crate::types::ToolId::new("t1"),
+                name: crate::types::ToolName::new("read"),
+                input: serde_json::json!({"path": "docs/guide.md"}),
+            }]));
+        let prompt = agent.build_system_prompt(&EventContext::new());
+        assert!(prompt.contains("Wrap prose at 80 columns"));
+    }
+
+    #[test]
+    fn build_request_does_not_cap_output_by_context_budget() {
+        let mut provider = MockProvider::simple_text("test");
+        provider.model = crate::types::ModelId::claude_opus();
+        let mut agent = agent_for_test(provider);
+        agent.set_runtime_settings(RuntimeSettings {
+            defaults: DefaultsConfig {
+                max_context_tokens: Some(8192),
+                ..DefaultsConfig::default()
+            },
+            ..RuntimeSettings::default()
+        });
+        agent.add_user_message("hello");
+
+        // The model's own output cap, not the 8192-token input budget.
+        let req = agent.build_request();
+        assert_eq!(req.max_tokens, 64_000);
+    }
+
+    #[test]
+    fn build_request_includes_rules_and_respects_max_tokens_override() {
+        let mut rules = RuleRegistry::new();
+        rules.register(crate::rules::Rule {
+            id: "test-rule".to_string(),
+            title: "Test Rule".to_string(),
+            content: "Always use snake_case".to_string(),
+            categories: vec![],
+            source: std::path::PathBuf::from("test"),
+            origin: crate::rules::RuleOrigin::Repo,
+            applies_to: vec![],
+        });
+        let mut agent = agent_for_test(MockProvider::simple_text("test")).with_rules(rules);
+        agent.set_runtime_settings(RuntimeSettings {
+            max_tokens_override: Some(123),
+            ..RuntimeSettings::default()
+        });
+        agent.add_user_message("hello");
+
+        let req = agent.build_request();
+
+        assert!(req.system.contains("Always use snake_case"));
+        assert_eq!(req.max_tokens, 123);
+        assert_eq!(req.messages.len(), 1);
+        assert!(!req.tools.is_empty());
+    }
+
+    #[test]
+    fn build_request_uses_custom_system_prompt_template() {
+        let mut rules = RuleRegistry::new();
+        rules.register(crate::rules::Rule {
+            id: "test-rule".to_string(),
+            title: "Test Rule".to_string(),
+            content: "Always use snake_case".to_string(),
+            categories: vec![],
+            source: std::path::PathBuf::from("test"),
+            origin: crate::rules::RuleOrigin::Repo,
+            applies_to: vec![],
+        });
+        let mut agent = agent_for_test(MockProvider::simple_text("test")).with_rules(rules);
+        agent.set_system_prompt(Some(
+            "You are a Rust reviewer working in {working_dir}.".to_string(),
+        ));
+        agent.add_user_message("hello");
+
+        let req = agent.build_request();
+
+        let expected = format!(
+            "You are a Rust reviewer working in {}.",
+            agent.tool_ctx.working_dir.display()
+        );
+        assert!(req.system.starts_with(&expected), "{}", req.system);
+        assert!(!req.system.contains("concise coding assistant"));
+        let rules_at = req.system.find("Always use snake_case").unwrap();
+        assert!(rules_at > expected.len());
+
+        agent.set_system_prompt(None);
+        assert!(
+            agent
+                .build_request()
+                .system
+                .contains("concise coding assistant")
+        );
+    }
+
+    #[test]
+    fn build_system_prompt_includes_instructions() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let user = tmp.path().join("user");
+        let repo = tmp.path().join("repo");
+        for (dir, text) in [(&user, "Prefer tabs"), (&repo, "Prefer spaces")] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join("instructions.md"), text).unwrap();
+        }
+        let instructions = ProjectInstructions::load(Some(&user), Some(&repo));
+        let agent =
+            agent_for_test(MockProvider::simple_text("test")).with_instructions(instructions);
+
+        let prompt = agent.build_system_prompt(&EventContext::new());
+        assert!(prompt.contains("## Project Instructions"));
+        assert!(prompt.contains("Prefer spaces"));
+        assert!(!prompt.contains("Prefer tabs"));
+    }
+
     #[test]
     fn build_system_prompt_includes_hook_context() {
         let provider = MockProvider::simple_text("test");
@@ -1280,6 +3374,29 @@ actions:
         assert!(prompt.len() < 5000 + 500);
     }
 
+    #[test]
+    fn build_system_prompt_trims_largest_injection_to_budget() {
+        let mut agent = agent_for_test(MockProvider::simple_text("test"));
+        let base = agent.base_system_prompt();
+        let budget = base.chars().count() + 200;
+        agent.set_runtime_settings(RuntimeSettings {
+            system_prompt_max_chars: Some(budget),
+            ..RuntimeSettings::default()
+        });
+        let mut ctx = EventContext::new();
+        ctx.metadata
+            .insert("git_status".to_string(), "clean".to_string());
+        ctx.metadata.insert("big".to_string(), "y".repeat(1500));
+
+        let prompt = agent.build_system_prompt(&ctx);
+
+        assert!(prompt.starts_with(&base));
+        assert!(prompt.chars().count() <= budget);
+        assert!(prompt.contains("[trimmed]"));
+        assert!(prompt.contains("### git_status\nclean"));
+        assert!(!prompt.contains(&"y".repeat(200)));
+    }
+
     struct MockSessionStore {
         events: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
     }
@@ -1426,6 +3543,8 @@ actions:
             content: "rule content".to_string(),
             categories: vec![],
             source: std::path::PathBuf::from("test"),
+            origin: crate::rules::RuleOrigin::Repo,
+            applies_to: vec![],
         });
 
         let agent = agent_for_test(provider).with_rules(rules);
@@ -1,9 +1,12 @@
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 use crate::file_refs::FileRefPolicy;
 use crate::fs_mode::FsMode;
+use crate::model_info::ModelInfo;
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -16,16 +19,58 @@ pub struct AppConfig {
     pub agents: AgentsConfig,
     pub paths: PathsConfig,
     pub persistence: PersistenceConfig,
+    pub observations: ObservationsConfig,
+    pub ui: UiConfig,
+    pub tool_approval: ToolApprovalConfig,
+    /// Ordered `provider/model` entries tried when the primary provider hits a
+    /// rate limit, a 5xx, or a network error.
+    pub fallback_models: Vec<String>,
+    /// Reload provider.json and config.json in the REPL when they change on disk.
+    pub watch_config: bool,
+    /// Append every fired event to `.looprs/events.log` as JSON lines.
+    pub event_log: bool,
+    /// Replaces the base instruction of the system prompt; `{working_dir}` is
+    /// substituted. Rules, instructions, and hook context are still appended.
+    pub system_prompt_template: Option<String>,
+    /// Tools the model may see and call, e.g. `["read", "grep"]`. Unset
+    /// enables every built-in tool, subject to `agents.fs_mode`.
+    pub enabled_tools: Option<Vec<String>>,
+    /// Cap on the system prompt length in characters. Over budget, hook
+    /// context, repo context, rules, and instructions are trimmed in that
+    /// order; the base instruction is always kept.
+    pub system_prompt_max_chars: Option<usize>,
+    /// Context window and output limits by exact model id, overriding the
+    /// built-in table in `looprs_core::model_info`.
+    pub model_limits: HashMap<String, ModelInfo>,
 }
 
 impl AppConfig {
     /// Load from user-owned `.looprs/config.json`, then overlay onboarding from app state file.
     // qual:allow(iosp) reason: "I/O boundary — reads config file and deserializes"
     pub fn load() -> anyhow::Result<Self> {
-        let path = Path::new(".looprs/config.json");
+        Self::load_from(Path::new(".looprs/config.json"))
+    }
+
+    /// Like `load`, reading `path` instead of `.looprs/config.json`.
+    // qual:allow(iosp) reason: "I/O boundary — reads config file and deserializes"
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
         let mut config: Self = if path.exists() {
             let content = fs::read_to_string(path)?;
-            serde_json::from_str(&content)?
+            let value: serde_json::Value = crate::config_file::from_json_with_env(&content)
+                .with_context(|| format!("Invalid config in {}", path.display()))?;
+            for key in unknown_keys(&value) {
+                let hint = REMOVED_KEYS
+                    .iter()
+                    .find(|(removed, _)| *removed == key)
+                    .map_or("unknown key, ignored", |(_, hint)| hint);
+                crate::ui::warn(format!("Warning: {}: {key}: {hint}", path.display()));
+            }
+            let config: Self = serde_json::from_value(value)
+                .with_context(|| format!("Invalid config in {}", path.display()))?;
+            config
+                .validate()
+                .with_context(|| format!("Invalid config in {}", path.display()))?;
+            config
         } else {
             Self::default()
         };
@@ -36,24 +81,140 @@ impl AppConfig {
         Ok(config)
     }
 
+    /// Range-check numeric settings that deserialize fine but can't work.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let defaults = &self.defaults;
+        check_positive("defaults.max_context_tokens", defaults.max_context_tokens)?;
+        check_range("defaults.temperature", defaults.temperature, 0.0, 2.0)?;
+        check_range("defaults.top_p", defaults.top_p, 0.0, 1.0)?;
+        check_positive("defaults.timeout_seconds", defaults.timeout_seconds)?;
+        check_positive(
+            "file_references.max_file_bytes",
+            Some(self.file_references.max_file_bytes),
+        )?;
+        check_positive(
+            "file_references.max_total_bytes",
+            Some(self.file_references.max_total_bytes),
+        )?;
+        check_positive("agents.max_parallel", Some(self.agents.max_parallel))?;
+        check_positive("system_prompt_max_chars", self.system_prompt_max_chars)?;
+        for (model, info) in &self.model_limits {
+            check_positive(
+                &format!("model_limits.{model}.context_window"),
+                Some(info.context_window),
+            )?;
+            check_positive(
+                &format!("model_limits.{model}.max_output_tokens"),
+                Some(info.max_output_tokens),
+            )?;
+        }
+        if let Some(enabled) = &self.enabled_tools {
+            let known: Vec<String> = crate::tools::get_tool_definitions()
+                .into_iter()
+                .map(|t| t.name)
+                .collect();
+            if let Some(unknown) = enabled.iter().find(|name| !known.contains(name)) {
+                anyhow::bail!(
+                    "enabled_tools: unknown tool '{unknown}' (expected one of: {})",
+                    known.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub fn file_ref_policy(&self) -> FileRefPolicy {
         FileRefPolicy::from_config(&self.file_references)
     }
 }
 
+/// Keys dropped from the schema, with what replaced them. They load without
+/// effect so older config files keep working.
+const REMOVED_KEYS: &[(&str, &str)] = &[(
+    "file_references.max_size_mb",
+    "no longer used, ignored; see file_references.max_file_bytes",
+)];
+
+/// Dotted paths of keys in `value` that `AppConfig` doesn't define. Objects
+/// that are empty by default (`model_limits`, `tool_approval.tools`) are
+/// free-form maps and aren't descended into.
+fn unknown_keys(value: &serde_json::Value) -> Vec<String> {
+    fn walk(
+        value: &serde_json::Value,
+        known: &serde_json::Value,
+        prefix: &str,
+        out: &mut Vec<String>,
+    ) {
+        let (Some(map), Some(known)) = (value.as_object(), known.as_object()) else {
+            return;
+        };
+        if known.is_empty() {
+            return;
+        }
+        for (key, child) in map {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match known.get(key) {
+                Some(known_child) => walk(child, known_child, &path, out),
+                None => out.push(path),
+            }
+        }
+    }
+    let known = serde_json::to_value(AppConfig::default()).unwrap_or_default();
+    let mut out = Vec::new();
+    walk(value, &known, "", &mut out);
+    out
+}
+
+/// Error unless `value` is unset or non-zero.
+pub(crate) fn check_positive<T: Default + PartialEq>(
+    key: &str,
+    value: Option<T>,
+) -> anyhow::Result<()> {
+    if value.is_some_and(|v| v == T::default()) {
+        anyhow::bail!("{key} must be greater than 0");
+    }
+    Ok(())
+}
+
+/// Error unless `value` is unset or within `min..=max`.
+pub(crate) fn check_range(key: &str, value: Option<f32>, min: f32, max: f32) -> anyhow::Result<()> {
+    if let Some(v) = value
+        && !(min..=max).contains(&v)
+    {
+        anyhow::bail!("{key} = {v} is out of range (expected {min} to {max})");
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct DefaultsConfig {
     pub max_context_tokens: Option<u32>,
     pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
     pub timeout_seconds: Option<u64>,
 }
 
+impl DefaultsConfig {
+    /// Sampling parameters for provider construction and validation.
+    pub fn sampling(&self) -> crate::providers::SamplingParams {
+        crate::providers::SamplingParams {
+            temperature: self.temperature,
+            top_p: self.top_p,
+        }
+    }
+}
+
 impl Default for DefaultsConfig {
     fn default() -> Self {
         Self {
             max_context_tokens: Some(8192),
             temperature: Some(0.2),
+            top_p: None,
             timeout_seconds: Some(120),
         }
     }
@@ -63,7 +224,10 @@ impl Default for DefaultsConfig {
 #[serde(default)]
 pub struct FileReferencesConfig {
     pub prefix: String,
-    pub max_size_mb: u64,
+    /// Files larger than this are truncated with a marker.
+    pub max_file_bytes: u64,
+    /// Budget across all references in one prompt; later references are skipped.
+    pub max_total_bytes: u64,
     pub allowed_extensions: Vec<String>,
 }
 
@@ -71,7 +235,8 @@ impl Default for FileReferencesConfig {
     fn default() -> Self {
         Self {
             prefix: "@".to_string(),
-            max_size_mb: 10,
+            max_file_bytes: 256 * 1024,
+            max_total_bytes: 512 * 1024,
             allowed_extensions: vec![
                 "rs", "py", "ts", "js", "go", "java", "md", "txt", "json", "yaml", "toml",
             ]
@@ -127,6 +292,10 @@ pub struct AgentsConfig {
     pub delegate_by_default: bool,
     pub fs_mode: FsMode,
     pub default_agent: Option<String>,
+    /// Bash commands still allowed in `update` fs mode, e.g. `"git status"`.
+    /// A command matches an entry exactly or with extra arguments, and may not
+    /// chain, pipe, or redirect.
+    pub update_mode_commands: Vec<String>,
 }
 
 impl Default for AgentsConfig {
@@ -138,6 +307,7 @@ impl Default for AgentsConfig {
             delegate_by_default: true,
             fs_mode: FsMode::Write,
             default_agent: None,
+            update_mode_commands: Vec::new(),
         }
     }
 }
@@ -189,6 +359,70 @@ pub struct PersistenceConfig {
     pub session_store: SessionStoreBackend,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ObservationsConfig {
+    /// Write each observation to the database as it is captured instead of
+    /// only at the end of a turn, so a crash mid-turn loses nothing.
+    pub autosave: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct UiConfig {
+    /// Style headings, lists, emphasis, and code fences in assistant output.
+    pub render_markdown: bool,
+}
+
+/// How a tool call is gated before it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolApproval {
+    /// Run without asking.
+    Allow,
+    /// Ask through the agent's approval callback (interactive sessions only).
+    Prompt,
+    /// Never run.
+    Deny,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolApprovalConfig {
+    /// Per-tool overrides, keyed by tool name. Unlisted tools use the built-in
+    /// policy (reads allowed, writes/shell prompted), then `default`.
+    pub tools: HashMap<String, ToolApproval>,
+    /// Policy for tools with neither an override nor a built-in policy.
+    pub default: ToolApproval,
+}
+
+impl Default for ToolApprovalConfig {
+    fn default() -> Self {
+        Self {
+            tools: HashMap::new(),
+            default: ToolApproval::Prompt,
+        }
+    }
+}
+
+impl ToolApprovalConfig {
+    pub fn policy_for(&self, tool_name: &str) -> ToolApproval {
+        self.tools
+            .get(tool_name)
+            .copied()
+            .or_else(|| builtin_tool_approval(tool_name))
+            .unwrap_or(self.default)
+    }
+}
+
+fn builtin_tool_approval(tool_name: &str) -> Option<ToolApproval> {
+    match tool_name {
+        "read" | "glob" | "grep" | "tree" => Some(ToolApproval::Allow),
+        "write" | "edit" | "move" | "delete" | "bash" | "nu" => Some(ToolApproval::Prompt),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct PathsConfig {
@@ -248,6 +482,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tool_approval_defaults_allow_reads_and_prompt_for_writes() {
+        let cfg = ToolApprovalConfig::default();
+        assert_eq!(cfg.policy_for("read"), ToolApproval::Allow);
+        assert_eq!(cfg.policy_for("grep"), ToolApproval::Allow);
+        assert_eq!(cfg.policy_for("bash"), ToolApproval::Prompt);
+        assert_eq!(cfg.policy_for("edit"), ToolApproval::Prompt);
+        assert_eq!(cfg.policy_for("unknown"), ToolApproval::Prompt);
+    }
+
+    #[test]
+    fn tool_approval_config_parses_overrides() {
+        let cfg: AppConfig =
+            serde_json::from_str(r#"{ "tool_approval": { "tools": { "bash": "deny" } } }"#)
+                .unwrap();
+        assert_eq!(cfg.tool_approval.policy_for("bash"), ToolApproval::Deny);
+        assert_eq!(cfg.tool_approval.policy_for("read"), ToolApproval::Allow);
+    }
+
+    #[test]
+    fn unknown_and_removed_keys_load_with_warning() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config.json");
+        let content = r#"{
+            "defaults": { "temprature": 0.5 },
+            "file_references": { "max_size_mb": 10, "prefix": "%" },
+            "model_limits": { "my-model": { "context_window": 1, "max_output_tokens": 1 } },
+            "tool_approval": { "tools": { "bash": "deny" } },
+            "future_section": {}
+        }"#;
+        std::fs::write(&path, content).unwrap();
+
+        let cfg = AppConfig::load_from(&path).unwrap();
+        assert_eq!(cfg.file_references.prefix, "%");
+        assert_eq!(cfg.tool_approval.policy_for("bash"), ToolApproval::Deny);
+
+        let value: serde_json::Value = serde_json::from_str(content).unwrap();
+        let mut keys = unknown_keys(&value);
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "defaults.temprature",
+                "file_references.max_size_mb",
+                "future_section"
+            ]
+        );
+    }
+
+    #[test]
+    fn out_of_range_value_is_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config.json");
+        std::fs::write(&path, r#"{ "defaults": { "temperature": 3.5 } }"#).unwrap();
+
+        let err = format!("{:#}", AppConfig::load_from(&path).unwrap_err());
+        assert!(err.contains("defaults.temperature = 3.5"), "{err}");
+        assert!(err.contains("0 to 2"), "{err}");
+
+        let mut cfg = AppConfig::default();
+        cfg.agents.max_parallel = 0;
+        let err = cfg.validate().unwrap_err().to_string();
+        assert!(err.contains("agents.max_parallel"), "{err}");
+    }
+
+    #[test]
+    fn enabled_tools_rejects_unknown_names() {
+        let cfg: AppConfig =
+            serde_json::from_str(r#"{ "enabled_tools": ["read", "grep"] }"#).unwrap();
+        assert!(cfg.validate().is_ok());
+
+        let cfg: AppConfig =
+            serde_json::from_str(r#"{ "enabled_tools": ["read", "shell"] }"#).unwrap();
+        let err = cfg.validate().unwrap_err().to_string();
+        assert!(err.contains("unknown tool 'shell'"), "{err}");
+    }
+
     #[test]
     fn test_pipeline_config_defaults_roundtrip() {
         let config = AppConfig::default();
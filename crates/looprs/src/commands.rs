@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-/// A custom command definition loaded from YAML
+/// A custom command definition loaded from YAML or TOML
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Command {
     pub name: String,
@@ -40,6 +40,43 @@ pub enum CommandAction {
     Outsource,
 }
 
+/// Expand argument placeholders in a prompt or shell template.
+///
+/// `$ARGS` and `$@` are the full argument string, `$1`, `$2`, ... are its
+/// whitespace-separated tokens (empty when absent). Any other `$` is kept.
+pub fn expand_placeholders(template: &str, args: &str) -> String {
+    let args = args.trim();
+    let positional: Vec<&str> = args.split_whitespace().collect();
+    let mut out = String::with_capacity(template.len() + args.len());
+    let mut rest = template;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        if let Some(tail) = after.strip_prefix("ARGS") {
+            out.push_str(args);
+            rest = tail;
+        } else if let Some(tail) = after.strip_prefix('@') {
+            out.push_str(args);
+            rest = tail;
+        } else {
+            let digits = after.len() - after.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            match after[..digits].parse::<usize>() {
+                Ok(n) if n > 0 => {
+                    out.push_str(positional.get(n - 1).copied().unwrap_or(""));
+                    rest = &after[digits..];
+                }
+                _ => {
+                    out.push('$');
+                    rest = after;
+                }
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
 /// Registry of custom commands
 pub struct CommandRegistry {
     commands: HashMap<String, Command>,
@@ -65,9 +102,10 @@ impl CommandRegistry {
             let entry = entry?;
             let path = entry.path();
 
-            if path.extension().and_then(|s| s.to_str()) == Some("yaml")
-                || path.extension().and_then(|s| s.to_str()) == Some("yml")
-            {
+            if matches!(
+                path.extension().and_then(|s| s.to_str()),
+                Some("yaml" | "yml" | "toml")
+            ) {
                 match Self::parse_command(&path) {
                     Ok(command) => {
                         registry.register(command);
@@ -86,10 +124,14 @@ impl CommandRegistry {
         Ok(registry)
     }
 
-    /// Parse a command from YAML file
+    /// Parse a command file, as TOML for `.toml` and YAML otherwise
     fn parse_command(path: &std::path::Path) -> anyhow::Result<Command> {
         let contents = fs::read_to_string(path)?;
-        let command: Command = serde_yaml::from_str(&contents)?;
+        let command: Command = if path.extension().and_then(|s| s.to_str()) == Some("toml") {
+            toml::from_str(&contents)?
+        } else {
+            serde_yaml::from_str(&contents)?
+        };
         Ok(command)
     }
 
@@ -263,6 +305,40 @@ action:
         assert_eq!(cmd1.name, cmd2.name);
     }
 
+    #[test]
+    fn test_load_toml_shell_command_with_aliases() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_command_file(
+            temp_dir.path(),
+            "lint.toml",
+            r#"name = "lint"
+description = "Run linter"
+aliases = ["l", "clippy"]
+
+[action]
+type = "shell"
+command = "cargo clippy"
+inject_output = true
+"#,
+        );
+
+        let registry =
+            CommandRegistry::load_from_directory(&temp_dir.path().to_path_buf()).unwrap();
+
+        assert!(registry.has("l"));
+        assert!(registry.has("clippy"));
+        match &registry.get("lint").unwrap().action {
+            CommandAction::Shell {
+                command,
+                inject_output,
+            } => {
+                assert_eq!(command, "cargo clippy");
+                assert!(inject_output);
+            }
+            _ => panic!("Expected Shell action"),
+        }
+    }
+
     #[test]
     fn test_list_deduplicates() {
         let temp_dir = TempDir::new().unwrap();
@@ -287,6 +363,30 @@ action:
         assert_eq!(registry.list().len(), 1);
     }
 
+    #[test]
+    fn test_expand_positional_and_args() {
+        let expanded = expand_placeholders(
+            "Review $1 focusing on $2. Full request: $ARGS",
+            "  src/agent.rs errors please ",
+        );
+        assert_eq!(
+            expanded,
+            "Review src/agent.rs focusing on errors. Full request: src/agent.rs errors please"
+        );
+    }
+
+    #[test]
+    fn test_expand_missing_positional_is_empty() {
+        assert_eq!(
+            expand_placeholders("diff $1..$2 ($@)", "main"),
+            "diff main.. (main)"
+        );
+        assert_eq!(
+            expand_placeholders("cost: $5 or $HOME", ""),
+            "cost:  or $HOME"
+        );
+    }
+
     #[test]
     fn test_message_action() {
         let temp_dir = TempDir::new().unwrap();
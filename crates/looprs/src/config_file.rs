@@ -1,8 +1,10 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
+use crate::app_config::{check_positive, check_range};
+
 /// Per-provider configuration settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -16,16 +18,27 @@ pub struct ProviderSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
 
+    /// Sampling temperature (overrides `defaults.temperature` in config.json)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
     /// API timeout in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout_secs: Option<u64>,
 
+    /// API base URL for OpenAI-compatible endpoints (vLLM, LM Studio, gateways)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+
     /// Custom settings per provider
     #[serde(flatten)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Provider configuration file schema
+///
+/// Unknown top-level keys are warned about and ignored. Inside a section they
+/// are kept: `ProviderSettings::extra` collects custom per-provider keys.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 #[derive(Default)]
@@ -42,10 +55,18 @@ pub struct ProviderConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub openai: Option<ProviderSettings>,
 
+    /// Gemini-specific settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gemini: Option<ProviderSettings>,
+
     /// Local/Ollama-specific settings
     #[serde(skip_serializing_if = "Option::is_none")]
     pub local: Option<ProviderSettings>,
 
+    /// Replay-provider settings (`model` names the trace to replay)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ProviderSettings>,
+
     /// Default settings applied to all providers
     #[serde(skip_serializing_if = "Option::is_none")]
     pub defaults: Option<ProviderSettings>,
@@ -55,16 +76,64 @@ impl ProviderConfig {
     /// Load config from `.looprs/provider.json`
     // qual:allow(iosp) reason: "I/O boundary — reads config file and deserializes"
     pub fn load() -> Result<Self> {
-        let config_path = Path::new(".looprs/provider.json");
+        Self::load_from(Path::new(".looprs/provider.json"))
+    }
+
+    /// Load config from `config_path`; a missing file yields the defaults.
+    // qual:allow(iosp) reason: "I/O boundary — reads config file and deserializes"
+    pub fn load_from(config_path: &Path) -> Result<Self> {
         if !config_path.exists() {
             return Ok(Self::default());
         }
 
         let content = fs::read_to_string(config_path)?;
-        let config: Self = serde_json::from_str(&content)?;
+        let value: serde_json::Value = from_json_with_env(&content)
+            .with_context(|| format!("Invalid config in {}", config_path.display()))?;
+        for key in unknown_keys(&value) {
+            crate::ui::warn(format!(
+                "Warning: {}: {key}: unknown key, ignored",
+                config_path.display()
+            ));
+        }
+        let config: Self = serde_json::from_value(value)
+            .with_context(|| format!("Invalid config in {}", config_path.display()))?;
+        config
+            .validate()
+            .with_context(|| format!("Invalid config in {}", config_path.display()))?;
         Ok(config)
     }
 
+    /// Range-check numeric settings in every section. Temperatures are held
+    /// to the section's provider limit: Anthropic accepts up to 1, the rest 2.
+    pub fn validate(&self) -> Result<()> {
+        let sections = [
+            ("defaults", &self.defaults),
+            ("anthropic", &self.anthropic),
+            ("openai", &self.openai),
+            ("gemini", &self.gemini),
+            ("local", &self.local),
+            ("replay", &self.replay),
+        ];
+        for (section, settings) in sections {
+            let Some(settings) = settings else {
+                continue;
+            };
+            let max_temperature = match section {
+                "anthropic" => crate::providers::anthropic::MAX_TEMPERATURE,
+                _ => 2.0,
+            };
+            check_positive(&format!("{section}.max_tokens"), settings.max_tokens)?;
+            check_positive(&format!("{section}.timeout_secs"), settings.timeout_secs)?;
+            check_range(
+                &format!("{section}.temperature"),
+                settings.temperature,
+                0.0,
+                max_temperature,
+            )?;
+        }
+        Ok(())
+    }
+
     /// Save config to `.looprs/provider.json`
     pub fn save(&self) -> Result<()> {
         fs::create_dir_all(".looprs")?;
@@ -73,16 +142,29 @@ impl ProviderConfig {
         Ok(())
     }
 
-    /// Get settings for a specific provider
-    pub fn get_provider_settings(&self, provider_name: &str) -> Option<&ProviderSettings> {
+    /// The provider.json section holding settings for `provider_name`
+    pub fn section_name(provider_name: &str) -> Option<&'static str> {
         match provider_name {
-            "anthropic" | "anthropic-sdk" | "claude-sdk" => self.anthropic.as_ref(),
-            "openai" | "openai-sdk" => self.openai.as_ref(),
-            "local" | "ollama" => self.local.as_ref(),
+            "anthropic" | "anthropic-sdk" | "claude-sdk" => Some("anthropic"),
+            "openai" | "openai-sdk" => Some("openai"),
+            "gemini" | "google" => Some("gemini"),
+            "local" | "ollama" => Some("local"),
+            "replay" => Some("replay"),
             _ => None,
         }
     }
 
+    /// Get settings for a specific provider
+    pub fn get_provider_settings(&self, provider_name: &str) -> Option<&ProviderSettings> {
+        match Self::section_name(provider_name)? {
+            "anthropic" => self.anthropic.as_ref(),
+            "openai" => self.openai.as_ref(),
+            "gemini" => self.gemini.as_ref(),
+            "local" => self.local.as_ref(),
+            _ => self.replay.as_ref(),
+        }
+    }
+
     /// Merge provider-specific settings with defaults
     pub fn merged_settings(&self, provider_name: &str) -> ProviderSettings {
         let mut merged = self.defaults.clone().unwrap_or_default();
@@ -94,9 +176,15 @@ impl ProviderConfig {
             if let Some(max_tokens) = provider_settings.max_tokens {
                 merged.max_tokens = Some(max_tokens);
             }
+            if let Some(temperature) = provider_settings.temperature {
+                merged.temperature = Some(temperature);
+            }
             if let Some(timeout_secs) = provider_settings.timeout_secs {
                 merged.timeout_secs = Some(timeout_secs);
             }
+            if let Some(base_url) = &provider_settings.base_url {
+                merged.base_url = Some(base_url.clone());
+            }
             // Merge extra settings
             for (k, v) in &provider_settings.extra {
                 merged.extra.insert(k.clone(), v.clone());
@@ -107,6 +195,127 @@ impl ProviderConfig {
     }
 }
 
+/// Top-level keys of provider.json.
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "provider",
+    "anthropic",
+    "openai",
+    "gemini",
+    "local",
+    "replay",
+    "defaults",
+];
+
+/// Top-level keys in `value` that `ProviderConfig` doesn't define.
+fn unknown_keys(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_object()
+        .map(|map| {
+            map.keys()
+                .filter(|key| !TOP_LEVEL_KEYS.contains(&key.as_str()))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Deserialize config JSON after expanding `${VAR}` references in its strings,
+/// so secrets can stay in the environment instead of the file.
+pub(crate) fn from_json_with_env<T: serde::de::DeserializeOwned>(content: &str) -> Result<T> {
+    let mut value: serde_json::Value = serde_json::from_str(content)?;
+    expand_env_vars(&mut value, &|name| std::env::var(name).ok())?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Replace `${VAR}` in every string in `value` with `lookup(VAR)`. An unset
+/// variable is an error rather than an empty string.
+fn expand_env_vars(
+    value: &mut serde_json::Value,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<()> {
+    match value {
+        serde_json::Value::String(s) if s.contains("${") => {
+            *s = expand_str(s, lookup)?;
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                expand_env_vars(item, lookup)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for item in map.values_mut() {
+                expand_env_vars(item, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand_str(s: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            anyhow::bail!("Unterminated ${{...}} in config value {s:?}");
+        };
+        let name = &after[..end];
+        match lookup(name) {
+            Some(v) => out.push_str(&v),
+            None => anyhow::bail!("Environment variable {name} referenced in config is not set"),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Set the value at `path` (nested object keys) in the JSON file at `file`,
+/// creating the file and intermediate objects as needed.
+///
+/// Does a read-merge-write so keys this crate doesn't model are kept as-is.
+// qual:allow(iosp) reason: "I/O boundary — reads, patches, and rewrites a config file"
+pub fn write_json_setting(file: &Path, path: &[&str], value: serde_json::Value) -> Result<()> {
+    let Some((last, parents)) = path.split_last() else {
+        anyhow::bail!("Empty setting path for {}", file.display());
+    };
+    let mut root = if file.exists() {
+        serde_json::from_str(&fs::read_to_string(file)?)?
+    } else {
+        serde_json::Value::Object(serde_json::Map::new())
+    };
+
+    let mut node = &mut root;
+    for key in parents {
+        let serde_json::Value::Object(map) = node else {
+            anyhow::bail!(
+                "Cannot set {} in {}: not an object",
+                path.join("."),
+                file.display()
+            );
+        };
+        node = map
+            .entry(key.to_string())
+            .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+    }
+    let serde_json::Value::Object(map) = node else {
+        anyhow::bail!(
+            "Cannot set {} in {}: not an object",
+            path.join("."),
+            file.display()
+        );
+    };
+    map.insert(last.to_string(), value);
+
+    if let Some(parent) = file.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(file, serde_json::to_string_pretty(&root)? + "\n")?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,6 +432,125 @@ mod tests {
         assert_eq!(merged.max_tokens, Some(8192));
     }
 
+    #[test]
+    fn provider_temperature_overrides_global_default() {
+        let config = ProviderConfig {
+            defaults: Some(ProviderSettings {
+                temperature: Some(0.2),
+                ..Default::default()
+            }),
+            openai: Some(ProviderSettings {
+                temperature: Some(0.9),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(config.merged_settings("openai").temperature, Some(0.9));
+        assert_eq!(config.merged_settings("anthropic").temperature, Some(0.2));
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "MY_KEY").then(|| "sk-secret".to_string())
+    }
+
+    #[test]
+    fn env_references_are_expanded() {
+        let mut value = serde_json::json!({
+            "openai": {"api_key": "${MY_KEY}", "base_url": "http://${MY_KEY}/v1"},
+            "list": ["${MY_KEY}"]
+        });
+        expand_env_vars(&mut value, &lookup).unwrap();
+        assert_eq!(value["openai"]["api_key"], "sk-secret");
+        assert_eq!(value["openai"]["base_url"], "http://sk-secret/v1");
+        assert_eq!(value["list"][0], "sk-secret");
+    }
+
+    #[test]
+    fn strings_without_placeholders_are_unchanged() {
+        let mut value =
+            serde_json::json!({"model": "gpt-4o", "note": "costs $5", "max_tokens": 10});
+        let before = value.clone();
+        expand_env_vars(&mut value, &lookup).unwrap();
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn unset_env_reference_is_an_error() {
+        let mut value = serde_json::json!({"openai": {"api_key": "${NOT_SET_ANYWHERE}"}});
+        let err = expand_env_vars(&mut value, &lookup).unwrap_err();
+        assert!(err.to_string().contains("NOT_SET_ANYWHERE"), "{err}");
+    }
+
+    #[test]
+    fn unknown_top_level_key_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("provider.json");
+        fs::write(&file, r#"{"providr": "openai", "provider": "gemini"}"#).unwrap();
+
+        let config = ProviderConfig::load_from(&file).unwrap();
+        assert_eq!(config.provider.as_deref(), Some("gemini"));
+        let value = serde_json::json!({"providr": "openai", "gemini": {}});
+        assert_eq!(unknown_keys(&value), ["providr"]);
+    }
+
+    #[test]
+    fn gemini_and_replay_have_their_own_sections() {
+        let config: ProviderConfig = serde_json::from_value(serde_json::json!({
+            "gemini": {"model": "gemini-2.5-pro"},
+            "replay": {"model": "trace-1"},
+            "openai": {"model": "gpt-4o"}
+        }))
+        .unwrap();
+        assert_eq!(
+            config.merged_settings("google").model.as_deref(),
+            Some("gemini-2.5-pro")
+        );
+        assert_eq!(
+            config.merged_settings("replay").model.as_deref(),
+            Some("trace-1")
+        );
+    }
+
+    #[test]
+    fn anthropic_temperature_is_capped_at_one() {
+        let settings = |temperature| {
+            Some(ProviderSettings {
+                temperature: Some(temperature),
+                ..Default::default()
+            })
+        };
+        let config = ProviderConfig {
+            anthropic: settings(1.5),
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("anthropic.temperature"), "{err}");
+
+        let config = ProviderConfig {
+            anthropic: settings(1.0),
+            gemini: settings(1.5),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn zero_max_tokens_is_rejected() {
+        let config = ProviderConfig {
+            openai: Some(ProviderSettings {
+                max_tokens: Some(0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("openai.max_tokens must be greater than 0"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn test_provider_config_serialization() {
         let config = ProviderConfig {
@@ -239,4 +567,43 @@ mod tests {
         let deserialized: ProviderConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.provider, Some("openai".to_string()));
     }
+
+    #[test]
+    fn write_json_setting_only_changes_the_target_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("provider.json");
+        fs::write(
+            &file,
+            r#"{"provider": "anthropic", "anthropic": {"model": "old", "custom_flag": true}, "team_note": "keep me"}"#,
+        )
+        .unwrap();
+
+        write_json_setting(&file, &["anthropic", "model"], serde_json::json!("new")).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!({
+                "provider": "anthropic",
+                "anthropic": {"model": "new", "custom_flag": true},
+                "team_note": "keep me"
+            })
+        );
+    }
+
+    #[test]
+    fn write_json_setting_creates_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".looprs").join("config.json");
+
+        write_json_setting(&file, &["defaults", "temperature"], serde_json::json!(0.5)).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!({"defaults": {"temperature": 0.5}})
+        );
+    }
 }
@@ -0,0 +1,140 @@
+//! Hot reload of `.looprs/provider.json` and `.looprs/config.json`.
+//!
+//! Enabled by `watch_config` in config.json. The watcher only records that a
+//! config file changed; the REPL polls `take_change` between inputs and
+//! applies `reload_configs` itself, so the agent is never touched mid-turn.
+
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::app_config::AppConfig;
+use crate::config_file::ProviderConfig;
+
+pub const PROVIDER_CONFIG_NAME: &str = "provider.json";
+pub const APP_CONFIG_NAME: &str = "config.json";
+
+/// Editors often write a file several times per save; wait this long for the
+/// burst to settle before reloading.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub struct ConfigWatcher {
+    // Dropping the watcher stops the notifications.
+    _watcher: RecommendedWatcher,
+    changes: Receiver<()>,
+}
+
+impl ConfigWatcher {
+    /// Watch `dir` (usually `.looprs`) for edits to either config file.
+    // qual:allow(iosp) reason: "I/O boundary — registers a filesystem watcher"
+    pub fn watch(dir: &Path) -> notify::Result<Self> {
+        let (tx, changes) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                if let Ok(event) = res
+                    && is_config_change(&event)
+                {
+                    let _ = tx.send(());
+                }
+            })?;
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// True once per burst of edits. Drains pending notifications, waiting up
+    /// to `DEBOUNCE` after the latest one so a half-written file isn't read.
+    pub fn take_change(&self) -> bool {
+        if self.changes.try_recv().is_err() {
+            return false;
+        }
+        while self.changes.recv_timeout(DEBOUNCE).is_ok() {}
+        true
+    }
+}
+
+/// Whether `event` created, modified, or removed one of the config files.
+fn is_config_change(event: &notify::Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) && event.paths.iter().any(|path| {
+        path.file_name()
+            .is_some_and(|name| name == PROVIDER_CONFIG_NAME || name == APP_CONFIG_NAME)
+    })
+}
+
+/// Re-read both config files from `dir`. This is what the REPL runs when the
+/// watcher reports a change.
+// qual:allow(iosp) reason: "I/O boundary — reads both config files"
+pub fn reload_configs(dir: &Path) -> anyhow::Result<(AppConfig, ProviderConfig)> {
+    let app_config = AppConfig::load_from(&dir.join(APP_CONFIG_NAME))?;
+    let provider_config = ProviderConfig::load_from(&dir.join(PROVIDER_CONFIG_NAME))?;
+    Ok((app_config, provider_config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn reload_picks_up_edited_provider_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(PROVIDER_CONFIG_NAME);
+        fs::write(
+            &file,
+            r#"{"provider": "openai", "openai": {"model": "gpt-4o"}}"#,
+        )
+        .unwrap();
+        let (_, before) = reload_configs(dir.path()).unwrap();
+        assert_eq!(
+            before.merged_settings("openai").model.as_deref(),
+            Some("gpt-4o")
+        );
+
+        fs::write(
+            &file,
+            r#"{"provider": "anthropic", "anthropic": {"model": "claude-sonnet-4-5", "max_tokens": 2048}}"#,
+        )
+        .unwrap();
+        let (_, after) = reload_configs(dir.path()).unwrap();
+        assert_eq!(after.provider.as_deref(), Some("anthropic"));
+        let settings = after.merged_settings("anthropic");
+        assert_eq!(settings.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(settings.max_tokens, Some(2048));
+    }
+
+    #[test]
+    fn reload_reports_malformed_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(PROVIDER_CONFIG_NAME), "{ not json").unwrap();
+        assert!(reload_configs(dir.path()).is_err());
+    }
+
+    #[test]
+    fn only_config_file_events_count_as_changes() {
+        let event = |kind, path: &str| notify::Event {
+            kind,
+            paths: vec![PathBuf::from(".looprs").join(path)],
+            attrs: Default::default(),
+        };
+        let modify = EventKind::Modify(ModifyKind::Any);
+        assert!(is_config_change(&event(modify, "provider.json")));
+        assert!(is_config_change(&event(
+            EventKind::Create(CreateKind::File),
+            "config.json"
+        )));
+        assert!(!is_config_change(&event(modify, "state.json")));
+        assert!(!is_config_change(&event(
+            EventKind::Access(AccessKind::Any),
+            "config.json"
+        )));
+    }
+}
@@ -0,0 +1,255 @@
+//! The settings actually in effect after merging env vars, `provider.json`,
+//! `config.json`, and built-in defaults, each tagged with where it came from.
+//! Backs `looprs config`.
+
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::app_config::AppConfig;
+use crate::config_file::{ProviderConfig, ProviderSettings};
+use crate::providers::detect_provider;
+
+/// Where an effective value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Environment variable.
+    Env(String),
+    /// JSON path in `.looprs/provider.json`.
+    ProviderFile(String),
+    /// JSON path in `.looprs/config.json`.
+    AppFile(String),
+    /// Built-in default.
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Env(var) => write!(f, "env {var}"),
+            Source::ProviderFile(path) => write!(f, "provider.json {path}"),
+            Source::AppFile(path) => write!(f, "config.json {path}"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveSetting {
+    pub key: &'static str,
+    pub value: String,
+    pub source: Source,
+}
+
+impl EffectiveSetting {
+    fn new(key: &'static str, value: impl fmt::Display, source: Source) -> Self {
+        Self {
+            key,
+            value: value.to_string(),
+            source,
+        }
+    }
+}
+
+/// Merge `app` and `provider` with the environment the same way startup does.
+///
+/// `app_file` is the raw JSON of `config.json` (`Value::Null` when absent); it
+/// tells a value the user wrote apart from one `AppConfig` filled in.
+pub fn effective_settings(
+    app: &AppConfig,
+    app_file: &Value,
+    provider: &ProviderConfig,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Vec<EffectiveSetting> {
+    let app_source = |path: &str| {
+        let pointer = format!("/{}", path.replace('.', "/"));
+        if app_file.pointer(&pointer).is_some() {
+            Source::AppFile(path.to_string())
+        } else {
+            Source::Default
+        }
+    };
+
+    let (provider_name, provider_source) = select_provider(provider, env);
+    let mut settings = vec![EffectiveSetting::new(
+        "provider",
+        &provider_name,
+        provider_source,
+    )];
+
+    let model = env("MODEL")
+        .map(|m| (m, Source::Env("MODEL".to_string())))
+        .or_else(|| provider_setting(provider, &provider_name, "model", |s| s.model.clone()))
+        .or_else(|| {
+            matches!(provider_name.as_str(), "local" | "ollama")
+                .then(|| env("OLLAMA_MODEL"))
+                .flatten()
+                .map(|m| (m, Source::Env("OLLAMA_MODEL".to_string())))
+        })
+        .unwrap_or_else(|| ("(provider default)".to_string(), Source::Default));
+    settings.push(EffectiveSetting::new("model", model.0, model.1));
+
+    let max_tokens = provider_setting(provider, &provider_name, "max_tokens", |s| s.max_tokens)
+        .map(|(v, source)| (v.to_string(), source))
+        .unwrap_or_else(|| ("(model limit)".to_string(), Source::Default));
+    settings.push(EffectiveSetting::new(
+        "max_tokens",
+        max_tokens.0,
+        max_tokens.1,
+    ));
+
+    let temperature = provider_setting(provider, &provider_name, "temperature", |s| s.temperature)
+        .map(|(v, source)| (v.to_string(), source))
+        .unwrap_or_else(|| {
+            let value = app
+                .defaults
+                .temperature
+                .map_or_else(|| "(unset)".to_string(), |v| v.to_string());
+            (value, app_source("defaults.temperature"))
+        });
+    settings.push(EffectiveSetting::new(
+        "temperature",
+        temperature.0,
+        temperature.1,
+    ));
+
+    let timeout = provider_setting(provider, &provider_name, "timeout_secs", |s| s.timeout_secs)
+        .map(|(v, source)| (v.to_string(), source))
+        .unwrap_or_else(|| {
+            let value = app
+                .defaults
+                .timeout_seconds
+                .map_or_else(|| "(unset)".to_string(), |v| v.to_string());
+            (value, app_source("defaults.timeout_seconds"))
+        });
+    settings.push(EffectiveSetting::new("timeout_secs", timeout.0, timeout.1));
+
+    let refs = &app.file_references;
+    settings.extend([
+        EffectiveSetting::new(
+            "file_references.prefix",
+            &refs.prefix,
+            app_source("file_references.prefix"),
+        ),
+        EffectiveSetting::new(
+            "file_references.max_file_bytes",
+            refs.max_file_bytes,
+            app_source("file_references.max_file_bytes"),
+        ),
+        EffectiveSetting::new(
+            "file_references.max_total_bytes",
+            refs.max_total_bytes,
+            app_source("file_references.max_total_bytes"),
+        ),
+        EffectiveSetting::new(
+            "file_references.allowed_extensions",
+            refs.allowed_extensions.join(","),
+            app_source("file_references.allowed_extensions"),
+        ),
+        EffectiveSetting::new(
+            "fs_mode",
+            app.agents.fs_mode.as_str(),
+            app_source("agents.fs_mode"),
+        ),
+    ]);
+    settings
+}
+
+/// Same order as `create_provider_with_overrides`: `PROVIDER`, then the
+/// config file, then whichever API key is set, then local Ollama.
+fn select_provider(
+    provider: &ProviderConfig,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> (String, Source) {
+    if let Some(name) = env("PROVIDER") {
+        return (name, Source::Env("PROVIDER".to_string()));
+    }
+    if let Some(name) = &provider.provider {
+        return (name.clone(), Source::ProviderFile("provider".to_string()));
+    }
+    match detect_provider(|var| env(var).is_some()) {
+        Some((var, name)) => (name.to_string(), Source::Env(var.to_string())),
+        None => ("ollama".to_string(), Source::Default),
+    }
+}
+
+/// A provider.json value as `merged_settings` resolves it, sourced to the
+/// provider's own section if set there, otherwise to `defaults`.
+fn provider_setting<T>(
+    provider: &ProviderConfig,
+    provider_name: &str,
+    key: &str,
+    get: impl Fn(&ProviderSettings) -> Option<T>,
+) -> Option<(T, Source)> {
+    let value = get(&provider.merged_settings(provider_name))?;
+    let in_section = provider
+        .get_provider_settings(provider_name)
+        .and_then(&get)
+        .is_some();
+    let section = ProviderConfig::section_name(provider_name)
+        .filter(|_| in_section)
+        .unwrap_or("defaults");
+    Some((value, Source::ProviderFile(format!("{section}.{key}"))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn find<'a>(settings: &'a [EffectiveSetting], key: &str) -> &'a EffectiveSetting {
+        settings.iter().find(|s| s.key == key).unwrap()
+    }
+
+    #[test]
+    fn env_override_wins_over_config_value() {
+        let provider = ProviderConfig {
+            provider: Some("openai".to_string()),
+            openai: Some(ProviderSettings {
+                model: Some("gpt-4o".to_string()),
+                max_tokens: Some(4096),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let env = |name: &str| (name == "MODEL").then(|| "gpt-5".to_string());
+
+        let settings = effective_settings(&AppConfig::default(), &Value::Null, &provider, &env);
+
+        let model = find(&settings, "model");
+        assert_eq!(model.value, "gpt-5");
+        assert_eq!(model.source, Source::Env("MODEL".to_string()));
+
+        let provider = find(&settings, "provider");
+        assert_eq!(provider.value, "openai");
+        assert_eq!(provider.source.to_string(), "provider.json provider");
+
+        let max_tokens = find(&settings, "max_tokens");
+        assert_eq!(max_tokens.value, "4096");
+        assert_eq!(
+            max_tokens.source.to_string(),
+            "provider.json openai.max_tokens"
+        );
+    }
+
+    #[test]
+    fn app_values_are_attributed_to_file_or_default() {
+        let app_file = json!({"defaults": {"temperature": 0.7}});
+        let app: AppConfig = serde_json::from_value(app_file.clone()).unwrap();
+        let env = |name: &str| (name == "ANTHROPIC_API_KEY").then(|| "sk".to_string());
+
+        let settings = effective_settings(&app, &app_file, &ProviderConfig::default(), &env);
+
+        assert_eq!(
+            find(&settings, "provider").source,
+            Source::Env("ANTHROPIC_API_KEY".to_string())
+        );
+        let temperature = find(&settings, "temperature");
+        assert_eq!(temperature.value, "0.7");
+        assert_eq!(
+            temperature.source.to_string(),
+            "config.json defaults.temperature"
+        );
+        assert_eq!(find(&settings, "fs_mode").source, Source::Default);
+    }
+}
@@ -39,6 +39,78 @@ pub enum ProviderError {
     #[error("API error: {0}")]
     #[diagnostic(code(looprs::provider::api))]
     ApiError(String),
+
+    #[error("Server error: {0}")]
+    #[diagnostic(
+        code(looprs::provider::server),
+        help("The provider failed on its side; retry later or configure a fallback model")
+    )]
+    ServerError(String),
+
+    #[error("{feature} is not supported by provider {provider}")]
+    #[diagnostic(
+        code(looprs::provider::unsupported),
+        help("Switch to a provider that supports this feature, or use a fallback path")
+    )]
+    Unsupported { feature: String, provider: String },
+
+    #[error(
+        "Rate limited by provider{}: {message}",
+        .retry_after.map(|d| format!(", retry in {}s", d.as_secs())).unwrap_or_default()
+    )]
+    #[diagnostic(
+        code(looprs::provider::rate_limited),
+        help("Wait before sending the next request, or switch to a fallback model")
+    )]
+    RateLimited {
+        /// Delay requested by the provider's `Retry-After` header, if any.
+        retry_after: Option<std::time::Duration>,
+        /// What the provider said, usually the response body.
+        message: String,
+    },
+}
+
+impl From<looprs_core::ports::UnsupportedFeature> for ProviderError {
+    fn from(err: looprs_core::ports::UnsupportedFeature) -> Self {
+        Self::Unsupported {
+            feature: err.feature,
+            provider: err.provider,
+        }
+    }
+}
+
+impl ProviderError {
+    /// Recover a typed `ProviderError` from the boxed error returned by
+    /// `InferenceProvider` methods. Unknown errors become `ApiError`.
+    pub fn from_boxed(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        let err = match err.downcast::<ProviderError>() {
+            Ok(provider_err) => return *provider_err,
+            Err(other) => other,
+        };
+        match err.downcast::<looprs_core::ports::UnsupportedFeature>() {
+            Ok(unsupported) => (*unsupported).into(),
+            Err(other) => Self::ApiError(other.to_string()),
+        }
+    }
+
+    pub fn is_unsupported(&self) -> bool {
+        matches!(self, Self::Unsupported { .. })
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Self::RateLimited { .. })
+    }
+
+    /// Worth retrying elsewhere: rate limits, 5xx responses and network
+    /// failures. Config and invalid-request errors fail the same way on
+    /// every provider.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::ServerError(_) => true,
+            Self::Http(e) => !e.is_builder() && !e.is_decode(),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -82,4 +154,17 @@ pub enum AgentError {
         )
     )]
     PipelineFailure(String),
+
+    #[error("Stopped after {0} tool iterations without a final answer")]
+    #[diagnostic(
+        code(looprs::agent::max_iterations),
+        help(
+            "The model kept requesting tools. Rephrase the request, or raise RuntimeSettings.max_tool_iterations"
+        )
+    )]
+    MaxIterationsExceeded(usize),
+
+    #[error("Turn cancelled")]
+    #[diagnostic(code(looprs::agent::cancelled))]
+    Cancelled,
 }
@@ -0,0 +1,114 @@
+//! Durable audit trail of fired events.
+//!
+//! Enabled by `event_log` in config.json. Each event is appended to
+//! `.looprs/events.log` as one JSON line with its name, a timestamp, and the
+//! `EventContext` metadata. Writes happen on a background thread so a slow
+//! disk never holds up a turn, and a failed write only warns.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+
+use serde_json::{Value, json};
+
+use crate::events::{Event, EventContext};
+
+pub const EVENT_LOG_PATH: &str = ".looprs/events.log";
+
+pub struct EventLog {
+    sender: Option<Sender<Value>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl EventLog {
+    /// Open `path` for appending, creating it and its parent directory.
+    // qual:allow(iosp) reason: "I/O boundary — opens the log file and spawns its writer"
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let (sender, records) = mpsc::channel::<Value>();
+        let path = path.to_path_buf();
+        let writer = std::thread::spawn(move || {
+            let mut file = file;
+            for record in records {
+                write_record(&mut file, &path, &record);
+            }
+        });
+        Ok(Self {
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// Queue `event` for writing. Never blocks on the file.
+    pub fn record(&self, event: Event, context: &EventContext) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(event_record(event, context));
+        }
+    }
+}
+
+impl Drop for EventLog {
+    /// Flush queued events before the log goes away.
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn event_record(event: Event, context: &EventContext) -> Value {
+    json!({
+        "ts": chrono::Utc::now().to_rfc3339(),
+        "event": event.name(),
+        "metadata": context.metadata,
+    })
+}
+
+fn write_record(file: &mut File, path: &Path, record: &Value) {
+    if let Err(e) = writeln!(file, "{record}") {
+        crate::ui::warn(format!(
+            "Warning: Failed to write event log {}: {e}",
+            path.display()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventManager;
+
+    #[test]
+    fn fired_events_are_appended_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".looprs").join("events.log");
+        let log = EventLog::open(&path).unwrap();
+
+        let mut manager = EventManager::new();
+        manager.on_any(move |event, ctx| log.record(event, ctx));
+        manager.fire(
+            Event::SessionStart,
+            &EventContext::new().with_metadata("provider".to_string(), "mock".to_string()),
+        );
+        manager.fire(Event::PreToolUse, &EventContext::new());
+        manager.fire(Event::SessionEnd, &EventContext::new());
+        drop(manager);
+
+        let lines: Vec<Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let names: Vec<&str> = lines.iter().map(|l| l["event"].as_str().unwrap()).collect();
+        assert_eq!(names, ["SessionStart", "PreToolUse", "SessionEnd"]);
+        assert_eq!(lines[0]["metadata"]["provider"], "mock");
+        assert!(lines[1]["metadata"].as_object().unwrap().is_empty());
+        assert!(chrono::DateTime::parse_from_rfc3339(lines[2]["ts"].as_str().unwrap()).is_ok());
+    }
+}
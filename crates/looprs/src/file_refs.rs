@@ -1,15 +1,20 @@
 use anyhow::{Context, Result};
+use std::ffi::OsStr;
 use std::fs;
+use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
 
 use crate::app_config::FileReferencesConfig;
 
 const DEFAULT_MAX_DIR_ENTRIES: usize = 200;
+/// `@git:<ref>:<path>` reads `path` as of `ref` instead of the working tree.
+const GIT_REF_PREFIX: &str = "git:";
 
 #[derive(Debug, Clone)]
 pub struct FileRefPolicy {
     allowed_extensions: Vec<String>,
-    max_size_bytes: u64,
+    max_file_bytes: u64,
+    max_total_bytes: u64,
 }
 
 impl FileRefPolicy {
@@ -20,7 +25,8 @@ impl FileRefPolicy {
                 .iter()
                 .map(|s| s.to_lowercase())
                 .collect(),
-            max_size_bytes: config.max_size_mb.saturating_mul(1024 * 1024),
+            max_file_bytes: config.max_file_bytes,
+            max_total_bytes: config.max_total_bytes,
         }
     }
 
@@ -44,6 +50,7 @@ pub fn resolve_file_references(
 ) -> Result<String> {
     let mut result = String::new();
     let mut last_end = 0;
+    let mut remaining = policy.max_total_bytes;
 
     // Find all @filename references
     for (start, reference) in find_file_references(text) {
@@ -51,9 +58,31 @@ pub fn resolve_file_references(
         result.push_str(&text[last_end..start]);
 
         // Resolve the reference
-        match resolve_reference(&reference, base_dir, policy) {
+        let resolved = if is_glob(&reference) && !reference.starts_with(GIT_REF_PREFIX) {
+            expand_glob(&reference, base_dir, policy, remaining)
+        } else {
+            let block = match reference.strip_prefix(GIT_REF_PREFIX) {
+                Some(spec) => {
+                    resolve_git_reference(spec, base_dir, policy, crate::plugins::system())
+                        .map(|(label, content)| file_block(&label, &content))
+                }
+                None => resolve_reference(&reference, base_dir, policy)
+                    .map(|content| file_block(&reference, &content)),
+            };
+            block.and_then(|block| {
+                if block.len() as u64 > remaining {
+                    anyhow::bail!(
+                        "file reference budget of {} bytes is used up",
+                        policy.max_total_bytes
+                    );
+                }
+                Ok(block)
+            })
+        };
+        match resolved {
             Ok(content) => {
-                result.push_str(&format!("\n```\n// File: {reference}\n{content}\n```\n"));
+                remaining = remaining.saturating_sub(content.len() as u64);
+                result.push_str(&content);
             }
             Err(e) => {
                 // If file not found, keep the original reference and add error note
@@ -88,11 +117,28 @@ fn find_file_references(text: &str) -> Vec<(usize, String)> {
                 || chars[i - 1] == ',';
 
             if at_boundary {
-                // Extract filename (alphanumeric, dash, underscore, dot, slash)
                 let start = i;
                 i += 1;
                 let mut filename = String::new();
 
+                // `@git:<ref>:<path>` refs may use `~`, `^`, etc., so take the
+                // whole token up to whitespace or closing punctuation.
+                if chars[i..].starts_with(&GIT_REF_PREFIX.chars().collect::<Vec<_>>()) {
+                    while i < chars.len()
+                        && !chars[i].is_whitespace()
+                        && !matches!(chars[i], ',' | ')' | ']' | '}')
+                    {
+                        filename.push(chars[i]);
+                        i += 1;
+                    }
+                    let trimmed_len = filename.trim_end_matches(['.', '?', '!']).len();
+                    filename.truncate(trimmed_len);
+                    references.push((start, filename));
+                    continue;
+                }
+
+                // Extract filename (alphanumeric, dash, underscore, dot, slash)
+
                 while i < chars.len() {
                     let c = chars[i];
                     if c.is_alphanumeric()
@@ -101,6 +147,7 @@ fn find_file_references(text: &str) -> Vec<(usize, String)> {
                         || c == '.'
                         || c == '/'
                         || c == '\\'
+                        || c == '*'
                     {
                         filename.push(c);
                         i += 1;
@@ -109,6 +156,20 @@ fn find_file_references(text: &str) -> Vec<(usize, String)> {
                     }
                 }
 
+                // Optional `:start-end` line range, only when a digit follows the colon
+                if !filename.is_empty()
+                    && i + 1 < chars.len()
+                    && chars[i] == ':'
+                    && chars[i + 1].is_ascii_digit()
+                {
+                    while i < chars.len()
+                        && (chars[i] == ':' || chars[i] == '-' || chars[i].is_ascii_digit())
+                    {
+                        filename.push(chars[i]);
+                        i += 1;
+                    }
+                }
+
                 if !filename.is_empty() {
                     references.push((start, filename));
                     continue;
@@ -121,8 +182,194 @@ fn find_file_references(text: &str) -> Vec<(usize, String)> {
     references
 }
 
+/// Fenced block with a `// File:` header, as injected for each file.
+fn file_block(name: &str, content: &str) -> String {
+    format!("\n```\n// File: {name}\n{content}\n```\n")
+}
+
+fn is_glob(reference: &str) -> bool {
+    reference.contains(['*', '?', '['])
+}
+
+/// Expand a glob reference into one file block per match, in path order.
+///
+/// Gitignored files and files the policy rejects are skipped. Stops with a
+/// truncation notice once `budget` bytes would be exceeded.
+// qual:allow(iosp) reason: "I/O boundary — walks the filesystem and reads matches"
+fn expand_glob(
+    pattern: &str,
+    base_dir: &Path,
+    policy: &FileRefPolicy,
+    budget: u64,
+) -> Result<String> {
+    let pat_path = Path::new(pattern);
+    if pat_path.is_absolute()
+        || pat_path
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        anyhow::bail!("Access denied: {pattern} is outside working directory");
+    }
+
+    let full_pattern = base_dir.join(pattern);
+    let pattern_str = full_pattern
+        .to_str()
+        .with_context(|| format!("Invalid glob: {pattern}"))?;
+    let mut paths: Vec<PathBuf> = glob::glob(pattern_str)
+        .with_context(|| format!("Invalid glob: {pattern}"))?
+        .filter_map(Result::ok)
+        .filter(|p| p.is_file())
+        .filter_map(|p| p.strip_prefix(base_dir).ok().map(Path::to_path_buf))
+        .collect();
+    paths.sort();
+    let paths = drop_gitignored(base_dir, paths);
+    if paths.is_empty() {
+        anyhow::bail!("No files match {pattern}");
+    }
+
+    let mut output = String::new();
+    let mut skipped = 0usize;
+    for (index, path) in paths.iter().enumerate() {
+        let name = path.to_string_lossy();
+        let Ok(content) = resolve_reference(&name, base_dir, policy) else {
+            skipped += 1;
+            continue;
+        };
+        let block = file_block(&name, &content);
+        if (output.len() + block.len()) as u64 > budget {
+            output.push_str(&format!(
+                "\n[truncated: {pattern} exceeded the {} byte budget; {} more file(s) not included]\n",
+                policy.max_total_bytes,
+                paths.len() - index
+            ));
+            break;
+        }
+        output.push_str(&block);
+    }
+    if skipped > 0 {
+        output.push_str(&format!(
+            "\n[skipped {skipped} file(s) matching {pattern} not allowed by the file reference policy]\n"
+        ));
+    }
+    Ok(output)
+}
+
+/// Remove paths (relative to `base_dir`) that git reports as ignored.
+/// Outside a git repo, or without git, everything is kept.
+pub(crate) fn drop_gitignored(base_dir: &Path, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    if paths.is_empty() {
+        return paths;
+    }
+    let mut args: Vec<std::ffi::OsString> = vec![
+        "-C".into(),
+        base_dir.into(),
+        "check-ignore".into(),
+        "--".into(),
+    ];
+    args.extend(paths.iter().map(|p| p.as_os_str().to_owned()));
+
+    // Exit 0: some paths ignored; 1: none ignored; anything else: not a repo.
+    let Ok(output) = crate::plugins::system().git(&args) else {
+        return paths;
+    };
+    if output.status.code() != Some(0) {
+        return paths;
+    }
+    let ignored: std::collections::HashSet<PathBuf> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(PathBuf::from)
+        .collect();
+    paths.into_iter().filter(|p| !ignored.contains(p)).collect()
+}
+
+/// Read a file at a git revision from a `<ref>:<path>` spec via `git show`.
+///
+/// Returns a `path @ ref` label alongside the content. The extension
+/// allowlist and `max_file_bytes` apply as for working-tree files. Never falls
+/// back to the working-tree file: a missing git, bad ref, or unknown path is
+/// an error.
+fn resolve_git_reference(
+    spec: &str,
+    base_dir: &Path,
+    policy: &FileRefPolicy,
+    plugins: &crate::plugins::Plugins,
+) -> Result<(String, String)> {
+    let Some((rev, path)) = spec
+        .split_once(':')
+        .filter(|(r, p)| !r.is_empty() && !p.is_empty())
+    else {
+        anyhow::bail!("Malformed git reference @git:{spec}: expected git:<ref>:<path>");
+    };
+    // git would parse a leading `-` as an option (e.g. `--output=<file>`).
+    if rev.starts_with('-') {
+        anyhow::bail!("Invalid git ref in @git:{spec}: refs may not start with '-'");
+    }
+    let rel = Path::new(path);
+    if rel.is_absolute()
+        || rel
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        anyhow::bail!("Access denied: {path} is outside working directory");
+    }
+    check_extension(rel, path, policy)?;
+
+    let object = format!("{rev}:{path}");
+    let output = plugins
+        .git(&[
+            OsStr::new("-C"),
+            base_dir.as_os_str(),
+            OsStr::new("show"),
+            OsStr::new(&object),
+        ])
+        .with_context(|| format!("git is unavailable to resolve @git:{spec}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git show {object} failed: {}", stderr.trim());
+    }
+    let label = format!("{path} @ {rev}");
+    let mut bytes = output.stdout;
+    let total = bytes.len() as u64;
+    bytes.truncate(policy.max_file_bytes.min(total) as usize);
+    let content = decode_capped(bytes, total, &label, policy.max_file_bytes)?;
+    Ok((label, content))
+}
+
+/// A 1-indexed, inclusive line range from an `@path:start-end` reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Split `path:start-end` into the path and its line range, if any.
+///
+/// A suffix after the last `:` is treated as a range when it is empty or
+/// starts with a digit or `-`; anything else is left as part of the path.
+fn split_line_range(reference: &str) -> Result<(&str, Option<LineRange>)> {
+    let Some((path, suffix)) = reference.rsplit_once(':') else {
+        return Ok((reference, None));
+    };
+    if !suffix.is_empty() && !suffix.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+        return Ok((reference, None));
+    }
+
+    let range = suffix
+        .split_once('-')
+        .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)))
+        .filter(|&(start, end)| start >= 1 && end >= start)
+        .map(|(start, end)| LineRange { start, end });
+    match range {
+        Some(range) => Ok((path, Some(range))),
+        None => anyhow::bail!(
+            "Malformed line range in @{reference}: expected start-end with 1 <= start <= end, e.g. {path}:10-40"
+        ),
+    }
+}
+
 /// Resolve a single file reference
-fn resolve_reference(filename: &str, base_dir: &Path, policy: &FileRefPolicy) -> Result<String> {
+fn resolve_reference(reference: &str, base_dir: &Path, policy: &FileRefPolicy) -> Result<String> {
+    let (filename, range) = split_line_range(reference)?;
     let path = base_dir.join(filename);
 
     // Security: prevent path traversal attacks
@@ -139,16 +386,24 @@ fn resolve_reference(filename: &str, base_dir: &Path, policy: &FileRefPolicy) ->
     }
 
     if canonical.is_dir() {
+        if range.is_some() {
+            anyhow::bail!("Line ranges only apply to files: {filename} is a directory");
+        }
         return list_directory(&canonical);
     }
 
-    let metadata = fs::metadata(&canonical)
-        .with_context(|| format!("Failed to read file metadata: {filename}"))?;
-    if metadata.len() > policy.max_size_bytes {
-        anyhow::bail!("File too large: {filename}");
+    check_extension(&canonical, filename, policy)?;
+
+    if let Some(range) = range {
+        return read_line_range(&canonical, filename, range, policy.max_file_bytes);
     }
 
-    let ext = canonical
+    read_capped(&canonical, filename, policy.max_file_bytes)
+}
+
+/// Error unless `path` has no extension or one the policy allows.
+fn check_extension(path: &Path, filename: &str, policy: &FileRefPolicy) -> Result<()> {
+    let ext = path
         .extension()
         .and_then(|s| s.to_str())
         .map(|s| s.to_lowercase())
@@ -156,11 +411,90 @@ fn resolve_reference(filename: &str, base_dir: &Path, policy: &FileRefPolicy) ->
     if !ext.is_empty() && !policy.allowed_extensions.contains(&ext) {
         anyhow::bail!("Unsupported file type: {filename}");
     }
+    Ok(())
+}
 
-    let bytes = fs::read(&canonical).with_context(|| format!("Failed to read file: {filename}"))?;
-    let content =
-        String::from_utf8(bytes).with_context(|| format!("File is not valid UTF-8: {filename}"))?;
+/// Read at most `max_bytes` of `path`, appending a marker when the file is longer.
+// qual:allow(iosp) reason: "I/O boundary — reads a bounded prefix of the file"
+fn read_capped(path: &Path, filename: &str, max_bytes: u64) -> Result<String> {
+    let file = fs::File::open(path).with_context(|| format!("Failed to read file: {filename}"))?;
+    let total = file
+        .metadata()
+        .with_context(|| format!("Failed to read file metadata: {filename}"))?
+        .len();
+    let mut bytes = Vec::new();
+    file.take(max_bytes)
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("Failed to read file: {filename}"))?;
+    decode_capped(bytes, total, filename, max_bytes)
+}
+
+/// Decode `bytes`, the first `max_bytes` of a `total`-byte file, appending a
+/// marker when the file was longer.
+fn decode_capped(bytes: Vec<u8>, total: u64, filename: &str, max_bytes: u64) -> Result<String> {
+    let mut content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        // The cut may land inside a multi-byte character; drop the partial tail.
+        Err(e) if total > max_bytes && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).expect("prefix up to valid_up_to is UTF-8")
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("File is not valid UTF-8: {filename}"));
+        }
+    };
+    if total > max_bytes {
+        content.push_str(&format!(
+            "\n[truncated: {filename} is {total} bytes; showing the first {max_bytes}]"
+        ));
+    }
+    Ok(content)
+}
 
+/// Read `range` from `path` with line numbers, clamping the end to EOF.
+///
+/// Only the first `max_bytes` of the file are read, as for a whole-file
+/// reference; a range cut short by the cap ends with a marker.
+// qual:allow(iosp) reason: "I/O boundary — reads the requested lines from disk"
+fn read_line_range(
+    path: &Path,
+    filename: &str,
+    range: LineRange,
+    max_bytes: u64,
+) -> Result<String> {
+    let file = fs::File::open(path).with_context(|| format!("Failed to read file: {filename}"))?;
+    let total = file
+        .metadata()
+        .with_context(|| format!("Failed to read file metadata: {filename}"))?
+        .len();
+    let limit = (range.end - range.start + 1) as u64;
+    let window = crate::tools::read_line_window(
+        BufReader::new(file.take(max_bytes)),
+        range.start - 1,
+        Some(limit),
+    )
+    .with_context(|| format!("Failed to read file: {filename}"))?;
+    let Some(mut content) = window else {
+        if total > max_bytes {
+            anyhow::bail!(
+                "Line range {}-{} starts past the first {max_bytes} bytes of {filename} (file_references.max_file_bytes)",
+                range.start,
+                range.end
+            );
+        }
+        anyhow::bail!(
+            "Line range {}-{} starts past the end of {filename}",
+            range.start,
+            range.end
+        );
+    };
+    if total > max_bytes && (content.lines().count() as u64) < limit {
+        content.push_str(&format!(
+            "\n[truncated: {filename} is {total} bytes; showing lines from the first {max_bytes}]"
+        ));
+    }
     Ok(content)
 }
 
@@ -169,19 +503,30 @@ pub fn resolve_at_reference(
     base_dir: &Path,
     policy: &FileRefPolicy,
 ) -> Result<AtReference> {
-    let path = base_dir.join(reference);
+    if let Some(spec) = reference.strip_prefix(GIT_REF_PREFIX) {
+        let (_, content) = resolve_git_reference(spec, base_dir, policy, crate::plugins::system())?;
+        return Ok(AtReference::File(content));
+    }
+
+    if is_glob(reference) {
+        let content = expand_glob(reference, base_dir, policy, policy.max_total_bytes)?;
+        return Ok(AtReference::File(content));
+    }
+
+    let (filename, _) = split_line_range(reference)?;
+    let path = base_dir.join(filename);
     let canonical = path
         .canonicalize()
-        .with_context(|| format!("File not found: {reference}"))?;
+        .with_context(|| format!("File not found: {filename}"))?;
 
     let base_canonical = base_dir
         .canonicalize()
         .unwrap_or_else(|_| base_dir.to_path_buf());
     if !canonical.starts_with(&base_canonical) {
-        anyhow::bail!("Access denied: {reference} is outside working directory");
+        anyhow::bail!("Access denied: {filename} is outside working directory");
     }
 
-    if canonical.is_dir() {
+    if canonical.is_dir() && filename == reference {
         let listing = list_directory(&canonical)?;
         return Ok(AtReference::Directory(listing));
     }
@@ -228,6 +573,24 @@ pub fn list_file_references(text: &str) -> Vec<String> {
         .collect()
 }
 
+/// The paths named by file references in text, without `git:<ref>:`
+/// prefixes or `:start-end` line ranges.
+pub fn referenced_paths(text: &str) -> Vec<String> {
+    find_file_references(text)
+        .into_iter()
+        .filter_map(
+            |(_, reference)| match reference.strip_prefix(GIT_REF_PREFIX) {
+                Some(spec) => spec.split_once(':').map(|(_, path)| path.to_string()),
+                None => Some(
+                    split_line_range(&reference)
+                        .map_or(reference.as_str(), |(path, _)| path)
+                        .to_string(),
+                ),
+            },
+        )
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,6 +621,14 @@ mod tests {
         assert!(refs.is_empty());
     }
 
+    #[test]
+    fn test_referenced_paths_strip_ranges_and_git_refs() {
+        assert_eq!(
+            referenced_paths("see @src/lib.rs:10-20 and @git:HEAD~1:src/main.rs, @Cargo.toml"),
+            ["src/lib.rs", "src/main.rs", "Cargo.toml"]
+        );
+    }
+
     #[test]
     fn test_email_not_a_reference() {
         // email@domain.com should not be treated as a file reference
@@ -353,6 +724,307 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_reference_with_line_range() {
+        let refs = find_file_references("See @src/main.rs:10-40, then @notes.md: done");
+        assert_eq!(refs[0].1, "src/main.rs:10-40");
+        assert_eq!(refs[1].1, "notes.md");
+    }
+
+    #[test]
+    fn test_resolve_line_range() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "a.txt", "one\ntwo\nthree\nfour\n");
+
+        let content =
+            resolve_reference("a.txt:2-3", temp_dir.path(), &FileRefPolicy::default()).unwrap();
+
+        assert_eq!(content, "   2| two\n   3| three");
+    }
+
+    #[test]
+    fn test_line_range_end_clamps_to_eof() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "a.txt", "one\ntwo\nthree\n");
+
+        let result = resolve_file_references(
+            "Check @a.txt:2-99",
+            temp_dir.path(),
+            &FileRefPolicy::default(),
+        )
+        .unwrap();
+
+        assert!(result.contains("// File: a.txt:2-99"));
+        assert!(result.contains("   3| three"));
+        assert!(!result.contains("one"));
+    }
+
+    #[test]
+    fn test_line_range_respects_max_file_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "a.txt", "one\ntwo\nthree\nfour\n");
+        let policy = FileRefPolicy {
+            max_file_bytes: 8,
+            ..FileRefPolicy::default()
+        };
+
+        let content = resolve_reference("a.txt:1-4", temp_dir.path(), &policy).unwrap();
+        assert!(content.starts_with("   1| one\n   2| two\n"), "{content}");
+        assert!(!content.contains("three"));
+        assert!(
+            content.contains("[truncated: a.txt is 19 bytes"),
+            "{content}"
+        );
+
+        let err = resolve_reference("a.txt:4-4", temp_dir.path(), &policy)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("first 8 bytes"), "{err}");
+    }
+
+    #[test]
+    fn test_malformed_line_range_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "a.txt", "one\ntwo\n");
+
+        for reference in ["a.txt:2-1", "a.txt:0-1", "a.txt:3", "a.txt:1-x"] {
+            let err = resolve_at_reference(reference, temp_dir.path(), &FileRefPolicy::default())
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("Malformed line range"),
+                "{reference}"
+            );
+        }
+
+        let result = resolve_file_references(
+            "Check @a.txt:2-1",
+            temp_dir.path(),
+            &FileRefPolicy::default(),
+        )
+        .unwrap();
+        assert!(result.contains("@a.txt:2-1"));
+        assert!(!result.contains("one"));
+    }
+
+    #[test]
+    fn test_glob_reference_expands_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "src/a.rs", "fn a() {}");
+        create_test_file(temp_dir.path(), "src/nested/b.rs", "fn b() {}");
+        create_test_file(temp_dir.path(), "src/notes.md", "not rust");
+
+        let result = resolve_file_references(
+            "Review @src/**/*.rs",
+            temp_dir.path(),
+            &FileRefPolicy::default(),
+        )
+        .unwrap();
+
+        assert!(result.contains("// File: src/a.rs\nfn a() {}"));
+        assert!(result.contains("// File: src/nested/b.rs\nfn b() {}"));
+        assert!(!result.contains("not rust"));
+        assert!(result.find("src/a.rs").unwrap() < result.find("src/nested/b.rs").unwrap());
+    }
+
+    #[test]
+    fn test_glob_reference_stops_at_byte_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "a.txt", &"a".repeat(60));
+        create_test_file(temp_dir.path(), "b.txt", &"b".repeat(60));
+        let policy = FileRefPolicy {
+            max_total_bytes: 100,
+            ..FileRefPolicy::default()
+        };
+
+        let content = match resolve_at_reference("*.txt", temp_dir.path(), &policy).unwrap() {
+            AtReference::File(content) => content,
+            other => panic!("unexpected reference: {other:?}"),
+        };
+
+        assert!(content.contains(&"a".repeat(60)));
+        assert!(!content.contains("bbb"));
+        assert!(content.contains("exceeded the 100 byte budget; 1 more file(s) not included"));
+    }
+
+    #[test]
+    fn test_oversized_file_is_truncated() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "big.txt", &"x".repeat(100));
+        let policy = FileRefPolicy {
+            max_file_bytes: 10,
+            ..FileRefPolicy::default()
+        };
+
+        let content = resolve_reference("big.txt", temp_dir.path(), &policy).unwrap();
+
+        assert_eq!(
+            content,
+            format!(
+                "{}\n[truncated: big.txt is 100 bytes; showing the first 10]",
+                "x".repeat(10)
+            )
+        );
+    }
+
+    #[test]
+    fn test_truncation_drops_partial_utf8_tail() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "snow.txt", "ab☃☃");
+        let policy = FileRefPolicy {
+            max_file_bytes: 4,
+            ..FileRefPolicy::default()
+        };
+
+        let content = resolve_reference("snow.txt", temp_dir.path(), &policy).unwrap();
+
+        assert!(content.starts_with("ab\n[truncated"));
+    }
+
+    #[test]
+    fn test_reference_skipped_once_total_budget_exceeded() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "a.txt", &"a".repeat(60));
+        create_test_file(temp_dir.path(), "b.txt", &"b".repeat(60));
+        let policy = FileRefPolicy {
+            max_total_bytes: 100,
+            ..FileRefPolicy::default()
+        };
+
+        let result =
+            resolve_file_references("Compare @a.txt and @b.txt", temp_dir.path(), &policy).unwrap();
+
+        assert!(result.contains(&"a".repeat(60)));
+        assert!(result.ends_with("and @b.txt"));
+    }
+
+    #[test]
+    fn test_find_git_reference() {
+        let refs = find_file_references("Diff @git:HEAD~1:src/agent.rs. Also @a.rs");
+        assert_eq!(refs[0].1, "git:HEAD~1:src/agent.rs");
+        assert_eq!(refs[1].1, "a.rs");
+    }
+
+    #[cfg(unix)]
+    fn git_plugins(
+        code: i32,
+        stdout: &str,
+        stderr: &str,
+    ) -> (
+        crate::plugins::Plugins,
+        std::sync::Arc<crate::plugins::MockRunner>,
+    ) {
+        use std::os::unix::process::ExitStatusExt;
+
+        let runner = std::sync::Arc::new(crate::plugins::MockRunner::new());
+        runner.push_output(Ok(std::process::Output {
+            status: std::process::ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }));
+        (
+            crate::plugins::Plugins::new(
+                runner.clone(),
+                std::sync::Arc::new(crate::plugins::testing::UsrBin),
+            ),
+            runner,
+        )
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_git_reference_reads_file_at_ref() {
+        let (plugins, runner) = git_plugins(0, "fn old() {}\n", "");
+
+        let (label, content) = resolve_git_reference(
+            "HEAD~1:src/agent.rs",
+            Path::new("/repo"),
+            &FileRefPolicy::default(),
+            &plugins,
+        )
+        .unwrap();
+
+        assert_eq!(label, "src/agent.rs @ HEAD~1");
+        assert_eq!(content, "fn old() {}\n");
+        let args: Vec<_> = runner.calls()[0]
+            .args
+            .iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(args, ["-C", "/repo", "show", "HEAD~1:src/agent.rs"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_git_reference_errors_on_unknown_ref() {
+        let (plugins, _) = git_plugins(128, "", "fatal: invalid object name 'nope'.\n");
+
+        let err = resolve_git_reference(
+            "nope:src/agent.rs",
+            Path::new("/repo"),
+            &FileRefPolicy::default(),
+            &plugins,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("git show nope:src/agent.rs failed"));
+        assert!(err.contains("invalid object name"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_git_reference_applies_file_policy() {
+        let policy = FileRefPolicy::from_config(&FileReferencesConfig {
+            max_file_bytes: 8,
+            ..FileReferencesConfig::default()
+        });
+
+        let (plugins, runner) = git_plugins(0, "", "");
+        let err = resolve_git_reference("HEAD:secrets.pem", Path::new("/repo"), &policy, &plugins)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unsupported file type"), "{err}");
+        assert!(runner.calls().is_empty());
+
+        let (plugins, _) = git_plugins(0, "fn old() { body() }\n", "");
+        let (_, content) =
+            resolve_git_reference("HEAD:src/a.rs", Path::new("/repo"), &policy, &plugins).unwrap();
+        assert_eq!(
+            content,
+            "fn old()\n[truncated: src/a.rs @ HEAD is 20 bytes; showing the first 8]"
+        );
+    }
+
+    #[test]
+    fn test_git_reference_rejects_option_like_rev() {
+        let (plugins, runner) = git_plugins(0, "", "");
+
+        let err = resolve_git_reference(
+            "--output=/tmp/x:foo.rs",
+            Path::new("/repo"),
+            &FileRefPolicy::default(),
+            &plugins,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("may not start with '-'"), "{err}");
+        assert!(runner.calls().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_git_reference() {
+        let err = resolve_git_reference(
+            "HEAD",
+            Path::new("."),
+            &FileRefPolicy::default(),
+            crate::plugins::system(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("expected git:<ref>:<path>"));
+    }
+
     #[test]
     fn test_reference_at_start_of_line() {
         let refs = find_file_references("@file.rs\nAnother line");
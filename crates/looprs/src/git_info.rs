@@ -21,20 +21,7 @@ pub fn collect() -> GitInfo {
 }
 
 fn branch_name() -> Option<String> {
-    let out = Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .output()
-        .ok()?;
-    if out.status.success() {
-        let s = String::from_utf8_lossy(&out.stdout).trim().to_string();
-        if s.is_empty() || s == "HEAD" {
-            None
-        } else {
-            Some(s)
-        }
-    } else {
-        None
-    }
+    crate::plugins::system().git_current_branch()
 }
 
 fn commits_ahead() -> u32 {
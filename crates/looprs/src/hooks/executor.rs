@@ -1,14 +1,30 @@
 use super::{Action, Hook, PromptCallback};
 use crate::app_config::AppConfig;
 use crate::events::EventContext;
+use crate::plugins::Plugins;
 use crate::state::AppState;
 use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::sync::{Mutex, OnceLock};
 
 pub struct HookExecutor;
 
+/// Timeout for `command` actions when neither the action nor config sets one.
+const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 30;
+/// Timeout for `http_request` actions when `defaults.timeout_seconds` is unset.
+const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 10;
+/// Response bodies beyond this many bytes are truncated before injection.
+const MAX_HTTP_BODY_BYTES: usize = 256 * 1024;
+
 /// Approval callback type - returns true if user approves, false if declined
 pub type ApprovalCallback = Box<dyn Fn(&str) -> bool + Send + Sync>;
 
+/// A callback that answers every approval prompt with `answer`, for runs with
+/// no console (`--approve-all` / `--deny-all`).
+pub fn constant_approval(answer: bool) -> ApprovalCallback {
+    Box::new(move |_| answer)
+}
+
 #[derive(Debug, Clone)]
 pub struct HookResult {
     pub hook_name: String,
@@ -82,7 +98,7 @@ impl HookExecutor {
     // qual:allow(iosp) reason: "I/O boundary — dispatches hook actions"
     fn execute_action(
         action: &Action,
-        _context: &EventContext,
+        context: &EventContext,
         approval_fn: Option<&ApprovalCallback>,
         prompt_fn: Option<&PromptCallback>,
         secret_prompt_fn: Option<&PromptCallback>,
@@ -95,7 +111,9 @@ impl HookExecutor {
                 inject_as,
                 requires_approval,
                 approval_prompt,
+                timeout_secs: action_timeout,
             } => {
+                let command = &substitute_command_vars(command, context);
                 // Check if approval is required
                 if *requires_approval {
                     let prompt = approval_prompt
@@ -118,12 +136,16 @@ impl HookExecutor {
                     }
                 }
 
-                let output = Self::run_command(command, timeout_secs)?;
+                let timeout = action_timeout
+                    .or(timeout_secs)
+                    .unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS);
+                let output = Self::run_command(command, Some(timeout))?;
                 Ok(Some((output, inject_as.clone())))
             }
             Action::Message { text } => {
-                crate::ui::info(text);
-                Ok(Some((text.clone(), None)))
+                let text = substitute_vars(text, context);
+                crate::ui::info(&text);
+                Ok(Some((text, None)))
             }
             Action::Conditional {
                 condition,
@@ -134,7 +156,7 @@ impl HookExecutor {
                     for action in actions {
                         if let Some(result) = Self::execute_action(
                             action,
-                            _context,
+                            context,
                             approval_fn,
                             prompt_fn,
                             secret_prompt_fn,
@@ -208,15 +230,91 @@ impl HookExecutor {
                 }
                 Ok(None)
             }
+            Action::HttpRequest {
+                url,
+                method,
+                headers,
+                inject_as,
+            } => match Self::run_http_request(url, method, headers, timeout_secs) {
+                Ok(body) => Ok(Some((body, inject_as.clone()))),
+                Err(e) => {
+                    crate::ui::warn(format!("Warning: http_request to {url} failed: {e}"));
+                    Ok(None)
+                }
+            },
         }
     }
 
+    /// Fetch `url` and return the (size-capped) response body. Non-2xx
+    /// statuses are errors.
+    ///
+    /// Hooks run synchronously, often from inside the agent's runtime, so the
+    /// request is driven on a dedicated thread with its own runtime.
+    // qual:allow(iosp) reason: "I/O boundary — performs an HTTP request"
+    fn run_http_request(
+        url: &str,
+        method: &str,
+        headers: &HashMap<String, String>,
+        timeout_secs: Option<u64>,
+    ) -> anyhow::Result<String> {
+        let method = reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+            .map_err(|_| anyhow::anyhow!("invalid HTTP method '{method}'"))?;
+        let timeout =
+            std::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_HTTP_TIMEOUT_SECS));
+
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?;
+                    runtime.block_on(async {
+                        let client = reqwest::Client::builder().timeout(timeout).build()?;
+                        let mut request = client.request(method, url);
+                        for (name, value) in headers {
+                            request = request.header(name, value);
+                        }
+                        let mut response = request.send().await?;
+                        let status = response.status();
+                        if !status.is_success() {
+                            anyhow::bail!("server returned {status}");
+                        }
+
+                        let mut body = Vec::new();
+                        while let Some(chunk) = response.chunk().await? {
+                            body.extend_from_slice(&chunk);
+                            if body.len() >= MAX_HTTP_BODY_BYTES {
+                                body.truncate(MAX_HTTP_BODY_BYTES);
+                                crate::ui::warn(format!(
+                                    "Warning: http_request body from {url} truncated to {MAX_HTTP_BODY_BYTES} bytes"
+                                ));
+                                break;
+                            }
+                        }
+                        Ok(String::from_utf8_lossy(&body).trim().to_string())
+                    })
+                })
+                .join()
+                .map_err(|_| anyhow::anyhow!("http_request thread panicked"))?
+        })
+    }
+
     /// Run a shell command and capture output, killing the process if it
     /// exceeds `timeout_secs` seconds.
     // qual:allow(iosp) reason: "I/O boundary — spawns shell process"
     fn run_command(command_str: &str, timeout_secs: Option<u64>) -> anyhow::Result<String> {
         let timeout = timeout_secs.map(std::time::Duration::from_secs);
-        let output = crate::shell::run_nu_command_with_timeout(command_str, timeout)?;
+        let output = match crate::shell::run_nu_command_with_timeout(command_str, timeout) {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                crate::ui::warn(format!(
+                    "Warning: Hook command timed out after {}s: {command_str}",
+                    timeout_secs.unwrap_or_default()
+                ));
+                return Ok(String::new());
+            }
+            Err(e) => return Err(e.into()),
+        };
 
         let stdout = String::from_utf8_lossy(&output.stdout).to_string();
 
@@ -228,17 +326,54 @@ impl HookExecutor {
         Ok(stdout.trim().to_string())
     }
 
-    /// Evaluate simple conditions (very basic for now)
+    /// Evaluate a condition: a single primitive, or primitives joined with
+    /// `&&` and `||` (`&&` binds tighter), e.g. `env_set:CI && on_branch:main`.
+    /// Evaluation short-circuits. An unknown or malformed sub-condition makes
+    /// the whole expression false.
     // qual:allow(iosp) reason: "I/O boundary — evaluates conditions with shell commands"
     fn eval_condition(
         condition: &str,
         local_ctx: &HashMap<String, String>,
     ) -> anyhow::Result<bool> {
-        // Simple condition evaluation: "on_branch:main" or "has_tool:git"
-        if condition.starts_with("on_branch:") {
-            let branch = condition.strip_prefix("on_branch:").unwrap_or("");
-            // Would check actual branch here
-            return Ok(branch == "main" || branch == "*"); // For now, accept main or wildcard
+        let clauses = split_condition(condition);
+        if clauses.len() == 1 && clauses[0].len() == 1 {
+            return Self::eval_primitive(clauses[0][0], local_ctx);
+        }
+
+        // Fail closed before evaluating anything, so `unknown || true` can't
+        // pass on the strength of its known side.
+        for term in clauses.iter().flatten() {
+            if let Err(problem) = Self::check_primitive(term) {
+                crate::ui::warn(format!(
+                    "Warning: In hook condition '{condition}': {problem}; skipping hook for safety"
+                ));
+                return Ok(false);
+            }
+        }
+
+        for clause in &clauses {
+            let mut all = true;
+            for term in clause {
+                if !Self::eval_primitive(term, local_ctx)? {
+                    all = false;
+                    break;
+                }
+            }
+            if all {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Evaluate one primitive condition such as `on_branch:main` or `has_tool:git`.
+    // qual:allow(iosp) reason: "I/O boundary — evaluates conditions with shell commands"
+    fn eval_primitive(
+        condition: &str,
+        local_ctx: &HashMap<String, String>,
+    ) -> anyhow::Result<bool> {
+        if let Some(pattern) = condition.strip_prefix("on_branch:") {
+            return Ok(Self::eval_on_branch(pattern, crate::plugins::system()));
         }
 
         if condition.starts_with("has_tool:") {
@@ -256,10 +391,36 @@ impl HookExecutor {
             }
         }
 
+        if let Some(rest) = condition.strip_prefix("matches:") {
+            let Some((key, pattern)) = rest.split_once(':') else {
+                crate::ui::warn(format!(
+                    "Warning: Malformed condition '{condition}'; expected matches:<key>:<regex>"
+                ));
+                return Ok(false);
+            };
+            let Some(re) = cached_regex(pattern) else {
+                crate::ui::warn(format!(
+                    "Warning: Invalid regex in condition '{condition}'; skipping hook for safety"
+                ));
+                return Ok(false);
+            };
+            return Ok(local_ctx.get(key).is_some_and(|v| re.is_match(v)));
+        }
+
         if let Some(var) = condition.strip_prefix("env_set:") {
             return Ok(std::env::var(var).map(|v| !v.is_empty()).unwrap_or(false));
         }
 
+        if let Some(rel) = condition.strip_prefix("file_exists:") {
+            if !is_contained_path(rel) {
+                crate::ui::warn(format!(
+                    "Warning: Condition '{condition}' must name a path inside the working directory; skipping hook for safety"
+                ));
+                return Ok(false);
+            }
+            return Ok(file_exists_in(&std::env::current_dir()?, rel));
+        }
+
         if let Some(rest) = condition.strip_prefix("config_flag:") {
             let parts: Vec<&str> = rest.splitn(2, '=').collect();
             if parts.len() == 2 {
@@ -285,12 +446,226 @@ impl HookExecutor {
         Ok(false)
     }
 
+    /// Check a condition's syntax without evaluating it. Returns the reason
+    /// the runtime would reject it, if any.
+    pub fn check_condition(condition: &str) -> Result<(), String> {
+        split_condition(condition)
+            .into_iter()
+            .flatten()
+            .try_for_each(Self::check_primitive)
+    }
+
+    fn check_primitive(condition: &str) -> Result<(), String> {
+        if condition.is_empty() {
+            return Err("empty operand in condition".to_string());
+        }
+        let prefixes = ["on_branch:", "has_tool:", "env_set:"];
+        if prefixes.iter().any(|p| condition.starts_with(p)) {
+            return Ok(());
+        }
+        if let Some(rel) = condition.strip_prefix("file_exists:") {
+            if !is_contained_path(rel) {
+                return Err(format!(
+                    "condition '{condition}' must name a relative path without '..'"
+                ));
+            }
+            return Ok(());
+        }
+        if let Some(rest) = condition.strip_prefix("equals:") {
+            return match rest.split_once(':') {
+                Some(_) => Ok(()),
+                None => Err(format!(
+                    "malformed condition '{condition}'; expected equals:<key>:<value>"
+                )),
+            };
+        }
+        if let Some(rest) = condition.strip_prefix("matches:") {
+            let Some((_, pattern)) = rest.split_once(':') else {
+                return Err(format!(
+                    "malformed condition '{condition}'; expected matches:<key>:<regex>"
+                ));
+            };
+            return regex::Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|e| format!("invalid regex in condition '{condition}': {e}"));
+        }
+        if let Some(rest) = condition.strip_prefix("config_flag:")
+            && rest.starts_with("onboarding.demo_seen=")
+        {
+            return Ok(());
+        }
+        Err(format!("unknown condition '{condition}'"))
+    }
+
+    /// Condition problems for a hook and any nested `conditional` actions.
+    pub fn check_hook_conditions(hook: &Hook) -> Vec<String> {
+        fn walk(actions: &[Action], problems: &mut Vec<String>) {
+            for action in actions {
+                if let Action::Conditional { condition, then } = action {
+                    if let Err(problem) = HookExecutor::check_condition(condition) {
+                        problems.push(problem);
+                    }
+                    walk(then, problems);
+                }
+            }
+        }
+
+        let mut problems = Vec::new();
+        if let Some(condition) = &hook.condition
+            && let Err(problem) = Self::check_condition(condition)
+        {
+            problems.push(problem);
+        }
+        walk(&hook.actions, &mut problems);
+        problems
+    }
+
+    /// Match the current VCS branch against `pattern` (`main`, `feature/*`, `*`).
+    /// Fails closed when no branch can be detected.
+    fn eval_on_branch(pattern: &str, plugins: &Plugins) -> bool {
+        let Some(branch) = current_branch(plugins) else {
+            crate::ui::warn(format!(
+                "Warning: on_branch:{pattern} could not detect a git or jj branch; skipping hook for safety"
+            ));
+            return false;
+        };
+        branch_matches(pattern, &branch)
+    }
+
     /// Check if a tool is available in PATH
     fn check_tool_available(tool: &str) -> anyhow::Result<bool> {
         Ok(crate::plugins::system().has_in_path(tool))
     }
 }
 
+/// Expand `${user_message}`, `${tool_name}`, `${tool_output}`, `${error}`,
+/// `${warning}`, `${session_context}`, and `${meta.KEY}` from the event
+/// context. Unset fields expand to an empty string; unknown placeholders are
+/// left literal with a warning. Values are inserted verbatim, unquoted.
+fn substitute_vars(template: &str, context: &EventContext) -> String {
+    substitute_with(template, context, |value| value)
+}
+
+/// Like `substitute_vars`, but each value becomes a Nushell raw string, so a
+/// message or tool output can't inject commands into a `command` action.
+fn substitute_command_vars(template: &str, context: &EventContext) -> String {
+    substitute_with(template, context, |value| nu_quote(&value))
+}
+
+fn substitute_with(
+    template: &str,
+    context: &EventContext,
+    render: impl Fn(String) -> String,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let key = &after[..end];
+        match lookup_var(key, context) {
+            Some(value) => out.push_str(&render(value)),
+            None => {
+                crate::ui::warn(format!("Warning: Unknown hook placeholder '${{{key}}}'"));
+                out.push_str(&rest[start..start + 2 + end + 1]);
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `value` as a Nushell raw string (`r#'…'#`), with enough `#`s that no
+/// quote inside it can close the string early.
+fn nu_quote(value: &str) -> String {
+    let mut hashes = "#".to_string();
+    while value.contains(&format!("'{hashes}")) {
+        hashes.push('#');
+    }
+    format!("r{hashes}'{value}'{hashes}")
+}
+
+fn lookup_var(key: &str, context: &EventContext) -> Option<String> {
+    let field = match key {
+        "user_message" => &context.user_message,
+        "tool_name" => &context.tool_name,
+        "tool_output" => &context.tool_output,
+        "error" => &context.error,
+        "warning" => &context.warning,
+        "session_context" => &context.session_context,
+        _ => {
+            return key
+                .strip_prefix("meta.")
+                .and_then(|meta_key| context.metadata.get(meta_key).cloned());
+        }
+    };
+    Some(field.clone().unwrap_or_default())
+}
+
+/// Whether `rel` is a non-empty relative path that stays inside its base
+/// directory: no root, drive prefix, or `..` component.
+fn is_contained_path(rel: &str) -> bool {
+    let path = Path::new(rel);
+    !rel.is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Whether `rel` exists under `root` without resolving outside it; a symlink
+/// pointing out of `root` counts as missing.
+// qual:allow(iosp) reason: "I/O boundary — checks the filesystem"
+fn file_exists_in(root: &Path, rel: &str) -> bool {
+    if !is_contained_path(rel) {
+        return false;
+    }
+    let (Ok(root), Ok(target)) = (root.canonicalize(), root.join(rel).canonicalize()) else {
+        return false;
+    };
+    target.starts_with(root)
+}
+
+/// Split a condition into OR-ed clauses of AND-ed primitives.
+fn split_condition(condition: &str) -> Vec<Vec<&str>> {
+    condition
+        .split("||")
+        .map(|clause| clause.split("&&").map(str::trim).collect())
+        .collect()
+}
+
+/// Compile `pattern` once per process; `None` if it is not a valid regex.
+fn cached_regex(pattern: &str) -> Option<regex::Regex> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<regex::Regex>>>> = OnceLock::new();
+    let mut cache = CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    cache
+        .entry(pattern.to_string())
+        .or_insert_with(|| regex::Regex::new(pattern).ok())
+        .clone()
+}
+
+fn branch_matches(pattern: &str, branch: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => branch.starts_with(prefix),
+        None => branch == pattern,
+    }
+}
+
+/// Current branch from git, falling back to the first jj bookmark on `@`
+/// or its parent.
+fn current_branch(plugins: &Plugins) -> Option<String> {
+    plugins
+        .git_current_branch()
+        .or_else(|| plugins.jj_current_bookmark())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,11 +721,88 @@ mod tests {
         assert_eq!(lines, 3);
     }
 
+    struct FixedResolver {
+        present: bool,
+    }
+
+    impl crate::plugins::ToolResolver for FixedResolver {
+        fn resolve(&self, tool: &str) -> Option<PathBuf> {
+            self.present
+                .then(|| PathBuf::from(format!("/usr/bin/{tool}")))
+        }
+    }
+
+    #[cfg(unix)]
+    fn plugins_with_outputs(outputs: &[(i32, &str)]) -> Plugins {
+        use std::os::unix::process::ExitStatusExt;
+        let runner = crate::plugins::MockRunner::new();
+        for (code, stdout) in outputs {
+            runner.push_output(Ok(std::process::Output {
+                status: std::process::ExitStatus::from_raw(*code),
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            }));
+        }
+        Plugins::new(Arc::new(runner), Arc::new(FixedResolver { present: true }))
+    }
+
     #[test]
-    fn test_condition_on_branch() {
-        let local_ctx: HashMap<String, String> = HashMap::new();
-        assert!(HookExecutor::eval_condition("on_branch:main", &local_ctx).unwrap());
-        assert!(HookExecutor::eval_condition("on_branch:*", &local_ctx).unwrap());
+    #[cfg(unix)]
+    fn test_condition_on_branch_matches_git_branch() {
+        let plugins = plugins_with_outputs(&[(0, "main\n")]);
+        assert!(HookExecutor::eval_on_branch("main", &plugins));
+
+        let plugins = plugins_with_outputs(&[(0, "main\n")]);
+        assert!(!HookExecutor::eval_on_branch("develop", &plugins));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_condition_on_branch_wildcard() {
+        let plugins = plugins_with_outputs(&[(0, "feature/login\n")]);
+        assert!(HookExecutor::eval_on_branch("feature/*", &plugins));
+
+        let plugins = plugins_with_outputs(&[(0, "fix/login\n")]);
+        assert!(!HookExecutor::eval_on_branch("feature/*", &plugins));
+
+        let plugins = plugins_with_outputs(&[(0, "anything\n")]);
+        assert!(HookExecutor::eval_on_branch("*", &plugins));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_condition_on_branch_falls_back_to_jj() {
+        // git fails (not a git repo), jj reports a bookmark on @
+        let plugins = plugins_with_outputs(&[(128 << 8, ""), (0, "main* other\n")]);
+        assert!(HookExecutor::eval_on_branch("main", &plugins));
+    }
+
+    #[test]
+    fn test_condition_on_branch_fails_closed_without_vcs() {
+        let plugins = Plugins::new(
+            Arc::new(crate::plugins::MockRunner::new()),
+            Arc::new(FixedResolver { present: false }),
+        );
+        assert!(!HookExecutor::eval_on_branch("*", &plugins));
+    }
+
+    #[test]
+    fn test_condition_matches_regex() {
+        let mut local_ctx: HashMap<String, String> = HashMap::new();
+        local_ctx.insert("status".to_string(), "build FAILED in 3s".to_string());
+
+        assert!(HookExecutor::eval_condition("matches:status:FAIL(ED)?", &local_ctx).unwrap());
+        assert!(!HookExecutor::eval_condition("matches:status:^ok$", &local_ctx).unwrap());
+        assert!(!HookExecutor::eval_condition("matches:missing:.*", &local_ctx).unwrap());
+    }
+
+    #[test]
+    fn test_condition_matches_invalid_regex_fails_closed() {
+        let mut local_ctx: HashMap<String, String> = HashMap::new();
+        local_ctx.insert("status".to_string(), "anything".to_string());
+
+        assert!(!HookExecutor::eval_condition("matches:status:([unclosed", &local_ctx).unwrap());
+        assert!(!HookExecutor::eval_condition("matches:no-pattern", &local_ctx).unwrap());
     }
 
     #[test]
@@ -359,6 +811,81 @@ mod tests {
         assert!(!HookExecutor::eval_condition("unknown_condition:foo", &local_ctx).unwrap());
     }
 
+    #[test]
+    fn test_condition_and_requires_both_sides() {
+        let mut local_ctx: HashMap<String, String> = HashMap::new();
+        local_ctx.insert("status".to_string(), "ok".to_string());
+
+        assert!(
+            HookExecutor::eval_condition("equals:status:ok && has_tool:echo", &local_ctx).unwrap()
+        );
+        assert!(
+            !HookExecutor::eval_condition("equals:status:ok && equals:status:fail", &local_ctx)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_condition_or_passes_with_one_side_true() {
+        let mut local_ctx: HashMap<String, String> = HashMap::new();
+        local_ctx.insert("status".to_string(), "ok".to_string());
+
+        assert!(
+            HookExecutor::eval_condition("equals:status:fail || equals:status:ok", &local_ctx)
+                .unwrap()
+        );
+        // `&&` binds tighter than `||`.
+        assert!(
+            HookExecutor::eval_condition(
+                "equals:status:fail && has_tool:echo || equals:status:ok",
+                &local_ctx
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_condition_compound_with_unknown_fails_closed() {
+        let mut local_ctx: HashMap<String, String> = HashMap::new();
+        local_ctx.insert("status".to_string(), "ok".to_string());
+
+        assert!(
+            !HookExecutor::eval_condition("equals:status:ok || on_moon:full", &local_ctx).unwrap()
+        );
+        assert!(!HookExecutor::eval_condition("equals:status:ok && ", &local_ctx).unwrap());
+        assert!(HookExecutor::check_condition("has_tool:git && on_moon:full").is_err());
+        assert!(HookExecutor::check_condition("has_tool:git || env_set:CI").is_ok());
+    }
+
+    #[test]
+    fn test_condition_file_exists() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+
+        assert!(file_exists_in(dir.path(), "Cargo.toml"));
+        assert!(file_exists_in(dir.path(), "./src/lib.rs"));
+        assert!(!file_exists_in(dir.path(), "package.json"));
+    }
+
+    #[test]
+    fn test_condition_file_exists_rejects_traversal() {
+        let dir = TempDir::new().unwrap();
+        let inner = dir.path().join("repo");
+        std::fs::create_dir(&inner).unwrap();
+        std::fs::write(dir.path().join("secret"), "").unwrap();
+
+        assert!(!file_exists_in(&inner, "../secret"));
+        assert!(!file_exists_in(&inner, "/etc/passwd"));
+        assert!(!file_exists_in(&inner, ""));
+
+        let local_ctx: HashMap<String, String> = HashMap::new();
+        assert!(!HookExecutor::eval_condition("file_exists:../secret", &local_ctx).unwrap());
+        assert!(HookExecutor::check_condition("file_exists:../secret").is_err());
+        assert!(HookExecutor::check_condition("file_exists:Cargo.toml").is_ok());
+    }
+
     #[test]
     fn test_check_tool_available() {
         let has_echo = HookExecutor::check_tool_available("echo").unwrap();
@@ -529,6 +1056,31 @@ actions:
         assert_eq!(results.len(), 0);
     }
 
+    #[test]
+    fn test_constant_approval_runs_or_skips_approval_actions() {
+        let yaml = r#"name: test_constant
+trigger: SessionStart
+actions:
+  - type: command
+    command: echo approved
+    requires_approval: true
+"#;
+        let file = create_test_hook_yaml(yaml);
+        let hook = crate::hooks::parse_hook(file.path()).unwrap();
+        let context = EventContext::new();
+
+        let approve_all = constant_approval(true);
+        let results =
+            HookExecutor::execute_hook_with_approval(&hook, &context, Some(&approve_all)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].output, "approved");
+
+        let deny_all = constant_approval(false);
+        let results =
+            HookExecutor::execute_hook_with_approval(&hook, &context, Some(&deny_all)).unwrap();
+        assert!(results.is_empty());
+    }
+
     #[test]
     fn test_approval_required_no_callback() {
         let yaml = r#"name: test_no_callback
@@ -698,4 +1250,144 @@ actions:
         let saved = std::fs::read_to_string(".looprs/state.json").unwrap();
         assert!(saved.contains("\"demo_seen\": true"));
     }
+
+    /// Serve a single canned HTTP response on a local port and return its URL.
+    fn serve_once(status: &str, body: &str) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{addr}/context")
+    }
+
+    fn http_hook(url: &str) -> Hook {
+        let yaml = format!(
+            r#"name: fetch
+trigger: SessionStart
+actions:
+  - type: http_request
+    url: "{url}"
+    headers:
+      Accept: text/plain
+    inject_as: remote_context
+"#
+        );
+        let file = create_test_hook_yaml(&yaml);
+        crate::hooks::parse_hook(file.path()).unwrap()
+    }
+
+    #[test]
+    fn http_request_injects_response_body() {
+        let hook = http_hook(&serve_once("200 OK", "remote notes"));
+        let results = HookExecutor::execute_hook(&hook, &EventContext::new()).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].output, "remote notes");
+        assert_eq!(results[0].inject_key.as_deref(), Some("remote_context"));
+    }
+
+    #[test]
+    fn http_request_server_error_injects_nothing() {
+        let hook = http_hook(&serve_once("500 Internal Server Error", "boom"));
+        let results = HookExecutor::execute_hook(&hook, &EventContext::new()).unwrap();
+
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn post_tool_use_command_substitutes_tool_name() {
+        let yaml = r#"name: echo_tool
+trigger: PostToolUse
+actions:
+  - type: command
+    command: "echo ran ${tool_name}"
+    inject_as: last_tool
+"#;
+        let file = create_test_hook_yaml(yaml);
+        let hook = crate::hooks::parse_hook(file.path()).unwrap();
+        let context = EventContext::new()
+            .with_tool_name("bash".to_string())
+            .with_tool_output("ok".to_string());
+
+        let results = HookExecutor::execute_hook(&hook, &context).unwrap();
+        assert_eq!(results[0].output, "ran bash");
+    }
+
+    #[test]
+    fn substitute_vars_expands_fields_and_metadata() {
+        let context = EventContext::new()
+            .with_user_message("hi".to_string())
+            .with_metadata("branch".to_string(), "main".to_string());
+
+        assert_eq!(
+            substitute_vars("${user_message} on ${meta.branch}${tool_name}", &context),
+            "hi on main"
+        );
+    }
+
+    #[test]
+    fn command_vars_are_quoted_against_injection() {
+        let context = EventContext::new()
+            .with_user_message("$(rm -rf ~)'; rm -rf /".to_string())
+            .with_tool_output("x'#y".to_string());
+
+        assert_eq!(
+            substitute_command_vars("echo ${user_message}", &context),
+            "echo r#'$(rm -rf ~)'; rm -rf /'#"
+        );
+        assert_eq!(
+            substitute_command_vars("echo ${tool_output}", &context),
+            "echo r##'x'#y'##"
+        );
+    }
+
+    #[test]
+    fn substitute_vars_leaves_unknown_placeholders_literal() {
+        let context = EventContext::new();
+        assert_eq!(
+            substitute_vars("${nope} ${meta.missing} ${unterminated", &context),
+            "${nope} ${meta.missing} ${unterminated"
+        );
+    }
+
+    #[test]
+    fn command_timeout_kills_hung_command() {
+        let yaml = r#"name: hung
+trigger: SessionStart
+actions:
+  - type: command
+    command: "sleep 5sec; echo done"
+    inject_as: never
+    timeout_secs: 1
+"#;
+        let file = create_test_hook_yaml(yaml);
+        let hook = crate::hooks::parse_hook(file.path()).unwrap();
+
+        let start = std::time::Instant::now();
+        let results = HookExecutor::execute_hook(&hook, &EventContext::new()).unwrap();
+
+        assert!(start.elapsed() < std::time::Duration::from_secs(3));
+        assert_eq!(results[0].output, "");
+    }
+
+    #[test]
+    fn check_condition_flags_unknown_and_malformed() {
+        assert!(HookExecutor::check_condition("has_tool:git").is_ok());
+        assert!(HookExecutor::check_condition("matches:name:^a+$").is_ok());
+        assert!(HookExecutor::check_condition("config_flag:onboarding.demo_seen=true").is_ok());
+        assert!(HookExecutor::check_condition("equals:missing_value").is_err());
+        assert!(HookExecutor::check_condition("matches:name:(").is_err());
+        assert!(HookExecutor::check_condition("on_moon:full").is_err());
+    }
 }
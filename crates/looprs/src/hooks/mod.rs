@@ -3,22 +3,59 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub mod executor;
 pub mod parser;
 
-pub use executor::{ApprovalCallback, HookExecutor};
-pub use parser::parse_hook;
+pub use executor::{ApprovalCallback, HookExecutor, constant_approval};
+pub use parser::{is_hook_file, parse_hook};
 pub type PromptCallback = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
 
-/// A hook is an event-triggered action defined in YAML
+/// A hook is an event-triggered action defined in YAML or JSON
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Hook {
     pub name: String,
     pub trigger: String, // Event name as string (SessionStart, PostToolUse, etc.)
     pub condition: Option<String>, // Optional filter condition
     pub actions: Vec<Action>,
+    /// Run concurrently with other `parallel` hooks for the same event.
+    /// Ignored for hooks that need the console (approvals or prompts).
+    #[serde(default)]
+    pub parallel: bool,
+    /// Keep the file but never register the hook.
+    #[serde(default)]
+    pub disabled: bool,
+    /// Directory the hook was loaded from; set by the registry, not the file.
+    #[serde(skip)]
+    pub source: HookSource,
+}
+
+/// Where a hook was loaded from. Repo hooks override user hooks with the same
+/// trigger and name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HookSource {
+    /// `~/.looprs/hooks/`
+    User,
+    /// `.looprs/hooks/` in the working directory
+    #[default]
+    Repo,
+}
+
+impl HookSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookSource::User => "user",
+            HookSource::Repo => "repo",
+        }
+    }
+}
+
+impl Hook {
+    /// Whether this hook can run off the main thread alongside other hooks.
+    pub fn runs_in_parallel(&self) -> bool {
+        self.parallel && !self.actions.iter().any(Action::needs_console)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +70,10 @@ pub enum Action {
         requires_approval: bool,
         #[serde(default)]
         approval_prompt: Option<String>,
+        /// Kill the command after this many seconds. Falls back to
+        /// `defaults.timeout_seconds`, then 30s.
+        #[serde(default)]
+        timeout_secs: Option<u64>,
     },
     #[serde(rename = "message")]
     Message { text: String },
@@ -52,6 +93,53 @@ pub enum Action {
     SetEnv { name: String, from_key: String },
     #[serde(rename = "set_config")]
     SetConfig { path: String, value: Value },
+    #[serde(rename = "http_request")]
+    HttpRequest {
+        url: String,
+        #[serde(default = "default_http_method")]
+        method: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        inject_as: Option<String>,
+    },
+}
+
+impl Action {
+    /// True if executing this action may prompt the user.
+    fn needs_console(&self) -> bool {
+        match self {
+            Action::Command {
+                requires_approval, ..
+            } => *requires_approval,
+            Action::Conditional { then, .. } => then.iter().any(Action::needs_console),
+            Action::Confirm { .. } | Action::Prompt { .. } | Action::SecretPrompt { .. } => true,
+            Action::Message { .. }
+            | Action::SetEnv { .. }
+            | Action::SetConfig { .. }
+            | Action::HttpRequest { .. } => false,
+        }
+    }
+}
+
+fn default_http_method() -> String {
+    "GET".to_string()
+}
+
+/// Result of validating one hook file with `HookRegistry::validate_directory`
+#[derive(Debug)]
+pub struct HookFileReport {
+    pub path: PathBuf,
+    /// The parsed hook, or the parse error as the runtime would report it
+    pub outcome: Result<Hook, String>,
+    /// Conditions the executor would reject at runtime
+    pub warnings: Vec<String>,
+}
+
+impl HookFileReport {
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
 }
 
 /// HookRegistry holds all loaded hooks keyed by event type
@@ -59,6 +147,8 @@ pub struct HookRegistry {
     hooks_by_event: HashMap<String, Vec<Hook>>,
     user_hooks: Vec<Hook>, // Loaded from ~/.looprs/hooks/
     repo_hooks: Vec<Hook>, // Loaded from .looprs/hooks/ (cwd)
+    overridden: Vec<Hook>, // User hooks replaced by a repo hook in the merge
+    disabled: Vec<Hook>,   // Hooks with `disabled: true` that won precedence
 }
 
 impl HookRegistry {
@@ -67,6 +157,8 @@ impl HookRegistry {
             hooks_by_event: HashMap::new(),
             user_hooks: Vec::new(),
             repo_hooks: Vec::new(),
+            overridden: Vec::new(),
+            disabled: Vec::new(),
         }
     }
 
@@ -81,10 +173,9 @@ impl HookRegistry {
             let entry = entry?;
             let path = entry.path();
 
-            if path.extension().and_then(|s| s.to_str()) == Some("yaml")
-                || path.extension().and_then(|s| s.to_str()) == Some("yml")
-            {
+            if is_hook_file(&path) {
                 match parse_hook(&path) {
+                    Ok(hook) if hook.disabled => registry.disabled.push(hook),
                     Ok(hook) => {
                         registry
                             .hooks_by_event
@@ -136,21 +227,20 @@ impl HookRegistry {
             return Ok(());
         }
 
-        let target = if is_user {
-            &mut self.user_hooks
+        let (target, source) = if is_user {
+            (&mut self.user_hooks, HookSource::User)
         } else {
-            &mut self.repo_hooks
+            (&mut self.repo_hooks, HookSource::Repo)
         };
 
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
 
-            if path.extension().and_then(|s| s.to_str()) == Some("yaml")
-                || path.extension().and_then(|s| s.to_str()) == Some("yml")
-            {
+            if is_hook_file(&path) {
                 match parse_hook(&path) {
-                    Ok(hook) => {
+                    Ok(mut hook) => {
+                        hook.source = source;
                         target.push(hook);
                     }
                     Err(e) => {
@@ -169,8 +259,15 @@ impl HookRegistry {
 
     /// Merge user and repo hooks with repo precedence
     /// Repo hooks with same (trigger, name) override user hooks
+    ///
+    /// Precedence is settled before `disabled` is looked at: a disabled repo
+    /// hook still replaces the user hook of the same name (so a repo can
+    /// switch off a user hook), and a disabled user hook is still replaced by
+    /// an enabled repo hook. Whatever wins is then dropped if disabled.
     fn merge_with_precedence(&mut self) {
         self.hooks_by_event.clear();
+        self.overridden.clear();
+        self.disabled.clear();
 
         // Add all user hooks first
         for hook in &self.user_hooks {
@@ -189,13 +286,50 @@ impl HookRegistry {
 
             // Check if user hook with same name exists
             if let Some(pos) = event_hooks.iter().position(|h| h.name == repo_hook.name) {
-                // Replace user hook with repo hook
-                event_hooks[pos] = repo_hook.clone();
+                // Replace user hook with repo hook, remembering what lost
+                let replaced = std::mem::replace(&mut event_hooks[pos], repo_hook.clone());
+                self.overridden.push(replaced);
             } else {
                 // Add new repo hook
                 event_hooks.push(repo_hook.clone());
             }
         }
+
+        for event_hooks in self.hooks_by_event.values_mut() {
+            let (disabled, enabled): (Vec<Hook>, Vec<Hook>) = std::mem::take(event_hooks)
+                .into_iter()
+                .partition(|hook| hook.disabled);
+            *event_hooks = enabled;
+            self.disabled.extend(disabled);
+        }
+        self.hooks_by_event.retain(|_, hooks| !hooks.is_empty());
+    }
+
+    /// Parse every hook file in `dir` without registering it, for `looprs hooks validate`.
+    /// Reports are sorted by path.
+    // qual:allow(iosp) reason: "I/O boundary — reads hook files from a directory"
+    pub fn validate_directory(dir: &Path) -> anyhow::Result<Vec<HookFileReport>> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_hook_file(path))
+            .collect();
+        paths.sort();
+
+        Ok(paths
+            .into_iter()
+            .map(|path| match parse_hook(&path) {
+                Ok(hook) => HookFileReport {
+                    warnings: HookExecutor::check_hook_conditions(&hook),
+                    outcome: Ok(hook),
+                    path,
+                },
+                Err(e) => HookFileReport {
+                    path,
+                    outcome: Err(e.to_string()),
+                    warnings: Vec::new(),
+                },
+            })
+            .collect())
     }
 
     /// Get all hooks for a specific event
@@ -203,6 +337,23 @@ impl HookRegistry {
         let event_name = event.name();
         self.hooks_by_event.get(event_name)
     }
+
+    /// Every registered hook, sorted by trigger then name.
+    pub fn all_hooks(&self) -> Vec<&Hook> {
+        let mut hooks: Vec<&Hook> = self.hooks_by_event.values().flatten().collect();
+        hooks.sort_by(|a, b| (&a.trigger, &a.name).cmp(&(&b.trigger, &b.name)));
+        hooks
+    }
+
+    /// User hooks that a repo hook with the same trigger and name replaced.
+    pub fn overridden(&self) -> &[Hook] {
+        &self.overridden
+    }
+
+    /// Hooks skipped because they set `disabled: true`.
+    pub fn disabled(&self) -> &[Hook] {
+        &self.disabled
+    }
 }
 
 impl Default for HookRegistry {
@@ -271,6 +422,55 @@ actions:
         );
     }
 
+    #[test]
+    fn test_validate_directory_reports_each_file() {
+        let dir = TempDir::new().unwrap();
+        create_test_hook_file(
+            dir.path(),
+            "a_good.yaml",
+            r#"name: good
+trigger: SessionStart
+condition: "on_moon:full"
+actions:
+  - type: message
+    text: "hi""#,
+        )
+        .unwrap();
+        create_test_hook_file(dir.path(), "b_bad.yaml", "name: [unclosed").unwrap();
+        create_test_hook_file(dir.path(), "notes.txt", "ignored").unwrap();
+
+        let reports = HookRegistry::validate_directory(dir.path()).unwrap();
+
+        assert_eq!(reports.len(), 2);
+        let good = reports[0].outcome.as_ref().unwrap();
+        assert_eq!(good.trigger, "SessionStart");
+        assert_eq!(
+            reports[0].warnings,
+            vec!["unknown condition 'on_moon:full'"]
+        );
+        assert!(!reports[1].is_ok());
+    }
+
+    #[test]
+    fn test_load_json_hook_registers_under_trigger() {
+        let repo_dir = TempDir::new().unwrap();
+        create_test_hook_file(
+            repo_dir.path(),
+            "after_tool.json",
+            r#"{"name": "json_hook", "trigger": "PostToolUse",
+                "actions": [{"type": "message", "text": "From JSON"}]}"#,
+        )
+        .unwrap();
+        create_test_hook_file(repo_dir.path(), "broken.json", "{not json").unwrap();
+
+        let registry =
+            HookRegistry::load_dual_source(None, Some(&repo_dir.path().to_path_buf())).unwrap();
+
+        let hooks = registry.hooks_for_event(&Event::PostToolUse).unwrap();
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].name, "json_hook");
+    }
+
     #[test]
     fn test_load_dual_source_repo_only() {
         let repo_dir = TempDir::new().unwrap();
@@ -342,6 +542,11 @@ actions:
         let hooks = registry.hooks_by_event.get("SessionStart").unwrap();
         assert_eq!(hooks.len(), 1);
         assert_eq!(hooks[0].name, "greeting");
+        assert_eq!(hooks[0].source, HookSource::Repo);
+
+        // The losing user hook is kept for explaining the override
+        assert_eq!(registry.overridden().len(), 1);
+        assert_eq!(registry.overridden()[0].source, HookSource::User);
 
         // Verify it's the repo version by checking action text
         if let Action::Message { text } = &hooks[0].actions[0] {
@@ -351,6 +556,86 @@ actions:
         }
     }
 
+    #[test]
+    fn disabled_hook_is_not_registered() {
+        let repo_dir = TempDir::new().unwrap();
+        create_test_hook_file(
+            repo_dir.path(),
+            "off.yaml",
+            r#"name: off
+trigger: SessionStart
+disabled: true
+actions:
+  - type: message
+    text: "Off""#,
+        )
+        .unwrap();
+        create_test_hook_file(
+            repo_dir.path(),
+            "on.yaml",
+            r#"name: on
+trigger: SessionStart
+actions:
+  - type: message
+    text: "On""#,
+        )
+        .unwrap();
+
+        let registry =
+            HookRegistry::load_dual_source(None, Some(&repo_dir.path().to_path_buf())).unwrap();
+        let hooks = registry.hooks_for_event(&Event::SessionStart).unwrap();
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].name, "on");
+        assert_eq!(registry.disabled().len(), 1);
+        assert_eq!(registry.disabled()[0].name, "off");
+
+        let registry = HookRegistry::load_from_directory(&repo_dir.path().to_path_buf()).unwrap();
+        assert_eq!(
+            registry
+                .hooks_for_event(&Event::SessionStart)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn disabled_repo_hook_still_overrides_user_hook() {
+        let user_dir = TempDir::new().unwrap();
+        let repo_dir = TempDir::new().unwrap();
+        create_test_hook_file(
+            user_dir.path(),
+            "greeting.yaml",
+            r#"name: greeting
+trigger: SessionStart
+actions:
+  - type: message
+    text: "User greeting""#,
+        )
+        .unwrap();
+        create_test_hook_file(
+            repo_dir.path(),
+            "greeting.yaml",
+            r#"name: greeting
+trigger: SessionStart
+disabled: true
+actions:
+  - type: message
+    text: "Repo greeting""#,
+        )
+        .unwrap();
+
+        let registry = HookRegistry::load_dual_source(
+            Some(&user_dir.path().to_path_buf()),
+            Some(&repo_dir.path().to_path_buf()),
+        )
+        .unwrap();
+
+        assert!(registry.hooks_for_event(&Event::SessionStart).is_none());
+        assert_eq!(registry.disabled()[0].source, HookSource::Repo);
+        assert_eq!(registry.overridden()[0].source, HookSource::User);
+    }
+
     #[test]
     fn test_load_dual_source_different_hooks_both_present() {
         let user_dir = TempDir::new().unwrap();
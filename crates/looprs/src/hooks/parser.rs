@@ -2,13 +2,25 @@ use super::Hook;
 use std::fs;
 use std::path::Path;
 
-/// Parse a YAML hook file
+/// Parse a hook file: `.json` as JSON, anything else as YAML
 pub fn parse_hook(path: &Path) -> anyhow::Result<Hook> {
     let contents = fs::read_to_string(path)?;
-    let hook: Hook = serde_yaml::from_str(&contents)?;
+    let hook: Hook = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&contents)?
+    } else {
+        serde_yaml::from_str(&contents)?
+    };
     Ok(hook)
 }
 
+/// Whether `path` has a hook file extension (`.yaml`, `.yml`, `.json`)
+pub fn is_hook_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|s| s.to_str()),
+        Some("yaml" | "yml" | "json")
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -16,6 +28,24 @@ mod tests {
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_parse_json_hook() {
+        let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(
+            file,
+            r#"{{"name": "json_hook", "trigger": "PostToolUse",
+               "actions": [{{"type": "command", "command": "echo hi", "inject_as": "greeting"}}]}}"#
+        )
+        .unwrap();
+
+        let hook = parse_hook(file.path()).unwrap();
+        assert_eq!(hook.name, "json_hook");
+        match &hook.actions[0] {
+            Action::Command { inject_as, .. } => assert_eq!(inject_as.as_deref(), Some("greeting")),
+            _ => panic!("Expected Command action"),
+        }
+    }
+
     #[test]
     fn test_parse_simple_hook() {
         let mut file = NamedTempFile::new().unwrap();
@@ -0,0 +1,99 @@
+//! Free-form project instructions (`.looprs/instructions.md`).
+//!
+//! Unlike rules, instructions are a single markdown file appended verbatim to
+//! the system prompt. The repo file (`.looprs/instructions.md`) replaces the
+//! user file (`~/.looprs/instructions.md`) when both exist.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const INSTRUCTIONS_FILE: &str = "instructions.md";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectInstructions {
+    pub content: String,
+    /// File the content was read from.
+    pub source: PathBuf,
+}
+
+impl ProjectInstructions {
+    /// Load from `user_dir` and `repo_dir` (each a `.looprs` directory); repo wins.
+    /// Missing or empty files are ignored.
+    // qual:allow(iosp) reason: "I/O boundary — reads instruction files from filesystem"
+    pub fn load(user_dir: Option<&Path>, repo_dir: Option<&Path>) -> Option<Self> {
+        [repo_dir, user_dir]
+            .into_iter()
+            .flatten()
+            .find_map(|dir| Self::load_file(&dir.join(INSTRUCTIONS_FILE)))
+    }
+
+    /// Load from `~/.looprs/` and `.looprs/` relative to the current directory.
+    pub fn load_default() -> Option<Self> {
+        let user_dir = dirs::home_dir().map(|h| h.join(".looprs"));
+        let repo_dir = PathBuf::from(".looprs");
+        Self::load(user_dir.as_deref(), Some(&repo_dir))
+    }
+
+    fn load_file(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        let content = content.trim();
+        if content.is_empty() {
+            return None;
+        }
+        Some(Self {
+            content: content.to_string(),
+            source: path.to_path_buf(),
+        })
+    }
+
+    /// Format as a labeled system prompt segment.
+    pub fn format_for_prompt(&self) -> String {
+        format!("\n\n## Project Instructions\n\n{}\n", self.content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_instructions(dir: &Path, content: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(INSTRUCTIONS_FILE), content).unwrap();
+    }
+
+    #[test]
+    fn repo_overrides_user() {
+        let tmp = TempDir::new().unwrap();
+        let user = tmp.path().join("user");
+        let repo = tmp.path().join("repo");
+        write_instructions(&user, "user instructions");
+        write_instructions(&repo, "repo instructions");
+
+        let loaded = ProjectInstructions::load(Some(&user), Some(&repo)).unwrap();
+        assert_eq!(loaded.content, "repo instructions");
+        assert_eq!(loaded.source, repo.join(INSTRUCTIONS_FILE));
+    }
+
+    #[test]
+    fn falls_back_to_user() {
+        let tmp = TempDir::new().unwrap();
+        let user = tmp.path().join("user");
+        write_instructions(&user, "user instructions");
+
+        let loaded = ProjectInstructions::load(Some(&user), Some(&tmp.path().join("missing")));
+        assert_eq!(loaded.unwrap().content, "user instructions");
+    }
+
+    #[test]
+    fn empty_file_is_ignored() {
+        let tmp = TempDir::new().unwrap();
+        let user = tmp.path().join("user");
+        let repo = tmp.path().join("repo");
+        write_instructions(&user, "user instructions");
+        write_instructions(&repo, "  \n");
+
+        let loaded = ProjectInstructions::load(Some(&user), Some(&repo)).unwrap();
+        assert_eq!(loaded.content, "user instructions");
+    }
+}
@@ -5,16 +5,21 @@ mod api;
 pub mod app_config;
 pub mod approval;
 pub mod baml_client;
+pub mod cancel;
 pub mod commands;
 mod config;
 mod config_file;
+pub mod config_watch;
 pub mod context;
+pub mod effective_config;
 pub mod errors;
+pub mod event_log;
 pub mod events;
 pub mod file_refs;
 pub mod fs_mode;
 pub mod git_info;
 pub mod hooks;
+pub mod instructions;
 pub mod jj;
 pub mod kan;
 pub mod model_badge;
@@ -37,29 +42,36 @@ pub mod state;
 pub mod system_monitor;
 mod tools;
 pub mod trace;
+pub mod trash;
 pub mod types;
 pub mod ui;
 
 pub use crate::adapters::{
     ChannelBroker, NullOutput, PluginsAdapter, RetryProvider, SqliteSessionStore,
 };
-pub use crate::agent::{Agent, RuntimeSettings};
+pub use crate::agent::{
+    Agent, CheckpointId, RuntimeSettings, SavedSession, SessionUsage, ToolCallRecord, TurnSummary,
+};
 pub use crate::agents::{AgentDefinition, AgentRegistry};
 pub use crate::approval::{console_approval_prompt, console_prompt, console_secret_prompt};
-pub use crate::commands::{Command, CommandAction, CommandRegistry};
-pub use crate::config_file::{ProviderConfig, ProviderSettings};
+pub use crate::cancel::CancellationToken;
+pub use crate::commands::{Command, CommandAction, CommandRegistry, expand_placeholders};
+pub use crate::config_file::{ProviderConfig, ProviderSettings, write_json_setting};
 pub use crate::context::SessionContext;
 pub use crate::errors::{AgentError, ProviderError, ToolContextError};
 pub use crate::events::{Event, EventContext, EventManager};
 pub use crate::file_refs::{has_file_references, list_file_references, resolve_file_references};
 pub use crate::fs_mode::FsMode;
 pub use crate::hooks::{ApprovalCallback, Hook, HookExecutor, HookRegistry, PromptCallback};
+pub use crate::instructions::ProjectInstructions;
 pub use crate::observation::Observation;
-pub use crate::observation_manager::ObservationManager;
+pub use crate::observation_manager::{ObservationEntry, ObservationFilter, ObservationManager};
 pub use crate::ports::{Message, MessageBroker};
 pub use crate::ports::{ObservationStore, PluginExecutor};
 pub use crate::providers::{ProviderOverrides, create_provider_with_overrides};
-pub use crate::rules::{Rule, RuleRegistry};
+pub use crate::rules::{Rule, RuleOrigin, RuleRegistry};
 pub use crate::skills::{Skill, SkillRegistry};
+pub use crate::tools::OutputSink;
 pub use crate::types::{ModelId, ToolId, ToolName};
 pub use looprs_core::ai_types;
+pub use looprs_core::model_info;
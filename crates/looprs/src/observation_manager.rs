@@ -6,32 +6,156 @@ use crate::observation::Observation;
 use crate::ports::ObservationStore;
 use crate::types::ToolId;
 
+const ENTRY_PREVIEW_CHARS: usize = 120;
+
+/// Narrows `ObservationManager::load_observations`. `None` fields match everything.
+#[derive(Debug, Clone)]
+pub struct ObservationFilter {
+    /// Exact tool name, e.g. `"edit"`.
+    pub tool: Option<String>,
+    /// Case-insensitive substring searched in both input and output.
+    pub query: Option<String>,
+    /// Maximum entries returned, most recent first.
+    pub limit: usize,
+}
+
+impl Default for ObservationFilter {
+    fn default() -> Self {
+        Self {
+            tool: None,
+            query: None,
+            limit: 20,
+        }
+    }
+}
+
+/// A stored observation, summarized for display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservationEntry {
+    /// Database row id.
+    pub id: i64,
+    pub session_id: String,
+    pub tool_name: String,
+    pub input_preview: String,
+    /// The input's path or command, taken from the full input.
+    pub target: Option<String>,
+    pub output_preview: String,
+    pub timestamp: u64,
+    /// Consecutive identical calls rolled into this entry.
+    pub count: u32,
+}
+
+impl ObservationEntry {
+    /// One-line label such as `read src/x.rs (x3)`: the tool, its path or
+    /// command when the input has one, and the repeat count.
+    pub fn summary(&self) -> String {
+        let target = self.target.as_deref().unwrap_or(&self.input_preview);
+        let mut summary = format!("{} {target}", self.tool_name);
+        if self.count > 1 {
+            summary.push_str(&format!(" (x{})", self.count));
+        }
+        summary
+    }
+}
+
+/// The argument that identifies a tool call: its path, command, or pattern.
+fn key_argument(input: &Value) -> Option<String> {
+    ["path", "file", "cmd", "command", "pattern"]
+        .iter()
+        .find_map(|key| input.get(key)?.as_str().map(str::to_string))
+}
+
+fn preview(text: &str) -> String {
+    let mut chars = text.chars();
+    let head: String = chars.by_ref().take(ENTRY_PREVIEW_CHARS).collect();
+    if chars.next().is_some() {
+        format!("{head}...")
+    } else {
+        head
+    }
+}
+
 /// Manages observation capture and storage across a session
 pub struct ObservationManager {
     session_id: String,
     observations: Vec<Observation>,
+    /// How many leading `observations` are already in the database.
+    persisted: usize,
+    /// Row id of the last observation written, so a later rollup onto it can
+    /// update the row instead of inserting a duplicate.
+    last_rowid: Option<i64>,
+    /// The last persisted observation has been rolled up since it was written.
+    last_row_stale: bool,
+}
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS observations (
+    session_id TEXT NOT NULL,
+    tool_name  TEXT NOT NULL,
+    input      TEXT NOT NULL,
+    output     TEXT NOT NULL,
+    tool_use_id TEXT,
+    timestamp  INTEGER NOT NULL,
+    context    TEXT,
+    count      INTEGER NOT NULL DEFAULT 1,
+    target     TEXT
+)";
+
+/// Columns added after the first release, with their definitions.
+const ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("count", "count INTEGER NOT NULL DEFAULT 1"),
+    ("target", "target TEXT"),
+];
+
+/// Create the table, adding columns that older databases lack.
+fn ensure_schema(conn: &rusqlite::Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(CREATE_TABLE)?;
+    for (name, definition) in ADDED_COLUMNS {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('observations') WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute_batch(&format!("ALTER TABLE observations ADD COLUMN {definition}"))?;
+        }
+    }
+    Ok(())
 }
 
 impl ObservationManager {
-    /// Persist all observations to a SQLite database at `path`.
-    pub fn persist(&self, path: &std::path::Path) -> anyhow::Result<()> {
+    /// Persist observations to a SQLite database at `path`.
+    ///
+    /// Only observations not written by an earlier call are inserted, so this
+    /// is safe to call after every capture and again at session end.
+    pub fn persist(&mut self, path: &std::path::Path) -> anyhow::Result<()> {
+        if self.pending() == 0 {
+            return Ok(());
+        }
         let conn = rusqlite::Connection::open(path)?;
-        conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS observations (
-                session_id TEXT NOT NULL,
-                tool_name  TEXT NOT NULL,
-                input      TEXT NOT NULL,
-                output     TEXT NOT NULL,
-                tool_use_id TEXT,
-                timestamp  INTEGER NOT NULL,
-                context    TEXT
-            )",
-        )?;
-        for obs in &self.observations {
+        ensure_schema(&conn)?;
+        if self.last_row_stale
+            && let Some(rowid) = self.last_rowid
+        {
+            let obs = &self.observations[self.persisted - 1];
+            conn.execute(
+                "UPDATE observations SET output = ?1, tool_use_id = ?2, timestamp = ?3, count = ?4
+                 WHERE rowid = ?5",
+                rusqlite::params![
+                    &obs.output,
+                    obs.tool_use_id.as_ref().map(|id| id.as_str()),
+                    obs.timestamp as i64,
+                    obs.count,
+                    rowid,
+                ],
+            )?;
+        }
+        self.last_row_stale = false;
+        for obs in &self.observations[self.persisted..] {
             conn.execute(
                 "INSERT INTO observations
-                 (session_id, tool_name, input, output, tool_use_id, timestamp, context)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                 (session_id, tool_name, input, output, tool_use_id, timestamp, context, count,
+                  target)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 rusqlite::params![
                     &obs.session_id,
                     &obs.tool_name,
@@ -40,20 +164,30 @@ impl ObservationManager {
                     obs.tool_use_id.as_ref().map(|id| id.as_str()),
                     obs.timestamp as i64,
                     obs.context.as_deref(),
+                    obs.count,
+                    key_argument(&obs.input),
                 ],
             )?;
+            self.last_rowid = Some(conn.last_insert_rowid());
+            self.persisted += 1;
         }
         Ok(())
     }
 
+    /// Observations captured, or rolled up, but not yet persisted.
+    pub fn pending(&self) -> usize {
+        self.observations.len() - self.persisted + usize::from(self.last_row_stale)
+    }
+
     /// Load observations for `session_id` from a SQLite database at `path`.
     pub fn load_from(session_id: &str, path: &std::path::Path) -> anyhow::Result<Self> {
         let conn = rusqlite::Connection::open(path)?;
+        ensure_schema(&conn)?;
         let mut stmt = conn.prepare(
-            "SELECT tool_name, input, output, tool_use_id, timestamp, context
-             FROM observations WHERE session_id = ?1 ORDER BY timestamp ASC",
+            "SELECT tool_name, input, output, tool_use_id, timestamp, context, count, rowid
+             FROM observations WHERE session_id = ?1 ORDER BY timestamp ASC, rowid ASC",
         )?;
-        let observations = stmt
+        let rows = stmt
             .query_map(rusqlite::params![session_id], |row| {
                 let input_str: String = row.get(1)?;
                 let tool_use_id_str: Option<String> = row.get(3)?;
@@ -65,12 +199,16 @@ impl ObservationManager {
                     tool_use_id_str,
                     timestamp as u64,
                     row.get::<_, Option<String>>(5)?,
+                    row.get::<_, u32>(6)?,
+                    row.get::<_, i64>(7)?,
                 ))
             })?
-            .collect::<Result<Vec<_>, _>>()?
+            .collect::<Result<Vec<_>, _>>()?;
+        let last_rowid = rows.last().map(|row| row.7);
+        let observations: Vec<_> = rows
             .into_iter()
             .map(
-                |(tool_name, input_str, output, tool_use_id, timestamp, context)| {
+                |(tool_name, input_str, output, tool_use_id, timestamp, context, count, _)| {
                     let input = serde_json::from_str(&input_str).unwrap_or(serde_json::Value::Null);
                     crate::observation::Observation {
                         tool_name,
@@ -80,16 +218,72 @@ impl ObservationManager {
                         timestamp,
                         session_id: session_id.to_string(),
                         context,
+                        count,
                     }
                 },
             )
             .collect();
         Ok(Self {
             session_id: session_id.to_string(),
+            persisted: observations.len(),
             observations,
+            last_rowid,
+            last_row_stale: false,
         })
     }
 
+    /// Search stored observations across sessions, most recent first.
+    pub fn load_observations(
+        path: &std::path::Path,
+        filter: &ObservationFilter,
+    ) -> anyhow::Result<Vec<ObservationEntry>> {
+        let conn = rusqlite::Connection::open(path)?;
+        ensure_schema(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT rowid, session_id, tool_name, input, output, timestamp, count, target
+             FROM observations
+             WHERE (?1 IS NULL OR tool_name = ?1)
+               AND (?2 IS NULL
+                    OR instr(lower(input), lower(?2)) > 0
+                    OR instr(lower(output), lower(?2)) > 0)
+             ORDER BY timestamp DESC, rowid DESC
+             LIMIT ?3",
+        )?;
+        let entries = stmt
+            .query_map(
+                rusqlite::params![filter.tool, filter.query, filter.limit as i64],
+                |row| {
+                    let input: String = row.get(3)?;
+                    // Rows written before `target` existed: derive it from the full input.
+                    let target = row.get::<_, Option<String>>(7)?.or_else(|| {
+                        serde_json::from_str(&input)
+                            .ok()
+                            .and_then(|input| key_argument(&input))
+                    });
+                    Ok(ObservationEntry {
+                        id: row.get(0)?,
+                        session_id: row.get(1)?,
+                        tool_name: row.get(2)?,
+                        input_preview: preview(&input),
+                        target,
+                        output_preview: preview(&row.get::<_, String>(4)?),
+                        timestamp: row.get::<_, i64>(5)? as u64,
+                        count: row.get(6)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// `~/.looprs/observations.db`, where sessions persist observations.
+    pub fn default_db_path() -> std::path::PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join(".looprs")
+            .join("observations.db")
+    }
+
     /// Create a new observation manager for this session
     pub fn new() -> Self {
         let timestamp = SystemTime::now()
@@ -102,6 +296,9 @@ impl ObservationManager {
         ObservationManager {
             session_id,
             observations: Vec::new(),
+            persisted: 0,
+            last_rowid: None,
+            last_row_stale: false,
         }
     }
 
@@ -110,7 +307,9 @@ impl ObservationManager {
         &self.session_id
     }
 
-    /// Capture a tool execution as an observation
+    /// Capture a tool execution as an observation. A call with the same tool
+    /// and input as the previous one is rolled into it: `count` goes up and
+    /// the latest output replaces the earlier one.
     pub fn capture(
         &mut self,
         tool_name: String,
@@ -125,7 +324,16 @@ impl ObservationManager {
             tool_use_id,
             self.session_id.clone(),
         );
-        self.observations.push(obs);
+        match self.observations.last_mut() {
+            Some(last) if last.tool_name == obs.tool_name && last.input == obs.input => {
+                let count = last.count + 1;
+                *last = Observation { count, ..obs };
+                if self.persisted == self.observations.len() {
+                    self.last_row_stale = true;
+                }
+            }
+            _ => self.observations.push(obs),
+        }
     }
 
     /// Get all observations in this session
@@ -146,6 +354,9 @@ impl ObservationManager {
     /// Clear all observations (usually called after saving)
     pub fn clear(&mut self) {
         self.observations.clear();
+        self.persisted = 0;
+        self.last_rowid = None;
+        self.last_row_stale = false;
     }
 }
 
@@ -179,6 +390,196 @@ mod tests {
         assert_eq!(loaded.session_id(), mgr.session_id());
     }
 
+    fn row_count(path: &std::path::Path) -> i64 {
+        rusqlite::Connection::open(path)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM observations", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn persist_only_writes_new_observations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("obs.db");
+
+        let mut mgr = ObservationManager::new();
+        for tool in ["read", "grep", "edit"] {
+            mgr.capture(
+                tool.to_string(),
+                serde_json::json!({}),
+                "ok".to_string(),
+                None,
+            );
+            mgr.persist(&path).unwrap();
+            assert_eq!(mgr.pending(), 0);
+        }
+        assert_eq!(row_count(&path), 3);
+
+        // A final session-end flush has nothing left to write.
+        mgr.persist(&path).unwrap();
+        assert_eq!(row_count(&path), 3);
+    }
+
+    fn seeded_db(dir: &std::path::Path) -> std::path::PathBuf {
+        let path = dir.join("obs.db");
+        let mut mgr = ObservationManager::new();
+        for (tool, input, output) in [
+            ("edit", serde_json::json!({"path": "Cargo.toml"}), "edited"),
+            ("edit", serde_json::json!({"path": "src/main.rs"}), "edited"),
+            (
+                "read",
+                serde_json::json!({"path": "Cargo.toml"}),
+                "[package]",
+            ),
+            ("bash", serde_json::json!({"cmd": "cargo test"}), "ok"),
+        ] {
+            mgr.capture(tool.to_string(), input, output.to_string(), None);
+        }
+        mgr.persist(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_observations_filters_by_tool_and_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = seeded_db(dir.path());
+
+        let filter = ObservationFilter {
+            tool: Some("edit".to_string()),
+            query: Some("cargo.TOML".to_string()),
+            limit: 20,
+        };
+        let entries = ObservationManager::load_observations(&path, &filter).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tool_name, "edit");
+        assert!(entries[0].input_preview.contains("Cargo.toml"));
+    }
+
+    #[test]
+    fn load_observations_query_searches_output_and_respects_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = seeded_db(dir.path());
+
+        let by_tool = ObservationFilter {
+            tool: Some("edit".to_string()),
+            ..ObservationFilter::default()
+        };
+        assert_eq!(
+            ObservationManager::load_observations(&path, &by_tool)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let by_output = ObservationFilter {
+            query: Some("[package]".to_string()),
+            ..ObservationFilter::default()
+        };
+        let entries = ObservationManager::load_observations(&path, &by_output).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tool_name, "read");
+
+        let limited = ObservationFilter {
+            limit: 3,
+            ..ObservationFilter::default()
+        };
+        let entries = ObservationManager::load_observations(&path, &limited).unwrap();
+        assert_eq!(entries.len(), 3);
+        // Same timestamp within a run, so newest-first falls back to insertion order.
+        assert_eq!(entries[0].tool_name, "bash");
+    }
+
+    #[test]
+    fn identical_consecutive_captures_roll_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("obs.db");
+        let read = || serde_json::json!({"path": "src/x.rs"});
+
+        let mut mgr = ObservationManager::new();
+        mgr.capture("read".to_string(), read(), "v1".to_string(), None);
+        mgr.persist(&path).unwrap();
+        for output in ["v2", "v3"] {
+            mgr.capture("read".to_string(), read(), output.to_string(), None);
+        }
+        assert_eq!(mgr.count(), 1);
+        assert_eq!(mgr.observations()[0].count, 3);
+        assert_eq!(mgr.observations()[0].output, "v3");
+        mgr.persist(&path).unwrap();
+        assert_eq!(row_count(&path), 1);
+
+        let entries =
+            ObservationManager::load_observations(&path, &ObservationFilter::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].count, 3);
+        assert_eq!(entries[0].summary(), "read src/x.rs (x3)");
+    }
+
+    #[test]
+    fn summary_uses_the_key_argument_of_long_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("obs.db");
+        let input = serde_json::json!({
+            "content": "x".repeat(500),
+            "path": "src/deep/module.rs",
+        });
+        assert!(input.to_string().len() > ENTRY_PREVIEW_CHARS);
+
+        let mut mgr = ObservationManager::new();
+        for _ in 0..2 {
+            mgr.capture("write".to_string(), input.clone(), "ok".to_string(), None);
+        }
+        mgr.persist(&path).unwrap();
+
+        let entries =
+            ObservationManager::load_observations(&path, &ObservationFilter::default()).unwrap();
+        assert!(!entries[0].input_preview.contains("src/deep/module.rs"));
+        assert_eq!(entries[0].summary(), "write src/deep/module.rs (x2)");
+    }
+
+    #[test]
+    fn rows_without_a_stored_target_derive_it_from_the_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("obs.db");
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE observations (
+                session_id TEXT NOT NULL, tool_name TEXT NOT NULL, input TEXT NOT NULL,
+                output TEXT NOT NULL, tool_use_id TEXT, timestamp INTEGER NOT NULL,
+                context TEXT, count INTEGER NOT NULL DEFAULT 1
+            );
+            INSERT INTO observations (session_id, tool_name, input, output, timestamp)
+            VALUES ('s', 'bash', '{\"cmd\":\"cargo test\"}', 'ok', 1);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let entries =
+            ObservationManager::load_observations(&path, &ObservationFilter::default()).unwrap();
+        assert_eq!(entries[0].target.as_deref(), Some("cargo test"));
+        assert_eq!(entries[0].summary(), "bash cargo test");
+    }
+
+    #[test]
+    fn distinct_captures_stay_separate() {
+        let mut mgr = ObservationManager::new();
+        for (tool, path) in [
+            ("read", "a.rs"),
+            ("read", "b.rs"),
+            ("grep", "b.rs"),
+            ("read", "a.rs"),
+        ] {
+            mgr.capture(
+                tool.to_string(),
+                serde_json::json!({"path": path}),
+                "ok".to_string(),
+                None,
+            );
+        }
+        assert_eq!(mgr.count(), 4);
+        assert!(mgr.observations().iter().all(|obs| obs.count == 1));
+    }
+
     #[test]
     fn test_observation_manager_creation() {
         let mgr = ObservationManager::new();
@@ -1,9 +1,13 @@
+use std::ffi::{OsStr, OsString};
+use std::process::Output;
+
 use super::{NamedTool, Plugins};
 
 /// Thin adapters for named external binaries.
 ///
-/// These are intentionally "dumb": they only provide tool identity + execution.
-/// Higher-level modules (jj/kan/tools) own argument construction and parsing.
+/// The adapters themselves only provide tool identity + execution. Output
+/// parsing that several modules share (e.g. the current branch) lives in the
+/// typed helpers on `Plugins` below; everything else stays with its caller.
 macro_rules! define_tool {
     ($name:ident, $bin:literal) => {
         pub struct $name<'a> {
@@ -37,3 +41,117 @@ define_tool!(Kan, "kan");
 define_tool!(Rg, "rg");
 define_tool!(Fd, "fd");
 define_tool!(Git, "git");
+
+fn os_args<S: AsRef<OsStr>>(args: &[S]) -> Vec<OsString> {
+    args.iter().map(|a| a.as_ref().to_os_string()).collect()
+}
+
+/// Stdout of a successful run, trimmed; `None` on failure or empty output.
+fn success_stdout(output: Output) -> Option<String> {
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
+impl Plugins {
+    /// Run `git` with `args`.
+    pub fn git<S: AsRef<OsStr>>(&self, args: &[S]) -> std::io::Result<Output> {
+        Git::new(self).output(os_args(args))
+    }
+
+    /// Run `jj` with `args`.
+    pub fn jj<S: AsRef<OsStr>>(&self, args: &[S]) -> std::io::Result<Output> {
+        Jj::new(self).output(os_args(args))
+    }
+
+    /// Run `rg` with `args`.
+    pub fn ripgrep<S: AsRef<OsStr>>(&self, args: &[S]) -> std::io::Result<Output> {
+        Rg::new(self).output(os_args(args))
+    }
+
+    /// Current git branch (`rev-parse --abbrev-ref HEAD`); `None` when git is
+    /// missing, outside a repo, or on a detached HEAD.
+    pub fn git_current_branch(&self) -> Option<String> {
+        let branch = success_stdout(self.git(&["rev-parse", "--abbrev-ref", "HEAD"]).ok()?)?;
+        (branch != "HEAD").then_some(branch)
+    }
+
+    /// First jj bookmark on `@`, else on `@-` (trailing `*` conflict markers stripped).
+    pub fn jj_current_bookmark(&self) -> Option<String> {
+        ["@", "@-"].into_iter().find_map(|rev| {
+            let output = self
+                .jj(&["log", "-r", rev, "--no-graph", "-T", "bookmarks"])
+                .ok()?;
+            success_stdout(output)?
+                .split_whitespace()
+                .next()
+                .map(|b| b.trim_end_matches('*').to_string())
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::plugins::MockRunner;
+    use crate::plugins::testing::plugins_with;
+    use std::path::PathBuf;
+
+    fn call_args(runner: &MockRunner, idx: usize) -> (PathBuf, Vec<String>) {
+        let call = &runner.calls()[idx];
+        let args = call
+            .args
+            .iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        (call.program.clone(), args)
+    }
+
+    #[test]
+    fn ripgrep_runs_rg_with_args() {
+        let (plugins, runner) = plugins_with(&[(0, "src/main.rs\n")]);
+        plugins.ripgrep(&["-l", "fn main"]).unwrap();
+
+        assert_eq!(
+            call_args(&runner, 0),
+            (
+                PathBuf::from("/usr/bin/rg"),
+                vec!["-l".into(), "fn main".into()]
+            )
+        );
+    }
+
+    #[test]
+    fn git_current_branch_parses_rev_parse() {
+        let (plugins, runner) = plugins_with(&[(0, "feature/x\n")]);
+        assert_eq!(plugins.git_current_branch().as_deref(), Some("feature/x"));
+        assert_eq!(
+            call_args(&runner, 0),
+            (
+                PathBuf::from("/usr/bin/git"),
+                vec!["rev-parse".into(), "--abbrev-ref".into(), "HEAD".into()]
+            )
+        );
+    }
+
+    #[test]
+    fn git_current_branch_is_none_when_detached() {
+        let (plugins, _) = plugins_with(&[(0, "HEAD\n")]);
+        assert_eq!(plugins.git_current_branch(), None);
+    }
+
+    #[test]
+    fn jj_current_bookmark_falls_back_to_parent() {
+        let (plugins, runner) = plugins_with(&[(0, ""), (0, "main* other\n")]);
+        assert_eq!(plugins.jj_current_bookmark().as_deref(), Some("main"));
+
+        let (program, args) = call_args(&runner, 1);
+        assert_eq!(program, PathBuf::from("/usr/bin/jj"));
+        assert_eq!(
+            args,
+            vec!["log", "-r", "@-", "--no-graph", "-T", "bookmarks"]
+        );
+    }
+}
@@ -4,9 +4,11 @@ mod resolve;
 mod runner;
 mod tool;
 
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::process::Output;
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
+use std::time::Duration;
 
 pub use registry::{ToolRegistry, ToolResolver};
 pub use resolve::PathResolver;
@@ -20,13 +22,18 @@ pub use tool::NamedTool;
 pub struct Plugins {
     runner: Arc<dyn Runner>,
     registry: ToolRegistry,
+    versions: Mutex<HashMap<String, Option<semver::Version>>>,
 }
 
+/// How long `Plugins::version` waits for `<tool> --version` before giving up.
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
 impl Plugins {
     pub fn new(runner: Arc<dyn Runner>, resolver: Arc<dyn ToolResolver>) -> Self {
         Self {
             runner,
             registry: ToolRegistry::new(resolver),
+            versions: Mutex::new(HashMap::new()),
         }
     }
 
@@ -58,8 +65,129 @@ impl Plugins {
         };
         out.status.success()
     }
+
+    /// Version reported by `<tool> --version`, cached per tool.
+    ///
+    /// Returns `None` if the tool is missing, fails, prints nothing
+    /// version-like, or does not answer within a couple of seconds.
+    pub fn version(&self, tool: &str) -> Option<semver::Version> {
+        if let Some(cached) = self.versions.lock().unwrap().get(tool) {
+            return cached.clone();
+        }
+        let version = self.probe_version(tool);
+        self.versions
+            .lock()
+            .unwrap()
+            .insert(tool.to_string(), version.clone());
+        version
+    }
+
+    fn probe_version(&self, tool: &str) -> Option<semver::Version> {
+        let program = self.registry.require(tool).ok()?;
+        let runner = Arc::clone(&self.runner);
+        let (tx, rx) = mpsc::channel();
+        // A tool that ignores --version may wait on stdin forever; the probe
+        // thread is abandoned rather than blocking the caller.
+        std::thread::spawn(move || {
+            let _ = tx.send(runner.output(&program, &[OsString::from("--version")]));
+        });
+        let output = rx.recv_timeout(VERSION_PROBE_TIMEOUT).ok()?.ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_version(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+/// First semver-looking token in `text`, e.g. `git version 2.43.0` or
+/// `ripgrep 14.1.0 (rev abc)`. Two-part versions get a `.0` patch, and extra
+/// components (`2.39.3.windows.1`) or pre-release suffixes are dropped.
+fn parse_version(text: &str) -> Option<semver::Version> {
+    text.split_whitespace().find_map(|token| {
+        let token = token.trim_start_matches('v');
+        if !token.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let parts: Vec<u64> = token
+            .split('.')
+            .take(3)
+            .map_while(|part| {
+                let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().ok()
+            })
+            .collect();
+        match parts.as_slice() {
+            [major, minor] => Some(semver::Version::new(*major, *minor, 0)),
+            [major, minor, patch] => Some(semver::Version::new(*major, *minor, *patch)),
+            _ => None,
+        }
+    })
 }
 
 pub fn system() -> &'static Plugins {
     Plugins::system()
 }
+
+/// Test doubles shared by tests that drive `Plugins` without real binaries.
+#[cfg(all(test, unix))]
+pub(crate) mod testing {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::path::PathBuf;
+
+    /// Resolves every tool to `/usr/bin/<tool>`.
+    pub(crate) struct UsrBin;
+
+    impl ToolResolver for UsrBin {
+        fn resolve(&self, tool: &str) -> Option<PathBuf> {
+            Some(PathBuf::from(format!("/usr/bin/{tool}")))
+        }
+    }
+
+    /// `Plugins` whose runner replays `(raw wait status, stdout)` pairs in order.
+    pub(crate) fn plugins_with(outputs: &[(i32, &str)]) -> (Plugins, Arc<MockRunner>) {
+        let runner = Arc::new(MockRunner::new());
+        for (code, stdout) in outputs {
+            runner.push_output(Ok(Output {
+                status: std::process::ExitStatus::from_raw(*code),
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            }));
+        }
+        (Plugins::new(runner.clone(), Arc::new(UsrBin)), runner)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::testing::plugins_with;
+    use super::*;
+
+    #[test]
+    fn version_parses_common_formats() {
+        let (plugins, _) = plugins_with(&[
+            (0, "ripgrep 14.1.0 (rev e50df40a19)\n-SIMD -AVX\n"),
+            (0, "git version 2.39.3 (Apple Git-146)\n"),
+            (0, "jj v0.25\n"),
+        ]);
+        assert_eq!(plugins.version("rg"), Some(semver::Version::new(14, 1, 0)));
+        assert_eq!(plugins.version("git"), Some(semver::Version::new(2, 39, 3)));
+        assert_eq!(plugins.version("jj"), Some(semver::Version::new(0, 25, 0)));
+    }
+
+    #[test]
+    fn version_is_cached_per_tool() {
+        let (plugins, runner) = plugins_with(&[(0, "fd 10.2.0\n")]);
+        assert_eq!(plugins.version("fd"), Some(semver::Version::new(10, 2, 0)));
+        assert_eq!(plugins.version("fd"), Some(semver::Version::new(10, 2, 0)));
+        assert_eq!(runner.calls().len(), 1);
+        assert_eq!(runner.calls()[0].args, vec![OsString::from("--version")]);
+    }
+
+    #[test]
+    fn version_is_none_without_version_output() {
+        let (plugins, _) = plugins_with(&[(0, "usage: tool [options]\n"), (1, "1.0.0\n")]);
+        assert_eq!(plugins.version("odd"), None);
+        assert_eq!(plugins.version("failing"), None);
+    }
+}
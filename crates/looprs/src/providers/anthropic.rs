@@ -6,27 +6,48 @@ use crate::api::ContentBlock;
 use crate::errors::ProviderError;
 use crate::ports::InferStream;
 
-use super::{InferenceRequest, InferenceResponse, LLMProvider, ProviderHttpClient, Usage};
+use super::{
+    HttpClient, InferenceRequest, InferenceResponse, LLMProvider, ProviderHttpClient,
+    SamplingParams, Usage,
+};
 use crate::types::ModelId;
 
 pub struct AnthropicProvider {
     http: ProviderHttpClient,
     key: String,
     model: ModelId,
+    sampling: SamplingParams,
 }
 
+/// Anthropic accepts temperatures in 0–1.
+pub(crate) const MAX_TEMPERATURE: f32 = 1.0;
+
 impl AnthropicProvider {
     pub fn new(key: String) -> Result<Self, ProviderError> {
         let model = std::env::var("MODEL").ok().map(ModelId::new);
-        Self::new_with_model(key, model)
+        Self::new_with_model(&HttpClient::new()?, key, model)
     }
 
-    pub fn new_with_model(key: String, model: Option<ModelId>) -> Result<Self, ProviderError> {
-        let http = ProviderHttpClient::default()?;
+    pub fn new_with_model(
+        http: &HttpClient,
+        key: String,
+        model: Option<ModelId>,
+    ) -> Result<Self, ProviderError> {
+        let http = ProviderHttpClient::default(http);
 
         let model = model.unwrap_or_else(ModelId::claude_opus);
 
-        Ok(Self { http, key, model })
+        Ok(Self {
+            http,
+            key,
+            model,
+            sampling: SamplingParams::default(),
+        })
+    }
+
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
     }
 }
 
@@ -40,7 +61,7 @@ impl LLMProvider for AnthropicProvider {
             "model": req.model.as_str(),
             "max_tokens": req.max_tokens,
             "system": req.system,
-            "messages": req.messages,
+            "messages": super::convert_to_anthropic_messages(&req.messages),
             "tools": req.tools
                 .iter()
                 .map(|t| json!({
@@ -53,10 +74,12 @@ impl LLMProvider for AnthropicProvider {
         if let Some(temp) = req.temperature {
             body["temperature"] = json!(temp);
         }
+        if let Some(top_p) = req.top_p {
+            body["top_p"] = json!(top_p);
+        }
 
         let res = self
             .http
-            .client()
             .post("https://api.anthropic.com/v1/messages")
             .header("x-api-key", &self.key)
             .header("anthropic-version", "2023-06-01")
@@ -67,11 +90,9 @@ impl LLMProvider for AnthropicProvider {
 
         if !res.status().is_success() {
             let status = res.status();
+            let headers = res.headers().clone();
             let err_text = res.text().await?;
-            return Err(ProviderError::ApiError(format!(
-                "Anthropic API Error {status}: {err_text}"
-            ))
-            .into());
+            return Err(super::status_error("Anthropic", status, &headers, &err_text).into());
         }
 
         let response_json: Value = res.json().await?;
@@ -164,7 +185,7 @@ impl LLMProvider for AnthropicProvider {
             "model": req.model.as_str(),
             "max_tokens": req.max_tokens,
             "system": req.system,
-            "messages": req.messages,
+            "messages": super::convert_to_anthropic_messages(&req.messages),
             "stream": true,
             "tools": req.tools.iter().map(|t| json!({
                 "name": t.name,
@@ -175,10 +196,12 @@ impl LLMProvider for AnthropicProvider {
         if let Some(temp) = req.temperature {
             body["temperature"] = json!(temp);
         }
+        if let Some(top_p) = req.top_p {
+            body["top_p"] = json!(top_p);
+        }
 
         let result = self
             .http
-            .client()
             .post("https://api.anthropic.com/v1/messages")
             .header("x-api-key", &self.key)
             .header("anthropic-version", "2023-06-01")
@@ -191,12 +214,11 @@ impl LLMProvider for AnthropicProvider {
             Ok(r) if r.status().is_success() => r,
             Ok(r) => {
                 let status = r.status();
+                let headers = r.headers().clone();
                 let err_text = r.text().await.unwrap_or_default();
+                let err = super::status_error("Anthropic", status, &headers, &err_text);
                 return Box::pin(stream::once(async move {
-                    Err(Box::new(ProviderError::ApiError(format!(
-                        "Anthropic API Error {status}: {err_text}"
-                    )))
-                        as Box<dyn std::error::Error + Send + Sync>)
+                    Err(Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
                 }));
             }
             Err(e) => {
@@ -252,6 +274,7 @@ impl LLMProvider for AnthropicProvider {
         if self.key.is_empty() {
             return Err(ProviderError::Config("Anthropic API key is empty".to_string()).into());
         }
+        self.sampling.check("anthropic", MAX_TEMPERATURE)?;
         Ok(())
     }
 }
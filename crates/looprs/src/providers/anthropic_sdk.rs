@@ -5,12 +5,13 @@ use crate::api::ContentBlock;
 use crate::errors::ProviderError;
 use crate::types::ModelId;
 
-use super::{InferenceRequest, InferenceResponse, LLMProvider, Usage};
+use super::{InferenceRequest, InferenceResponse, LLMProvider, SamplingParams, Usage};
 
 pub struct AnthropicSdkProvider {
     client: Anthropic,
     key: String,
     model: ModelId,
+    sampling: SamplingParams,
 }
 
 impl AnthropicSdkProvider {
@@ -24,7 +25,33 @@ impl AnthropicSdkProvider {
         let client = Anthropic::new(Some(key.clone()))
             .map_err(|e| ProviderError::Config(format!("Anthropic SDK setup error: {e}")))?;
 
-        Ok(Self { client, key, model })
+        Ok(Self {
+            client,
+            key,
+            model,
+            sampling: SamplingParams::default(),
+        })
+    }
+
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
+    }
+}
+
+/// Map an SDK error onto `ProviderError`, keeping rate limits typed so
+/// `RetryProvider` waits and retries as it does for the HTTP provider.
+fn sdk_error(err: claudius::Error) -> ProviderError {
+    match err {
+        claudius::Error::RateLimit {
+            message,
+            retry_after,
+            ..
+        } => ProviderError::RateLimited {
+            retry_after: retry_after.map(std::time::Duration::from_secs),
+            message: format!("Anthropic SDK Error: {message}"),
+        },
+        other => ProviderError::ApiError(format!("Anthropic SDK Error: {other}")),
     }
 }
 
@@ -38,7 +65,7 @@ impl LLMProvider for AnthropicSdkProvider {
             "model": req.model.as_str(),
             "max_tokens": req.max_tokens,
             "system": req.system,
-            "messages": req.messages,
+            "messages": super::convert_to_anthropic_messages(&req.messages),
             "tools": req.tools
                 .iter()
                 .map(|t| json!({
@@ -52,13 +79,12 @@ impl LLMProvider for AnthropicSdkProvider {
         if let Some(temp) = req.temperature {
             body["temperature"] = json!(temp);
         }
+        if let Some(top_p) = req.top_p {
+            body["top_p"] = json!(top_p);
+        }
 
         let params: MessageCreateParams = serde_json::from_value(body)?;
-        let response = self
-            .client
-            .send(params)
-            .await
-            .map_err(|e| ProviderError::ApiError(format!("Anthropic SDK Error: {e}")))?;
+        let response = self.client.send(params).await.map_err(sdk_error)?;
 
         let response_json: Value = serde_json::to_value(response)?;
 
@@ -145,6 +171,8 @@ impl LLMProvider for AnthropicSdkProvider {
         if self.key.is_empty() {
             return Err(ProviderError::Config("Anthropic API key is empty".to_string()).into());
         }
+        self.sampling
+            .check("anthropic", super::anthropic::MAX_TEMPERATURE)?;
         Ok(())
     }
 }
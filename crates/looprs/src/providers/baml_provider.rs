@@ -60,6 +60,7 @@ fn message_to_chat(msg: &Message) -> ChatMessage {
             ContentBlock::ToolResult { content, .. } => {
                 format!("[tool_result: {content}]")
             }
+            ContentBlock::Image { media_type, .. } => format!("[image: {media_type}]"),
         })
         .collect::<Vec<_>>()
         .join("\n");
@@ -78,6 +79,8 @@ impl InferenceProvider for BamlProvider {
     ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
         use crate::baml_client::async_client::B;
 
+        super::reject_images(req, self.name())?;
+
         let messages: Vec<ChatMessage> = req.messages.iter().map(message_to_chat).collect();
 
         let text = B
@@ -114,6 +117,10 @@ impl InferenceProvider for BamlProvider {
     fn supports_tool_use(&self) -> bool {
         false
     }
+
+    fn supports_images(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +134,22 @@ mod tests {
         assert_inference_provider_contract(&p);
     }
 
+    #[tokio::test]
+    async fn embed_is_unsupported_by_default() {
+        let p = BamlProvider::new("DefaultClient", ModelId::new("claude-3-5-haiku-20241022"));
+        let err = p.embed(&["hello".to_string()]).await.unwrap_err();
+        let err = ProviderError::from_boxed(err);
+        assert!(
+            matches!(
+                &err,
+                ProviderError::Unsupported { feature, provider }
+                    if feature == "embeddings" && provider == "baml"
+            ),
+            "got {err:?}"
+        );
+        assert!(err.is_unsupported());
+    }
+
     #[tokio::test]
     #[ignore = "live: set LOOPRS_RUN_LIVE_LLM_TESTS=1"]
     async fn live_contract() {
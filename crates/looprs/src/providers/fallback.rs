@@ -0,0 +1,255 @@
+//! Fallback provider: tries an ordered chain of providers until one answers.
+//!
+//! Reports the primary provider's name and model so the UI and config keep
+//! describing what the user picked; `last_used` says who actually answered.
+
+use std::sync::Mutex;
+
+use crate::errors::ProviderError;
+
+use super::{InferenceRequest, InferenceResponse, LLMProvider};
+use crate::types::ModelId;
+
+pub struct FallbackProvider {
+    providers: Vec<Box<dyn LLMProvider>>,
+    last_used: Mutex<Option<usize>>,
+}
+
+impl FallbackProvider {
+    /// `providers[0]` is the primary. Errors if the chain is empty.
+    pub fn new(providers: Vec<Box<dyn LLMProvider>>) -> Result<Self, ProviderError> {
+        if providers.is_empty() {
+            return Err(ProviderError::Config(
+                "Fallback chain needs at least one provider".to_string(),
+            ));
+        }
+        Ok(Self {
+            providers,
+            last_used: Mutex::new(None),
+        })
+    }
+
+    /// `provider/model` of the provider that answered the most recent request.
+    pub fn last_used(&self) -> Option<String> {
+        let idx = (*self.last_used.lock().unwrap())?;
+        let provider = &self.providers[idx];
+        Some(format!("{}/{}", provider.name(), provider.model().as_str()))
+    }
+
+    fn primary(&self) -> &dyn LLMProvider {
+        self.providers[0].as_ref()
+    }
+}
+
+/// Only failures another provider might not share move down the chain.
+/// Errors that are not a `ProviderError` come from transports we cannot
+/// classify, so they are treated as transient.
+fn is_transient(err: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    err.downcast_ref::<ProviderError>()
+        .is_none_or(ProviderError::is_transient)
+}
+
+#[async_trait::async_trait]
+impl LLMProvider for FallbackProvider {
+    async fn infer(
+        &self,
+        req: &InferenceRequest,
+    ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let mut errors = Vec::new();
+        let mut last_err = None;
+        for (idx, provider) in self.providers.iter().enumerate() {
+            // Each provider answers with its own model, not the primary's.
+            let mut attempt = req.clone();
+            attempt.model = provider.model().clone();
+            match provider.infer(&attempt).await {
+                Ok(response) => {
+                    if idx > 0 {
+                        crate::ui::warn(format!(
+                            "Warning: fell back to {}/{} after: {}",
+                            provider.name(),
+                            provider.model().as_str(),
+                            errors.join("; ")
+                        ));
+                    }
+                    *self.last_used.lock().unwrap() = Some(idx);
+                    return Ok(response);
+                }
+                Err(e) if !is_transient(e.as_ref()) => return Err(e),
+                Err(e) => {
+                    errors.push(format!("{}: {e}", provider.name()));
+                    last_err = Some(e);
+                }
+            }
+        }
+        if errors.len() > 1 {
+            crate::ui::warn(format!(
+                "Warning: every provider in the fallback chain failed: {}",
+                errors.join("; ")
+            ));
+        }
+        Err(last_err.expect("fallback chain is never empty"))
+    }
+
+    fn name(&self) -> &str {
+        self.primary().name()
+    }
+
+    fn model(&self) -> &ModelId {
+        self.primary().model()
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.primary().validate_config()
+    }
+
+    fn supports_tool_use(&self) -> bool {
+        self.providers.iter().all(|p| p.supports_tool_use())
+    }
+
+    fn supports_images(&self) -> bool {
+        self.providers.iter().all(|p| p.supports_images())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ContentBlock;
+    use crate::providers::Usage;
+
+    #[derive(Clone, Copy)]
+    enum Outcome {
+        Answer,
+        Overloaded,
+        RateLimited,
+        BadConfig,
+    }
+
+    struct FixedProvider {
+        name: &'static str,
+        model: ModelId,
+        outcome: Outcome,
+    }
+
+    impl FixedProvider {
+        fn boxed(name: &'static str, outcome: Outcome) -> Box<dyn LLMProvider> {
+            Box::new(Self {
+                name,
+                model: ModelId::new(format!("{name}-model")),
+                outcome,
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LLMProvider for FixedProvider {
+        async fn infer(
+            &self,
+            req: &InferenceRequest,
+        ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
+            let err = match self.outcome {
+                Outcome::Answer => None,
+                Outcome::Overloaded => Some(ProviderError::ServerError(format!(
+                    "{} API Error 503: overloaded",
+                    self.name
+                ))),
+                Outcome::RateLimited => Some(ProviderError::RateLimited {
+                    retry_after: None,
+                    message: format!("{} quota exhausted", self.name),
+                }),
+                Outcome::BadConfig => Some(ProviderError::Config("bad base_url".to_string())),
+            };
+            if let Some(err) = err {
+                return Err(err.into());
+            }
+            Ok(InferenceResponse {
+                content: vec![ContentBlock::Text {
+                    text: format!("{} answered with {}", self.name, req.model.as_str()),
+                }],
+                stop_reason: "end_turn".to_string(),
+                usage: Usage {
+                    input_tokens: 1,
+                    output_tokens: 1,
+                },
+            })
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn model(&self) -> &ModelId {
+            &self.model
+        }
+
+        fn validate_config(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Ok(())
+        }
+    }
+
+    fn request() -> InferenceRequest {
+        InferenceRequest {
+            model: ModelId::new("primary-model"),
+            messages: Vec::new(),
+            tools: Vec::new(),
+            max_tokens: 16,
+            temperature: None,
+            top_p: None,
+            system: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_primary_errors() {
+        let provider = FallbackProvider::new(vec![
+            FixedProvider::boxed("primary", Outcome::Overloaded),
+            FixedProvider::boxed("backup", Outcome::Answer),
+        ])
+        .unwrap();
+
+        let response = provider.infer(&request()).await.unwrap();
+
+        assert_eq!(
+            response.content,
+            vec![ContentBlock::Text {
+                text: "backup answered with backup-model".to_string()
+            }]
+        );
+        assert_eq!(provider.last_used().as_deref(), Some("backup/backup-model"));
+        assert_eq!(provider.name(), "primary");
+    }
+
+    #[tokio::test]
+    async fn returns_the_last_error_intact_when_every_provider_fails() {
+        let provider = FallbackProvider::new(vec![
+            FixedProvider::boxed("primary", Outcome::Overloaded),
+            FixedProvider::boxed("backup", Outcome::RateLimited),
+        ])
+        .unwrap();
+
+        let err = provider.infer(&request()).await.unwrap_err();
+        let err = err.downcast::<ProviderError>().unwrap();
+        assert!(err.is_rate_limited(), "{err}");
+        assert!(err.to_string().contains("backup quota exhausted"), "{err}");
+        assert_eq!(provider.last_used(), None);
+    }
+
+    #[tokio::test]
+    async fn non_transient_errors_do_not_fall_back() {
+        let provider = FallbackProvider::new(vec![
+            FixedProvider::boxed("primary", Outcome::BadConfig),
+            FixedProvider::boxed("backup", Outcome::Answer),
+        ])
+        .unwrap();
+
+        let err = provider.infer(&request()).await.unwrap_err();
+        let err = err.downcast::<ProviderError>().unwrap();
+        assert!(matches!(*err, ProviderError::Config(_)), "{err}");
+        assert_eq!(provider.last_used(), None);
+    }
+
+    #[test]
+    fn empty_chain_is_rejected() {
+        assert!(FallbackProvider::new(Vec::new()).is_err());
+    }
+}
@@ -1,30 +1,54 @@
 use serde_json::{Value, json};
+use std::collections::HashMap;
 
 use crate::api::ContentBlock;
 use crate::errors::ProviderError;
 
-use super::{InferenceRequest, InferenceResponse, LLMProvider, ProviderHttpClient, Usage};
+use super::{
+    HttpClient, InferenceRequest, InferenceResponse, LLMProvider, ProviderHttpClient,
+    SamplingParams, Usage,
+};
 use crate::types::ModelId;
 
-const GEMINI_BASE_URL: &str =
-    "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions";
+const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+
+/// Model used when neither `MODEL` nor provider.json names one.
+pub(crate) const DEFAULT_MODEL: &str = "gemini-2.0-flash";
+
+/// Gemini accepts temperatures in 0–2.
+const MAX_TEMPERATURE: f32 = 2.0;
 
 pub struct GeminiProvider {
     http: ProviderHttpClient,
     key: String,
     model: ModelId,
+    sampling: SamplingParams,
 }
 
 impl GeminiProvider {
     pub fn new(key: String) -> Result<Self, ProviderError> {
         let model = std::env::var("MODEL").ok().map(ModelId::new);
-        Self::new_with_model(key, model)
+        Self::new_with_model(&HttpClient::new()?, key, model)
     }
 
-    pub fn new_with_model(key: String, model: Option<ModelId>) -> Result<Self, ProviderError> {
-        let http = ProviderHttpClient::default()?;
-        let model = model.unwrap_or_else(|| ModelId::new("gemini-2.0-flash"));
-        Ok(Self { http, key, model })
+    pub fn new_with_model(
+        http: &HttpClient,
+        key: String,
+        model: Option<ModelId>,
+    ) -> Result<Self, ProviderError> {
+        let http = ProviderHttpClient::default(http);
+        let model = model.unwrap_or_else(|| ModelId::new(DEFAULT_MODEL));
+        Ok(Self {
+            http,
+            key,
+            model,
+            sampling: SamplingParams::default(),
+        })
+    }
+
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
     }
 }
 
@@ -34,51 +58,13 @@ impl LLMProvider for GeminiProvider {
         &self,
         req: &InferenceRequest,
     ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let tools = req
-            .tools
-            .iter()
-            .map(|t| {
-                json!({
-                    "type": "function",
-                    "function": {
-                        "name": t.name,
-                        "description": t.description,
-                        "parameters": t.input_schema,
-                    }
-                })
-            })
-            .collect::<Vec<_>>();
-
-        let mut messages: Vec<Value> = vec![json!({
-            "role": "system",
-            "content": req.system
-        })];
-        messages.extend(
-            req.messages
-                .iter()
-                .flat_map(super::convert_to_openai_messages),
-        );
-
-        let mut body = json!({
-            "model": req.model.as_str(),
-            "messages": messages,
-            "max_tokens": req.max_tokens,
-        });
-
-        if !tools.is_empty() {
-            body["tools"] = json!(tools);
-            body["tool_choice"] = json!("auto");
-        }
-
-        if let Some(temp) = req.temperature {
-            body["temperature"] = json!(temp);
-        }
+        let body = request_body(req);
+        let url = format!("{GEMINI_BASE_URL}/{}:generateContent", req.model.as_str());
 
         let res = self
             .http
-            .client()
-            .post(GEMINI_BASE_URL)
-            .bearer_auth(&self.key)
+            .post(&url)
+            .header("x-goog-api-key", &self.key)
             .header("Content-Type", "application/json")
             .json(&body)
             .send()
@@ -86,80 +72,13 @@ impl LLMProvider for GeminiProvider {
 
         if !res.status().is_success() {
             let status = res.status();
+            let headers = res.headers().clone();
             let err_text = res.text().await?;
-            return Err(
-                ProviderError::ApiError(format!("Gemini API Error {status}: {err_text}")).into(),
-            );
+            return Err(super::status_error("Gemini", status, &headers, &err_text).into());
         }
 
         let response_json: Value = res.json().await?;
-
-        let choice = response_json
-            .get("choices")
-            .and_then(|arr| arr.as_array())
-            .and_then(|arr| arr.first())
-            .ok_or_else(|| ProviderError::InvalidResponse("No choices in response".to_string()))?;
-
-        let message = choice
-            .get("message")
-            .ok_or_else(|| ProviderError::InvalidResponse("No message in choice".to_string()))?;
-
-        let mut blocks = Vec::new();
-
-        if let Some(text) = message.get("content").and_then(|v| v.as_str())
-            && !text.is_empty()
-        {
-            blocks.push(ContentBlock::Text {
-                text: text.to_string(),
-            });
-        }
-
-        if let Some(tool_calls) = message.get("tool_calls").and_then(|v| v.as_array()) {
-            for tool_call in tool_calls {
-                if let (Some(id), Some(function)) = (tool_call.get("id"), tool_call.get("function"))
-                    && let (Some(name), Some(args)) =
-                        (function.get("name"), function.get("arguments"))
-                {
-                    let args_str = if let Some(s) = args.as_str() {
-                        serde_json::from_str(s).unwrap_or(args.clone())
-                    } else {
-                        args.clone()
-                    };
-                    blocks.push(ContentBlock::ToolUse {
-                        id: crate::types::ToolId::new(id.as_str().unwrap_or("")),
-                        name: crate::types::ToolName::new(name.as_str().unwrap_or("")),
-                        input: args_str,
-                    });
-                }
-            }
-        }
-
-        let stop_reason = choice
-            .get("finish_reason")
-            .and_then(|v| v.as_str())
-            .unwrap_or("stop")
-            .to_string();
-
-        let usage = if let Some(u) = response_json.get("usage") {
-            Usage {
-                input_tokens: u.get("prompt_tokens").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
-                output_tokens: u
-                    .get("completion_tokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32,
-            }
-        } else {
-            Usage {
-                input_tokens: 0,
-                output_tokens: 0,
-            }
-        };
-
-        Ok(InferenceResponse {
-            content: blocks,
-            stop_reason,
-            usage,
-        })
+        Ok(parse_response(&response_json)?)
     }
 
     fn name(&self) -> &str {
@@ -174,10 +93,179 @@ impl LLMProvider for GeminiProvider {
         if self.key.is_empty() {
             return Err(ProviderError::Config("Gemini API key is empty".to_string()).into());
         }
+        self.sampling.check("gemini", MAX_TEMPERATURE)?;
         Ok(())
     }
 }
 
+/// The native `generateContent` body for `req`: `systemInstruction`,
+/// `contents` with `user`/`model` turns, `functionDeclarations`, and
+/// `generationConfig`.
+fn request_body(req: &InferenceRequest) -> Value {
+    // functionResponse parts are matched to calls by name, not id.
+    let tool_names: HashMap<&str, &str> = req
+        .messages
+        .iter()
+        .flat_map(|m| m.content.iter())
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse { id, name, .. } => Some((id.as_str(), name.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    let contents: Vec<Value> = req
+        .messages
+        .iter()
+        .map(|msg| {
+            let role = if msg.role == "assistant" {
+                "model"
+            } else {
+                "user"
+            };
+            let parts: Vec<Value> = msg
+                .content
+                .iter()
+                .map(|block| content_part(block, &tool_names))
+                .collect();
+            json!({ "role": role, "parts": parts })
+        })
+        .collect();
+
+    let mut generation_config = json!({ "maxOutputTokens": req.max_tokens });
+    if let Some(temp) = req.temperature {
+        generation_config["temperature"] = json!(temp);
+    }
+    if let Some(top_p) = req.top_p {
+        generation_config["topP"] = json!(top_p);
+    }
+
+    let mut body = json!({
+        "contents": contents,
+        "generationConfig": generation_config,
+    });
+    if !req.system.is_empty() {
+        body["systemInstruction"] = json!({ "parts": [{ "text": req.system }] });
+    }
+    if !req.tools.is_empty() {
+        let declarations: Vec<Value> = req
+            .tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "parameters": schema_for_gemini(&t.input_schema),
+                })
+            })
+            .collect();
+        body["tools"] = json!([{ "functionDeclarations": declarations }]);
+    }
+    body
+}
+
+fn content_part(block: &ContentBlock, tool_names: &HashMap<&str, &str>) -> Value {
+    match block {
+        ContentBlock::Text { text } => json!({ "text": text }),
+        ContentBlock::Image { media_type, data } => {
+            json!({ "inlineData": { "mimeType": media_type, "data": data } })
+        }
+        ContentBlock::ToolUse { name, input, .. } => {
+            json!({ "functionCall": { "name": name.as_str(), "args": input } })
+        }
+        ContentBlock::ToolResult {
+            tool_use_id,
+            content,
+        } => json!({
+            "functionResponse": {
+                "name": tool_names.get(tool_use_id.as_str()).copied().unwrap_or_default(),
+                "response": { "content": content },
+            }
+        }),
+    }
+}
+
+/// `schema` without the JSON Schema keywords Gemini's OpenAPI subset rejects.
+fn schema_for_gemini(schema: &Value) -> Value {
+    match schema {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "$schema" | "additionalProperties"))
+                .map(|(key, value)| (key.clone(), schema_for_gemini(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(schema_for_gemini).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Text and `functionCall` parts of the first candidate, with `usageMetadata`.
+/// Calls without an `id` get `call_<n>` so tool results can be paired.
+fn parse_response(response_json: &Value) -> Result<InferenceResponse, ProviderError> {
+    let candidate = response_json
+        .get("candidates")
+        .and_then(|arr| arr.as_array())
+        .and_then(|arr| arr.first())
+        .ok_or_else(|| ProviderError::InvalidResponse("No candidates in response".to_string()))?;
+
+    let parts = candidate
+        .pointer("/content/parts")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut blocks = Vec::new();
+    for (idx, part) in parts.iter().enumerate() {
+        if let Some(text) = part.get("text").and_then(|v| v.as_str())
+            && !text.is_empty()
+        {
+            blocks.push(ContentBlock::Text {
+                text: text.to_string(),
+            });
+        }
+        if let Some(call) = part.get("functionCall")
+            && let Some(name) = call.get("name").and_then(|v| v.as_str())
+        {
+            let id = call
+                .get("id")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("call_{idx}"));
+            blocks.push(ContentBlock::ToolUse {
+                id: crate::types::ToolId::new(id),
+                name: crate::types::ToolName::new(name),
+                input: call.get("args").cloned().unwrap_or_else(|| json!({})),
+            });
+        }
+    }
+
+    let has_tool_use = blocks
+        .iter()
+        .any(|b| matches!(b, ContentBlock::ToolUse { .. }));
+    let stop_reason = match candidate.get("finishReason").and_then(|v| v.as_str()) {
+        _ if has_tool_use => "tool_use".to_string(),
+        Some("STOP") | None => "end_turn".to_string(),
+        Some("MAX_TOKENS") => "max_tokens".to_string(),
+        Some(other) => other.to_ascii_lowercase(),
+    };
+
+    let count = |key: &str| {
+        response_json
+            .pointer(&format!("/usageMetadata/{key}"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32
+    };
+    let usage = Usage {
+        input_tokens: count("promptTokenCount"),
+        output_tokens: count("candidatesTokenCount"),
+    };
+
+    Ok(InferenceResponse {
+        content: blocks,
+        stop_reason,
+        usage,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,6 +278,112 @@ mod tests {
         assert_inference_provider_contract(&p);
     }
 
+    #[test]
+    fn request_body_uses_native_contents_tools_and_config() {
+        let req = InferenceRequest {
+            model: ModelId::new("gemini-2.0-flash"),
+            messages: vec![
+                crate::api::Message::user("list files"),
+                crate::api::Message::assistant(vec![ContentBlock::ToolUse {
+                    id: crate::types::ToolId::new("call_0"),
+                    name: crate::types::ToolName::new("glob"),
+                    input: json!({"pattern": "*.rs"}),
+                }]),
+                crate::api::Message::tool_results(vec![ContentBlock::ToolResult {
+                    tool_use_id: crate::types::ToolId::new("call_0"),
+                    content: "main.rs".to_string(),
+                }]),
+            ],
+            tools: vec![crate::api::ToolDefinition {
+                name: "glob".to_string(),
+                description: "Find files".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "additionalProperties": false,
+                    "properties": {"pattern": {"type": "string"}}
+                }),
+            }],
+            max_tokens: 256,
+            temperature: Some(0.2),
+            top_p: None,
+            system: "be brief".to_string(),
+        };
+
+        let body = request_body(&req);
+
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "be brief");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 256);
+        assert!(body["generationConfig"].get("topP").is_none());
+        assert_eq!(
+            body["contents"][0],
+            json!({"role": "user", "parts": [{"text": "list files"}]})
+        );
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(
+            body["contents"][1]["parts"][0]["functionCall"],
+            json!({"name": "glob", "args": {"pattern": "*.rs"}})
+        );
+        assert_eq!(body["contents"][2]["role"], "user");
+        assert_eq!(
+            body["contents"][2]["parts"][0]["functionResponse"],
+            json!({"name": "glob", "response": {"content": "main.rs"}})
+        );
+        let declaration = &body["tools"][0]["functionDeclarations"][0];
+        assert_eq!(declaration["name"], "glob");
+        assert!(
+            declaration["parameters"]
+                .get("additionalProperties")
+                .is_none()
+        );
+        assert_eq!(
+            declaration["parameters"]["properties"]["pattern"]["type"],
+            "string"
+        );
+    }
+
+    #[test]
+    fn parse_response_reads_text_function_calls_and_usage() {
+        let recorded = json!({
+            "candidates": [{
+                "finishReason": "STOP",
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Looking."},
+                        {"functionCall": {"name": "glob", "args": {"pattern": "*.rs"}}}
+                    ]
+                }
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 42,
+                "candidatesTokenCount": 7,
+                "totalTokenCount": 49
+            }
+        });
+
+        let resp = parse_response(&recorded).unwrap();
+
+        assert_eq!(resp.stop_reason, "tool_use");
+        assert_eq!(resp.usage.input_tokens, 42);
+        assert_eq!(resp.usage.output_tokens, 7);
+        assert!(matches!(&resp.content[0], ContentBlock::Text { text } if text == "Looking."));
+        let ContentBlock::ToolUse { id, name, input } = &resp.content[1] else {
+            panic!("expected tool use, got {:?}", resp.content[1]);
+        };
+        assert_eq!(id.as_str(), "call_1");
+        assert_eq!(name.as_str(), "glob");
+        assert_eq!(input["pattern"], "*.rs");
+
+        let text_only = json!({
+            "candidates": [{"finishReason": "MAX_TOKENS", "content": {"parts": [{"text": "cut"}]}}]
+        });
+        let resp = parse_response(&text_only).unwrap();
+        assert_eq!(resp.stop_reason, "max_tokens");
+        assert_eq!(resp.usage.input_tokens, 0);
+
+        assert!(parse_response(&json!({"candidates": []})).is_err());
+    }
+
     #[tokio::test]
     #[ignore = "live: set LOOPRS_RUN_LIVE_LLM_TESTS=1"]
     async fn live_contract() {
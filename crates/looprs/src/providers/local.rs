@@ -1,19 +1,53 @@
 use serde_json::{Value, json};
-use std::time::Duration;
 
 use crate::api::ContentBlock;
 use crate::errors::ProviderError;
 
-use super::{InferenceRequest, InferenceResponse, LLMProvider, Usage};
+use super::{
+    HttpClient, InferenceRequest, InferenceResponse, LLMProvider, ProviderHttpClient,
+    SamplingParams, Usage,
+};
 use crate::types::ModelId;
 
 const OLLAMA_TIMEOUT_SECS: u64 = 120;
 const HEALTH_CHECK_TIMEOUT_SECS: u64 = 5;
+const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+/// Ollama passes temperature to llama.cpp, which is happy with 0–2.
+const MAX_TEMPERATURE: f32 = 2.0;
 
 pub struct LocalProvider {
-    client: reqwest::Client,
+    http: ProviderHttpClient,
     host: String,
     model: ModelId,
+    /// Models reported by `/api/tags`, when the listing succeeded.
+    installed_models: Option<Vec<String>>,
+    sampling: SamplingParams,
+}
+
+fn ollama_host() -> String {
+    std::env::var("OLLAMA_HOST").unwrap_or_else(|_| DEFAULT_OLLAMA_HOST.to_string())
+}
+
+/// Extract model names from an Ollama `/api/tags` response body.
+fn parse_tags(body: &Value) -> Result<Vec<String>, ProviderError> {
+    let models = body
+        .get("models")
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            ProviderError::InvalidResponse("No models array in /api/tags".to_string())
+        })?;
+    Ok(models
+        .iter()
+        .filter_map(|m| m.get("name").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Ollama treats a bare name as `<name>:latest`.
+fn is_installed(model: &str, installed: &[String]) -> bool {
+    installed
+        .iter()
+        .any(|name| name == model || name.strip_suffix(":latest") == Some(model))
 }
 
 impl LocalProvider {
@@ -22,16 +56,16 @@ impl LocalProvider {
             .or_else(|_| std::env::var("OLLAMA_MODEL"))
             .ok()
             .map(ModelId::new);
-        Self::new_with_model(model)
+        Self::new_with_model(&HttpClient::new()?, model)
     }
 
-    pub fn new_with_model(model: Option<ModelId>) -> Result<Self, ProviderError> {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(OLLAMA_TIMEOUT_SECS))
-            .build()?;
+    pub fn new_with_model(
+        http: &HttpClient,
+        model: Option<ModelId>,
+    ) -> Result<Self, ProviderError> {
+        let http = ProviderHttpClient::new(http, OLLAMA_TIMEOUT_SECS);
 
-        let host =
-            std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string());
+        let host = ollama_host();
         let model = match model {
             Some(model) => model,
             None => {
@@ -49,24 +83,50 @@ impl LocalProvider {
         };
 
         Ok(Self {
-            client,
+            http,
             host,
             model,
+            installed_models: None,
+            sampling: SamplingParams::default(),
         })
     }
 
-    pub async fn is_available() -> bool {
-        let host =
-            std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string());
-        let client = match reqwest::Client::builder()
-            .timeout(Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS))
-            .build()
-        {
-            Ok(c) => c,
-            Err(_) => return false,
-        };
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Record the installed models so `validate_config` can flag a configured
+    /// model that Ollama doesn't have. Listing failures are ignored here.
+    pub async fn with_installed_models(mut self, http: &HttpClient) -> Self {
+        self.installed_models = Self::list_models(http).await.ok();
+        self
+    }
+
+    /// Names of the models installed in the Ollama instance at `OLLAMA_HOST`.
+    pub async fn list_models(http: &HttpClient) -> Result<Vec<String>, ProviderError> {
+        let res = ProviderHttpClient::new(http, HEALTH_CHECK_TIMEOUT_SECS)
+            .get(format!("{}/api/tags", ollama_host()))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let err_text = res.text().await?;
+            return Err(ProviderError::ApiError(format!(
+                "Ollama API Error {status}: {err_text}"
+            )));
+        }
+        let body: Value = res.json().await?;
+        parse_tags(&body)
+    }
 
-        match client.get(format!("{host}/api/tags")).send().await {
+    pub async fn is_available(http: &HttpClient) -> bool {
+        let host = ollama_host();
+        match ProviderHttpClient::new(http, HEALTH_CHECK_TIMEOUT_SECS)
+            .get(format!("{host}/api/tags"))
+            .send()
+            .await
+        {
             Ok(res) => res.status().is_success(),
             Err(_) => false,
         }
@@ -91,6 +151,8 @@ impl LocalProvider {
                         "\n[TOOL_RESULT id={tool_use_id}]\n{result_content}"
                     ));
                 }
+                // Rejected up front by `infer`.
+                ContentBlock::Image { .. } => {}
             }
         }
 
@@ -107,6 +169,8 @@ impl LLMProvider for LocalProvider {
         &self,
         req: &InferenceRequest,
     ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
+        super::reject_images(req, self.name())?;
+
         let mut messages = vec![json!({
             "role": "system",
             "content": req.system
@@ -120,11 +184,14 @@ impl LLMProvider for LocalProvider {
             "stream": false,
         });
         if let Some(temp) = req.temperature {
-            body["options"] = json!({ "temperature": temp });
+            body["options"]["temperature"] = json!(temp);
+        }
+        if let Some(top_p) = req.top_p {
+            body["options"]["top_p"] = json!(top_p);
         }
 
         let res = self
-            .client
+            .http
             .post(format!("{}/api/chat", self.host))
             .header("Content-Type", "application/json")
             .json(&body)
@@ -217,12 +284,25 @@ impl LLMProvider for LocalProvider {
         if self.host.is_empty() {
             return Err(ProviderError::Config("Ollama host is empty".to_string()).into());
         }
+        self.sampling.check("ollama", MAX_TEMPERATURE)?;
+        if let Some(installed) = &self.installed_models
+            && !is_installed(self.model.as_str(), installed)
+        {
+            crate::ui::warn(format!(
+                "Warning: model {} is not installed in Ollama at {}; run `looprs models` to list installed models",
+                self.model, self.host
+            ));
+        }
         Ok(())
     }
 
     fn supports_tool_use(&self) -> bool {
         false
     }
+
+    fn supports_images(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
@@ -232,19 +312,76 @@ mod tests {
 
     #[test]
     fn local_provider_satisfies_inference_provider_contract() {
-        let p = LocalProvider::new_with_model(Some(ModelId::new("llama3")))
-            .expect("LocalProvider::new_with_model must succeed in test");
+        let p = LocalProvider::new_with_model(
+            &HttpClient::new().unwrap(),
+            Some(ModelId::new("llama3")),
+        )
+        .expect("LocalProvider::new_with_model must succeed in test");
         assert_inference_provider_contract(&p);
     }
 
+    #[tokio::test]
+    async fn image_blocks_are_rejected_before_sending() {
+        let p = LocalProvider::new_with_model(
+            &HttpClient::new().unwrap(),
+            Some(ModelId::new("llama3")),
+        )
+        .unwrap();
+        let req = InferenceRequest {
+            model: ModelId::new("llama3"),
+            messages: vec![crate::api::Message::user_with_image(
+                "what is this?",
+                "image/png",
+                "iVBORw0KGgo=",
+            )],
+            tools: vec![],
+            max_tokens: 64,
+            temperature: None,
+            top_p: None,
+            system: String::new(),
+        };
+
+        let err = ProviderError::from_boxed(p.infer(&req).await.unwrap_err());
+        assert!(err.is_unsupported(), "{err}");
+        assert!(err.to_string().contains("Image input"), "{err}");
+    }
+
+    #[test]
+    fn parse_tags_lists_installed_model_names() {
+        let body: Value = serde_json::from_str(
+            r#"{"models":[
+                {"name":"llama3:latest","model":"llama3:latest","size":4661224676,
+                 "digest":"365c0bd3c000","details":{"family":"llama","parameter_size":"8.0B"}},
+                {"name":"qwen2.5-coder:7b","model":"qwen2.5-coder:7b","size":4683087332,
+                 "digest":"2b0496514337","details":{"family":"qwen2","parameter_size":"7.6B"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let models = parse_tags(&body).unwrap();
+        assert_eq!(models, ["llama3:latest", "qwen2.5-coder:7b"]);
+        assert!(is_installed("llama3", &models));
+        assert!(is_installed("qwen2.5-coder:7b", &models));
+        assert!(!is_installed("qwen2.5-coder", &models));
+    }
+
+    #[test]
+    fn parse_tags_rejects_body_without_models() {
+        let err = parse_tags(&json!({"error": "nope"})).unwrap_err();
+        assert!(matches!(err, ProviderError::InvalidResponse(_)));
+    }
+
     #[tokio::test]
     #[ignore = "live: set LOOPRS_RUN_LIVE_LLM_TESTS=1"]
     async fn live_contract() {
         if std::env::var("LOOPRS_RUN_LIVE_LLM_TESTS").is_err() {
             return;
         }
-        let p = LocalProvider::new_with_model(Some(ModelId::new("llama3")))
-            .expect("LocalProvider::new_with_model must succeed");
+        let p = LocalProvider::new_with_model(
+            &HttpClient::new().unwrap(),
+            Some(ModelId::new("llama3")),
+        )
+        .expect("LocalProvider::new_with_model must succeed");
         looprs_core::ports::test_contracts::assert_inference_provider_live_contract(&p).await;
     }
 }
@@ -1,13 +1,17 @@
 use std::env;
+use std::sync::Arc;
 use std::time::Duration;
 
 pub mod anthropic;
 pub mod anthropic_sdk;
 pub mod baml_provider;
+pub mod fallback;
 pub mod gemini;
 pub mod local;
 pub mod openai;
 pub mod openai_sdk;
+pub mod pricing;
+pub mod replay;
 
 use crate::api::ContentBlock;
 use crate::errors::ProviderError;
@@ -21,24 +25,44 @@ pub use looprs_core::ports::inference_provider::{InferenceRequest, InferenceResp
 
 const DEFAULT_TIMEOUT_SECS: u64 = 120;
 
+/// The connection pool providers send requests through. Cheap to clone: the
+/// caller builds one and hands it to every provider it creates, so rebuilding
+/// a provider (e.g. `:set model`) keeps the pool.
+#[derive(Debug, Clone)]
+pub struct HttpClient(Arc<Client>);
+
+impl HttpClient {
+    pub fn new() -> Result<Self, ProviderError> {
+        Ok(Self(Arc::new(Client::builder().build()?)))
+    }
+}
+
+/// A provider's view of an `HttpClient`, with its own request timeout.
 pub(crate) struct ProviderHttpClient {
-    client: Client,
+    client: Arc<Client>,
+    timeout: Duration,
 }
 
 impl ProviderHttpClient {
-    pub fn new(timeout_secs: u64) -> Result<Self, ProviderError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(timeout_secs))
-            .build()?;
-        Ok(Self { client })
+    pub fn new(http: &HttpClient, timeout_secs: u64) -> Self {
+        Self {
+            client: http.0.clone(),
+            timeout: Duration::from_secs(timeout_secs),
+        }
+    }
+
+    pub fn default(http: &HttpClient) -> Self {
+        Self::new(http, DEFAULT_TIMEOUT_SECS)
     }
 
-    pub fn default() -> Result<Self, ProviderError> {
-        Self::new(DEFAULT_TIMEOUT_SECS)
+    /// A POST to `url` that times out after this handle's timeout.
+    pub fn post(&self, url: impl reqwest::IntoUrl) -> reqwest::RequestBuilder {
+        self.client.post(url).timeout(self.timeout)
     }
 
-    pub fn client(&self) -> &Client {
-        &self.client
+    /// A GET to `url` that times out after this handle's timeout.
+    pub fn get(&self, url: impl reqwest::IntoUrl) -> reqwest::RequestBuilder {
+        self.client.get(url).timeout(self.timeout)
     }
 }
 
@@ -46,6 +70,47 @@ impl ProviderHttpClient {
 pub struct ProviderOverrides {
     /// Model override (e.g. from CLI -m/--model)
     pub model: Option<ModelId>,
+    /// Sampling parameters requests will carry, checked by `validate_config`.
+    pub sampling: SamplingParams,
+}
+
+/// Sampling parameters a provider is configured to send.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SamplingParams {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+}
+
+impl SamplingParams {
+    /// Reject `temperature` outside `0..=max_temperature` and `top_p` outside `0..=1`.
+    pub(crate) fn check(&self, provider: &str, max_temperature: f32) -> Result<(), ProviderError> {
+        if let Some(temp) = self.temperature
+            && !(0.0..=max_temperature).contains(&temp)
+        {
+            return Err(ProviderError::Config(format!(
+                "temperature {temp} is out of range for {provider} (must be between 0 and {max_temperature})"
+            )));
+        }
+        if let Some(top_p) = self.top_p
+            && !(0.0..=1.0).contains(&top_p)
+        {
+            return Err(ProviderError::Config(format!(
+                "top_p {top_p} is out of range for {provider} (must be between 0 and 1)"
+            )));
+        }
+        Ok(())
+    }
+
+    /// [`check`](Self::check) against the temperature limit of the provider
+    /// named `provider`, for settings changed before it is rebuilt.
+    pub fn check_for(&self, provider: &str) -> Result<(), ProviderError> {
+        let max_temperature = match provider {
+            "anthropic" | "anthropic-sdk" | "claude-sdk" => anthropic::MAX_TEMPERATURE,
+            // OpenAI, Gemini, and Ollama all accept up to 2.
+            _ => openai::MAX_TEMPERATURE,
+        };
+        self.check(provider, max_temperature)
+    }
 }
 
 /// Check if an OpenAI model is a reasoning model (o1, o3 series).
@@ -64,6 +129,7 @@ pub(crate) fn supports_temperature(model: &str) -> bool {
 pub(crate) fn convert_to_openai_messages(msg: &crate::api::Message) -> Vec<Value> {
     let mut messages = Vec::new();
     let mut text_parts = Vec::new();
+    let mut image_parts = Vec::new();
     let mut tool_calls = Vec::new();
 
     for block in &msg.content {
@@ -71,6 +137,12 @@ pub(crate) fn convert_to_openai_messages(msg: &crate::api::Message) -> Vec<Value
             ContentBlock::Text { text } => {
                 text_parts.push(text.clone());
             }
+            ContentBlock::Image { media_type, data } => {
+                image_parts.push(json!({
+                    "type": "image_url",
+                    "image_url": { "url": format!("data:{media_type};base64,{data}") }
+                }));
+            }
             ContentBlock::ToolUse { id, name, input } => {
                 tool_calls.push(json!({
                     "id": id.as_str(),
@@ -94,12 +166,20 @@ pub(crate) fn convert_to_openai_messages(msg: &crate::api::Message) -> Vec<Value
         }
     }
 
-    if !text_parts.is_empty() || !tool_calls.is_empty() {
+    if !text_parts.is_empty() || !image_parts.is_empty() || !tool_calls.is_empty() {
         let mut main_msg = json!({
             "role": msg.role,
         });
 
-        if !text_parts.is_empty() {
+        if !image_parts.is_empty() {
+            // Images need the array form of `content`; text rides along as a part.
+            let mut parts = Vec::new();
+            if !text_parts.is_empty() {
+                parts.push(json!({ "type": "text", "text": text_parts.join("\n") }));
+            }
+            parts.extend(image_parts);
+            main_msg["content"] = json!(parts);
+        } else if !text_parts.is_empty() {
             main_msg["content"] = json!(text_parts.join("\n"));
         } else if tool_calls.is_empty() {
             main_msg["content"] = json!("");
@@ -109,12 +189,97 @@ pub(crate) fn convert_to_openai_messages(msg: &crate::api::Message) -> Vec<Value
             main_msg["tool_calls"] = json!(tool_calls);
         }
 
-        messages.insert(0, main_msg);
+        // An assistant turn precedes the results of its tool calls; anything
+        // sent with tool results (images) follows them.
+        if tool_calls.is_empty() && !messages.is_empty() {
+            messages.push(main_msg);
+        } else {
+            messages.insert(0, main_msg);
+        }
     }
 
     messages
 }
 
+/// Convert looprs messages to Anthropic-format JSON. Text and tool blocks
+/// already serialize in Anthropic's shape; images are wrapped in a base64
+/// `source` object.
+///
+/// Shared by both `anthropic` and `anthropic_sdk` providers.
+pub(crate) fn convert_to_anthropic_messages(messages: &[crate::api::Message]) -> Vec<Value> {
+    messages
+        .iter()
+        .map(|msg| {
+            let content: Vec<Value> = msg
+                .content
+                .iter()
+                .map(|block| match block {
+                    ContentBlock::Image { media_type, data } => json!({
+                        "type": "image",
+                        "source": {
+                            "type": "base64",
+                            "media_type": media_type,
+                            "data": data,
+                        }
+                    }),
+                    other => serde_json::to_value(other).unwrap_or_default(),
+                })
+                .collect();
+            json!({ "role": msg.role, "content": content })
+        })
+        .collect()
+}
+
+/// Error for providers that cannot send image blocks, raised before the
+/// request goes out so the user sees why rather than a garbled prompt.
+pub(crate) fn reject_images(req: &InferenceRequest, provider: &str) -> Result<(), ProviderError> {
+    if req.messages.iter().any(|m| m.has_image()) {
+        return Err(ProviderError::Unsupported {
+            feature: "Image input".to_string(),
+            provider: provider.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Error for a non-success HTTP response: `RateLimited` for 429, carrying the
+/// `Retry-After` delay when the provider sent one, `ServerError` for 5xx,
+/// otherwise `ApiError`.
+/// Either way the message keeps the response body.
+pub(crate) fn status_error(
+    provider: &str,
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    body: &str,
+) -> ProviderError {
+    let message = format!("{provider} API Error {status}: {body}");
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return ProviderError::RateLimited {
+            retry_after: retry_after(headers),
+            message,
+        };
+    }
+    if status.is_server_error() {
+        return ProviderError::ServerError(message);
+    }
+    ProviderError::ApiError(message)
+}
+
+/// `Retry-After` as either delay-seconds or an HTTP date.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
 /// Create a provider based on configuration priority:
 /// 1. Environment variables (highest priority)
 /// 2. .looprs/provider.json config file
@@ -122,76 +287,204 @@ pub(crate) fn convert_to_openai_messages(msg: &crate::api::Message) -> Vec<Value
 /// 4. Try local Ollama
 /// 5. Error if none found
 pub async fn create_provider_with_overrides(
+    http: &HttpClient,
     overrides: ProviderOverrides,
 ) -> Result<Box<dyn LLMProvider>, ProviderError> {
-    // Load config file if available
-    let config_file = crate::config_file::ProviderConfig::load().ok();
+    // Load config file if available; a malformed one is an error, not a silent default
+    let config_file = Some(
+        crate::config_file::ProviderConfig::load()
+            .map_err(|e| ProviderError::Config(format!("{e:#}")))?,
+    );
 
     // Step 1: Check explicit PROVIDER env var (highest priority)
     if let Ok(provider_name) = env::var("PROVIDER") {
-        return create_provider_by_name(&provider_name, &config_file, overrides).await;
+        return create_provider_by_name(http, &provider_name, &config_file, overrides).await;
     }
 
     // Step 2: Check config file provider setting
     if let Some(config) = config_file.as_ref()
         && let Some(provider_name) = &config.provider
     {
-        return create_provider_by_name(provider_name, &config_file, overrides).await;
+        return create_provider_by_name(http, provider_name, &config_file, overrides).await;
     }
 
     // Step 3: Try providers in priority order based on available API keys
-    if env::var("ANTHROPIC_API_KEY").is_ok() {
-        return create_provider_by_name("anthropic", &config_file, overrides).await;
-    }
-
-    if env::var("OPENAI_API_KEY").is_ok() {
-        return create_provider_by_name("openai", &config_file, overrides).await;
-    }
-
-    if env::var("GEMINI_API_KEY").is_ok() || env::var("GOOGLE_API_KEY").is_ok() {
-        return create_provider_by_name("gemini", &config_file, overrides).await;
+    if let Some((_, provider_name)) = detect_provider(|var| env::var(var).is_ok()) {
+        return create_provider_by_name(http, provider_name, &config_file, overrides).await;
     }
 
     // Step 4: Try local Ollama
-    if local::LocalProvider::is_available().await {
-        return create_provider_by_name("ollama", &config_file, overrides).await;
+    if local::LocalProvider::is_available(http).await {
+        return create_provider_by_name(http, "ollama", &config_file, overrides).await;
     }
 
     // Step 5: Error if none found
     Err(ProviderError::NoProviderConfigured)
 }
 
+/// API key env vars checked when neither `PROVIDER` nor provider.json names a
+/// provider, in priority order, with the provider each one selects.
+pub const API_KEY_DETECTION: &[(&str, &str)] = &[
+    ("ANTHROPIC_API_KEY", "anthropic"),
+    ("OPENAI_API_KEY", "openai"),
+    ("GEMINI_API_KEY", "gemini"),
+    ("GOOGLE_API_KEY", "gemini"),
+];
+
+/// The first `API_KEY_DETECTION` entry whose env var is set, as `(var, provider)`.
+pub fn detect_provider(has_env: impl Fn(&str) -> bool) -> Option<(&'static str, &'static str)> {
+    API_KEY_DETECTION
+        .iter()
+        .copied()
+        .find(|(var, _)| has_env(var))
+}
+
 /// Create a provider using an already-loaded config (for in-session switching).
 ///
 /// Skips disk I/O. Uses the supplied `config` directly. Env vars still take
 /// priority over the config's `provider` field so `PROVIDER=anthropic` wins.
 pub async fn create_provider_from_config(
+    http: &HttpClient,
     config: &crate::config_file::ProviderConfig,
     overrides: ProviderOverrides,
 ) -> Result<Box<dyn LLMProvider>, ProviderError> {
     let config_file = Some(config.clone());
 
     if let Some(provider_name) = &config.provider {
-        return create_provider_by_name(provider_name, &config_file, overrides).await;
+        return create_provider_by_name(http, provider_name, &config_file, overrides).await;
     }
 
-    if env::var("ANTHROPIC_API_KEY").is_ok() {
-        return create_provider_by_name("anthropic", &config_file, overrides).await;
+    if let Some((_, provider_name)) = detect_provider(|var| env::var(var).is_ok()) {
+        return create_provider_by_name(http, provider_name, &config_file, overrides).await;
     }
 
-    if env::var("OPENAI_API_KEY").is_ok() {
-        return create_provider_by_name("openai", &config_file, overrides).await;
+    if local::LocalProvider::is_available(http).await {
+        return create_provider_by_name(http, "ollama", &config_file, overrides).await;
     }
 
-    if env::var("GEMINI_API_KEY").is_ok() || env::var("GOOGLE_API_KEY").is_ok() {
-        return create_provider_by_name("gemini", &config_file, overrides).await;
-    }
+    Err(ProviderError::NoProviderConfigured)
+}
 
-    if local::LocalProvider::is_available().await {
-        return create_provider_by_name("ollama", &config_file, overrides).await;
+/// Wrap `primary` in a `FallbackProvider` built from `chain` entries of the
+/// form `provider/model` (or just `provider` for its default model). Entries
+/// that cannot be constructed are skipped with a warning; with nothing left
+/// to fall back to, `primary` is returned unchanged. Fallbacks get the same
+/// `sampling` and provider.json settings as the primary.
+pub async fn with_fallbacks(
+    http: &HttpClient,
+    primary: Box<dyn LLMProvider>,
+    chain: &[String],
+    sampling: SamplingParams,
+    config: &crate::config_file::ProviderConfig,
+) -> Box<dyn LLMProvider> {
+    let config_file = Some(config.clone());
+    let mut providers = vec![primary];
+    for entry in chain {
+        let (name, model) = match entry.split_once('/') {
+            Some((name, model)) => (name, Some(ModelId::new(model))),
+            None => (entry.as_str(), None),
+        };
+        let overrides = ProviderOverrides { model, sampling };
+        match create_provider_by_name(http, name, &config_file, overrides).await {
+            Ok(provider) => providers.push(provider),
+            Err(e) => crate::ui::warn(format!("Warning: skipping fallback {entry}: {e}")),
+        }
+    }
+    if providers.len() == 1 {
+        return providers.remove(0);
     }
+    Box::new(
+        fallback::FallbackProvider::new(providers)
+            .expect("fallback chain has at least two providers"),
+    )
+}
 
-    Err(ProviderError::NoProviderConfigured)
+/// A provider name accepted by `PROVIDER` and provider.json.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderInfo {
+    pub name: &'static str,
+    /// Other names that select the same provider.
+    pub aliases: &'static [&'static str],
+    /// An API key is set, or for Ollama, the server answered.
+    pub configured: bool,
+    /// Model used when neither `MODEL` nor provider.json names one. Ollama
+    /// has none: it needs `MODEL` or `OLLAMA_MODEL`.
+    pub default_model: Option<ModelId>,
+}
+
+/// Every provider `create_provider_with_overrides` understands, in
+/// auto-detection order. `has_env` reports whether an env var is set.
+pub fn known_providers(
+    has_env: impl Fn(&str) -> bool,
+    ollama_reachable: bool,
+) -> Vec<ProviderInfo> {
+    let anthropic = has_env("ANTHROPIC_API_KEY");
+    let openai = has_env("OPENAI_API_KEY");
+    let info = |name, aliases: &'static [&'static str], configured, default_model| ProviderInfo {
+        name,
+        aliases,
+        configured,
+        default_model,
+    };
+    vec![
+        info("anthropic", &[], anthropic, Some(ModelId::claude_opus())),
+        info("openai", &[], openai, Some(ModelId::gpt_5_mini())),
+        info(
+            "gemini",
+            &["google"],
+            has_env("GEMINI_API_KEY") || has_env("GOOGLE_API_KEY"),
+            Some(ModelId::new(gemini::DEFAULT_MODEL)),
+        ),
+        info("ollama", &["local"], ollama_reachable, None),
+        info(
+            "anthropic-sdk",
+            &["claude-sdk"],
+            anthropic,
+            Some(ModelId::claude_opus()),
+        ),
+        info("openai-sdk", &[], openai, Some(ModelId::gpt_5_mini())),
+        info(
+            "baml",
+            &[],
+            anthropic || openai,
+            baml_provider::BamlProvider::for_provider("baml", None)
+                .ok()
+                .map(|p| p.model().clone()),
+        ),
+        info(
+            "replay",
+            &[],
+            has_env("REPLAY_FILE"),
+            Some(ModelId::new(replay::REPLAY_MODEL)),
+        ),
+    ]
+}
+
+/// `known_providers` for the current environment, probing Ollama.
+pub async fn list_providers(http: &HttpClient) -> Vec<ProviderInfo> {
+    let ollama_reachable = local::LocalProvider::is_available(http).await;
+    known_providers(|key| env::var(key).is_ok(), ollama_reachable)
+}
+
+/// Model ids worth offering for `provider`: the installed models for Ollama,
+/// otherwise the known ids of the provider's API family. Empty when unknown
+/// or when Ollama can't be reached.
+pub async fn known_models(http: &HttpClient, provider: &str) -> Vec<String> {
+    let family = match provider {
+        "anthropic" | "anthropic-sdk" | "claude-sdk" => "anthropic",
+        "openai" | "openai-sdk" => "openai",
+        "gemini" | "google" => "gemini",
+        "ollama" | "local" => {
+            return local::LocalProvider::list_models(http)
+                .await
+                .unwrap_or_default();
+        }
+        _ => return Vec::new(),
+    };
+    crate::model_info::known_ids(family)
+        .iter()
+        .map(|id| id.to_string())
+        .collect()
 }
 
 /// Resolve the effective model id from overrides, env, and config file.
@@ -212,57 +505,98 @@ fn resolve_model(
         })
 }
 
-/// Create a provider by explicit name
+/// Create a provider by explicit name and validate its configuration, so bad
+/// settings (empty keys, out-of-range sampling) fail at startup, not mid-turn.
 async fn create_provider_by_name(
+    http: &HttpClient,
+    name: &str,
+    config_file: &Option<crate::config_file::ProviderConfig>,
+    mut overrides: ProviderOverrides,
+) -> Result<Box<dyn LLMProvider>, ProviderError> {
+    // A provider-specific temperature in provider.json beats the global default.
+    if let Some(temperature) = config_file
+        .as_ref()
+        .and_then(|c| c.merged_settings(&name.to_lowercase()).temperature)
+    {
+        overrides.sampling.temperature = Some(temperature);
+    }
+    let provider = build_provider_by_name(http, name, config_file, overrides).await?;
+    provider
+        .validate_config()
+        .map_err(|e| match e.downcast::<ProviderError>() {
+            Ok(e) => *e,
+            Err(e) => ProviderError::Config(e.to_string()),
+        })?;
+    Ok(provider)
+}
+
+async fn build_provider_by_name(
+    http: &HttpClient,
     name: &str,
     config_file: &Option<crate::config_file::ProviderConfig>,
     overrides: ProviderOverrides,
 ) -> Result<Box<dyn LLMProvider>, ProviderError> {
+    let sampling = overrides.sampling;
     match name.to_lowercase().as_str() {
         "anthropic" => {
             let key = env::var("ANTHROPIC_API_KEY")
                 .map_err(|_| ProviderError::MissingApiKey("anthropic".to_string()))?;
             let model = resolve_model("anthropic", config_file, &overrides);
-            Ok(Box::new(anthropic::AnthropicProvider::new_with_model(
-                key, model,
-            )?))
+            Ok(Box::new(
+                anthropic::AnthropicProvider::new_with_model(http, key, model)?
+                    .with_sampling(sampling),
+            ))
         }
         "anthropic-sdk" | "claude-sdk" => {
             let key = env::var("ANTHROPIC_API_KEY")
                 .map_err(|_| ProviderError::MissingApiKey("anthropic".to_string()))?;
             let model = resolve_model("anthropic", config_file, &overrides);
             Ok(Box::new(
-                anthropic_sdk::AnthropicSdkProvider::new_with_model(key, model)?,
+                anthropic_sdk::AnthropicSdkProvider::new_with_model(key, model)?
+                    .with_sampling(sampling),
             ))
         }
         "openai" => {
             let key = env::var("OPENAI_API_KEY")
                 .map_err(|_| ProviderError::MissingApiKey("openai".to_string()))?;
             let model = resolve_model("openai", config_file, &overrides);
-            Ok(Box::new(openai::OpenAIProvider::new_with_model(
-                key, model,
-            )?))
+            let provider =
+                openai::OpenAIProvider::new_with_model(http, key, model)?.with_sampling(sampling);
+            // OPENAI_BASE_URL (applied in the constructor) wins over config.
+            let config_base_url = config_file
+                .as_ref()
+                .and_then(|c| c.merged_settings("openai").base_url);
+            match config_base_url {
+                Some(base_url) if env::var("OPENAI_BASE_URL").is_err() => {
+                    Ok(Box::new(provider.with_base_url(&base_url)?))
+                }
+                _ => Ok(Box::new(provider)),
+            }
         }
         "openai-sdk" => {
             let key = env::var("OPENAI_API_KEY")
                 .map_err(|_| ProviderError::MissingApiKey("openai".to_string()))?;
             let model = resolve_model("openai", config_file, &overrides);
-            Ok(Box::new(openai_sdk::OpenAISdkProvider::new_with_model(
-                key, model,
-            )?))
+            Ok(Box::new(
+                openai_sdk::OpenAISdkProvider::new_with_model(key, model)?.with_sampling(sampling),
+            ))
         }
         "gemini" | "google" => {
             let key = env::var("GEMINI_API_KEY")
                 .or_else(|_| env::var("GOOGLE_API_KEY"))
                 .map_err(|_| ProviderError::MissingApiKey("gemini".to_string()))?;
             let model = resolve_model("gemini", config_file, &overrides);
-            Ok(Box::new(gemini::GeminiProvider::new_with_model(
-                key, model,
-            )?))
+            Ok(Box::new(
+                gemini::GeminiProvider::new_with_model(http, key, model)?.with_sampling(sampling),
+            ))
         }
         "ollama" | "local" => {
             let model = resolve_model("local", config_file, &overrides);
-            Ok(Box::new(local::LocalProvider::new_with_model(model)?))
+            let provider = local::LocalProvider::new_with_model(http, model)?
+                .with_sampling(sampling)
+                .with_installed_models(http)
+                .await;
+            Ok(Box::new(provider))
         }
         "baml" => {
             let model = resolve_model("anthropic", config_file, &overrides);
@@ -270,6 +604,10 @@ async fn create_provider_by_name(
                 "baml", model,
             )?))
         }
+        "replay" => {
+            let model = resolve_model("replay", config_file, &overrides);
+            Ok(Box::new(replay::ReplayProvider::from_env(model)?))
+        }
         other => Err(ProviderError::Config(format!("Unknown provider: {other}"))),
     }
 }
@@ -313,6 +651,65 @@ mod tests {
         assert_eq!(result[0]["tool_calls"][0]["function"]["name"], "read");
     }
 
+    #[test]
+    fn convert_to_openai_messages_with_image() {
+        let msg = Message::user_with_image("what is this?", "image/png", "iVBORw0KGgo=");
+        let result = convert_to_openai_messages(&msg);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0]["content"],
+            json!([
+                { "type": "text", "text": "what is this?" },
+                {
+                    "type": "image_url",
+                    "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" }
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn convert_to_openai_messages_puts_tool_result_image_after_results() {
+        let msg = Message::tool_results(vec![
+            ContentBlock::ToolResult {
+                tool_use_id: ToolId::new("call_1"),
+                content: "(image logo.png: image/png, 8 bytes)".into(),
+            },
+            ContentBlock::Image {
+                media_type: "image/png".into(),
+                data: "iVBORw0KGgo=".into(),
+            },
+        ]);
+        let result = convert_to_openai_messages(&msg);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["role"], "tool");
+        assert_eq!(result[1]["role"], "user");
+        assert_eq!(result[1]["content"][0]["type"], "image_url");
+    }
+
+    #[test]
+    fn convert_to_anthropic_messages_with_image() {
+        let msg = Message::user_with_image("what is this?", "image/png", "iVBORw0KGgo=");
+        let result = convert_to_anthropic_messages(&[msg]);
+        assert_eq!(
+            result,
+            [json!({
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "what is this?" },
+                    {
+                        "type": "image",
+                        "source": {
+                            "type": "base64",
+                            "media_type": "image/png",
+                            "data": "iVBORw0KGgo="
+                        }
+                    }
+                ]
+            })]
+        );
+    }
+
     #[test]
     fn is_reasoning_model_detects_o1_o3() {
         assert!(is_reasoning_model("o1-preview"));
@@ -320,10 +717,189 @@ mod tests {
         assert!(!is_reasoning_model("gpt-4o"));
     }
 
+    #[test]
+    fn every_provider_enforces_its_sampling_limits() {
+        let sampling = |temperature, top_p| SamplingParams { temperature, top_p };
+        let limits = [
+            ("anthropic", 1.0),
+            ("anthropic-sdk", 1.0),
+            ("claude-sdk", 1.0),
+            ("openai", 2.0),
+            ("openai-sdk", 2.0),
+            ("gemini", 2.0),
+            ("ollama", 2.0),
+        ];
+        for (provider, max_temperature) in limits {
+            assert!(
+                sampling(Some(max_temperature), Some(0.9))
+                    .check_for(provider)
+                    .is_ok(),
+                "{provider}"
+            );
+            for bad in [max_temperature + 0.5, -0.5] {
+                let err = sampling(Some(bad), None).check_for(provider).unwrap_err();
+                assert!(err.to_string().contains("temperature"), "{provider}: {err}");
+            }
+            let err = sampling(None, Some(1.2)).check_for(provider).unwrap_err();
+            assert!(err.to_string().contains("top_p"), "{provider}: {err}");
+        }
+    }
+
+    #[test]
+    fn sampling_check_names_parameter_and_bound() {
+        let ok = SamplingParams {
+            temperature: Some(1.0),
+            top_p: Some(0.9),
+        };
+        assert!(ok.check("anthropic", 1.0).is_ok());
+
+        let hot = SamplingParams {
+            temperature: Some(1.5),
+            top_p: None,
+        };
+        let err = hot.check("anthropic", 1.0).unwrap_err().to_string();
+        assert!(err.contains("temperature 1.5"), "{err}");
+        assert!(err.contains("between 0 and 1"), "{err}");
+        assert!(hot.check("openai", 2.0).is_ok());
+        assert!(hot.check_for("claude-sdk").is_err());
+        assert!(hot.check_for("gemini").is_ok());
+
+        let wide = SamplingParams {
+            temperature: None,
+            top_p: Some(-0.1),
+        };
+        let err = wide.check("openai", 2.0).unwrap_err().to_string();
+        assert!(err.contains("top_p -0.1"), "{err}");
+    }
+
     #[test]
     fn supports_temperature_excludes_reasoning_and_gpt5() {
         assert!(supports_temperature("gpt-4o"));
         assert!(!supports_temperature("o1-preview"));
         assert!(!supports_temperature("gpt-5-mini"));
     }
+
+    #[test]
+    fn status_error_maps_429_to_rate_limited() {
+        use reqwest::StatusCode;
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        let err = status_error(
+            "Anthropic",
+            StatusCode::TOO_MANY_REQUESTS,
+            &headers,
+            "slow down",
+        );
+        assert!(matches!(
+            err,
+            ProviderError::RateLimited { retry_after: Some(d), .. } if d == Duration::from_secs(7)
+        ));
+        assert!(err.to_string().contains("retry in 7s"), "{err}");
+        assert!(err.to_string().contains("slow down"), "{err}");
+
+        let err = status_error(
+            "OpenAI",
+            StatusCode::TOO_MANY_REQUESTS,
+            &HeaderMap::new(),
+            "slow down",
+        );
+        assert!(matches!(
+            err,
+            ProviderError::RateLimited {
+                retry_after: None,
+                ..
+            }
+        ));
+
+        let err = status_error("OpenAI", StatusCode::BAD_GATEWAY, &headers, "upstream");
+        assert!(!err.is_rate_limited());
+        assert!(matches!(err, ProviderError::ServerError(_)));
+        assert!(err.is_transient());
+        assert!(err.to_string().contains("OpenAI API Error 502"), "{err}");
+
+        let err = status_error("OpenAI", StatusCode::BAD_REQUEST, &headers, "bad tool");
+        assert!(matches!(err, ProviderError::ApiError(_)));
+        assert!(!err.is_transient());
+    }
+
+    #[test]
+    fn retry_after_accepts_http_date() {
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn providers_share_the_callers_http_client() {
+        let http = HttpClient::new().unwrap();
+        let first = ProviderHttpClient::new(&http, 30);
+        let second = ProviderHttpClient::default(&http);
+        assert!(Arc::ptr_eq(&first.client, &second.client));
+        let other = ProviderHttpClient::default(&HttpClient::new().unwrap());
+        assert!(!Arc::ptr_eq(&first.client, &other.client));
+        assert_eq!(first.timeout, Duration::from_secs(30));
+        assert_eq!(second.timeout, Duration::from_secs(DEFAULT_TIMEOUT_SECS));
+    }
+
+    #[test]
+    fn known_providers_flags_configured_from_env() {
+        let env = ["OPENAI_API_KEY", "GOOGLE_API_KEY"];
+        let providers = known_providers(|key| env.contains(&key), false);
+
+        let names: Vec<&str> = providers.iter().map(|p| p.name).collect();
+        assert_eq!(
+            names,
+            [
+                "anthropic",
+                "openai",
+                "gemini",
+                "ollama",
+                "anthropic-sdk",
+                "openai-sdk",
+                "baml",
+                "replay"
+            ]
+        );
+        let configured: Vec<&str> = providers
+            .iter()
+            .filter(|p| p.configured)
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(configured, ["openai", "gemini", "openai-sdk", "baml"]);
+
+        let gemini = &providers[2];
+        assert_eq!(gemini.aliases, ["google"]);
+        assert_eq!(
+            gemini.default_model,
+            Some(ModelId::new(gemini::DEFAULT_MODEL))
+        );
+        assert_eq!(providers[3].default_model, None);
+        assert!(known_providers(|_| false, true)[3].configured);
+    }
+
+    #[tokio::test]
+    async fn known_models_lists_ids_for_provider_family() {
+        let http = HttpClient::new().unwrap();
+        let anthropic = known_models(&http, "anthropic").await;
+        assert!(anthropic.contains(&ModelId::claude_opus().to_string()));
+        assert_eq!(anthropic, known_models(&http, "claude-sdk").await);
+
+        let gemini = known_models(&http, "google").await;
+        assert_eq!(gemini[0], gemini::DEFAULT_MODEL);
+        assert!(gemini.iter().all(|id| id.starts_with("gemini-")));
+
+        assert!(
+            known_models(&http, "openai")
+                .await
+                .contains(&"gpt-5-mini".to_string())
+        );
+        assert!(known_models(&http, "replay").await.is_empty());
+    }
 }
@@ -3,9 +3,17 @@ use serde_json::{Value, json};
 use crate::api::ContentBlock;
 use crate::errors::ProviderError;
 
-use super::{InferenceRequest, InferenceResponse, LLMProvider, ProviderHttpClient, Usage};
+use super::{
+    HttpClient, InferenceRequest, InferenceResponse, LLMProvider, ProviderHttpClient,
+    SamplingParams, Usage,
+};
 use crate::types::ModelId;
 
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// OpenAI accepts temperatures in 0–2.
+pub(crate) const MAX_TEMPERATURE: f32 = 2.0;
+
 /// OpenAI provider implementation
 ///
 /// API differences:
@@ -16,20 +24,55 @@ pub struct OpenAIProvider {
     http: ProviderHttpClient,
     key: String,
     model: ModelId,
+    base_url: String,
+    sampling: SamplingParams,
 }
 
 impl OpenAIProvider {
     pub fn new(key: String) -> Result<Self, ProviderError> {
         let model = std::env::var("MODEL").ok().map(ModelId::new);
-        Self::new_with_model(key, model)
+        Self::new_with_model(&HttpClient::new()?, key, model)
     }
 
-    pub fn new_with_model(key: String, model: Option<ModelId>) -> Result<Self, ProviderError> {
-        let http = ProviderHttpClient::default()?;
+    pub fn new_with_model(
+        http: &HttpClient,
+        key: String,
+        model: Option<ModelId>,
+    ) -> Result<Self, ProviderError> {
+        let http = ProviderHttpClient::default(http);
 
         let model = model.unwrap_or_else(ModelId::gpt_5_mini);
 
-        Ok(Self { http, key, model })
+        let provider = Self {
+            http,
+            key,
+            model,
+            base_url: DEFAULT_OPENAI_BASE_URL.to_string(),
+            sampling: SamplingParams::default(),
+        };
+        match std::env::var("OPENAI_BASE_URL") {
+            Ok(base_url) => provider.with_base_url(&base_url),
+            Err(_) => Ok(provider),
+        }
+    }
+
+    /// Point at an OpenAI-compatible endpoint, e.g. `http://localhost:8000/v1`.
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self, ProviderError> {
+        self.base_url = parse_base_url(base_url)?;
+        Ok(self)
+    }
+
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn chat_completions_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url)
     }
 
     fn convert_to_openai_messages(msg: &crate::api::Message) -> Vec<Value> {
@@ -94,11 +137,13 @@ impl LLMProvider for OpenAIProvider {
         {
             body["temperature"] = json!(temp);
         }
+        if let Some(top_p) = req.top_p {
+            body["top_p"] = json!(top_p);
+        }
 
         let res = self
             .http
-            .client()
-            .post("https://api.openai.com/v1/chat/completions")
+            .post(self.chat_completions_url())
             .bearer_auth(&self.key)
             .header("Content-Type", "application/json")
             .json(&body)
@@ -107,10 +152,9 @@ impl LLMProvider for OpenAIProvider {
 
         if !res.status().is_success() {
             let status = res.status();
+            let headers = res.headers().clone();
             let err_text = res.text().await?;
-            return Err(
-                ProviderError::ApiError(format!("OpenAI API Error {status}: {err_text}")).into(),
-            );
+            return Err(super::status_error("OpenAI", status, &headers, &err_text).into());
         }
 
         let response_json: Value = res.json().await?;
@@ -199,12 +243,65 @@ impl LLMProvider for OpenAIProvider {
         if self.key.is_empty() {
             return Err(ProviderError::Config("OpenAI API key is empty".to_string()).into());
         }
+        parse_base_url(&self.base_url)?;
+        self.sampling.check("openai", MAX_TEMPERATURE)?;
         Ok(())
     }
 }
 
+/// Validate an http(s) base URL and strip any trailing slash.
+fn parse_base_url(base_url: &str) -> Result<String, ProviderError> {
+    let url = reqwest::Url::parse(base_url)
+        .map_err(|e| ProviderError::Config(format!("Invalid OpenAI base URL '{base_url}': {e}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ProviderError::Config(format!(
+            "OpenAI base URL must be http or https: {base_url}"
+        )));
+    }
+    Ok(base_url.trim_end_matches('/').to_string())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_base_url_is_used_for_requests() {
+        let provider = OpenAIProvider::new_with_model(
+            &HttpClient::new().unwrap(),
+            "sk-test".to_string(),
+            None,
+        )
+        .unwrap()
+        .with_base_url("http://localhost:8000/v1/")
+        .unwrap();
+
+        assert_eq!(provider.base_url(), "http://localhost:8000/v1");
+        assert_eq!(
+            provider.chat_completions_url(),
+            "http://localhost:8000/v1/chat/completions"
+        );
+        assert!(provider.validate_config().is_ok());
+    }
+
+    #[test]
+    fn invalid_base_url_is_rejected() {
+        let provider = OpenAIProvider::new_with_model(
+            &HttpClient::new().unwrap(),
+            "sk-test".to_string(),
+            None,
+        )
+        .unwrap();
+        assert!(provider.with_base_url("localhost:8000").is_err());
+
+        let provider = OpenAIProvider::new_with_model(
+            &HttpClient::new().unwrap(),
+            "sk-test".to_string(),
+            None,
+        )
+        .unwrap();
+        assert!(provider.with_base_url("not a url").is_err());
+    }
 
     #[test]
     fn test_is_reasoning_model() {
@@ -6,12 +6,13 @@ use crate::api::ContentBlock;
 use crate::errors::ProviderError;
 use crate::types::ModelId;
 
-use super::{InferenceRequest, InferenceResponse, LLMProvider, Usage};
+use super::{InferenceRequest, InferenceResponse, LLMProvider, SamplingParams, Usage};
 
 pub struct OpenAISdkProvider {
     client: Client<OpenAIConfig>,
     key: String,
     model: ModelId,
+    sampling: SamplingParams,
 }
 
 impl OpenAISdkProvider {
@@ -24,7 +25,17 @@ impl OpenAISdkProvider {
         let model = model.unwrap_or_else(ModelId::gpt_5_mini);
         let config = OpenAIConfig::new().with_api_key(&key);
         let client = Client::with_config(config);
-        Ok(Self { client, key, model })
+        Ok(Self {
+            client,
+            key,
+            model,
+            sampling: SamplingParams::default(),
+        })
+    }
+
+    pub fn with_sampling(mut self, sampling: SamplingParams) -> Self {
+        self.sampling = sampling;
+        self
     }
 
     fn convert_to_openai_messages(msg: &crate::api::Message) -> Vec<Value> {
@@ -77,6 +88,27 @@ impl OpenAISdkProvider {
     }
 }
 
+/// Map an SDK error onto `ProviderError`, keeping rate limits typed so
+/// `RetryProvider` retries them. An exhausted quota is also a 429 but won't
+/// clear by waiting, so only `rate_limit_exceeded` counts.
+fn sdk_error(err: async_openai::error::OpenAIError) -> ProviderError {
+    use async_openai::error::OpenAIError;
+    let rate_limited = match &err {
+        OpenAIError::Reqwest(e) => e.status().map(|s| s.as_u16()) == Some(429),
+        OpenAIError::ApiError(e) => e.code.as_deref() == Some("rate_limit_exceeded"),
+        _ => false,
+    };
+    let message = format!("OpenAI SDK Error: {err}");
+    if rate_limited {
+        ProviderError::RateLimited {
+            retry_after: None,
+            message,
+        }
+    } else {
+        ProviderError::ApiError(message)
+    }
+}
+
 #[async_trait::async_trait]
 impl LLMProvider for OpenAISdkProvider {
     // qual:allow(iosp) reason: "I/O boundary — builds and sends HTTP request to OpenAI API"
@@ -132,13 +164,16 @@ impl LLMProvider for OpenAISdkProvider {
         {
             body["temperature"] = json!(temp);
         }
+        if let Some(top_p) = req.top_p {
+            body["top_p"] = json!(top_p);
+        }
 
         let response_json: Value = self
             .client
             .chat()
             .create_byot(body)
             .await
-            .map_err(|e| ProviderError::ApiError(format!("OpenAI SDK Error: {e}")))?;
+            .map_err(sdk_error)?;
 
         let choice = response_json
             .get("choices")
@@ -209,6 +244,8 @@ impl LLMProvider for OpenAISdkProvider {
         if self.key.is_empty() {
             return Err(ProviderError::Config("OpenAI API key is empty".to_string()).into());
         }
+        self.sampling
+            .check("openai", super::openai::MAX_TEMPERATURE)?;
         Ok(())
     }
 }
@@ -0,0 +1,96 @@
+//! Per-model pricing used to turn token usage into an estimated dollar cost.
+//!
+//! Prices are USD per million tokens. Entries are matched in order against the
+//! lowercased model id, so list more specific patterns first.
+
+use super::Usage;
+use crate::types::ModelId;
+
+/// How a pricing entry is matched against a model id.
+#[derive(Debug, Clone, Copy)]
+enum Match {
+    Contains(&'static str),
+    Prefix(&'static str),
+}
+
+impl Match {
+    fn matches(self, model: &str) -> bool {
+        match self {
+            Match::Contains(pattern) => model.contains(pattern),
+            Match::Prefix(pattern) => model.starts_with(pattern),
+        }
+    }
+}
+
+/// Input and output price in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+const fn price(input_per_million: f64, output_per_million: f64) -> ModelPrice {
+    ModelPrice {
+        input_per_million,
+        output_per_million,
+    }
+}
+
+static PRICES: &[(Match, ModelPrice)] = &[
+    (Match::Contains("claude-opus-4"), price(15.0, 75.0)),
+    (Match::Contains("claude-sonnet-4"), price(3.0, 15.0)),
+    (Match::Contains("claude-haiku-4"), price(0.8, 4.0)),
+    (Match::Contains("gpt-5-mini"), price(1.1, 4.4)),
+    (Match::Prefix("gpt-4o"), price(2.5, 10.0)),
+    (Match::Prefix("gemini-2.0-flash"), price(0.1, 0.4)),
+    (Match::Prefix("gemini-2.5-pro"), price(1.25, 10.0)),
+];
+
+/// Price for `model`, or `None` if it is not in the table.
+pub fn price_for(model: &ModelId) -> Option<ModelPrice> {
+    let model = model.as_str().to_lowercase();
+    PRICES
+        .iter()
+        .find(|(matcher, _)| matcher.matches(&model))
+        .map(|(_, price)| *price)
+}
+
+/// Estimated USD cost of `usage` at `model`'s pricing. `None` for unknown models.
+pub fn estimate_cost(model: &ModelId, usage: &Usage) -> Option<f64> {
+    let price = price_for(model)?;
+    Some(
+        (usage.input_tokens as f64 / 1_000_000.0) * price.input_per_million
+            + (usage.output_tokens as f64 / 1_000_000.0) * price.output_per_million,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input_tokens: u32, output_tokens: u32) -> Usage {
+        Usage {
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    #[test]
+    fn estimates_cost_for_known_model() {
+        let model = ModelId::new("claude-sonnet-4-20250514");
+        let cost = estimate_cost(&model, &usage(1_000_000, 100_000)).unwrap();
+        assert!((cost - 4.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        let model = ModelId::new("GPT-4o-mini");
+        assert_eq!(price_for(&model), Some(price(2.5, 10.0)));
+    }
+
+    #[test]
+    fn unknown_model_has_no_estimate() {
+        let model = ModelId::new("llama3.1:8b");
+        assert_eq!(estimate_cost(&model, &usage(1_000, 1_000)), None);
+    }
+}
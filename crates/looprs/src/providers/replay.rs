@@ -0,0 +1,170 @@
+//! Replay provider: returns scripted responses from a JSON file, in order.
+//!
+//! Useful for deterministic end-to-end runs of tool loops and hooks without a
+//! live model. The file is a JSON array of responses:
+//!
+//! ```json
+//! [
+//!   {"content": [{"type": "tool_use", "id": "call_1", "name": "read", "input": {"path": "Cargo.toml"}}],
+//!    "stop_reason": "tool_use"},
+//!   {"content": [{"type": "text", "text": "Done."}]}
+//! ]
+//! ```
+
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::api::ContentBlock;
+use crate::errors::ProviderError;
+
+use super::{InferenceRequest, InferenceResponse, LLMProvider, Usage};
+use crate::types::ModelId;
+
+pub(crate) const REPLAY_MODEL: &str = "replay";
+
+#[derive(Debug, Deserialize)]
+struct ReplayResponse {
+    content: Vec<ContentBlock>,
+    #[serde(default = "default_stop_reason")]
+    stop_reason: String,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+fn default_stop_reason() -> String {
+    "end_turn".to_string()
+}
+
+impl From<ReplayResponse> for InferenceResponse {
+    fn from(r: ReplayResponse) -> Self {
+        Self {
+            content: r.content,
+            stop_reason: r.stop_reason,
+            usage: r.usage.unwrap_or(Usage {
+                input_tokens: 0,
+                output_tokens: 0,
+            }),
+        }
+    }
+}
+
+pub struct ReplayProvider {
+    responses: Mutex<VecDeque<InferenceResponse>>,
+    model: ModelId,
+    source: PathBuf,
+}
+
+impl ReplayProvider {
+    /// Load responses from the file named by `REPLAY_FILE`.
+    pub fn from_env(model: Option<ModelId>) -> Result<Self, ProviderError> {
+        let path = std::env::var("REPLAY_FILE").map_err(|_| {
+            ProviderError::Config("Set REPLAY_FILE to a JSON file of responses".to_string())
+        })?;
+        Self::from_file(Path::new(&path), model)
+    }
+
+    // qual:allow(iosp) reason: "I/O boundary — reads the replay script from disk"
+    pub fn from_file(path: &Path, model: Option<ModelId>) -> Result<Self, ProviderError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ProviderError::Config(format!("Failed to read {}: {e}", path.display()))
+        })?;
+        let responses: Vec<ReplayResponse> = serde_json::from_str(&content)?;
+        Ok(Self {
+            responses: Mutex::new(responses.into_iter().map(Into::into).collect()),
+            model: model.unwrap_or_else(|| ModelId::new(REPLAY_MODEL)),
+            source: path.to_path_buf(),
+        })
+    }
+
+    /// Responses not yet returned.
+    pub fn remaining(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+}
+
+#[async_trait::async_trait]
+impl LLMProvider for ReplayProvider {
+    async fn infer(
+        &self,
+        _req: &InferenceRequest,
+    ) -> Result<InferenceResponse, Box<dyn std::error::Error + Send + Sync>> {
+        self.responses.lock().unwrap().pop_front().ok_or_else(|| {
+            ProviderError::InvalidResponse(format!(
+                "replay script {} is exhausted",
+                self.source.display()
+            ))
+            .into()
+        })
+    }
+
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn model(&self) -> &ModelId {
+        &self.model
+    }
+
+    fn validate_config(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::NullOutput;
+    use crate::agent::Agent;
+    use crate::tools::executor::StubToolExecutor;
+    use tempfile::NamedTempFile;
+
+    fn script(json: &str) -> NamedTempFile {
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), json).unwrap();
+        file
+    }
+
+    #[tokio::test]
+    async fn replays_tool_loop_then_final_answer() {
+        let file = script(
+            r#"[
+                {"content": [{"type": "tool_use", "id": "call_1", "name": "read", "input": {"path": "Cargo.toml"}}],
+                 "stop_reason": "tool_use",
+                 "usage": {"input_tokens": 10, "output_tokens": 2}},
+                {"content": [{"type": "text", "text": "All done."}]}
+            ]"#,
+        );
+        let provider = ReplayProvider::from_file(file.path(), None).unwrap();
+        assert_eq!(provider.remaining(), 2);
+
+        let mut agent = Agent::new(Box::new(provider))
+            .unwrap()
+            .with_output(Box::new(NullOutput))
+            .with_tool_executor(Box::new(StubToolExecutor::default()));
+        agent.add_user_message("read the manifest");
+        agent.run_turn().await.unwrap();
+
+        assert_eq!(agent.latest_assistant_text(), Some("All done.".to_string()));
+        assert_eq!(agent.usage().input_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn errors_when_exhausted() {
+        let file = script("[]");
+        let provider = ReplayProvider::from_file(file.path(), None).unwrap();
+        let req = InferenceRequest {
+            model: provider.model().clone(),
+            messages: Vec::new(),
+            tools: Vec::new(),
+            max_tokens: 16,
+            temperature: None,
+            top_p: None,
+            system: String::new(),
+        };
+
+        let err = provider.infer(&req).await.unwrap_err();
+        assert!(err.to_string().contains("exhausted"));
+    }
+}
@@ -1,3 +1,4 @@
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -15,13 +16,71 @@ pub struct Rule {
     pub categories: Vec<String>,
     /// Source path for debugging
     pub source: PathBuf,
+    /// Whether the rule came from `~/.looprs/rules` or `.looprs/rules`
+    pub origin: RuleOrigin,
+    /// Glob patterns from frontmatter; when non-empty the rule is only
+    /// injected for turns that touch a matching path
+    pub applies_to: Vec<String>,
+}
+
+/// Optional YAML frontmatter at the top of a rule file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RuleFrontmatter {
+    applies_to: Vec<String>,
+    /// Keys other tools put in rule frontmatter; ignored with a warning
+    #[serde(flatten)]
+    unknown: std::collections::BTreeMap<String, serde_yaml::Value>,
+}
+
+/// Which rules directory a rule was loaded from. Repo rules replace user rules
+/// with the same id.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RuleOrigin {
+    User,
+    #[default]
+    Repo,
+}
+
+impl RuleOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleOrigin::User => "user",
+            RuleOrigin::Repo => "repo",
+        }
+    }
+}
+
+/// One line of `RuleRegistry::describe`.
+#[derive(Debug, Clone)]
+pub struct RuleDescription<'a> {
+    pub rule: &'a Rule,
+    /// A repo rule with the same id replaced this one.
+    pub overridden: bool,
 }
 
 impl Rule {
     /// Parse a rule from markdown file
     pub fn from_file(path: &Path) -> Result<Self, String> {
-        let content = fs::read_to_string(path)
+        let raw = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read rule file {}: {}", path.display(), e))?;
+        let (frontmatter, content) = split_frontmatter(&raw)
+            .map_err(|e| format!("Invalid frontmatter in {}: {e}", path.display()))?;
+        for key in frontmatter.unknown.keys() {
+            crate::ui::warn(format!(
+                "Warning: {}: unknown frontmatter key '{key}', ignored",
+                path.display()
+            ));
+        }
+        for pattern in &frontmatter.applies_to {
+            glob::Pattern::new(pattern).map_err(|e| {
+                format!(
+                    "Invalid applies_to glob '{pattern}' in {}: {e}",
+                    path.display()
+                )
+            })?;
+        }
+        let content = content.to_string();
 
         // Extract ID from filename (without extension)
         let id = path
@@ -42,6 +101,25 @@ impl Rule {
             content,
             categories,
             source: path.to_path_buf(),
+            origin: RuleOrigin::default(),
+            applies_to: frontmatter.applies_to,
+        })
+    }
+
+    /// Whether the rule should be injected for a turn touching `paths`.
+    /// Rules without `applies_to` always apply.
+    pub fn applies_to_any(&self, paths: &[String]) -> bool {
+        if self.applies_to.is_empty() {
+            return true;
+        }
+        let patterns: Vec<glob::Pattern> = self
+            .applies_to
+            .iter()
+            .filter_map(|p| glob::Pattern::new(p).ok())
+            .collect();
+        paths.iter().any(|path| {
+            let path = path.strip_prefix("./").unwrap_or(path);
+            patterns.iter().any(|pattern| pattern.matches(path))
         })
     }
 }
@@ -49,22 +127,40 @@ impl Rule {
 /// Registry for loading and managing rules
 pub struct RuleRegistry {
     rules: HashMap<String, Rule>,
+    /// User rules replaced by a repo rule with the same id.
+    overridden: Vec<Rule>,
 }
 
 impl RuleRegistry {
     pub fn new() -> Self {
         Self {
             rules: HashMap::new(),
+            overridden: Vec::new(),
         }
     }
 
-    /// Register a single rule
+    /// Register a single rule. A repo rule replaces a user rule with the same id.
     pub fn register(&mut self, rule: Rule) {
-        self.rules.insert(rule.id.clone(), rule);
+        let origin = rule.origin;
+        if let Some(previous) = self.rules.insert(rule.id.clone(), rule)
+            && previous.origin == RuleOrigin::User
+            && origin == RuleOrigin::Repo
+        {
+            self.overridden.push(previous);
+        }
     }
 
     /// Load rules from a directory (non-recursive)
     pub fn load_from_directory(&mut self, dir: &Path) -> Result<usize, String> {
+        self.load_from_directory_as(dir, RuleOrigin::Repo)
+    }
+
+    /// Load rules from a directory (non-recursive), tagging each with `origin`
+    pub fn load_from_directory_as(
+        &mut self,
+        dir: &Path,
+        origin: RuleOrigin,
+    ) -> Result<usize, String> {
         if !dir.exists() {
             return Ok(0);
         }
@@ -92,7 +188,7 @@ impl RuleRegistry {
 
                 match Rule::from_file(&path) {
                     Ok(rule) => {
-                        self.register(rule);
+                        self.register(Rule { origin, ..rule });
                         loaded += 1;
                     }
                     Err(e) => {
@@ -113,7 +209,7 @@ impl RuleRegistry {
         // Load user-level rules first (~/.looprs/rules/)
         if let Ok(home) = std::env::var("HOME") {
             let user_rules_dir = std::path::PathBuf::from(home).join(".looprs").join("rules");
-            if let Err(e) = registry.load_from_directory(&user_rules_dir) {
+            if let Err(e) = registry.load_from_directory_as(&user_rules_dir, RuleOrigin::User) {
                 eprintln!("⚠️  Failed to load user rules: {e}");
             }
         }
@@ -142,15 +238,56 @@ impl RuleRegistry {
         self.rules.len()
     }
 
+    /// Active rules ordered by origin (user, then repo), then id.
+    pub fn sorted(&self) -> Vec<&Rule> {
+        let mut rules: Vec<&Rule> = self.rules.values().collect();
+        rules.sort_by(|a, b| (a.origin, &a.id).cmp(&(b.origin, &b.id)));
+        rules
+    }
+
+    /// Every loaded rule, including user rules a repo rule overrode, ordered
+    /// by origin then id.
+    pub fn describe(&self) -> Vec<RuleDescription<'_>> {
+        let mut described: Vec<RuleDescription<'_>> = self
+            .sorted()
+            .into_iter()
+            .map(|rule| RuleDescription {
+                rule,
+                overridden: false,
+            })
+            .chain(self.overridden.iter().map(|rule| RuleDescription {
+                rule,
+                overridden: true,
+            }))
+            .collect();
+        described.sort_by(|a, b| (a.rule.origin, &a.rule.id).cmp(&(b.rule.origin, &b.rule.id)));
+        described
+    }
+
     /// Format rules for injection into system prompts
     pub fn format_for_prompt(&self) -> String {
-        if self.rules.is_empty() {
+        Self::format_rules(self.sorted())
+    }
+
+    /// Like `format_for_prompt`, leaving out scoped rules whose `applies_to`
+    /// matches none of `paths`.
+    pub fn format_for_paths(&self, paths: &[String]) -> String {
+        let rules = self
+            .sorted()
+            .into_iter()
+            .filter(|rule| rule.applies_to_any(paths))
+            .collect();
+        Self::format_rules(rules)
+    }
+
+    fn format_rules(rules: Vec<&Rule>) -> String {
+        if rules.is_empty() {
             return String::new();
         }
 
         let mut output = String::from("\n## Project Rules and Guidelines\n\n");
 
-        for rule in self.rules.values() {
+        for rule in rules {
             output.push_str(&format!("### {}\n\n", rule.title));
             output.push_str(&rule.content);
             output.push_str("\n\n");
@@ -166,6 +303,25 @@ impl Default for RuleRegistry {
     }
 }
 
+/// Split a leading `---` YAML block off `content`. Content without one has
+/// empty frontmatter.
+fn split_frontmatter(content: &str) -> Result<(RuleFrontmatter, &str), String> {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return Ok((RuleFrontmatter::default(), content));
+    };
+    let Some(end) = rest.find("\n---") else {
+        return Err("missing closing ---".to_string());
+    };
+    let frontmatter = serde_yaml::from_str::<Option<RuleFrontmatter>>(&rest[..end])
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+    let body = rest[end + 4..].trim_start_matches(['\r', '\n']);
+    Ok((frontmatter, body))
+}
+
 /// Extract the first # heading from markdown content
 fn extract_title(content: &str) -> Option<String> {
     for line in content.lines() {
@@ -283,4 +439,97 @@ mod tests {
         assert!(registry.get("real-rule").is_some());
         assert!(registry.get("README").is_none());
     }
+
+    fn write_rule(dir: &Path, id: &str, body: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(format!("{id}.md")), body).unwrap();
+    }
+
+    #[test]
+    fn repo_rule_overrides_user_rule_and_is_reported() {
+        let dir = TempDir::new().unwrap();
+        let user_dir = dir.path().join("user");
+        let repo_dir = dir.path().join("repo");
+        write_rule(&user_dir, "style", "# User Style\n\nUse tabs.");
+        write_rule(&user_dir, "commits", "# Commits\n\nSign commits.");
+        write_rule(&repo_dir, "style", "# Repo Style\n\nUse spaces.");
+
+        let mut registry = RuleRegistry::new();
+        registry
+            .load_from_directory_as(&user_dir, RuleOrigin::User)
+            .unwrap();
+        registry.load_from_directory(&repo_dir).unwrap();
+
+        let style = registry.get("style").unwrap();
+        assert_eq!(style.origin, RuleOrigin::Repo);
+        assert_eq!(style.title, "Repo Style");
+
+        let described: Vec<(&str, RuleOrigin, bool)> = registry
+            .describe()
+            .iter()
+            .map(|d| (d.rule.id.as_str(), d.rule.origin, d.overridden))
+            .collect();
+        assert_eq!(
+            described,
+            [
+                ("commits", RuleOrigin::User, false),
+                ("style", RuleOrigin::User, true),
+                ("style", RuleOrigin::Repo, false),
+            ]
+        );
+
+        let prompt = registry.format_for_prompt();
+        assert!(!prompt.contains("Use tabs."));
+        assert!(prompt.find("### Commits").unwrap() < prompt.find("### Repo Style").unwrap());
+    }
+
+    #[test]
+    fn frontmatter_applies_to_is_parsed_and_stripped() {
+        let dir = TempDir::new().unwrap();
+        write_rule(
+            dir.path(),
+            "rust",
+            "---\napplies_to:\n  - \"src/**/*.rs\"\n---\n# Rust Style\n\nNo unwrap.",
+        );
+
+        let rule = Rule::from_file(&dir.path().join("rust.md")).unwrap();
+        assert_eq!(rule.applies_to, ["src/**/*.rs"]);
+        assert_eq!(rule.title, "Rust Style");
+        assert!(rule.content.starts_with("# Rust Style"));
+    }
+
+    #[test]
+    fn unknown_frontmatter_keys_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        write_rule(
+            dir.path(),
+            "rust",
+            "---\ndescription: Rust style\nalwaysApply: true\napplies_to:\n  - \"*.rs\"\n---\n# Rust Style\n\nNo unwrap.",
+        );
+
+        let rule = Rule::from_file(&dir.path().join("rust.md")).unwrap();
+        assert_eq!(rule.applies_to, ["*.rs"]);
+        assert_eq!(rule.title, "Rust Style");
+    }
+
+    #[test]
+    fn scoped_rule_is_injected_only_for_matching_paths() {
+        let dir = TempDir::new().unwrap();
+        write_rule(
+            dir.path(),
+            "rust",
+            "---\napplies_to: [\"src/**/*.rs\"]\n---\n# Rust Style\n\nNo unwrap.",
+        );
+        write_rule(dir.path(), "general", "# General\n\nBe concise.");
+        let mut registry = RuleRegistry::new();
+        registry.load_from_directory(dir.path()).unwrap();
+
+        let matched = registry.format_for_paths(&["./src/agent/mod.rs".to_string()]);
+        assert!(matched.contains("No unwrap."));
+        assert!(matched.contains("Be concise."));
+
+        let filtered = registry.format_for_paths(&["docs/guide.md".to_string()]);
+        assert!(!filtered.contains("No unwrap."));
+        assert!(filtered.contains("Be concise."));
+    }
 }
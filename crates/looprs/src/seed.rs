@@ -1,28 +1,99 @@
 //! Seed example config files into a directory. Never overwrites user config.
 
 use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::app_config::AppConfig;
 use crate::config_file::ProviderConfig;
 
-/// Write example config files into `dir`. Creates `config.json.example` and
-/// `provider.json.example`. Does not overwrite existing `config.json` or `provider.json`.
-pub fn seed_into(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
-    std::fs::create_dir_all(dir)?;
-    let mut written = Vec::new();
+/// Names accepted by `SeedOptions::only`, with the file each one writes.
+pub const EXAMPLES: &[(&str, &str)] = &[
+    ("config", "config.json.example"),
+    ("provider", "provider.json.example"),
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct SeedOptions {
+    /// Overwrite example files that already exist.
+    pub force: bool,
+    /// Seed only this example (a name from `EXAMPLES`).
+    pub only: Option<String>,
+}
+
+/// Which example files `seed_into` wrote and which it left alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    pub written: Vec<PathBuf>,
+    pub skipped: Vec<PathBuf>,
+}
 
-    let config_example = dir.join("config.json.example");
-    let content = serde_json::to_string_pretty(&AppConfig::default())?;
-    std::fs::write(&config_example, content)?;
-    written.push(config_example);
+/// Write example config files into `dir`: `config.json.example` and
+/// `provider.json.example`, or just the one named by `options.only`.
+/// Existing examples are skipped unless `options.force` is set. The real
+/// `config.json` and `provider.json` are never touched.
+// qual:allow(iosp) reason: "I/O boundary — writes example files"
+pub fn seed_into(dir: &Path, options: &SeedOptions) -> Result<SeedReport> {
+    let selected: Vec<&(&str, &str)> = match &options.only {
+        Some(only) => {
+            let Some(example) = EXAMPLES.iter().find(|(name, _)| *name == only.as_str()) else {
+                let names: Vec<&str> = EXAMPLES.iter().map(|(name, _)| *name).collect();
+                anyhow::bail!(
+                    "Unknown example '{only}'; expected one of: {}",
+                    names.join(", ")
+                );
+            };
+            vec![example]
+        }
+        None => EXAMPLES.iter().collect(),
+    };
+
+    std::fs::create_dir_all(dir)?;
+    let mut report = SeedReport::default();
+    for (name, file_name) in selected {
+        let path = dir.join(file_name);
+        if path.exists() && !options.force {
+            report.skipped.push(path);
+            continue;
+        }
+        std::fs::write(&path, example_content(name)?)?;
+        report.written.push(path);
+    }
+    Ok(report)
+}
 
-    let provider_example = dir.join("provider.json.example");
-    let content = serde_json::to_string_pretty(&ProviderConfig::default())?;
-    std::fs::write(&provider_example, content)?;
-    written.push(provider_example);
+impl SeedOptions {
+    /// Parse `looprs seed` arguments into options and a target directory
+    /// (default `.looprs`). Example names are matched before directories, so
+    /// seed into a directory named like an example as `./config`.
+    pub fn from_args(args: &[String]) -> Result<(Self, String), String> {
+        let mut options = Self::default();
+        let mut dir = None;
+        for arg in args {
+            match arg.as_str() {
+                "--force" => options.force = true,
+                flag if flag.starts_with('-') => return Err(format!("unknown flag '{flag}'")),
+                name if EXAMPLES.iter().any(|(n, _)| *n == name) => {
+                    if options.only.replace(name.to_string()).is_some() {
+                        return Err("only one example NAME may be given".to_string());
+                    }
+                }
+                other => {
+                    if dir.replace(other.to_string()).is_some() {
+                        return Err(format!("unexpected argument '{other}'"));
+                    }
+                }
+            }
+        }
+        Ok((options, dir.unwrap_or_else(|| ".looprs".to_string())))
+    }
+}
 
-    Ok(written)
+fn example_content(name: &str) -> Result<String> {
+    Ok(match name {
+        "config" => serde_json::to_string_pretty(&AppConfig::default())?,
+        "provider" => serde_json::to_string_pretty(&ProviderConfig::default())?,
+        _ => anyhow::bail!("Unknown example '{name}'"),
+    })
 }
 
 /// Expand leading `~` to home directory.
@@ -47,8 +118,9 @@ mod tests {
     fn seed_creates_example_files() {
         let tmp = TempDir::new().unwrap();
         let dir = tmp.path().join(".looprs");
-        let written = seed_into(&dir).unwrap();
-        assert_eq!(written.len(), 2);
+        let report = seed_into(&dir, &SeedOptions::default()).unwrap();
+        assert_eq!(report.written.len(), 2);
+        assert!(report.skipped.is_empty());
         assert!(dir.join("config.json.example").exists());
         assert!(dir.join("provider.json.example").exists());
         let config: serde_json::Value = serde_json::from_str(
@@ -59,6 +131,76 @@ mod tests {
         assert!(config.get("onboarding").is_some());
     }
 
+    #[test]
+    fn seed_skips_existing_examples_by_default() {
+        let tmp = TempDir::new().unwrap();
+        let existing = tmp.path().join("config.json.example");
+        std::fs::write(&existing, "my edits").unwrap();
+
+        let report = seed_into(tmp.path(), &SeedOptions::default()).unwrap();
+
+        assert_eq!(report.skipped, [existing.clone()]);
+        assert_eq!(report.written, [tmp.path().join("provider.json.example")]);
+        assert_eq!(std::fs::read_to_string(&existing).unwrap(), "my edits");
+    }
+
+    #[test]
+    fn seed_force_overwrites_existing_examples() {
+        let tmp = TempDir::new().unwrap();
+        let existing = tmp.path().join("config.json.example");
+        std::fs::write(&existing, "my edits").unwrap();
+
+        let options = SeedOptions {
+            force: true,
+            ..SeedOptions::default()
+        };
+        let report = seed_into(tmp.path(), &options).unwrap();
+
+        assert_eq!(report.written.len(), 2);
+        assert!(report.skipped.is_empty());
+        assert_ne!(std::fs::read_to_string(&existing).unwrap(), "my edits");
+    }
+
+    #[test]
+    fn seed_only_writes_the_selected_example() {
+        let tmp = TempDir::new().unwrap();
+        let options = SeedOptions {
+            only: Some("provider".to_string()),
+            ..SeedOptions::default()
+        };
+
+        let report = seed_into(tmp.path(), &options).unwrap();
+
+        assert_eq!(report.written, [tmp.path().join("provider.json.example")]);
+        assert!(!tmp.path().join("config.json.example").exists());
+
+        let unknown = SeedOptions {
+            only: Some("hooks".to_string()),
+            ..SeedOptions::default()
+        };
+        let err = seed_into(tmp.path(), &unknown).unwrap_err();
+        assert!(err.to_string().contains("config, provider"), "{err}");
+    }
+
+    #[test]
+    fn from_args_matches_names_before_dirs_and_rejects_unknown_flags() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        let (options, dir) = SeedOptions::from_args(&args(&["--force", "provider"])).unwrap();
+        assert!(options.force);
+        assert_eq!(options.only.as_deref(), Some("provider"));
+        assert_eq!(dir, ".looprs");
+
+        let (options, dir) = SeedOptions::from_args(&args(&["config", "~/.looprs"])).unwrap();
+        assert_eq!(options.only.as_deref(), Some("config"));
+        assert_eq!(dir, "~/.looprs");
+
+        let err = SeedOptions::from_args(&args(&["--froce"])).unwrap_err();
+        assert!(err.contains("--froce"), "{err}");
+        assert!(SeedOptions::from_args(&args(&["a", "b"])).is_err());
+        assert!(SeedOptions::from_args(&args(&["config", "provider"])).is_err());
+    }
+
     #[test]
     fn expand_tilde_plain_path_unchanged() {
         let p = expand_tilde("/foo/bar");
@@ -1,7 +1,11 @@
-use std::io;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
 use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
+use crate::cancel::CancellationToken;
+
 pub const NUSHELL_BIN: &str = "nu";
 pub const BASH_BIN: &str = "bash";
 
@@ -13,11 +17,16 @@ pub fn run_bash_command(command: &str) -> io::Result<Output> {
     run_bash_command_with_timeout(command, None)
 }
 
+/// Run a Bash command with `cwd` as its current directory.
+pub fn run_bash_command_in(command: &str, cwd: &Path) -> io::Result<Output> {
+    run_with_timeout(BASH_BIN, &["-c", command], None, Some(cwd))
+}
+
 /// Run a Nushell command with an optional wall-clock timeout.
 ///
 /// Returns `Err` with `ErrorKind::TimedOut` if the process exceeds `timeout`.
 pub fn run_nu_command_with_timeout(command: &str, timeout: Option<Duration>) -> io::Result<Output> {
-    run_with_timeout(NUSHELL_BIN, &["-c", command], timeout)
+    run_with_timeout(NUSHELL_BIN, &["-c", command], timeout, None)
 }
 
 /// Run a Bash command with an optional wall-clock timeout.
@@ -25,15 +34,115 @@ pub fn run_bash_command_with_timeout(
     command: &str,
     timeout: Option<Duration>,
 ) -> io::Result<Output> {
-    run_with_timeout(BASH_BIN, &["-c", command], timeout)
+    run_with_timeout(BASH_BIN, &["-c", command], timeout, None)
+}
+
+/// Run a Bash command, passing each line of stdout and stderr to `on_line`
+/// as it is printed. The returned `Output` still holds everything captured.
+///
+/// Once `cancel` fires, the command and everything it started are killed
+/// and the call returns `ErrorKind::Interrupted`.
+// qual:allow(iosp) reason: "I/O boundary — spawns the command and pumps its pipes"
+pub fn run_bash_command_streaming(
+    command: &str,
+    cwd: Option<&Path>,
+    on_line: &(dyn Fn(&str) + Sync),
+    cancel: Option<&CancellationToken>,
+) -> io::Result<Output> {
+    let mut cmd = Command::new(BASH_BIN);
+    cmd.args(["-c", command])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    #[cfg(unix)]
+    if cancel.is_some() {
+        use std::os::unix::process::CommandExt;
+        // Own process group, so a cancel also reaches children holding the pipes.
+        cmd.process_group(0);
+    }
+    let mut child = cmd.spawn()?;
+    let pid = child.id();
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    let done = AtomicBool::new(false);
+    let (stdout, stderr) = std::thread::scope(|scope| {
+        if let Some(cancel) = cancel {
+            scope.spawn(|| kill_on_cancel(pid, cancel, &done));
+        }
+        let stderr = scope.spawn(|| pump_lines(stderr, on_line));
+        let stdout = pump_lines(stdout, on_line);
+        let stderr = stderr.join().expect("stderr reader panicked");
+        done.store(true, Ordering::SeqCst);
+        (stdout, stderr)
+    });
+    let status = child.wait()?;
+    if cancel.is_some_and(CancellationToken::is_cancelled) {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "command cancelled",
+        ));
+    }
+    Ok(Output {
+        status,
+        stdout: stdout?,
+        stderr: stderr?,
+    })
+}
+
+/// Poll `cancel` until `done`; on cancel, kill the process group led by `pid`.
+fn kill_on_cancel(pid: u32, cancel: &CancellationToken, done: &AtomicBool) {
+    while !done.load(Ordering::SeqCst) {
+        if cancel.is_cancelled() {
+            kill_process_group(pid);
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    // The group leader is not reaped until the pipes close, so `pid` still
+    // names the group this module spawned.
+    unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGKILL) };
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {}
+
+/// Read `pipe` to the end, reporting each line (without its newline).
+fn pump_lines(pipe: impl Read, on_line: &(dyn Fn(&str) + Sync)) -> io::Result<Vec<u8>> {
+    let mut reader = BufReader::new(pipe);
+    let mut captured = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(captured);
+        }
+        captured.extend_from_slice(&line);
+        on_line(String::from_utf8_lossy(&line).trim_end_matches(['\n', '\r']));
+    }
 }
 
-fn run_with_timeout(bin: &str, args: &[&str], timeout: Option<Duration>) -> io::Result<Output> {
-    let mut child = Command::new(bin)
+fn run_with_timeout(
+    bin: &str,
+    args: &[&str],
+    timeout: Option<Duration>,
+    cwd: Option<&Path>,
+) -> io::Result<Output> {
+    let mut command = Command::new(bin);
+    command
         .args(args)
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
+        .stderr(Stdio::piped());
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    let mut child = command.spawn()?;
 
     match timeout {
         None => child.wait_with_output(),
@@ -84,6 +193,49 @@ mod tests {
         assert_eq!(err.kind(), io::ErrorKind::TimedOut);
     }
 
+    #[test]
+    fn streaming_reports_lines_as_they_arrive() {
+        let lines = std::sync::Mutex::new(Vec::new());
+        let output = run_bash_command_streaming(
+            "echo one; sleep 0.1; echo two >&2; echo three",
+            None,
+            &|line: &str| lines.lock().unwrap().push(line.to_string()),
+            None,
+        )
+        .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "one\nthree\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "two\n");
+        let mut lines = lines.into_inner().unwrap();
+        assert_eq!(lines[0], "one");
+        lines.sort();
+        assert_eq!(lines, ["one", "three", "two"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn streaming_cancel_kills_the_command_and_its_children() {
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            trigger.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let err = run_bash_command_streaming(
+            "echo start; sleep 30 | cat; echo never",
+            None,
+            &|_: &str| {},
+            Some(&cancel),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
     #[test]
     fn no_timeout_completes_normally() {
         let out = run_bash_command_with_timeout("echo done", None).unwrap();
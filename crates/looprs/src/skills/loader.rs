@@ -35,6 +35,9 @@ impl SkillRegistry {
 
     /// Load skills from two directories with precedence (repo overrides user)
     pub fn load_with_precedence(&mut self, user_dir: &Path, repo_dir: &Path) -> Result<usize> {
+        self.user_dir = Some(user_dir.to_path_buf());
+        self.repo_dir = Some(repo_dir.to_path_buf());
+
         // Load user skills first (if directory exists)
         if user_dir.exists() {
             let _ = self.load_from_directory(user_dir);
@@ -5,7 +5,9 @@ pub mod discovery;
 pub mod loader;
 pub mod parser;
 
-use std::path::PathBuf;
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// A skill loaded from SKILL.md with YAML frontmatter
 #[derive(Debug, Clone, PartialEq)]
@@ -13,23 +15,105 @@ pub struct Skill {
     pub name: String,
     pub description: Option<String>,
     pub triggers: Vec<String>,
+    /// Tools the agent may use while this skill is active; empty means all.
+    /// A repo skill replaces a same-named user skill wholesale, so its
+    /// restriction (or lack of one) is what applies.
+    pub allowed_tools: Vec<String>,
+    /// Higher-priority skills are injected first when several auto-trigger.
+    pub priority: i32,
     pub content: String,
     pub source_path: PathBuf,
 }
 
+/// Where a skill was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillSource {
+    /// `~/.looprs/skills/`
+    User,
+    /// `.looprs/skills/` in the current repo
+    Repo,
+    /// Registered directly or loaded from another directory
+    Other,
+}
+
+impl std::fmt::Display for SkillSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkillSource::User => write!(f, "user"),
+            SkillSource::Repo => write!(f, "repo"),
+            SkillSource::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// Listing entry for `/skills`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkillSummary {
+    pub name: String,
+    pub description: Option<String>,
+    pub triggers: Vec<String>,
+    pub source: SkillSource,
+}
+
+/// Trigger prefix marking a (case-insensitive) regex instead of a substring
+const REGEX_TRIGGER_PREFIX: &str = "re:";
+
+/// A trigger prepared for matching against lowercased input
+enum TriggerMatcher {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl TriggerMatcher {
+    fn compile(trigger: &str) -> Option<Self> {
+        let Some(pattern) = trigger.strip_prefix(REGEX_TRIGGER_PREFIX) else {
+            return Some(Self::Substring(trigger.to_lowercase()));
+        };
+        match RegexBuilder::new(pattern).case_insensitive(true).build() {
+            Ok(re) => Some(Self::Regex(re)),
+            Err(e) => {
+                eprintln!("Warning: Ignoring invalid skill trigger '{trigger}': {e}");
+                None
+            }
+        }
+    }
+
+    fn matches(&self, input_lower: &str) -> bool {
+        match self {
+            Self::Substring(needle) => input_lower.contains(needle.as_str()),
+            Self::Regex(re) => re.is_match(input_lower),
+        }
+    }
+}
+
 /// Registry for loading and matching skills
 pub struct SkillRegistry {
     skills: Vec<Skill>,
+    /// Compiled triggers keyed by skill name, built on `register`
+    matchers: HashMap<String, Vec<TriggerMatcher>>,
+    user_dir: Option<PathBuf>,
+    repo_dir: Option<PathBuf>,
 }
 
 impl SkillRegistry {
     pub fn new() -> Self {
-        Self { skills: Vec::new() }
+        Self {
+            skills: Vec::new(),
+            matchers: HashMap::new(),
+            user_dir: None,
+            repo_dir: None,
+        }
     }
 
     pub fn register(&mut self, skill: Skill) {
         // Remove existing skill with same name (for precedence)
         self.skills.retain(|s| s.name != skill.name);
+        let matchers = skill
+            .triggers
+            .iter()
+            .filter_map(|t| TriggerMatcher::compile(t))
+            .collect();
+        self.matchers.insert(skill.name.clone(), matchers);
         self.skills.push(skill);
     }
 
@@ -41,18 +125,53 @@ impl SkillRegistry {
         self.skills.iter().collect()
     }
 
-    /// Find skills with triggers matching the input (case-insensitive substring)
+    /// Summaries of every loaded skill, sorted by name
+    pub fn describe(&self) -> Vec<SkillSummary> {
+        let mut summaries: Vec<SkillSummary> = self
+            .skills
+            .iter()
+            .map(|skill| SkillSummary {
+                name: skill.name.clone(),
+                description: skill.description.clone(),
+                triggers: skill.triggers.clone(),
+                source: self.source_of(&skill.source_path),
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        summaries
+    }
+
+    fn source_of(&self, path: &Path) -> SkillSource {
+        let under = |dir: &Option<PathBuf>| dir.as_ref().is_some_and(|d| path.starts_with(d));
+        if under(&self.repo_dir) {
+            SkillSource::Repo
+        } else if under(&self.user_dir) {
+            SkillSource::User
+        } else {
+            SkillSource::Other
+        }
+    }
+
+    /// Find skills with triggers matching the input (case-insensitive),
+    /// ordered by descending priority then name.
+    /// Triggers prefixed with `re:` are regexes; others match as substrings.
     pub fn find_matching(&self, input: &str) -> Vec<&Skill> {
         let input_lower = input.to_lowercase();
-        self.skills
+        let mut matches: Vec<&Skill> = self
+            .skills
             .iter()
             .filter(|skill| {
-                skill
-                    .triggers
-                    .iter()
-                    .any(|trigger| input_lower.contains(&trigger.to_lowercase()))
+                self.matchers
+                    .get(&skill.name)
+                    .is_some_and(|m| m.iter().any(|t| t.matches(&input_lower)))
             })
-            .collect()
+            .collect();
+        matches.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        matches
     }
 }
 
@@ -73,6 +192,8 @@ mod tests {
             name: "test-skill".to_string(),
             description: None,
             triggers: vec!["test".to_string()],
+            allowed_tools: Vec::new(),
+            priority: 0,
             content: "content".to_string(),
             source_path: PathBuf::from("/test"),
         };
@@ -83,6 +204,63 @@ mod tests {
         assert_eq!(registry.get("nonexistent"), None);
     }
 
+    #[test]
+    fn test_describe_reports_source() {
+        let mut registry = SkillRegistry::new();
+        registry.user_dir = Some(PathBuf::from("/home/u/.looprs/skills"));
+        registry.repo_dir = Some(PathBuf::from("/repo/.looprs/skills"));
+        for (name, path) in [
+            ("zeta", "/repo/.looprs/skills/zeta/SKILL.md"),
+            ("alpha", "/home/u/.looprs/skills/alpha/SKILL.md"),
+            ("loose", "/elsewhere/SKILL.md"),
+        ] {
+            registry.register(Skill {
+                name: name.to_string(),
+                description: Some(format!("{name} skill")),
+                triggers: vec![name.to_string()],
+                allowed_tools: Vec::new(),
+                priority: 0,
+                content: "".to_string(),
+                source_path: PathBuf::from(path),
+            });
+        }
+
+        let summaries = registry.describe();
+        let listed: Vec<_> = summaries
+            .iter()
+            .map(|s| (s.name.as_str(), s.source))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("alpha", SkillSource::User),
+                ("loose", SkillSource::Other),
+                ("zeta", SkillSource::Repo),
+            ]
+        );
+        assert_eq!(summaries[0].description.as_deref(), Some("alpha skill"));
+        assert_eq!(summaries[0].triggers, vec!["alpha"]);
+    }
+
+    #[test]
+    fn test_allowed_tools_round_trips_through_registry() {
+        let mut registry = SkillRegistry::new();
+        registry.register(Skill {
+            name: "review".to_string(),
+            description: None,
+            triggers: vec!["review".to_string()],
+            allowed_tools: vec!["read".to_string(), "grep".to_string()],
+            priority: 0,
+            content: "".to_string(),
+            source_path: PathBuf::from("/test"),
+        });
+
+        assert_eq!(
+            registry.get("review").unwrap().allowed_tools,
+            vec!["read", "grep"]
+        );
+    }
+
     #[test]
     fn test_find_matching_case_insensitive() {
         let mut registry = SkillRegistry::new();
@@ -90,6 +268,8 @@ mod tests {
             name: "rust-testing".to_string(),
             description: None,
             triggers: vec!["cargo test".to_string(), "rust test".to_string()],
+            allowed_tools: Vec::new(),
+            priority: 0,
             content: "".to_string(),
             source_path: PathBuf::from("/test"),
         });
@@ -107,6 +287,8 @@ mod tests {
             name: "error-handling".to_string(),
             description: None,
             triggers: vec!["? operator".to_string()],
+            allowed_tools: Vec::new(),
+            priority: 0,
             content: "".to_string(),
             source_path: PathBuf::from("/test"),
         });
@@ -117,6 +299,44 @@ mod tests {
         assert_eq!(matches[0].name, "error-handling");
     }
 
+    fn regex_skill(trigger: &str) -> Skill {
+        Skill {
+            name: "testing".to_string(),
+            description: None,
+            triggers: vec![trigger.to_string()],
+            allowed_tools: Vec::new(),
+            priority: 0,
+            content: "".to_string(),
+            source_path: PathBuf::from("/test"),
+        }
+    }
+
+    #[test]
+    fn test_find_matching_regex_trigger_matches_word() {
+        let mut registry = SkillRegistry::new();
+        registry.register(regex_skill(r"re:\btests?\b"));
+
+        assert_eq!(registry.find_matching("Run the TESTS please").len(), 1);
+    }
+
+    #[test]
+    fn test_find_matching_regex_trigger_rejects_substring_hit() {
+        let mut registry = SkillRegistry::new();
+        registry.register(regex_skill(r"re:\btest\b"));
+
+        // A plain "test" trigger would fire on "latest"
+        assert!(registry.find_matching("show the latest release").is_empty());
+    }
+
+    #[test]
+    fn test_invalid_regex_trigger_is_ignored() {
+        let mut registry = SkillRegistry::new();
+        registry.register(regex_skill("re:("));
+
+        assert!(registry.find_matching("(").is_empty());
+        assert!(registry.get("testing").is_some());
+    }
+
     #[test]
     fn test_find_matching_multiple_skills() {
         let mut registry = SkillRegistry::new();
@@ -124,6 +344,8 @@ mod tests {
             name: "skill-1".to_string(),
             description: None,
             triggers: vec!["test".to_string()],
+            allowed_tools: Vec::new(),
+            priority: 0,
             content: "".to_string(),
             source_path: PathBuf::from("/test1"),
         });
@@ -131,6 +353,8 @@ mod tests {
             name: "skill-2".to_string(),
             description: None,
             triggers: vec!["testing".to_string()],
+            allowed_tools: Vec::new(),
+            priority: 0,
             content: "".to_string(),
             source_path: PathBuf::from("/test2"),
         });
@@ -139,4 +363,27 @@ mod tests {
         let matches = registry.find_matching("testing my code");
         assert_eq!(matches.len(), 2);
     }
+
+    #[test]
+    fn test_find_matching_orders_by_priority_then_name() {
+        let mut registry = SkillRegistry::new();
+        for (name, priority) in [("style", 0), ("docs", 0), ("safety", 10), ("cleanup", -5)] {
+            registry.register(Skill {
+                name: name.to_string(),
+                description: None,
+                triggers: vec!["deploy".to_string()],
+                allowed_tools: Vec::new(),
+                priority,
+                content: "".to_string(),
+                source_path: PathBuf::from("/test"),
+            });
+        }
+
+        let order: Vec<&str> = registry
+            .find_matching("deploy to prod")
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(order, vec!["safety", "docs", "style", "cleanup"]);
+    }
 }
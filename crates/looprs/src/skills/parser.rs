@@ -9,6 +9,10 @@ struct SkillFrontmatter {
     name: String,
     description: Option<String>,
     triggers: Vec<String>,
+    #[serde(default)]
+    allowed_tools: Vec<String>,
+    #[serde(default)]
+    priority: i32,
 }
 
 /// Parse SKILL.md file with YAML frontmatter  
@@ -36,6 +40,8 @@ pub fn parse_skill_file(path: &Path, content: &str) -> Result<super::Skill> {
         name: frontmatter.name,
         description: frontmatter.description,
         triggers: frontmatter.triggers,
+        allowed_tools: frontmatter.allowed_tools,
+        priority: frontmatter.priority,
         content: parts[2].trim().to_string(),
         source_path: path.to_path_buf(),
     })
@@ -144,6 +150,29 @@ Content only.
         assert_eq!(skill.description, None);
         assert_eq!(skill.triggers, vec!["test"]);
         assert_eq!(skill.content, "# Minimal Skill\n\nContent only.");
+        assert!(skill.allowed_tools.is_empty());
+        assert_eq!(skill.priority, 0);
+    }
+
+    #[test]
+    fn test_parse_allowed_tools() {
+        let content = r#"---
+name: review
+triggers:
+  - "review"
+allowed_tools:
+  - read
+  - grep
+priority: 5
+---
+Read-only review.
+"#;
+
+        let path = PathBuf::from("/test/review/SKILL.md");
+        let skill = parse_skill_file(&path, content).unwrap();
+
+        assert_eq!(skill.allowed_tools, vec!["read", "grep"]);
+        assert_eq!(skill.priority, 5);
     }
 
     #[test]
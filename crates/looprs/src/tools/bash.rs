@@ -1,4 +1,5 @@
 use super::ToolArgs;
+use super::ToolContext;
 use super::error::ToolError;
 use serde_json::Value;
 
@@ -7,10 +8,33 @@ use serde_json::Value;
 const MAX_OUTPUT_BYTES: usize = 512 * 1024; // 512 KiB
 
 // qual:allow(iosp) reason: "I/O boundary — parses args, runs shell, returns output"
-pub(super) fn tool_bash(args: &Value) -> Result<String, ToolError> {
+pub(super) fn tool_bash(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
     let args = ToolArgs::new(args);
     let cmd = args.get_str("cmd")?;
-    let output = crate::shell::run_bash_command(cmd)?;
+    let dir = match args.get_str_optional("cwd")? {
+        Some(cwd) => {
+            let dir = ctx.resolve_path(cwd)?;
+            if !dir.is_dir() {
+                return Err(ToolError::InvalidPath(format!(
+                    "cwd is not an existing directory: {cwd}"
+                )));
+            }
+            Some(dir)
+        }
+        None => None,
+    };
+    let output = match (ctx.output_sink(), ctx.cancel(), dir.as_deref()) {
+        (None, None, Some(dir)) => crate::shell::run_bash_command_in(cmd, dir)?,
+        (None, None, None) => crate::shell::run_bash_command(cmd)?,
+        (sink, cancel, dir) => {
+            let on_line = |line: &str| {
+                if let Some(sink) = sink {
+                    sink(line);
+                }
+            };
+            crate::shell::run_bash_command_streaming(cmd, dir, &on_line, cancel)?
+        }
+    };
 
     let stdout = truncate_bytes(&output.stdout, MAX_OUTPUT_BYTES);
     let mut result = String::from_utf8_lossy(stdout).to_string();
@@ -54,13 +78,68 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    fn ctx(dir: &std::path::Path) -> ToolContext {
+        ToolContext::from_working_dir(dir.to_path_buf(), crate::fs_mode::FsMode::Write)
+    }
+
     #[test]
     fn bash_runs_command() {
+        let dir = tempfile::tempdir().unwrap();
         let args = json!({"cmd": "echo ok"});
-        let out = tool_bash(&args).expect("bash tool should run");
+        let out = tool_bash(&args, &ctx(dir.path())).expect("bash tool should run");
         assert!(out.contains("ok"));
     }
 
+    #[test]
+    fn bash_cwd_runs_in_subdirectory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+
+        let out = tool_bash(&json!({"cmd": "pwd", "cwd": "sub"}), &ctx(dir.path())).unwrap();
+
+        let expected = dir.path().join("sub").canonicalize().unwrap();
+        assert_eq!(std::path::Path::new(out.trim()), expected);
+    }
+
+    #[test]
+    fn bash_cwd_rejects_escapes_and_missing_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("file.txt"), "x").unwrap();
+        let ctx = ctx(dir.path());
+
+        for cwd in ["..", "/tmp", "sub/../../.."] {
+            let err = tool_bash(&json!({"cmd": "pwd", "cwd": cwd}), &ctx).unwrap_err();
+            assert!(
+                matches!(err, ToolError::PathOutsideWorkingDir(_)),
+                "{cwd}: {err}"
+            );
+        }
+        for cwd in ["missing", "file.txt"] {
+            let err = tool_bash(&json!({"cmd": "pwd", "cwd": cwd}), &ctx).unwrap_err();
+            assert!(matches!(err, ToolError::InvalidPath(_)), "{cwd}: {err}");
+        }
+    }
+
+    #[test]
+    fn bash_streams_lines_to_output_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut ctx = ctx(dir.path());
+        let seen = lines.clone();
+        ctx.set_output_sink(Some(std::sync::Arc::new(move |line: &str| {
+            seen.lock().unwrap().push(line.to_string());
+        })));
+
+        let out = tool_bash(
+            &json!({"cmd": "for i in 1 2 3; do echo step $i; sleep 0.05; done"}),
+            &ctx,
+        )
+        .unwrap();
+
+        assert_eq!(*lines.lock().unwrap(), ["step 1", "step 2", "step 3"]);
+        assert_eq!(out, "step 1\nstep 2\nstep 3\n");
+    }
+
     #[test]
     fn truncate_bytes_within_limit() {
         let data = b"hello";
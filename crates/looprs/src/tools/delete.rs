@@ -0,0 +1,172 @@
+use super::ToolArgs;
+use super::ToolContext;
+use super::error::ToolError;
+use super::normalize_relative;
+use crate::trash::Trash;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// qual:allow(iosp) reason: "I/O boundary — parses args, trashes or removes a path"
+pub(super) fn tool_delete(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+    let args = ToolArgs::new(args);
+    let path = args.get_str("path")?;
+    let permanent = args.get_bool("permanent", false);
+
+    let (base, full_path) = resolve_entry(ctx, path, "delete")?;
+    let Ok(metadata) = fs::symlink_metadata(&full_path) else {
+        return Err(ToolError::FileNotFound(path.to_string()));
+    };
+    if full_path
+        .strip_prefix(&base)
+        .is_ok_and(|rel| rel.starts_with(".git"))
+    {
+        return Err(ToolError::InvalidPath(format!(
+            "refusing to delete {path}: it is inside .git"
+        )));
+    }
+
+    // The trash is rooted at the canonical working dir so original paths
+    // come out relative.
+    let trash = Trash::new(&base);
+    if full_path.starts_with(trash.root()) {
+        return Err(ToolError::InvalidPath(format!(
+            "{path} is already in the trash"
+        )));
+    }
+
+    if permanent {
+        if metadata.is_dir() {
+            fs::remove_dir_all(&full_path)?;
+        } else {
+            fs::remove_file(&full_path)?;
+        }
+        return Ok(format!("Deleted {path} permanently"));
+    }
+
+    let entry = trash
+        .trash(&full_path)
+        .map_err(|e| ToolError::Io(io::Error::other(format!("{e:#}"))))?;
+    let location = trash.root().join(&entry.id);
+    let location = location.strip_prefix(&base).unwrap_or(&location);
+    Ok(format!(
+        "Moved {path} to {}; undo with /trash restore {}",
+        location.display(),
+        entry.id
+    ))
+}
+
+/// Resolve `path` like `ToolContext::resolve_path`, but canonicalize only its
+/// parent: a symlink is deleted or moved itself, never the file it points to.
+/// Returns the canonical working dir and the path. The working dir itself is
+/// refused; `verb` names the operation in that error.
+pub(super) fn resolve_entry(
+    ctx: &ToolContext,
+    path: &str,
+    verb: &str,
+) -> Result<(PathBuf, PathBuf), ToolError> {
+    let p = Path::new(path);
+    if p.is_absolute() {
+        return Err(ToolError::PathOutsideWorkingDir(path.to_string()));
+    }
+    let rel =
+        normalize_relative(p).map_err(|_| ToolError::PathOutsideWorkingDir(path.to_string()))?;
+    let Some(name) = rel.file_name() else {
+        return Err(ToolError::InvalidPath(format!(
+            "refusing to {verb} {path}: it is the working directory"
+        )));
+    };
+    let base = ctx.working_dir.canonicalize()?;
+    let parent = base
+        .join(rel.parent().unwrap_or(Path::new("")))
+        .canonicalize()
+        .map_err(|_| ToolError::FileNotFound(path.to_string()))?;
+    if !parent.starts_with(&base) {
+        return Err(ToolError::PathOutsideWorkingDir(path.to_string()));
+    }
+    Ok((base, parent.join(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn delete_moves_file_to_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/old.rs"), "hello").unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_delete(&json!({"path": "src/old.rs"}), &ctx).unwrap();
+        assert!(out.contains(".looprs/trash/"), "{out}");
+        assert!(!dir.path().join("src/old.rs").exists());
+
+        let entries = Trash::new(&dir.path().canonicalize().unwrap())
+            .list()
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].original_path, std::path::Path::new("src/old.rs"));
+        assert!(out.contains(&entries[0].id));
+    }
+
+    #[test]
+    fn permanent_delete_skips_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        tool_delete(&json!({"path": "a.txt", "permanent": true}), &ctx).unwrap();
+
+        assert!(!dir.path().join("a.txt").exists());
+        assert!(!dir.path().join(".looprs/trash").exists());
+    }
+
+    #[test]
+    fn delete_refuses_working_dir_and_git() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/config"), "x").unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        for path in [".", "src/..", ".git", ".git/config"] {
+            let err = tool_delete(&json!({"path": path, "permanent": true}), &ctx).unwrap_err();
+            assert!(matches!(err, ToolError::InvalidPath(_)), "{path}: {err:?}");
+        }
+        assert!(dir.path().join(".git/config").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn delete_removes_symlink_not_target() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("target.txt"), "keep").unwrap();
+        std::os::unix::fs::symlink("target.txt", dir.path().join("link")).unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        tool_delete(&json!({"path": "link", "permanent": true}), &ctx).unwrap();
+
+        assert!(fs::symlink_metadata(dir.path().join("link")).is_err());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("target.txt")).unwrap(),
+            "keep"
+        );
+    }
+
+    #[test]
+    fn delete_blocks_path_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let err = tool_delete(&json!({"path": "../escape.txt"}), &ctx).unwrap_err();
+        assert!(matches!(err, ToolError::PathOutsideWorkingDir(_)));
+    }
+}
@@ -0,0 +1,112 @@
+//! Line diffs for previewing `write` and `edit` before they touch disk.
+
+use super::edit::apply_edit;
+use super::{Tool, ToolArgs, ToolContext, enforce_fs_mode};
+use serde_json::Value;
+use similar::TextDiff;
+use std::fs;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+/// Unchanged lines kept around each change.
+const CONTEXT_LINES: usize = 3;
+/// Past this the diff settles for a coarser, still-correct result instead of
+/// a minimal one, so a huge rewrite can't stall the approval prompt.
+const DIFF_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// What `write` or `edit` would change, as a unified diff headed by the path.
+/// `None` for other tools, for calls the tool or `fs_mode` would reject, for
+/// files that exist but can't be read, and for no-ops.
+// qual:allow(iosp) reason: "I/O boundary — reads the current file contents"
+pub fn preview_change(tool: &str, args: &Value, ctx: &ToolContext) -> Option<String> {
+    let tool = match tool {
+        "write" => Tool::Write,
+        "edit" => Tool::Edit,
+        _ => return None,
+    };
+    enforce_fs_mode(tool, args, ctx).ok()?;
+    let args = ToolArgs::new(args);
+    let path = args.get_str("path").ok()?;
+    let full_path = ctx.resolve_path(path).ok()?;
+    let (old, new) = match tool {
+        Tool::Write => {
+            let old = match fs::read_to_string(&full_path) {
+                Ok(old) => old,
+                Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+                Err(_) => return None,
+            };
+            (old, args.get_str("content").ok()?.to_string())
+        }
+        _ => {
+            let old = fs::read_to_string(&full_path).ok()?;
+            let new = apply_edit(&old, &args).ok()?;
+            (old, new)
+        }
+    };
+    let diff = unified_diff(&old, &new);
+    (!diff.is_empty()).then(|| format!("--- {path}\n+++ {path}\n{diff}"))
+}
+
+/// Unified diff hunks (`@@ -a,b +c,d @@`) turning `old` into `new`, or an
+/// empty string when they match.
+pub fn unified_diff(old: &str, new: &str) -> String {
+    TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(old, new)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unified_diff_shows_changes_with_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "@@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -10,3 +10,4 @@\n j\n k\n l\n+m\n"
+        );
+        assert_eq!(unified_diff(old, old), "");
+        assert_eq!(unified_diff("", "x\n"), "@@ -0,0 +1 @@\n+x\n");
+    }
+
+    #[test]
+    fn preview_change_diffs_edit_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "fn main() {\n    foo();\n}\n").unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let args = json!({"path": "a.rs", "old": "foo", "new": "bar"});
+        let preview = preview_change("edit", &args, &ctx).unwrap();
+        assert_eq!(
+            preview,
+            "--- a.rs\n+++ a.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    foo();\n+    bar();\n }\n"
+        );
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "fn main() {\n    foo();\n}\n"
+        );
+
+        let missing = json!({"path": "a.rs", "old": "nope", "new": "bar"});
+        assert_eq!(preview_change("edit", &missing, &ctx), None);
+        let read_only =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Read);
+        assert_eq!(preview_change("edit", &args, &read_only), None);
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let unreadable = json!({"path": "sub", "content": "hi\n"});
+        assert_eq!(preview_change("write", &unreadable, &ctx), None);
+        let created = json!({"path": "new.txt", "content": "hi\n"});
+        assert_eq!(
+            preview_change("write", &created, &ctx).as_deref(),
+            Some("--- new.txt\n+++ new.txt\n@@ -0,0 +1 @@\n+hi\n")
+        );
+    }
+}
@@ -8,31 +8,47 @@ use std::fs;
 pub(super) fn tool_edit(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
     let args = ToolArgs::new(args);
     let path = args.get_str("path")?;
-    let old = args.get_str("old")?;
-    let new = args.get_str("new")?;
-    let all = args.get_bool("all", false);
 
     let full_path = ctx.resolve_path(path)?;
     let text =
         fs::read_to_string(&full_path).map_err(|_| ToolError::FileNotFound(path.to_string()))?;
 
+    fs::write(&full_path, apply_edit(&text, &args)?)?;
+    Ok("ok".to_string())
+}
+
+/// `text` with the edit described by `args` applied, or the error `edit`
+/// would report. Shared with the change preview so both agree.
+pub(super) fn apply_edit(text: &str, args: &ToolArgs) -> Result<String, ToolError> {
+    let old = args.get_str("old")?;
+    let new = args.get_str("new")?;
+    let all = args.get_bool("all", false);
+    let expect_count = args.get_u64("expect_count")?;
+
     if !text.contains(old) {
         return Err(ToolError::PatternNotFound(old.to_string()));
     }
 
     let count = text.matches(old).count();
+    if let Some(expected) = expect_count
+        && count as u64 != expected
+    {
+        return Err(ToolError::ReplaceCountMismatch {
+            expected,
+            found: count,
+        });
+    }
+    // A matching expect_count is an explicit request to replace every match.
+    let all = all || expect_count.is_some();
     if !all && count > 1 {
         return Err(ToolError::AmbiguousPattern(count));
     }
 
-    let replacement = if all {
+    Ok(if all {
         text.replace(old, new)
     } else {
         text.replacen(old, new, 1)
-    };
-
-    fs::write(&full_path, replacement)?;
-    Ok("ok".to_string())
+    })
 }
 
 #[cfg(test)]
@@ -57,4 +73,40 @@ mod tests {
         let content = fs::read_to_string(file).unwrap();
         assert_eq!(content, "hello there");
     }
+
+    #[test]
+    fn edit_with_matching_expect_count_replaces_all() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "foo(); foo(); foo();").unwrap();
+
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+        let args = json!({"path": "a.rs", "old": "foo", "new": "bar", "expect_count": 3});
+
+        assert_eq!(tool_edit(&args, &ctx).unwrap(), "ok");
+        assert_eq!(fs::read_to_string(file).unwrap(), "bar(); bar(); bar();");
+    }
+
+    #[test]
+    fn edit_with_mismatched_expect_count_leaves_file_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.rs");
+        fs::write(&file, "foo(); foo(); foo();").unwrap();
+
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+        let args =
+            json!({"path": "a.rs", "old": "foo", "new": "bar", "all": true, "expect_count": 2});
+
+        let err = tool_edit(&args, &ctx).unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::ReplaceCountMismatch {
+                expected: 2,
+                found: 3
+            }
+        ));
+        assert_eq!(fs::read_to_string(file).unwrap(), "foo(); foo(); foo();");
+    }
 }
@@ -18,6 +18,10 @@ pub enum ToolError {
     #[diagnostic(code(looprs::tool::ambiguous_pattern))]
     AmbiguousPattern(usize),
 
+    #[error("Expected {expected} occurrence(s) to replace but found {found}; file left unchanged")]
+    #[diagnostic(code(looprs::tool::replace_count_mismatch))]
+    ReplaceCountMismatch { expected: u64, found: usize },
+
     #[error("Missing required parameter: {0}")]
     #[diagnostic(code(looprs::tool::missing_parameter))]
     MissingParameter(String),
@@ -26,10 +30,18 @@ pub enum ToolError {
     #[diagnostic(code(looprs::tool::invalid_parameter_type))]
     InvalidParameterType { key: String, expected: &'static str },
 
+    #[error("Invalid value for {key}: '{value}' (expected one of: {expected})")]
+    #[diagnostic(code(looprs::tool::invalid_parameter_value))]
+    InvalidParameterValue {
+        key: String,
+        value: String,
+        expected: &'static str,
+    },
+
     #[error("Unknown tool: {0}")]
     #[diagnostic(
         code(looprs::tool::unknown),
-        help("Available tools: read, write, edit, glob, grep, nu, bash")
+        help("Available tools: read, write, edit, move, delete, glob, grep, tree, nu, bash")
     )]
     UnknownTool(String),
 
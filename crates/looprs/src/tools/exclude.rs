@@ -0,0 +1,73 @@
+//! `exclude` parameter shared by `glob` and `grep`.
+
+use std::path::Path;
+
+use super::ToolArgs;
+use super::error::ToolError;
+
+/// Glob patterns whose matches are dropped from a tool's results. Patterns
+/// are matched against paths relative to the search base, and `*` crosses
+/// directories, so `*_test.rs` excludes test files at any depth.
+#[derive(Debug)]
+pub(super) struct Exclude {
+    patterns: Vec<glob::Pattern>,
+}
+
+impl Exclude {
+    /// Read `exclude` from `args`: a single glob or a list of globs.
+    pub(super) fn from_args(args: &ToolArgs) -> Result<Self, ToolError> {
+        let patterns = args
+            .get_str_list("exclude")?
+            .into_iter()
+            .map(glob::Pattern::new)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// The raw patterns, for handing to `rg --glob`.
+    pub(super) fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(glob::Pattern::as_str)
+    }
+
+    /// Whether `path` (under `base`) matches any exclude pattern.
+    pub(super) fn excludes(&self, base: &Path, path: &Path) -> bool {
+        let rel = path.strip_prefix(base).unwrap_or(path);
+        let rel = if rel.as_os_str().is_empty() {
+            // `base` is the file itself; match on its name.
+            Path::new(path.file_name().unwrap_or_default())
+        } else {
+            rel
+        };
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_path(rel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn accepts_a_single_glob_or_a_list() {
+        let base = Path::new("/repo");
+        let single = json!({"exclude": "*_test.rs"});
+        let exclude = Exclude::from_args(&ToolArgs::new(&single)).unwrap();
+        assert!(exclude.excludes(base, Path::new("/repo/src/a_test.rs")));
+        assert!(!exclude.excludes(base, Path::new("/repo/src/a.rs")));
+
+        let list = json!({"exclude": ["target/*", "*.md"]});
+        let exclude = Exclude::from_args(&ToolArgs::new(&list)).unwrap();
+        assert!(exclude.excludes(base, Path::new("/repo/target/debug/x")));
+        assert!(exclude.excludes(base, Path::new("/repo/README.md")));
+        assert!(!exclude.excludes(base, Path::new("/repo/src/lib.rs")));
+
+        let none = json!({});
+        let exclude = Exclude::from_args(&ToolArgs::new(&none)).unwrap();
+        assert_eq!(exclude.patterns().count(), 0);
+
+        let bad = json!({"exclude": 3});
+        assert!(Exclude::from_args(&ToolArgs::new(&bad)).is_err());
+    }
+}
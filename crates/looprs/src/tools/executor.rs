@@ -1,20 +1,26 @@
 use serde_json::Value;
 
-use crate::tools::{ToolContext, ToolError, execute_tool};
+use crate::tools::{ToolContext, ToolError, ToolOutput, execute_tool};
 
 /// Port: dispatch a named agent tool call.
 ///
 /// Abstracts the free `execute_tool` function so the Agent can be tested
 /// with a stub executor instead of a real subprocess/filesystem backend.
 pub trait ToolExecutor: Send + Sync {
-    fn execute(&self, name: &str, args: &Value, ctx: &ToolContext) -> Result<String, ToolError>;
+    fn execute(&self, name: &str, args: &Value, ctx: &ToolContext)
+    -> Result<ToolOutput, ToolError>;
 }
 
 /// Production adapter: delegates to `tools::execute_tool`.
 pub struct DefaultToolExecutor;
 
 impl ToolExecutor for DefaultToolExecutor {
-    fn execute(&self, name: &str, args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+    fn execute(
+        &self,
+        name: &str,
+        args: &Value,
+        ctx: &ToolContext,
+    ) -> Result<ToolOutput, ToolError> {
         execute_tool(name, args, ctx)
     }
 }
@@ -36,7 +42,12 @@ impl Default for StubToolExecutor {
 
 #[cfg(test)]
 impl ToolExecutor for StubToolExecutor {
-    fn execute(&self, _name: &str, _args: &Value, _ctx: &ToolContext) -> Result<String, ToolError> {
-        Ok(self.response.clone())
+    fn execute(
+        &self,
+        _name: &str,
+        _args: &Value,
+        _ctx: &ToolContext,
+    ) -> Result<ToolOutput, ToolError> {
+        Ok(ToolOutput::Text(self.response.clone()))
     }
 }
@@ -1,18 +1,66 @@
 use super::ToolArgs;
 use super::ToolContext;
+use super::ToolOutput;
 use super::error::ToolError;
+use super::exclude::Exclude;
 use crate::config::{MAX_GLOB_HITS, MAX_GLOB_OUTPUT_CHARS};
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How `tool_glob` orders its hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GlobSort {
+    /// Newest first.
+    Mtime,
+    /// File name, lexicographic.
+    Name,
+    /// Largest first.
+    Size,
+    /// Full path, lexicographic.
+    Path,
+}
+
+impl GlobSort {
+    fn parse(value: Option<&str>) -> Result<Self, ToolError> {
+        match value.unwrap_or("mtime") {
+            "mtime" => Ok(Self::Mtime),
+            "name" => Ok(Self::Name),
+            "size" => Ok(Self::Size),
+            "path" => Ok(Self::Path),
+            other => Err(ToolError::InvalidParameterValue {
+                key: "sort".to_string(),
+                value: other.to_string(),
+                expected: "mtime, name, size, path",
+            }),
+        }
+    }
+
+    fn apply(self, paths: &mut [PathBuf]) {
+        match self {
+            Self::Mtime => paths.sort_by_cached_key(|p| {
+                std::cmp::Reverse(fs::metadata(p).and_then(|m| m.modified()).ok())
+            }),
+            Self::Name => paths.sort_by(|a, b| a.file_name().cmp(&b.file_name()).then(a.cmp(b))),
+            Self::Size => paths.sort_by_cached_key(|p| {
+                std::cmp::Reverse(fs::metadata(p).map(|m| m.len()).unwrap_or(0))
+            }),
+            Self::Path => paths.sort(),
+        }
+    }
+}
 
 // qual:allow(iosp) reason: "I/O boundary — parses args, walks filesystem"
-pub(super) fn tool_glob(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+pub(super) fn tool_glob(args: &Value, ctx: &ToolContext) -> Result<ToolOutput, ToolError> {
     let args = ToolArgs::new(args);
     let pattern = args.get_str("pat")?;
     let path_prefix = args.get_str_optional("path")?.unwrap_or(".");
+    let sort = GlobSort::parse(args.get_str_optional("sort")?)?;
+    let reverse = args.get_bool("reverse", false);
+    let exclude = Exclude::from_args(&args)?;
 
     // Prevent escaping the base directory via the pattern itself.
-    let pat_path = std::path::Path::new(pattern);
+    let pat_path = Path::new(pattern);
     if pat_path.is_absolute()
         || pat_path
             .components()
@@ -27,16 +75,21 @@ pub(super) fn tool_glob(args: &Value, ctx: &ToolContext) -> Result<String, ToolE
         .to_str()
         .ok_or_else(|| ToolError::InvalidPath(pattern.to_string()))?;
 
-    let mut paths: Vec<_> = glob::glob(pattern_str)?.filter_map(Result::ok).collect();
+    let mut paths: Vec<_> = glob::glob(pattern_str)?
+        .filter_map(Result::ok)
+        .filter(|path| !exclude.excludes(&base, path))
+        .collect();
 
-    paths.sort_by(|a, b| {
-        let m_a = fs::metadata(a).and_then(|m| m.modified()).ok();
-        let m_b = fs::metadata(b).and_then(|m| m.modified()).ok();
-        m_b.cmp(&m_a)
-    });
+    sort.apply(&mut paths);
+    if reverse {
+        paths.reverse();
+    }
 
     if paths.is_empty() {
-        return Ok("none".to_string());
+        return Ok(ToolOutput::Structured {
+            text: "none".to_string(),
+            data: json!([]),
+        });
     }
 
     let mut omitted_entries = 0usize;
@@ -46,6 +99,7 @@ pub(super) fn tool_glob(args: &Value, ctx: &ToolContext) -> Result<String, ToolE
     }
 
     let mut output = String::new();
+    let mut listed = Vec::new();
     let mut truncated_by_chars = false;
 
     for (index, path) in paths.iter().enumerate() {
@@ -63,6 +117,7 @@ pub(super) fn tool_glob(args: &Value, ctx: &ToolContext) -> Result<String, ToolE
             output.push('\n');
         }
         output.push_str(&line);
+        listed.push(line);
     }
 
     if omitted_entries > 0 {
@@ -83,7 +138,10 @@ pub(super) fn tool_glob(args: &Value, ctx: &ToolContext) -> Result<String, ToolE
         }
     }
 
-    Ok(output)
+    Ok(ToolOutput::Structured {
+        text: output,
+        data: json!(listed),
+    })
 }
 
 #[cfg(test)]
@@ -103,11 +161,106 @@ mod tests {
             ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
         let args = json!({"pat": "*.txt"});
 
-        let out = tool_glob(&args, &ctx).unwrap();
+        let out = tool_glob(&args, &ctx).unwrap().to_display_string();
         assert!(out.contains("a.txt"));
         assert!(out.contains("b.txt"));
     }
 
+    #[test]
+    fn glob_structured_output_is_array_of_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        fs::write(dir.path().join("b.rs"), "b").unwrap();
+
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+        let out = tool_glob(&json!({"pat": "*.txt"}), &ctx).unwrap();
+
+        let data = out.structured().expect("glob returns structured output");
+        let paths = data.as_array().unwrap();
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].as_str().unwrap().ends_with("a.txt"));
+    }
+
+    fn listed(out: &ToolOutput) -> Vec<String> {
+        out.structured()
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                Path::new(p.as_str().unwrap())
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn glob_name_sort_is_lexicographic_and_reverse_inverts_it() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["b.txt", "C.txt", "a.txt", "aa.txt"] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_glob(&json!({"pat": "*.txt", "sort": "name"}), &ctx).unwrap();
+        assert_eq!(listed(&out), ["C.txt", "a.txt", "aa.txt", "b.txt"]);
+
+        let out = tool_glob(
+            &json!({"pat": "*.txt", "sort": "name", "reverse": true}),
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(listed(&out), ["b.txt", "aa.txt", "a.txt", "C.txt"]);
+    }
+
+    #[test]
+    fn glob_size_sort_is_largest_first_and_unknown_sort_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("small.txt"), "x").unwrap();
+        fs::write(dir.path().join("big.txt"), "x".repeat(100)).unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_glob(&json!({"pat": "*.txt", "sort": "size"}), &ctx).unwrap();
+        assert_eq!(listed(&out), ["big.txt", "small.txt"]);
+
+        let err = tool_glob(&json!({"pat": "*.txt", "sort": "random"}), &ctx).unwrap_err();
+        assert!(
+            matches!(err, ToolError::InvalidParameterValue { .. }),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn glob_exclude_drops_matching_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        for name in ["lib.rs", "lib_test.rs", "main.rs", "main_test.rs"] {
+            fs::write(dir.path().join("src").join(name), name).unwrap();
+        }
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_glob(
+            &json!({"pat": "**/*.rs", "sort": "name", "exclude": "*_test.rs"}),
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(listed(&out), ["lib.rs", "main.rs"]);
+
+        let out = tool_glob(
+            &json!({"pat": "**/*.rs", "sort": "name", "exclude": ["*_test.rs", "src/main.rs"]}),
+            &ctx,
+        )
+        .unwrap();
+        assert_eq!(listed(&out), ["lib.rs"]);
+    }
+
     #[test]
     fn glob_returns_none_when_empty() {
         let dir = tempfile::tempdir().unwrap();
@@ -116,7 +269,8 @@ mod tests {
         let args = json!({"pat": "*.txt"});
 
         let out = tool_glob(&args, &ctx).unwrap();
-        assert_eq!(out, "none");
+        assert_eq!(out.to_display_string(), "none");
+        assert_eq!(out.structured(), Some(&json!([])));
     }
 
     #[test]
@@ -131,7 +285,7 @@ mod tests {
             ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
         let args = json!({"pat": "*.txt"});
 
-        let out = tool_glob(&args, &ctx).unwrap();
+        let out = tool_glob(&args, &ctx).unwrap().to_display_string();
         assert!(out.contains("[truncated glob results:"));
         assert!(out.contains("omitted"));
     }
@@ -1,10 +1,13 @@
 use super::ToolArgs;
 use super::ToolContext;
+use super::ToolOutput;
 use super::error::ToolError;
+use super::exclude::Exclude;
 use regex::Regex;
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::ffi::OsString;
 use std::fs;
+use std::path::PathBuf;
 
 use super::availability;
 use crate::config::MAX_GREP_HITS;
@@ -13,36 +16,60 @@ use crate::plugins::binaries::Rg;
 
 /// Try to use ripgrep (rg) if available, fall back to pure regex implementation
 // qual:allow(iosp) reason: "I/O boundary — parses args, searches files via rg or regex"
-pub(super) fn tool_grep(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+pub(super) fn tool_grep(args: &Value, ctx: &ToolContext) -> Result<ToolOutput, ToolError> {
     let args = ToolArgs::new(args);
     let pat_str = args.get_str("pat")?;
     let path_prefix = args.get_str_optional("path")?.unwrap_or(".");
+    let exclude = Exclude::from_args(&args)?;
 
+    // A file path scans just that file; a directory is searched recursively.
     let base = ctx.resolve_path(path_prefix)?;
 
     // Try rg first if available
-    if availability::is_rg_available()
-        && let Ok(result) = try_rg(pat_str, &base)
-    {
-        return Ok(result);
-    }
+    let text = match availability::is_rg_available() {
+        true => {
+            try_rg(pat_str, &base, &exclude).or_else(|_| grep_fallback(pat_str, &base, &exclude))?
+        }
+        // Fall back to pure Rust implementation
+        false => grep_fallback(pat_str, &base, &exclude)?,
+    };
+    let data = structured_hits(&text);
+    Ok(ToolOutput::Structured { text, data })
+}
 
-    // Fall back to pure Rust implementation
-    grep_fallback(pat_str, &base)
+/// Parse `path:line:text` hit lines into `{matches: [{path, line, text}], count}`.
+fn structured_hits(text: &str) -> Value {
+    let matches: Vec<Value> = text
+        .lines()
+        .filter_map(|hit| {
+            let mut parts = hit.splitn(3, ':');
+            let path = parts.next()?;
+            let line = parts.next()?.parse::<u64>().ok()?;
+            let text = parts.next()?.trim();
+            Some(json!({"path": path, "line": line, "text": text}))
+        })
+        .collect();
+    json!({"count": matches.len(), "matches": matches})
 }
 
-/// Try to use ripgrep for searching
-fn try_rg(pattern: &str, path: &std::path::Path) -> Result<String, ToolError> {
-    let args: Vec<OsString> = vec![
+/// Try to use ripgrep for searching. Excludes become negated `--glob`s, so
+/// they apply on top of rg's own `.gitignore` handling.
+fn try_rg(pattern: &str, path: &std::path::Path, exclude: &Exclude) -> Result<String, ToolError> {
+    let mut args: Vec<OsString> = vec![
         "--max-count".into(),
         MAX_GREP_HITS.to_string().into(),
         "--line-number".into(),
+        "--with-filename".into(),
         "--no-heading".into(),
         "--color".into(),
         "never".into(),
-        pattern.into(),
-        path.as_os_str().to_os_string(),
     ];
+    for excluded in exclude.patterns() {
+        args.push("--glob".into());
+        args.push(format!("!{excluded}").into());
+    }
+    args.push(pattern.into());
+    args.push(path.as_os_str().to_os_string());
 
     let output = Rg::system()
         .output(args)
@@ -66,20 +93,31 @@ fn try_rg(pattern: &str, path: &std::path::Path) -> Result<String, ToolError> {
 }
 
 /// Pure Rust fallback using regex
-fn grep_fallback(pat_str: &str, base: &std::path::Path) -> Result<String, ToolError> {
+fn grep_fallback(
+    pat_str: &str,
+    base: &std::path::Path,
+    exclude: &Exclude,
+) -> Result<String, ToolError> {
     let re = Regex::new(pat_str)?;
-    let glob_pattern = base.join("**/*");
-    let pattern_str = glob_pattern
-        .to_str()
-        .ok_or_else(|| ToolError::InvalidPath(base.display().to_string()))?;
+    let files: Vec<PathBuf> = if base.is_file() {
+        vec![base.to_path_buf()]
+    } else {
+        let glob_pattern = base.join("**/*");
+        let pattern_str = glob_pattern
+            .to_str()
+            .ok_or_else(|| ToolError::InvalidPath(base.display().to_string()))?;
+        glob::glob(pattern_str)?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.is_file())
+            .collect()
+    };
+    let files = files
+        .into_iter()
+        .filter(|entry| !exclude.excludes(base, entry));
 
     let mut hits = Vec::new();
 
-    for entry in glob::glob(pattern_str)?.filter_map(Result::ok) {
-        if !entry.is_file() {
-            continue;
-        }
-
+    for entry in files {
         let Ok(content) = fs::read_to_string(&entry) else {
             continue;
         };
@@ -120,7 +158,7 @@ mod tests {
             ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
         let args = json!({"pat": "match"});
 
-        let out = tool_grep(&args, &ctx).unwrap();
+        let out = tool_grep(&args, &ctx).unwrap().to_display_string();
         assert!(out.contains("match me"));
     }
 
@@ -134,7 +172,7 @@ mod tests {
             ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
         let args = json!({"pat": "xyz"});
 
-        let out = tool_grep(&args, &ctx).unwrap();
+        let out = tool_grep(&args, &ctx).unwrap().to_display_string();
         assert_eq!(out, "none");
     }
 
@@ -153,6 +191,53 @@ mod tests {
         assert!(wrong.to_string().contains("Missing required parameter"));
     }
 
+    #[test]
+    fn grep_path_to_file_scans_only_that_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src").join("a.rs"), "fn target() {}\n").unwrap();
+        fs::write(dir.path().join("src").join("b.rs"), "fn target() {}\n").unwrap();
+
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let file_hits = tool_grep(&json!({"pat": "target", "path": "src/a.rs"}), &ctx).unwrap();
+        let data = file_hits.structured().unwrap();
+        assert_eq!(data["count"], 1);
+        assert!(
+            data["matches"][0]["path"]
+                .as_str()
+                .unwrap()
+                .ends_with("a.rs")
+        );
+
+        let dir_hits = tool_grep(&json!({"pat": "target", "path": "src"}), &ctx).unwrap();
+        assert_eq!(dir_hits.structured().unwrap()["count"], 2);
+
+        let err = tool_grep(&json!({"pat": "target", "path": "../elsewhere.rs"}), &ctx);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn grep_exclude_skips_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("lib.rs"), "needle\n").unwrap();
+        fs::write(dir.path().join("lib_test.rs"), "needle\n").unwrap();
+
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+        let out = tool_grep(&json!({"pat": "needle", "exclude": "*_test.rs"}), &ctx).unwrap();
+
+        let data = out.structured().unwrap();
+        assert_eq!(data["count"], 1);
+        assert!(
+            data["matches"][0]["path"]
+                .as_str()
+                .unwrap()
+                .ends_with("lib.rs")
+        );
+    }
+
     #[test]
     fn grep_handles_regex_patterns() {
         let dir = tempfile::tempdir().unwrap();
@@ -163,9 +248,30 @@ mod tests {
             ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
         let args = json!({"pat": "test\\d+"});
 
-        let out = tool_grep(&args, &ctx).unwrap();
+        let out = tool_grep(&args, &ctx).unwrap().to_display_string();
         assert!(out.contains("test123"));
         assert!(out.contains("test456"));
         assert!(!out.contains("hello"));
     }
+
+    #[test]
+    fn grep_structured_output_has_paths_and_line_numbers() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "hello\nmatch me\n").unwrap();
+
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+        let out = tool_grep(&json!({"pat": "match"}), &ctx).unwrap();
+
+        let data = out.structured().unwrap();
+        assert_eq!(data["count"], 1);
+        assert_eq!(data["matches"][0]["line"], 2);
+        assert_eq!(data["matches"][0]["text"], "match me");
+        assert!(
+            data["matches"][0]["path"]
+                .as_str()
+                .unwrap()
+                .ends_with("a.txt")
+        );
+    }
 }
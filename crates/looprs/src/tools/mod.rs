@@ -1,15 +1,24 @@
 mod availability;
 mod bash;
+mod delete;
+mod diff;
 mod edit;
 pub mod error;
+mod exclude;
 pub mod executor;
 mod glob;
 mod grep;
+mod r#move;
 mod nu;
+mod output;
 mod read;
+mod tree;
 mod write;
 
+pub use diff::{preview_change, unified_diff};
 pub use executor::{DefaultToolExecutor, ToolExecutor};
+pub use output::ToolOutput;
+pub(crate) use read::read_line_window;
 
 use serde_json::{Value, json};
 use std::env;
@@ -19,6 +28,7 @@ use std::sync::{
     atomic::{AtomicU8, Ordering},
 };
 
+use crate::cancel::CancellationToken;
 use crate::fs_mode::FsMode;
 
 use crate::api::ToolDefinition;
@@ -26,9 +36,15 @@ use crate::errors::ToolContextError;
 
 pub use error::ToolError;
 
+/// Receives command output one line at a time while a tool is still running.
+pub type OutputSink = Arc<dyn Fn(&str) + Send + Sync>;
+
 pub struct ToolContext {
     pub working_dir: PathBuf,
     fs_mode: Arc<AtomicU8>,
+    output_sink: Option<OutputSink>,
+    update_mode_commands: Vec<String>,
+    cancel: Option<CancellationToken>,
 }
 
 impl ToolContext {
@@ -41,6 +57,9 @@ impl ToolContext {
         Ok(Self {
             working_dir: env::current_dir().map_err(ToolContextError::WorkingDirUnavailable)?,
             fs_mode: Arc::new(AtomicU8::new(mode.to_u8())),
+            output_sink: None,
+            update_mode_commands: Vec::new(),
+            cancel: None,
         })
     }
 
@@ -49,9 +68,18 @@ impl ToolContext {
         Self {
             working_dir,
             fs_mode: Arc::new(AtomicU8::new(mode.to_u8())),
+            output_sink: None,
+            update_mode_commands: Vec::new(),
+            cancel: None,
         }
     }
 
+    /// Allow these bash commands in `FsMode::Update`; see `set_update_mode_commands`.
+    pub fn with_update_mode_commands(mut self, commands: Vec<String>) -> Self {
+        self.update_mode_commands = commands;
+        self
+    }
+
     pub fn fs_mode(&self) -> FsMode {
         FsMode::from_u8(self.fs_mode.load(Ordering::Relaxed))
     }
@@ -64,6 +92,47 @@ impl ToolContext {
         self.fs_mode.clone()
     }
 
+    /// Stream `bash` output through `sink` as it arrives. Without a sink the
+    /// command's output is only returned once it exits.
+    pub fn set_output_sink(&mut self, sink: Option<OutputSink>) {
+        self.output_sink = sink;
+    }
+
+    pub fn output_sink(&self) -> Option<&OutputSink> {
+        self.output_sink.as_ref()
+    }
+
+    /// Kill a running `bash` command once `cancel` fires. Without a token the
+    /// command runs to completion.
+    pub fn set_cancel(&mut self, cancel: Option<CancellationToken>) {
+        self.cancel = cancel;
+    }
+
+    pub fn cancel(&self) -> Option<&CancellationToken> {
+        self.cancel.as_ref()
+    }
+
+    /// Bash commands that may still run in `FsMode::Update`, for commands
+    /// known not to modify files (e.g. `git status`, `cargo check`).
+    pub fn set_update_mode_commands(&mut self, commands: Vec<String>) {
+        self.update_mode_commands = commands;
+    }
+
+    /// Whether `cmd` is an allowlisted update-mode command: an entry alone or
+    /// followed by arguments, with no operators that could chain another
+    /// command or redirect output to a file.
+    fn allows_in_update_mode(&self, cmd: &str) -> bool {
+        const SHELL_OPERATORS: &[char] = &[';', '&', '|', '>', '<', '`', '$', '\n'];
+        let cmd = cmd.trim();
+        if cmd.contains(SHELL_OPERATORS) {
+            return false;
+        }
+        self.update_mode_commands.iter().any(|allowed| {
+            cmd.strip_prefix(allowed.trim())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+        })
+    }
+
     /// Resolve a user-provided path within the working directory.
     ///
     /// Security: this is a jail. Relative paths may not escape `working_dir`.
@@ -181,9 +250,31 @@ impl<'a> ToolArgs<'a> {
         }
     }
 
+    /// A string or an array of strings; missing or null is empty.
+    pub fn get_str_list(&self, key: &str) -> Result<Vec<&str>, ToolError> {
+        let map = self.object()?;
+        let invalid = || ToolError::InvalidParameterType {
+            key: key.to_string(),
+            expected: "string or array of strings",
+        };
+        match map.get(key) {
+            None | Some(Value::Null) => Ok(Vec::new()),
+            Some(Value::String(s)) => Ok(vec![s.as_str()]),
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|item| item.as_str().ok_or_else(invalid))
+                .collect(),
+            Some(_) => Err(invalid()),
+        }
+    }
+
     pub fn get_u64(&self, key: &str) -> Result<Option<u64>, ToolError> {
         self.get_optional(key, Value::as_u64, "u64")
     }
+
+    pub fn get_i64(&self, key: &str) -> Result<Option<i64>, ToolError> {
+        self.get_optional(key, Value::as_i64, "i64")
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -193,17 +284,23 @@ pub enum Tool {
     Edit,
     Glob,
     Grep,
+    Tree,
+    Move,
+    Delete,
     Nu,
     Bash,
 }
 
 impl Tool {
-    const ALL: [Tool; 7] = [
+    const ALL: [Tool; 10] = [
         Tool::Read,
         Tool::Write,
         Tool::Edit,
+        Tool::Move,
+        Tool::Delete,
         Tool::Glob,
         Tool::Grep,
+        Tool::Tree,
         Tool::Nu,
         Tool::Bash,
     ];
@@ -214,8 +311,11 @@ impl Tool {
             Tool::Read => "read",
             Tool::Write => "write",
             Tool::Edit => "edit",
+            Tool::Move => "move",
+            Tool::Delete => "delete",
             Tool::Glob => "glob",
             Tool::Grep => "grep",
+            Tool::Tree => "tree",
             Tool::Nu => "nu",
             Tool::Bash => "bash",
         }
@@ -226,8 +326,11 @@ impl Tool {
             "read" => Some(Tool::Read),
             "write" => Some(Tool::Write),
             "edit" => Some(Tool::Edit),
+            "move" | "rename" => Some(Tool::Move),
+            "delete" => Some(Tool::Delete),
             "glob" => Some(Tool::Glob),
             "grep" => Some(Tool::Grep),
+            "tree" => Some(Tool::Tree),
             "nu" | "nushell" => Some(Tool::Nu),
             "bash" => Some(Tool::Bash),
             _ => None,
@@ -249,7 +352,7 @@ impl Tool {
                         },
                         "offset": {
                             "type": "integer",
-                            "description": "Line number to start from (0-indexed)",
+                            "description": "Line number to start from (0-indexed); negative counts back from the end, e.g. -50 for the last 50 lines",
                             "default": 0
                         },
                         "limit": {
@@ -301,14 +404,59 @@ impl Tool {
                             "type": "boolean",
                             "description": "Replace all occurrences (default: false)",
                             "default": false
+                        },
+                        "expect_count": {
+                            "type": "integer",
+                            "description": "Exact number of occurrences to replace; the edit fails without writing if the file has a different count"
                         }
                     },
                     "required": ["path", "old", "new"]
                 }),
             },
+            Tool::Move => ToolDefinition {
+                name: "move".into(),
+                description:
+                    "Move or rename a file or directory (a symlink is moved itself). Parent directories of the destination are created if needed; an existing destination is an error."
+                        .into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "Path to move"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination path"
+                        }
+                    },
+                    "required": ["from", "to"]
+                }),
+            },
+            Tool::Delete => ToolDefinition {
+                name: "delete".into(),
+                description:
+                    "Delete a file or directory. By default it is moved to .looprs/trash/ and can be restored; set permanent=true to remove it outright."
+                        .into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Path to delete"
+                        },
+                        "permanent": {
+                            "type": "boolean",
+                            "description": "Skip the trash and delete permanently (default: false)",
+                            "default": false
+                        }
+                    },
+                    "required": ["path"]
+                }),
+            },
             Tool::Glob => ToolDefinition {
                 name: "glob".into(),
-                description: "Find files matching glob pattern. Results sorted by modification time (newest first).".into(),
+                description: "Find files matching glob pattern. Results sorted by modification time (newest first) unless `sort` says otherwise.".into(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
@@ -320,6 +468,22 @@ impl Tool {
                             "type": "string",
                             "description": "Base directory for search (default: current directory)",
                             "default": "."
+                        },
+                        "exclude": {
+                            "type": ["string", "array"],
+                            "items": {"type": "string"},
+                            "description": "Glob or list of globs (relative to the search base) to leave out, e.g. '*_test.rs'"
+                        },
+                        "sort": {
+                            "type": "string",
+                            "enum": ["mtime", "name", "size", "path"],
+                            "description": "Order results by modification time (newest first), file name, size (largest first), or full path",
+                            "default": "mtime"
+                        },
+                        "reverse": {
+                            "type": "boolean",
+                            "description": "Reverse the sort order",
+                            "default": false
                         }
                     },
                     "required": ["pat"]
@@ -351,13 +515,47 @@ impl Tool {
                         },
                         "path": {
                             "type": "string",
-                            "description": "Base directory for search (default: current directory)",
+                            "description": "File to search, or base directory to search recursively (default: current directory)",
                             "default": "."
+                        },
+                        "exclude": {
+                            "type": ["string", "array"],
+                            "items": {"type": "string"},
+                            "description": "Glob or list of globs (relative to the search base) to leave out, e.g. '*_test.rs'"
                         }
                     },
                     "required": ["pat"]
                 }),
             },
+            Tool::Tree => ToolDefinition {
+                name: "tree".into(),
+                description: "Show an indented directory tree, skipping gitignored paths. Bounded by depth and max_entries.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {
+                            "type": "string",
+                            "description": "Directory to show (default: current directory)",
+                            "default": "."
+                        },
+                        "depth": {
+                            "type": "integer",
+                            "description": "How many levels to descend (default: 2)",
+                            "default": 2
+                        },
+                        "max_entries": {
+                            "type": "integer",
+                            "description": "Maximum number of entries to list (default: 200)",
+                            "default": 200
+                        },
+                        "include_ignored": {
+                            "type": "boolean",
+                            "description": "Also list gitignored paths (default: false)",
+                            "default": false
+                        }
+                    }
+                }),
+            },
             Tool::Bash => ToolDefinition {
                 name: "bash".into(),
                 description: "Execute a Bash command. Returns stdout and stderr.".into(),
@@ -367,6 +565,10 @@ impl Tool {
                         "cmd": {
                             "type": "string",
                             "description": "Bash command to execute"
+                        },
+                        "cwd": {
+                            "type": "string",
+                            "description": "Directory to run in, relative to the working directory (default: current directory)"
                         }
                     },
                     "required": ["cmd"]
@@ -375,15 +577,18 @@ impl Tool {
         }
     }
 
-    pub fn execute(&self, args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+    pub fn execute(&self, args: &Value, ctx: &ToolContext) -> Result<ToolOutput, ToolError> {
         match self {
             Tool::Read => read::tool_read(args, ctx),
-            Tool::Write => write::tool_write(args, ctx),
-            Tool::Edit => edit::tool_edit(args, ctx),
             Tool::Glob => glob::tool_glob(args, ctx),
             Tool::Grep => grep::tool_grep(args, ctx),
-            Tool::Nu => nu::tool_nu(args),
-            Tool::Bash => bash::tool_bash(args),
+            Tool::Write => write::tool_write(args, ctx).map(ToolOutput::Text),
+            Tool::Edit => edit::tool_edit(args, ctx).map(ToolOutput::Text),
+            Tool::Move => r#move::tool_move(args, ctx).map(ToolOutput::Text),
+            Tool::Delete => delete::tool_delete(args, ctx).map(ToolOutput::Text),
+            Tool::Tree => tree::tool_tree(args, ctx).map(ToolOutput::Text),
+            Tool::Nu => nu::tool_nu(args).map(ToolOutput::Text),
+            Tool::Bash => bash::tool_bash(args, ctx).map(ToolOutput::Text),
         }
     }
 }
@@ -416,20 +621,36 @@ fn enforce_fs_mode(tool: Tool, args: &Value, ctx: &ToolContext) -> Result<(), To
     match mode {
         FsMode::Write => Ok(()),
         FsMode::Read => match tool {
-            Tool::Write | Tool::Edit | Tool::Nu | Tool::Bash => Err(ToolError::ModeDenied {
-                tool: tool.name().to_string(),
-                mode: mode.as_str().to_string(),
-                reason: "file writes are disabled".to_string(),
-            }),
+            Tool::Write | Tool::Edit | Tool::Move | Tool::Delete | Tool::Nu | Tool::Bash => {
+                Err(ToolError::ModeDenied {
+                    tool: tool.name().to_string(),
+                    mode: mode.as_str().to_string(),
+                    reason: "file writes are disabled".to_string(),
+                })
+            }
             _ => Ok(()),
         },
         FsMode::Update => match tool {
+            Tool::Bash
+                if ToolArgs::new(args)
+                    .get_str("cmd")
+                    .is_ok_and(|cmd| ctx.allows_in_update_mode(cmd)) =>
+            {
+                Ok(())
+            }
             Tool::Nu | Tool::Bash => Err(ToolError::ModeDenied {
                 tool: tool.name().to_string(),
                 mode: mode.as_str().to_string(),
-                reason: "shell commands are disabled (they can create/modify files)".to_string(),
+                reason: "shell commands are disabled (they can create/modify files); \
+                         allow read-only ones with agents.update_mode_commands"
+                    .to_string(),
             }),
             Tool::Edit => Ok(()),
+            Tool::Delete => Err(ToolError::ModeDenied {
+                tool: tool.name().to_string(),
+                mode: mode.as_str().to_string(),
+                reason: "cannot delete files in update mode".to_string(),
+            }),
             Tool::Write => {
                 let tool_args = ToolArgs::new(args);
                 let path = tool_args.get_str("path")?;
@@ -444,12 +665,31 @@ fn enforce_fs_mode(tool: Tool, args: &Value, ctx: &ToolContext) -> Result<(), To
                     })
                 }
             }
+            Tool::Move => {
+                let tool_args = ToolArgs::new(args);
+                let src = ctx.resolve_path(tool_args.get_str("from")?)?;
+                let dest = ctx.resolve_path(tool_args.get_str("to")?)?;
+                // Overwriting an existing file or renaming within the same
+                // directory doesn't grow the tree's layout.
+                let in_place = src.is_file() && src.parent() == dest.parent();
+                if dest.is_file() || in_place {
+                    Ok(())
+                } else {
+                    Err(ToolError::ModeDenied {
+                        tool: tool.name().to_string(),
+                        mode: mode.as_str().to_string(),
+                        reason:
+                            "can only overwrite an existing file or rename in place in update mode"
+                                .to_string(),
+                    })
+                }
+            }
             _ => Ok(()),
         },
     }
 }
 
-pub fn execute_tool(name: &str, args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+pub fn execute_tool(name: &str, args: &Value, ctx: &ToolContext) -> Result<ToolOutput, ToolError> {
     match Tool::from_name(name) {
         Some(tool) => {
             enforce_fs_mode(tool, args, ctx)?;
@@ -801,6 +1041,15 @@ mod tests {
         let err = execute_tool("edit", &args, &ctx).unwrap_err();
         assert!(matches!(err, ToolError::ModeDenied { .. }));
 
+        let args = serde_json::json!({"from": "a.txt", "to": "b.txt"});
+        let err = execute_tool("move", &args, &ctx).unwrap_err();
+        assert!(matches!(err, ToolError::ModeDenied { .. }));
+
+        let args = serde_json::json!({"path": "a.txt"});
+        let err = execute_tool("delete", &args, &ctx).unwrap_err();
+        assert!(matches!(err, ToolError::ModeDenied { .. }));
+        assert!(file.exists());
+
         let args = serde_json::json!({"cmd": "echo hi"});
         let err = execute_tool("nu", &args, &ctx).unwrap_err();
         assert!(matches!(err, ToolError::ModeDenied { .. }));
@@ -897,7 +1146,9 @@ mod tests {
 
         std::fs::write(dir.path().join("existing.txt"), "old").unwrap();
         let args = serde_json::json!({"path": "existing.txt", "content": "new"});
-        let out = execute_tool("write", &args, &ctx).unwrap();
+        let out = execute_tool("write", &args, &ctx)
+            .unwrap()
+            .to_display_string();
         assert_eq!(out, "ok");
         let content = std::fs::read_to_string(dir.path().join("existing.txt")).unwrap();
         assert_eq!(content, "new");
@@ -910,9 +1161,57 @@ mod tests {
         let ctx = ToolContext::from_working_dir(dir.path().to_path_buf(), FsMode::Update);
 
         let args = serde_json::json!({"path": "a.txt", "old": "world", "new": "there"});
-        let out = execute_tool("edit", &args, &ctx).unwrap();
+        let out = execute_tool("edit", &args, &ctx)
+            .unwrap()
+            .to_display_string();
         assert_eq!(out, "ok");
         let content = std::fs::read_to_string(dir.path().join("a.txt")).unwrap();
         assert_eq!(content, "hello there");
     }
+
+    #[test]
+    fn update_mode_allows_in_place_rename_but_not_relocation() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let ctx = ToolContext::from_working_dir(dir.path().to_path_buf(), FsMode::Update);
+
+        let args = serde_json::json!({"from": "a.txt", "to": "sub/a.txt"});
+        let err = execute_tool("move", &args, &ctx).unwrap_err();
+        assert!(matches!(err, ToolError::ModeDenied { .. }));
+
+        let args = serde_json::json!({"from": "a.txt", "to": "b.txt"});
+        let out = execute_tool("rename", &args, &ctx)
+            .unwrap()
+            .to_display_string();
+        assert_eq!(out, "ok");
+        assert!(dir.path().join("b.txt").is_file());
+    }
+
+    #[test]
+    fn update_mode_runs_allowlisted_bash_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("keep.txt"), "x").unwrap();
+        let ctx = ToolContext::from_working_dir(dir.path().to_path_buf(), FsMode::Update)
+            .with_update_mode_commands(vec!["git status".to_string(), "echo".to_string()]);
+
+        let out = execute_tool("bash", &serde_json::json!({"cmd": "echo read only"}), &ctx)
+            .unwrap()
+            .to_display_string();
+        assert!(out.contains("read only"));
+        let status = execute_tool("bash", &serde_json::json!({"cmd": "git status"}), &ctx);
+        assert!(!matches!(status, Err(ToolError::ModeDenied { .. })));
+
+        for cmd in [
+            "rm keep.txt",
+            "git status; rm keep.txt",
+            "echo x > keep.txt",
+            "echo $(rm keep.txt)",
+            "echoes",
+        ] {
+            let err = execute_tool("bash", &serde_json::json!({"cmd": cmd}), &ctx).unwrap_err();
+            assert!(matches!(err, ToolError::ModeDenied { .. }), "{cmd}: {err}");
+        }
+        assert!(dir.path().join("keep.txt").is_file());
+    }
 }
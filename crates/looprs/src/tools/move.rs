@@ -0,0 +1,218 @@
+use super::ToolArgs;
+use super::ToolContext;
+use super::delete::resolve_entry;
+use super::error::ToolError;
+use crate::trash::Trash;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// qual:allow(iosp) reason: "I/O boundary — parses args, renames file"
+pub(super) fn tool_move(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+    let args = ToolArgs::new(args);
+    let from = args.get_str("from")?;
+    let to = args.get_str("to")?;
+
+    let (base, src) = resolve_entry(ctx, from, "move")?;
+    if fs::symlink_metadata(&src).is_err() {
+        return Err(ToolError::FileNotFound(from.to_string()));
+    }
+    refuse_protected(&base, &src, from)?;
+
+    // Jail-check the destination before creating any of its parents.
+    let checked = ctx.resolve_path(to)?;
+    refuse_protected(&base, &checked, to)?;
+    if let Some(parent) = checked.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let (_, dest) = resolve_entry(ctx, to, "move")?;
+    refuse_protected(&base, &dest, to)?;
+    if fs::symlink_metadata(&dest).is_ok() {
+        return Err(ToolError::InvalidPath(format!(
+            "{to} already exists; move or delete it first"
+        )));
+    }
+
+    rename_or_copy(&src, &dest, from, |src, dest| fs::rename(src, dest))?;
+    Ok("ok".to_string())
+}
+
+/// Error if `full_path` is inside `.git` or the trash under `base`.
+fn refuse_protected(base: &Path, full_path: &Path, path: &str) -> Result<(), ToolError> {
+    let inside = if full_path
+        .strip_prefix(base)
+        .is_ok_and(|rel| rel.starts_with(".git"))
+    {
+        ".git"
+    } else if full_path.starts_with(Trash::new(base).root()) {
+        "the trash"
+    } else {
+        return Ok(());
+    };
+    Err(ToolError::InvalidPath(format!(
+        "refusing to move {path}: it is inside {inside}"
+    )))
+}
+
+/// `rename`, falling back to copy + remove when `src` and `dest` are on
+/// different filesystems. Only regular files take the fallback; anything else
+/// on another filesystem is an error naming `from`.
+fn rename_or_copy(
+    src: &Path,
+    dest: &Path,
+    from: &str,
+    rename: impl Fn(&Path, &Path) -> io::Result<()>,
+) -> Result<(), ToolError> {
+    match rename(src, dest) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if !fs::symlink_metadata(src).is_ok_and(|m| m.is_file()) {
+                return Err(ToolError::InvalidPath(format!(
+                    "cannot move {from} to another filesystem: only regular files can be copied across"
+                )));
+            }
+            fs::copy(src, dest)?;
+            fs::remove_file(src)?;
+            Ok(())
+        }
+        result => Ok(result?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn move_renames_file_into_new_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let args = json!({"from": "a.txt", "to": "sub/b.txt"});
+        let out = tool_move(&args, &ctx).unwrap();
+        assert_eq!(out, "ok");
+
+        assert!(!dir.path().join("a.txt").exists());
+        let content = fs::read_to_string(dir.path().join("sub/b.txt")).unwrap();
+        assert_eq!(content, "hello");
+    }
+
+    #[test]
+    fn move_blocks_destination_outside_jail() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let args = json!({"from": "a.txt", "to": "../escape.txt"});
+        let err = tool_move(&args, &ctx).unwrap_err();
+        match err {
+            ToolError::PathOutsideWorkingDir(_) => {}
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert!(dir.path().join("a.txt").exists());
+    }
+
+    #[test]
+    fn move_missing_source_is_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let args = json!({"from": "missing.txt", "to": "b.txt"});
+        let err = tool_move(&args, &ctx).unwrap_err();
+        assert!(matches!(err, ToolError::FileNotFound(_)));
+    }
+
+    fn ctx(dir: &Path) -> ToolContext {
+        ToolContext::from_working_dir(dir.to_path_buf(), crate::fs_mode::FsMode::Write)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn move_renames_symlink_not_its_target() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("target.txt"), "hello").unwrap();
+        std::os::unix::fs::symlink("target.txt", dir.path().join("link")).unwrap();
+
+        tool_move(&json!({"from": "link", "to": "renamed"}), &ctx(dir.path())).unwrap();
+
+        let moved = fs::symlink_metadata(dir.path().join("renamed")).unwrap();
+        assert!(moved.file_type().is_symlink());
+        assert!(fs::symlink_metadata(dir.path().join("link")).is_err());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("target.txt")).unwrap(),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn move_refuses_to_overwrite_existing_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "new").unwrap();
+        fs::write(dir.path().join("b.txt"), "old").unwrap();
+
+        let err = tool_move(&json!({"from": "a.txt", "to": "b.txt"}), &ctx(dir.path()))
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("already exists"), "{err}");
+        assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(dir.path().join("b.txt")).unwrap(), "old");
+    }
+
+    #[test]
+    fn move_refuses_git_trash_and_working_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/HEAD"), "ref").unwrap();
+        fs::create_dir_all(dir.path().join(".looprs/trash/1")).unwrap();
+        fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        let ctx = ctx(dir.path());
+
+        for (from, to, expected) in [
+            (".git", "git-backup", "inside .git"),
+            (".git/HEAD", "HEAD", "inside .git"),
+            ("a.txt", ".git/a.txt", "inside .git"),
+            (".looprs/trash/1", "restored", "inside the trash"),
+            ("a.txt", ".looprs/trash/a.txt", "inside the trash"),
+            (".", "elsewhere", "working directory"),
+        ] {
+            let err = tool_move(&json!({"from": from, "to": to}), &ctx)
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains(expected), "{from} -> {to}: {err}");
+        }
+        assert!(dir.path().join(".git/HEAD").exists());
+        assert!(dir.path().join("a.txt").exists());
+    }
+
+    #[test]
+    fn cross_device_directory_move_is_a_clear_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        let crosses = |_: &Path, _: &Path| Err(io::Error::from(io::ErrorKind::CrossesDevices));
+
+        let err =
+            rename_or_copy(&dir.path().join("src"), &dir.path().join("dst"), crosses).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::CrossesDevices);
+        assert!(dir.path().join("src").exists());
+
+        rename_or_copy(
+            &dir.path().join("a.txt"),
+            &dir.path().join("b.txt"),
+            crosses,
+        )
+        .unwrap();
+        assert!(!dir.path().join("a.txt").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.txt")).unwrap(),
+            "hello"
+        );
+    }
+}
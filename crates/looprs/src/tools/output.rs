@@ -0,0 +1,73 @@
+use crate::api::ContentBlock;
+use serde_json::{Value, json};
+
+/// Result of a successful tool call.
+///
+/// `Text` is what most tools return. `Structured` carries the same text the
+/// model sees plus a JSON form (paths, line numbers, counts) for programmatic
+/// consumers such as the scriptable `--json` output. `Image` carries a short
+/// description as its text and the base64 image, which the agent attaches to
+/// the tool results as an image block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolOutput {
+    Text(String),
+    Structured {
+        text: String,
+        data: Value,
+    },
+    Image {
+        text: String,
+        media_type: String,
+        data: String,
+    },
+}
+
+impl ToolOutput {
+    /// Text shown to the model and in the REPL.
+    pub fn to_display_string(&self) -> String {
+        match self {
+            ToolOutput::Text(text)
+            | ToolOutput::Structured { text, .. }
+            | ToolOutput::Image { text, .. } => text.clone(),
+        }
+    }
+
+    /// The structured form, if the tool produced one.
+    pub fn structured(&self) -> Option<&Value> {
+        match self {
+            ToolOutput::Text(_) | ToolOutput::Image { .. } => None,
+            ToolOutput::Structured { data, .. } => Some(data),
+        }
+    }
+
+    /// The image as a content block, for `Image` outputs.
+    pub fn image(&self) -> Option<ContentBlock> {
+        match self {
+            ToolOutput::Image {
+                media_type, data, ..
+            } => Some(ContentBlock::Image {
+                media_type: media_type.clone(),
+                data: data.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Structured data when present, otherwise the text as a JSON string.
+    /// Images are described by media type, without their bytes.
+    pub fn to_json(&self) -> Value {
+        match self {
+            ToolOutput::Text(text) => Value::String(text.clone()),
+            ToolOutput::Structured { data, .. } => data.clone(),
+            ToolOutput::Image {
+                text, media_type, ..
+            } => json!({"type": "image", "media_type": media_type, "text": text}),
+        }
+    }
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        ToolOutput::Text(text)
+    }
+}
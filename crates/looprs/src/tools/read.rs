@@ -1,35 +1,131 @@
 use super::ToolArgs;
 use super::ToolContext;
+use super::ToolOutput;
 use super::error::ToolError;
-use serde_json::Value;
+use base64::Engine as _;
+use serde_json::{Value, json};
 use std::fmt::Write as _;
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Largest image returned inline as base64.
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+/// How much of a file is sniffed for NUL bytes to decide it's binary.
+const BINARY_SNIFF_BYTES: u64 = 8 * 1024;
 
 // qual:allow(iosp) reason: "I/O boundary — parses args, reads file, formats output"
-pub(super) fn tool_read(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+pub(super) fn tool_read(args: &Value, ctx: &ToolContext) -> Result<ToolOutput, ToolError> {
     let args = ToolArgs::new(args);
     let path = args.get_str("path")?;
-    let offset = args.get_u64("offset")?.unwrap_or(0) as usize;
+    let offset = args.get_i64("offset")?.unwrap_or(0);
     let limit = args.get_u64("limit")?;
 
     let full_path = ctx.resolve_path(path)?;
 
-    let file = fs::File::open(&full_path).map_err(|_| ToolError::FileNotFound(path.to_string()))?;
-    let mut reader = BufReader::new(file);
+    if let Some(media_type) = image_media_type(&full_path) {
+        return read_image(&full_path, path, media_type);
+    }
+    if is_binary(&full_path).map_err(|_| ToolError::FileNotFound(path.to_string()))? {
+        let size = fs::metadata(&full_path)?.len();
+        return Ok(ToolOutput::Text(format!("(binary file, {size} bytes)")));
+    }
+
+    let open = || fs::File::open(&full_path).map_err(|_| ToolError::FileNotFound(path.to_string()));
+    // A negative offset counts back from the end, like `tail -n`.
+    let offset = if offset < 0 {
+        let total = count_lines(BufReader::new(open()?))
+            .map_err(|_| ToolError::FileNotFound(path.to_string()))?;
+        total.saturating_sub(offset.unsigned_abs() as usize)
+    } else {
+        offset as usize
+    };
+
+    let file = open()?;
+    let window = read_line_window(BufReader::new(file), offset, limit)
+        .map_err(|_| ToolError::FileNotFound(path.to_string()))?;
+
+    // Each numbered line is `{:4}| text`; the structured form drops the gutter.
+    let lines: Vec<&str> = window
+        .as_deref()
+        .unwrap_or("")
+        .lines()
+        .filter_map(|line| line.split_once("| ").map(|(_, text)| text))
+        .collect();
+    let data = json!({"path": path, "start_line": offset + 1, "lines": lines});
+    Ok(ToolOutput::Structured {
+        text: window.unwrap_or_else(|| "(EOF)".to_string()),
+        data,
+    })
+}
+
+fn image_media_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Images come back as base64 so multimodal providers can see them; the text
+/// names the file for the transcript.
+fn read_image(full_path: &Path, path: &str, media_type: &str) -> Result<ToolOutput, ToolError> {
+    let size = fs::metadata(full_path)
+        .map_err(|_| ToolError::FileNotFound(path.to_string()))?
+        .len();
+    if size > MAX_IMAGE_BYTES {
+        return Ok(ToolOutput::Text(format!(
+            "(image file, {size} bytes; exceeds the {MAX_IMAGE_BYTES} byte inline limit)"
+        )));
+    }
+    let bytes = fs::read(full_path)?;
+    Ok(ToolOutput::Image {
+        text: format!("(image {path}: {media_type}, {size} bytes)"),
+        media_type: media_type.to_string(),
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+    })
+}
+
+fn is_binary(path: &Path) -> std::io::Result<bool> {
+    let mut head = Vec::new();
+    fs::File::open(path)?
+        .take(BINARY_SNIFF_BYTES)
+        .read_to_end(&mut head)?;
+    Ok(head.contains(&0))
+}
+
+fn count_lines(mut reader: impl BufRead) -> std::io::Result<usize> {
+    let mut buf = Vec::new();
+    let mut count = 0;
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        buf.clear();
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Number and return up to `limit` lines after skipping `offset`.
+///
+/// `Ok(None)` when the window starts at or past EOF. A `limit` of zero yields
+/// an empty window.
+pub(crate) fn read_line_window(
+    mut reader: impl BufRead,
+    offset: usize,
+    limit: Option<u64>,
+) -> std::io::Result<Option<String>> {
     let mut line = String::new();
 
     if limit == Some(0) {
-        return Ok(String::new());
+        return Ok(Some(String::new()));
     }
 
     for _ in 0..offset {
         line.clear();
-        let bytes_read = reader
-            .read_line(&mut line)
-            .map_err(|_| ToolError::FileNotFound(path.to_string()))?;
-        if bytes_read == 0 {
-            return Ok("(EOF)".to_string());
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
         }
     }
 
@@ -43,12 +139,9 @@ pub(super) fn tool_read(args: &Value, ctx: &ToolContext) -> Result<String, ToolE
         }
 
         line.clear();
-        let bytes_read = reader
-            .read_line(&mut line)
-            .map_err(|_| ToolError::FileNotFound(path.to_string()))?;
-        if bytes_read == 0 {
+        if reader.read_line(&mut line)? == 0 {
             if written == 0 {
-                return Ok("(EOF)".to_string());
+                return Ok(None);
             }
             break;
         }
@@ -64,7 +157,7 @@ pub(super) fn tool_read(args: &Value, ctx: &ToolContext) -> Result<String, ToolE
         output.pop();
     }
 
-    Ok(output)
+    Ok(Some(output))
 }
 
 #[cfg(test)]
@@ -84,7 +177,11 @@ mod tests {
         let args = json!({"path": "a.txt", "offset": 1, "limit": 1});
 
         let out = tool_read(&args, &ctx).unwrap();
-        assert!(out.contains("2| b"));
+        assert!(out.to_display_string().contains("2| b"));
+        assert_eq!(
+            out.structured(),
+            Some(&json!({"path": "a.txt", "start_line": 2, "lines": ["b"]}))
+        );
     }
 
     #[test]
@@ -100,4 +197,79 @@ mod tests {
             other => panic!("unexpected error: {other:?}"),
         }
     }
+
+    fn numbered_file(lines: usize) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let content: String = (1..=lines).map(|n| format!("line {n}\n")).collect();
+        fs::write(dir.path().join("log.txt"), content).unwrap();
+        dir
+    }
+
+    #[test]
+    fn negative_offset_reads_from_end() {
+        let dir = numbered_file(100);
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_read(&json!({"path": "log.txt", "offset": -10}), &ctx)
+            .unwrap()
+            .to_display_string();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], "  91| line 91");
+        assert_eq!(lines[9], " 100| line 100");
+
+        let out = tool_read(&json!({"path": "log.txt", "offset": -10, "limit": 2}), &ctx)
+            .unwrap()
+            .to_display_string();
+        assert_eq!(out, "  91| line 91\n  92| line 92");
+    }
+
+    #[test]
+    fn negative_offset_past_start_clamps_to_first_line() {
+        let dir = numbered_file(3);
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_read(&json!({"path": "log.txt", "offset": -50}), &ctx)
+            .unwrap()
+            .to_display_string();
+        assert_eq!(out, "   1| line 1\n   2| line 2\n   3| line 3");
+    }
+
+    #[test]
+    fn image_is_returned_as_base64() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        fs::write(dir.path().join("logo.PNG"), png).unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_read(&json!({"path": "logo.PNG"}), &ctx).unwrap();
+        assert_eq!(
+            out.to_display_string(),
+            format!("(image logo.PNG: image/png, {} bytes)", png.len())
+        );
+        let Some(crate::api::ContentBlock::Image { media_type, data }) = out.image() else {
+            panic!("expected an image block, got {out:?}");
+        };
+        assert_eq!(media_type, "image/png");
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .unwrap();
+        assert_eq!(decoded, png);
+    }
+
+    #[test]
+    fn binary_file_returns_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("blob.bin"), [0u8, 159, 146, 150, 1, 2]).unwrap();
+        let ctx =
+            ToolContext::from_working_dir(dir.path().to_path_buf(), crate::fs_mode::FsMode::Write);
+
+        let out = tool_read(&json!({"path": "blob.bin"}), &ctx)
+            .unwrap()
+            .to_display_string();
+        assert_eq!(out, "(binary file, 6 bytes)");
+    }
 }
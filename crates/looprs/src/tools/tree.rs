@@ -0,0 +1,143 @@
+use super::ToolArgs;
+use super::ToolContext;
+use super::error::ToolError;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_DEPTH: u64 = 2;
+const DEFAULT_MAX_ENTRIES: u64 = 200;
+
+struct TreeWalk<'a> {
+    root: &'a Path,
+    max_depth: usize,
+    max_entries: usize,
+    include_ignored: bool,
+    lines: Vec<String>,
+    truncated: bool,
+}
+
+// qual:allow(iosp) reason: "I/O boundary — parses args, walks filesystem"
+pub(super) fn tool_tree(args: &Value, ctx: &ToolContext) -> Result<String, ToolError> {
+    let args = ToolArgs::new(args);
+    let path = args.get_str_optional("path")?.unwrap_or(".");
+    let depth = args.get_u64("depth")?.unwrap_or(DEFAULT_DEPTH) as usize;
+    let max_entries = args.get_u64("max_entries")?.unwrap_or(DEFAULT_MAX_ENTRIES) as usize;
+    let include_ignored = args.get_bool("include_ignored", false);
+
+    let root = ctx.resolve_path(path)?;
+    if !root.is_dir() {
+        return Err(ToolError::InvalidPath(format!("{path} is not a directory")));
+    }
+
+    let mut walk = TreeWalk {
+        root: &root,
+        max_depth: depth,
+        max_entries,
+        include_ignored,
+        lines: vec![path.to_string()],
+        truncated: false,
+    };
+    walk.visit(Path::new(""), "", 1)?;
+
+    let mut output = walk.lines.join("\n");
+    if walk.truncated {
+        output.push_str(&format!(
+            "\n[truncated: showing the first {max_entries} entries; narrow the path or lower depth]"
+        ));
+    }
+    Ok(output)
+}
+
+impl TreeWalk<'_> {
+    /// Render the children of `rel` (relative to the root) at `level`.
+    fn visit(&mut self, rel: &Path, prefix: &str, level: usize) -> Result<(), ToolError> {
+        if level > self.max_depth {
+            return Ok(());
+        }
+        let children = self.children(rel)?;
+        let count = children.len();
+        for (index, child) in children.into_iter().enumerate() {
+            if self.lines.len() > self.max_entries {
+                self.truncated = true;
+                return Ok(());
+            }
+            let last = index + 1 == count;
+            let connector = if last { "└── " } else { "├── " };
+            let name = child.file_name().unwrap_or_default().to_string_lossy();
+            let is_dir = self.root.join(&child).is_dir();
+            let suffix = if is_dir { "/" } else { "" };
+            self.lines
+                .push(format!("{prefix}{connector}{name}{suffix}"));
+
+            if is_dir {
+                let nested = format!("{prefix}{}", if last { "    " } else { "│   " });
+                self.visit(&child, &nested, level + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sorted children of `rel`, without `.git` and (unless asked) ignored paths.
+    fn children(&self, rel: &Path) -> Result<Vec<PathBuf>, ToolError> {
+        let mut children: Vec<PathBuf> = fs::read_dir(self.root.join(rel))?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name() != ".git")
+            .map(|entry| rel.join(entry.file_name()))
+            .collect();
+        children.sort();
+        if self.include_ignored {
+            return Ok(children);
+        }
+        Ok(crate::file_refs::drop_gitignored(self.root, children))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn ctx_for(dir: &Path) -> ToolContext {
+        ToolContext::from_working_dir(dir.to_path_buf(), crate::fs_mode::FsMode::Read)
+    }
+
+    #[test]
+    fn tree_renders_connectors_and_limits_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/deep")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        fs::write(dir.path().join("src/deep/inner.rs"), "").unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+
+        let out = tool_tree(&json!({}), &ctx_for(dir.path())).unwrap();
+        assert_eq!(
+            out,
+            ".\n├── Cargo.toml\n└── src/\n    ├── deep/\n    └── lib.rs"
+        );
+
+        let out = tool_tree(&json!({"depth": 1}), &ctx_for(dir.path())).unwrap();
+        assert_eq!(out, ".\n├── Cargo.toml\n└── src/");
+    }
+
+    #[test]
+    fn tree_reports_truncation_past_max_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..5 {
+            fs::write(dir.path().join(format!("f{i}.txt")), "").unwrap();
+        }
+
+        let out = tool_tree(&json!({"max_entries": 2}), &ctx_for(dir.path())).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[1..3], ["├── f0.txt", "├── f1.txt"]);
+        assert!(lines[3].starts_with("[truncated: showing the first 2 entries"));
+    }
+
+    #[test]
+    fn tree_blocks_path_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = tool_tree(&json!({"path": ".."}), &ctx_for(dir.path())).unwrap_err();
+        assert!(matches!(err, ToolError::PathOutsideWorkingDir(_)));
+    }
+}
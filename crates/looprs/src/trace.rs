@@ -1,12 +1,80 @@
-use anyhow::Result;
-use serde_json::json;
+//! Per-session turn traces under `.looprs/observability/traces/<session>.jsonl`.
+//!
+//! Each line is one `TraceRecord`: a summary of the request sent and the
+//! response received, small enough to grep and stable enough to parse.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::api::ContentBlock;
 use crate::observability;
-use crate::providers::{InferenceRequest, InferenceResponse};
+use crate::providers::{InferenceRequest, InferenceResponse, Usage};
+
+/// One inference round trip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceRecord {
+    pub session_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub request: TraceRequest,
+    pub response: TraceResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceRequest {
+    pub model: String,
+    pub message_count: usize,
+    pub tool_count: usize,
+    /// Length of the system prompt in bytes.
+    pub system_len: usize,
+    pub max_tokens: u32,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceResponse {
+    pub stop_reason: String,
+    pub usage: Usage,
+    /// `text`, `tool_use`, `tool_result`, or `image` for each content block, in order.
+    pub block_kinds: Vec<String>,
+}
+
+impl TraceRecord {
+    pub fn new(session_id: &str, request: &InferenceRequest, response: &InferenceResponse) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            timestamp: Utc::now(),
+            request: TraceRequest {
+                model: request.model.as_str().to_string(),
+                message_count: request.messages.len(),
+                tool_count: request.tools.len(),
+                system_len: request.system.len(),
+                max_tokens: request.max_tokens,
+                temperature: request.temperature,
+                top_p: request.top_p,
+            },
+            response: TraceResponse {
+                stop_reason: response.stop_reason.clone(),
+                usage: response.usage.clone(),
+                block_kinds: response.content.iter().map(block_kind).collect(),
+            },
+        }
+    }
+}
+
+fn block_kind(block: &ContentBlock) -> String {
+    match block {
+        ContentBlock::Text { .. } => "text",
+        ContentBlock::ToolUse { .. } => "tool_use",
+        ContentBlock::ToolResult { .. } => "tool_result",
+        ContentBlock::Image { .. } => "image",
+    }
+    .to_string()
+}
 
 pub fn append_turn_trace(
     session_id: &str,
@@ -23,45 +91,119 @@ pub fn append_turn_trace_in_dir(
     request: &InferenceRequest,
     response: &InferenceResponse,
 ) -> Result<()> {
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0);
-
-    let trace_dir = base_dir;
-    fs::create_dir_all(trace_dir)?;
-
-    let trace_file = trace_dir.join(format!("{session_id}.jsonl"));
-
-    let record = json!({
-        "timestamp": timestamp,
-        "session_id": session_id,
-        "turn": {
-            "request": {
-                "model": request.model.as_str(),
-                "messages": &request.messages,
-                "tools": &request.tools,
-                "max_tokens": request.max_tokens,
-                "temperature": request.temperature,
-                "system": &request.system,
-            },
-            "response": {
-                "content": &response.content,
-                "stop_reason": &response.stop_reason,
-                "usage": &response.usage,
-            }
-        }
-    });
+    fs::create_dir_all(base_dir)?;
 
+    let record = TraceRecord::new(session_id, request, response);
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open(&trace_file)?;
+        .open(session_trace_path(base_dir, session_id))?;
     writeln!(file, "{}", serde_json::to_string(&record)?)?;
 
     Ok(())
 }
 
+/// Every trace recorded for `session_id`, oldest first. A session with no
+/// trace file has no records. Lines in the older `turn` format are upgraded;
+/// lines that parse as neither are skipped with a warning.
+pub fn read_traces(session_id: &str) -> Result<Vec<TraceRecord>> {
+    read_traces_in_dir(&observability::trace_dir(), session_id)
+}
+
+pub fn read_traces_in_dir(base_dir: &Path, session_id: &str) -> Result<Vec<TraceRecord>> {
+    let path = session_trace_path(base_dir, session_id);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let mut records = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_trace_line(line) {
+            Some(record) => records.push(record),
+            None => crate::ui::warn(format!(
+                "Warning: skipping unreadable trace at {}:{}",
+                path.display(),
+                idx + 1
+            )),
+        }
+    }
+    Ok(records)
+}
+
+fn parse_trace_line(line: &str) -> Option<TraceRecord> {
+    serde_json::from_str::<TraceRecord>(line)
+        .ok()
+        .or_else(|| serde_json::from_str::<LegacyTrace>(line).ok()?.upgrade())
+}
+
+/// A line written before traces were `TraceRecord`s: the full request and
+/// response under `turn`, with a Unix-seconds timestamp.
+#[derive(Deserialize)]
+struct LegacyTrace {
+    timestamp: i64,
+    session_id: String,
+    turn: LegacyTurn,
+}
+
+#[derive(Deserialize)]
+struct LegacyTurn {
+    request: LegacyRequest,
+    response: LegacyResponse,
+}
+
+#[derive(Deserialize)]
+struct LegacyRequest {
+    model: String,
+    #[serde(default)]
+    messages: Vec<serde_json::Value>,
+    #[serde(default)]
+    tools: Vec<serde_json::Value>,
+    #[serde(default)]
+    system: String,
+    max_tokens: u32,
+    temperature: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct LegacyResponse {
+    #[serde(default)]
+    content: Vec<serde_json::Value>,
+    stop_reason: String,
+    usage: Usage,
+}
+
+impl LegacyTrace {
+    fn upgrade(self) -> Option<TraceRecord> {
+        let LegacyTurn { request, response } = self.turn;
+        Some(TraceRecord {
+            session_id: self.session_id,
+            timestamp: DateTime::from_timestamp(self.timestamp, 0)?,
+            request: TraceRequest {
+                model: request.model,
+                message_count: request.messages.len(),
+                tool_count: request.tools.len(),
+                system_len: request.system.len(),
+                max_tokens: request.max_tokens,
+                temperature: request.temperature,
+                top_p: None,
+            },
+            response: TraceResponse {
+                stop_reason: response.stop_reason,
+                usage: response.usage,
+                block_kinds: response
+                    .content
+                    .iter()
+                    .map(|block| block["type"].as_str().unwrap_or("unknown").to_string())
+                    .collect(),
+            },
+        })
+    }
+}
+
 pub fn session_trace_path(base_dir: &Path, session_id: &str) -> PathBuf {
     base_dir.join(format!("{session_id}.jsonl"))
 }
@@ -69,17 +211,15 @@ pub fn session_trace_path(base_dir: &Path, session_id: &str) -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::{ContentBlock, Message, ToolDefinition};
-    use crate::providers::Usage;
+    use crate::api::{Message, ToolDefinition};
     use crate::types::{ModelId, ToolId, ToolName};
+    use serde_json::json;
     use tempfile::TempDir;
 
-    #[test]
-    fn append_turn_trace_writes_jsonl_record() {
-        let temp = TempDir::new().expect("tempdir");
-        let req = InferenceRequest {
+    fn request(messages: Vec<Message>) -> InferenceRequest {
+        InferenceRequest {
             model: ModelId::new("mock-model"),
-            messages: vec![Message::user("hello")],
+            messages,
             tools: vec![ToolDefinition {
                 name: "read".to_string(),
                 description: "Read file".to_string(),
@@ -87,20 +227,34 @@ mod tests {
             }],
             max_tokens: 1024,
             temperature: Some(0.2),
+            top_p: None,
             system: "system prompt".to_string(),
-        };
-        let resp = InferenceResponse {
-            content: vec![ContentBlock::ToolUse {
-                id: ToolId::new("tool_1"),
-                name: ToolName::new("read"),
-                input: json!({"path": "README.md"}),
-            }],
-            stop_reason: "tool_use".to_string(),
+        }
+    }
+
+    fn response(content: Vec<ContentBlock>, stop_reason: &str) -> InferenceResponse {
+        InferenceResponse {
+            content,
+            stop_reason: stop_reason.to_string(),
             usage: Usage {
                 input_tokens: 10,
                 output_tokens: 4,
             },
-        };
+        }
+    }
+
+    #[test]
+    fn append_turn_trace_writes_jsonl_record() {
+        let temp = TempDir::new().expect("tempdir");
+        let req = request(vec![Message::user("hello")]);
+        let resp = response(
+            vec![ContentBlock::ToolUse {
+                id: ToolId::new("tool_1"),
+                name: ToolName::new("read"),
+                input: json!({"path": "README.md"}),
+            }],
+            "tool_use",
+        );
 
         append_turn_trace_in_dir(temp.path(), "sess-42", &req, &resp).expect("trace append");
 
@@ -110,7 +264,118 @@ mod tests {
         let parsed: serde_json::Value = serde_json::from_str(line).expect("parse json line");
 
         assert_eq!(parsed["session_id"], "sess-42");
-        assert_eq!(parsed["turn"]["request"]["model"], "mock-model");
-        assert_eq!(parsed["turn"]["response"]["stop_reason"], "tool_use");
+        assert_eq!(parsed["request"]["model"], "mock-model");
+        assert_eq!(parsed["request"]["system_len"], 13);
+        assert_eq!(parsed["response"]["stop_reason"], "tool_use");
+        assert_eq!(parsed["response"]["block_kinds"], json!(["tool_use"]));
+    }
+
+    #[test]
+    fn read_traces_round_trips_appended_records() {
+        let temp = TempDir::new().expect("tempdir");
+        let first = request(vec![Message::user("read it")]);
+        append_turn_trace_in_dir(
+            temp.path(),
+            "sess-7",
+            &first,
+            &response(
+                vec![
+                    ContentBlock::Text {
+                        text: "Reading".to_string(),
+                    },
+                    ContentBlock::ToolUse {
+                        id: ToolId::new("t1"),
+                        name: ToolName::new("read"),
+                        input: json!({"path": "README.md"}),
+                    },
+                ],
+                "tool_use",
+            ),
+        )
+        .unwrap();
+        let second = request(vec![
+            Message::user("read it"),
+            Message::tool_results(vec![ContentBlock::ToolResult {
+                tool_use_id: ToolId::new("t1"),
+                content: "# looprs".to_string(),
+            }]),
+        ]);
+        append_turn_trace_in_dir(
+            temp.path(),
+            "sess-7",
+            &second,
+            &response(
+                vec![ContentBlock::Text {
+                    text: "Done".to_string(),
+                }],
+                "end_turn",
+            ),
+        )
+        .unwrap();
+
+        let traces = read_traces_in_dir(temp.path(), "sess-7").unwrap();
+
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].request.message_count, 1);
+        assert_eq!(traces[0].response.block_kinds, ["text", "tool_use"]);
+        assert_eq!(traces[1].request.message_count, 2);
+        assert_eq!(traces[1].request.max_tokens, 1024);
+        assert_eq!(traces[1].response.stop_reason, "end_turn");
+        assert_eq!(traces[1].response.usage.output_tokens, 4);
+        assert!(traces[0].timestamp <= traces[1].timestamp);
+        assert!(
+            read_traces_in_dir(temp.path(), "missing")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn read_traces_upgrades_legacy_lines_and_skips_garbage() {
+        let temp = TempDir::new().expect("tempdir");
+        append_turn_trace_in_dir(
+            temp.path(),
+            "sess-8",
+            &request(vec![Message::user("hi")]),
+            &response(
+                vec![ContentBlock::Text {
+                    text: "Hello".to_string(),
+                }],
+                "end_turn",
+            ),
+        )
+        .unwrap();
+        let legacy = json!({
+            "timestamp": 1_700_000_000,
+            "session_id": "sess-8",
+            "turn": {
+                "request": {
+                    "model": "old-model",
+                    "messages": [{"role": "user", "content": "hi"}],
+                    "tools": [],
+                    "max_tokens": 512,
+                    "temperature": null,
+                    "system": "be brief",
+                },
+                "response": {
+                    "content": [{"type": "text", "text": "Hello"}],
+                    "stop_reason": "end_turn",
+                    "usage": {"input_tokens": 3, "output_tokens": 1},
+                }
+            }
+        });
+        let path = session_trace_path(temp.path(), "sess-8");
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{legacy}").unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let traces = read_traces_in_dir(temp.path(), "sess-8").unwrap();
+
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[1].request.model, "old-model");
+        assert_eq!(traces[1].request.message_count, 1);
+        assert_eq!(traces[1].request.system_len, 8);
+        assert_eq!(traces[1].response.block_kinds, ["text"]);
+        assert_eq!(traces[1].timestamp.timestamp(), 1_700_000_000);
     }
 }
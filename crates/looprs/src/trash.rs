@@ -0,0 +1,285 @@
+//! Working-directory trash under `.looprs/trash/`.
+//!
+//! Each trashed path lives in its own entry directory alongside a `meta.json`
+//! describing where it came from, so it can be listed, restored, or purged.
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+const TRASH_DIR: &str = ".looprs/trash";
+const META_FILE: &str = "meta.json";
+const PAYLOAD_NAME: &str = "payload";
+
+/// Metadata for one trashed file or directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// Original location, relative to the working directory.
+    pub original_path: PathBuf,
+    pub trashed_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+pub struct Trash {
+    working_dir: PathBuf,
+    root: PathBuf,
+}
+
+impl Trash {
+    pub fn new(working_dir: &Path) -> Self {
+        Self {
+            working_dir: working_dir.to_path_buf(),
+            root: working_dir.join(TRASH_DIR),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Move `path` (absolute, or relative to the working directory) into the trash.
+    // qual:allow(iosp) reason: "I/O boundary — moves a path into the trash directory"
+    pub fn trash(&self, path: &Path) -> anyhow::Result<TrashEntry> {
+        let source = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.working_dir.join(path)
+        };
+        // symlink_metadata so a link is trashed as itself, dangling or not.
+        if fs::symlink_metadata(&source).is_err() {
+            bail!("Path not found: {}", path.display());
+        }
+        let original_path = source
+            .strip_prefix(&self.working_dir)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| source.clone());
+
+        let trashed_at = Utc::now();
+        let short = uuid::Uuid::new_v4().simple().to_string();
+        let id = format!("{}-{}", trashed_at.format("%Y%m%d%H%M%S"), &short[..8]);
+        let entry_dir = self.root.join(&id);
+        fs::create_dir_all(&entry_dir)
+            .with_context(|| format!("creating {}", entry_dir.display()))?;
+
+        let entry = TrashEntry {
+            id,
+            original_path,
+            trashed_at,
+            size_bytes: path_size(&source),
+        };
+        fs::rename(&source, entry_dir.join(PAYLOAD_NAME))
+            .with_context(|| format!("moving {} to trash", source.display()))?;
+        fs::write(
+            entry_dir.join(META_FILE),
+            serde_json::to_string_pretty(&entry)?,
+        )?;
+        Ok(entry)
+    }
+
+    /// All trashed entries, newest first. Entries with unreadable metadata are skipped.
+    // qual:allow(iosp) reason: "I/O boundary — reads entry metadata from disk"
+    pub fn list(&self) -> anyhow::Result<Vec<TrashEntry>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.root)? {
+            let meta_path = dir_entry?.path().join(META_FILE);
+            let Ok(content) = fs::read_to_string(&meta_path) else {
+                continue;
+            };
+            match serde_json::from_str::<TrashEntry>(&content) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => log::warn!("skipping trash entry {}: {e}", meta_path.display()),
+            }
+        }
+        entries.sort_by(|a, b| b.trashed_at.cmp(&a.trashed_at));
+        Ok(entries)
+    }
+
+    /// Find an entry by exact id, unique id prefix, or original path.
+    pub fn find(&self, key: &str) -> anyhow::Result<TrashEntry> {
+        let entries = self.list()?;
+        if let Some(entry) = entries
+            .iter()
+            .find(|e| e.id == key || e.original_path == Path::new(key))
+        {
+            return Ok(entry.clone());
+        }
+        let matches: Vec<&TrashEntry> = entries.iter().filter(|e| e.id.starts_with(key)).collect();
+        match matches.as_slice() {
+            [entry] => Ok((*entry).clone()),
+            [] => bail!("No trash entry matching '{key}'"),
+            _ => bail!(
+                "'{key}' matches {} trash entries; use the full id",
+                matches.len()
+            ),
+        }
+    }
+
+    /// Restore an entry to its original location. Refuses to overwrite, and
+    /// refuses entries whose metadata points outside the working directory.
+    // qual:allow(iosp) reason: "I/O boundary — moves a trashed path back into place"
+    pub fn restore(&self, key: &str) -> anyhow::Result<PathBuf> {
+        let entry = self.find(key)?;
+        if !is_plain_relative(&entry.original_path) || !is_plain_relative(Path::new(&entry.id)) {
+            bail!(
+                "Cannot restore {}: not a path inside the working directory",
+                entry.original_path.display()
+            );
+        }
+        let destination = self.working_dir.join(&entry.original_path);
+        if fs::symlink_metadata(&destination).is_ok() {
+            bail!(
+                "Cannot restore: {} already exists",
+                entry.original_path.display()
+            );
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+            // A symlinked directory on the way could still lead elsewhere.
+            if !parent
+                .canonicalize()?
+                .starts_with(self.working_dir.canonicalize()?)
+            {
+                bail!(
+                    "Cannot restore {}: its parent directory is outside the working directory",
+                    entry.original_path.display()
+                );
+            }
+        }
+        let entry_dir = self.root.join(&entry.id);
+        fs::rename(entry_dir.join(PAYLOAD_NAME), &destination)
+            .with_context(|| format!("restoring {}", entry.original_path.display()))?;
+        fs::remove_dir_all(&entry_dir)?;
+        Ok(destination)
+    }
+
+    /// Permanently delete every entry. Returns how many were removed.
+    // qual:allow(iosp) reason: "I/O boundary — permanently deletes trash contents"
+    pub fn empty(&self) -> anyhow::Result<usize> {
+        if !self.root.exists() {
+            return Ok(0);
+        }
+        let mut removed = 0;
+        for dir_entry in fs::read_dir(&self.root)? {
+            let path = dir_entry?.path();
+            if path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::remove_file(&path)?;
+            }
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+/// True for a non-empty path made only of plain names (no root, `..`, or prefix).
+fn is_plain_relative(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+fn path_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn trash_with_file(name: &str, content: &str) -> (TempDir, Trash, TrashEntry) {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join(name);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, content).unwrap();
+        let trash = Trash::new(tmp.path());
+        let entry = trash.trash(Path::new(name)).unwrap();
+        (tmp, trash, entry)
+    }
+
+    #[test]
+    fn list_shows_trashed_file_with_size() {
+        let (tmp, trash, entry) = trash_with_file("src/old.rs", "hello");
+        assert!(!tmp.path().join("src/old.rs").exists());
+
+        let entries = trash.list().unwrap();
+        assert_eq!(entries, vec![entry]);
+        assert_eq!(entries[0].original_path, PathBuf::from("src/old.rs"));
+        assert_eq!(entries[0].size_bytes, 5);
+    }
+
+    #[test]
+    fn list_empty_when_no_trash_dir() {
+        let tmp = TempDir::new().unwrap();
+        assert!(Trash::new(tmp.path()).list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn restore_moves_file_back() {
+        let (tmp, trash, entry) = trash_with_file("src/old.rs", "hello");
+        let restored = trash.restore(&entry.id).unwrap();
+        assert_eq!(restored, tmp.path().join("src/old.rs"));
+        assert_eq!(fs::read_to_string(&restored).unwrap(), "hello");
+        assert!(trash.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn restore_by_original_path() {
+        let (tmp, trash, _) = trash_with_file("notes.txt", "x");
+        trash.restore("notes.txt").unwrap();
+        assert!(tmp.path().join("notes.txt").exists());
+    }
+
+    #[test]
+    fn restore_refuses_to_overwrite() {
+        let (tmp, trash, entry) = trash_with_file("notes.txt", "x");
+        fs::write(tmp.path().join("notes.txt"), "new").unwrap();
+        assert!(trash.restore(&entry.id).is_err());
+        assert_eq!(trash.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn restore_refuses_paths_outside_working_dir() {
+        let (tmp, trash, mut entry) = trash_with_file("notes.txt", "x");
+        let meta = trash.root().join(&entry.id).join(META_FILE);
+        for bad in [
+            "../escaped.txt",
+            "/tmp/escaped.txt",
+            "src/../../escaped.txt",
+        ] {
+            entry.original_path = PathBuf::from(bad);
+            fs::write(&meta, serde_json::to_string(&entry).unwrap()).unwrap();
+            let err = trash.restore(&entry.id).unwrap_err().to_string();
+            assert!(
+                err.contains("not a path inside the working directory"),
+                "{err}"
+            );
+        }
+        assert!(!tmp.path().parent().unwrap().join("escaped.txt").exists());
+        assert_eq!(trash.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn empty_removes_all_entries() {
+        let (tmp, trash, _) = trash_with_file("a.txt", "a");
+        fs::write(tmp.path().join("b.txt"), "b").unwrap();
+        trash.trash(Path::new("b.txt")).unwrap();
+
+        assert_eq!(trash.empty().unwrap(), 2);
+        assert!(trash.list().unwrap().is_empty());
+    }
+}
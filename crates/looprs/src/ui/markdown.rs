@@ -0,0 +1,311 @@
+//! Minimal terminal markdown rendering for assistant output.
+//!
+//! Parsing is split from styling so the structure can be tested without a
+//! terminal. The parser is line-based and forgiving: an unclosed fence or
+//! emphasis marker (common mid-stream) renders as code or literal text rather
+//! than failing.
+
+use colored::*;
+
+/// Inline run of text within a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Span {
+    Text(String),
+    Bold(String),
+    Italic(String),
+    Code(String),
+}
+
+/// One rendered line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Block {
+    Heading {
+        level: usize,
+        spans: Vec<Span>,
+    },
+    Bullet {
+        indent: usize,
+        spans: Vec<Span>,
+    },
+    Paragraph(Vec<Span>),
+    /// A line inside a fenced code block; `lang` is the fence's info string.
+    Code {
+        lang: Option<String>,
+        text: String,
+    },
+    /// An opening or closing fence line.
+    Fence {
+        lang: Option<String>,
+    },
+    Blank,
+}
+
+/// Parse `text` into one block per line.
+pub fn parse(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    // `Some(lang)` while inside a fence.
+    let mut fence: Option<Option<String>> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            match fence.take() {
+                Some(lang) => blocks.push(Block::Fence { lang }),
+                None => {
+                    let info = trimmed[3..].trim();
+                    let lang = (!info.is_empty()).then(|| info.to_string());
+                    blocks.push(Block::Fence { lang: lang.clone() });
+                    fence = Some(lang);
+                }
+            }
+            continue;
+        }
+
+        if let Some(lang) = &fence {
+            blocks.push(Block::Code {
+                lang: lang.clone(),
+                text: line.to_string(),
+            });
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            blocks.push(Block::Blank);
+        } else if let Some((level, rest)) = heading(trimmed) {
+            blocks.push(Block::Heading {
+                level,
+                spans: parse_inline(rest),
+            });
+        } else if let Some(rest) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker))
+        {
+            let indent = (line.len() - trimmed.len()) / 2;
+            blocks.push(Block::Bullet {
+                indent,
+                spans: parse_inline(rest),
+            });
+        } else {
+            blocks.push(Block::Paragraph(parse_inline(line)));
+        }
+    }
+
+    blocks
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    line[level..]
+        .strip_prefix(' ')
+        .map(|rest| (level, rest.trim()))
+}
+
+/// Split a line into plain, bold (`**`), italic (`*`/`_`), and code (`` ` ``) spans.
+/// Unmatched markers are kept as literal text.
+pub fn parse_inline(line: &str) -> Vec<Span> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let parsed = match chars[i] {
+            '`' => delimited(&chars, i, "`", false).map(|(s, end)| (Span::Code(s), end)),
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                delimited(&chars, i, "**", true).map(|(s, end)| (Span::Bold(s), end))
+            }
+            '*' => delimited(&chars, i, "*", true).map(|(s, end)| (Span::Italic(s), end)),
+            // `_` only at word boundaries so snake_case identifiers stay intact.
+            '_' if i == 0 || !chars[i - 1].is_alphanumeric() => {
+                delimited(&chars, i, "_", true).map(|(s, end)| (Span::Italic(s), end))
+            }
+            _ => None,
+        };
+
+        match parsed {
+            Some((span, end)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::Text(std::mem::take(&mut plain)));
+                }
+                spans.push(span);
+                i = end;
+            }
+            None => {
+                plain.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        spans.push(Span::Text(plain));
+    }
+    spans
+}
+
+/// Find the content between a `marker` at `start` and its closing `marker`.
+/// Returns the content and the index just past the closing marker.
+fn delimited(
+    chars: &[char],
+    start: usize,
+    marker: &str,
+    emphasis: bool,
+) -> Option<(String, usize)> {
+    let marker: Vec<char> = marker.chars().collect();
+    let open_end = start + marker.len();
+    let first = *chars.get(open_end)?;
+    if emphasis && first.is_whitespace() {
+        return None;
+    }
+
+    let mut j = open_end;
+    while j + marker.len() <= chars.len() {
+        if chars[j..j + marker.len()] == marker[..] && j > open_end {
+            let close_end = j + marker.len();
+            let closes_word = chars.get(close_end).is_none_or(|c| !c.is_alphanumeric());
+            if !emphasis || (!chars[j - 1].is_whitespace() && closes_word) {
+                return Some((chars[open_end..j].iter().collect(), close_end));
+            }
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Render markdown `text` with terminal styling.
+pub fn render(text: &str) -> String {
+    parse(text)
+        .iter()
+        .map(render_block)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_block(block: &Block) -> String {
+    match block {
+        Block::Heading { level: 1, spans } => render_spans(spans).bold().underline().to_string(),
+        Block::Heading { spans, .. } => render_spans(spans).bold().cyan().to_string(),
+        Block::Bullet { indent, spans } => {
+            format!(
+                "{}{} {}",
+                "  ".repeat(*indent),
+                "•".cyan(),
+                render_spans(spans)
+            )
+        }
+        Block::Paragraph(spans) => render_spans(spans),
+        Block::Code { text, .. } => format!(" {text} ").on_truecolor(40, 44, 52).to_string(),
+        Block::Fence { lang } => lang.as_deref().unwrap_or("").dimmed().to_string(),
+        Block::Blank => String::new(),
+    }
+}
+
+fn render_spans(spans: &[Span]) -> String {
+    spans
+        .iter()
+        .map(|span| match span {
+            Span::Text(text) => text.clone(),
+            Span::Bold(text) => text.bold().to_string(),
+            Span::Italic(text) => text.italic().to_string(),
+            Span::Code(text) => text.yellow().to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Span {
+        Span::Text(s.to_string())
+    }
+
+    #[test]
+    fn parses_headings_lists_and_paragraphs() {
+        let blocks = parse("# Plan\n\n- first **step**\n  * nested `code`\nDone.");
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading {
+                    level: 1,
+                    spans: vec![text("Plan")]
+                },
+                Block::Blank,
+                Block::Bullet {
+                    indent: 0,
+                    spans: vec![text("first "), Span::Bold("step".to_string())]
+                },
+                Block::Bullet {
+                    indent: 1,
+                    spans: vec![text("nested "), Span::Code("code".to_string())]
+                },
+                Block::Paragraph(vec![text("Done.")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_fenced_code_with_language() {
+        let blocks = parse("```rust\nfn main() {}\n```\nafter");
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Fence {
+                    lang: Some("rust".to_string())
+                },
+                Block::Code {
+                    lang: Some("rust".to_string()),
+                    text: "fn main() {}".to_string()
+                },
+                Block::Fence {
+                    lang: Some("rust".to_string())
+                },
+                Block::Paragraph(vec![text("after")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_fence_treats_rest_as_code() {
+        let blocks = parse("```\n# not a heading");
+        assert_eq!(
+            blocks[1],
+            Block::Code {
+                lang: None,
+                text: "# not a heading".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn inline_emphasis_and_unmatched_markers() {
+        assert_eq!(
+            parse_inline("an *italic* and _also_ word"),
+            vec![
+                text("an "),
+                Span::Italic("italic".to_string()),
+                text(" and "),
+                Span::Italic("also".to_string()),
+                text(" word"),
+            ]
+        );
+        assert_eq!(
+            parse_inline("snake_case_name and 2 * 3 and **open"),
+            vec![text("snake_case_name and 2 * 3 and **open")]
+        );
+    }
+
+    #[test]
+    fn malformed_input_never_panics() {
+        for input in [
+            "*", "**", "`", "_", "#", "```", "- ", "* *", "**`x**`", "é*ü*",
+        ] {
+            let _ = render(input);
+        }
+    }
+}
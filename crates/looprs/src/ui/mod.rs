@@ -1,8 +1,19 @@
 use colored::*;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::observability;
 use crate::sanitize;
 
+pub mod markdown;
+
+/// Set from `ui.render_markdown` at startup.
+static RENDER_MARKDOWN: AtomicBool = AtomicBool::new(false);
+
+/// Style assistant text as markdown instead of printing it verbatim.
+pub fn set_render_markdown(enabled: bool) {
+    RENDER_MARKDOWN.store(enabled, Ordering::Relaxed);
+}
+
 /// Environment variable that enables machine-readable JSON logs when set to "1" or "true".
 const MACHINE_LOG_ENV: &str = "LOOPRS_MACHINE_LOG";
 
@@ -281,7 +292,11 @@ pub fn header(provider: &str, model: &str, cwd: &str) {
 
 pub fn assistant_text(text: &str) {
     let safe = sanitize::sanitize_preview_for_console(text);
-    println!("\n{} {}", "●".blue().bold(), safe.blue());
+    if RENDER_MARKDOWN.load(Ordering::Relaxed) {
+        println!("\n{} {}", "●".blue().bold(), markdown::render(&safe));
+    } else {
+        println!("\n{} {}", "●".blue().bold(), safe.blue());
+    }
     emit_machine_event("assistant_text", serde_json::json!({ "text": text }));
 }
 
@@ -315,6 +330,32 @@ pub fn tool_err(err_msg: &str) {
     emit_machine_event("tool_err", serde_json::json!({ "error": err_msg }));
 }
 
+/// One line of output from a still-running tool, indented under its call.
+pub fn tool_output_line(line: &str) {
+    let safe = sanitize::sanitize_preview_for_console(line);
+    println!("  {} {}", "│".dimmed(), safe.dimmed());
+}
+
+/// A pending file change as a unified diff, indented under its tool call,
+/// with removed lines in red and added lines in green.
+pub fn diff_preview(diff: &str) {
+    for line in diff.lines() {
+        let safe = sanitize::sanitize_preview_for_console(line);
+        let styled = if line.starts_with("+++") || line.starts_with("---") {
+            safe.bold()
+        } else if line.starts_with('+') {
+            safe.green()
+        } else if line.starts_with('-') {
+            safe.red()
+        } else if line.starts_with("@@") {
+            safe.cyan()
+        } else {
+            safe.dimmed()
+        };
+        println!("  {} {styled}", "│".dimmed());
+    }
+}
+
 pub fn section_title(title: &str) {
     let safe = sanitize::sanitize_preview_for_console(title);
     println!("\n{}", safe.dimmed());